@@ -78,19 +78,34 @@ Hint: Make sure the provided ethereum.url and ethereum.password are good.",
     info!(location=?database_path, "Database migrated.");
     verify_database_chain(&storage, starknet_chain).context("Verifying database")?;
 
-    let sequencer = match config.sequencer_url {
+    let mut sequencer_config = match config.sequencer_url {
         Some(url) => {
             info!(?url, "Using custom Sequencer address");
-            let client = sequencer::Client::with_url(url).unwrap();
-            let sequencer_chain = client.chain().await.unwrap();
-            if sequencer_chain != starknet_chain {
-                tracing::error!(sequencer=%sequencer_chain, ethereum=%starknet_chain, "Sequencer and Ethereum network mismatch");
-                anyhow::bail!("Sequencer and Ethereum network mismatch. Sequencer is on {sequencer_chain} but Ethereum is on {starknet_chain}");
-            }
-            client
+            sequencer::SequencerConfig::new(url).with_chain(starknet_chain)
         }
-        None => sequencer::Client::new(starknet_chain).unwrap(),
+        None => sequencer::SequencerConfig::for_chain(starknet_chain),
     };
+    if let Some(max) = config.sequencer_max_concurrent_submissions {
+        sequencer_config = sequencer_config.with_max_concurrent_submissions(max);
+    }
+    if let Some(threshold) = config.sequencer_slow_request_threshold {
+        sequencer_config = sequencer_config.with_slow_request_threshold(threshold);
+    }
+    if config.sequencer_offline {
+        sequencer_config = sequencer_config.with_offline();
+    }
+    let sequencer =
+        sequencer::Client::with_config(sequencer_config).context("Creating Sequencer client")?;
+    if config.sequencer_offline {
+        tracing::warn!("Sequencer offline mode is enabled, L2 sync and submissions will fail");
+    } else if let Err(e) = sequencer.probe(starknet_chain).await {
+        tracing::error!(reason=?e, ethereum=%starknet_chain, "Feeder gateway probe failed");
+        return Err(e.context(
+            "Probing the Sequencer gateway.
+
+Hint: Make sure the provided sequencer-url is reachable and serves the same network as Ethereum.",
+        ));
+    }
     let sync_state = Arc::new(state::SyncState::default());
     let pending_state = state::PendingData::default();
     let pending_interval = match config.poll_pending {
@@ -133,11 +148,39 @@ Hint: Make sure the provided ethereum.url and ethereum.password are good.",
         false => api,
     };
 
-    let (rpc_handle, local_addr) = rpc::RpcServer::new(config.http_rpc_addr, api)
+    let context_options = rpc_context_options(&config, &api.storage)?;
+    let rate_limiter = rpc_rate_limiter(&config);
+
+    if let Some(batch_deploy_addr) = config.batch_deploy_addr {
+        use rpc::v02::batch_deploy::{self, Checkpoint};
+
+        let context = rpc::v02::RpcContext::from(&api).with_options(context_options.clone());
+        let checkpoint = config
+            .batch_deploy_checkpoint
+            .as_deref()
+            .map(Checkpoint::open)
+            .transpose()
+            .context("Opening the batch deploy checkpoint")?
+            .map(Arc::new);
+        let _jh = batch_deploy::spawn_server(
+            batch_deploy_addr,
+            context,
+            config.batch_deploy_concurrency,
+            checkpoint,
+        )
+        .await;
+        info!("📦 Batch deploy endpoint started on: {}", batch_deploy_addr);
+    }
+
+    let rpc_server = rpc::RpcServer::new(config.http_rpc_addr, api)
         .with_middleware(RpcMetricsMiddleware)
-        .run()
-        .await
-        .context("Starting the RPC server")?;
+        .with_disabled_methods(config.disabled_rpc_methods)
+        .with_context_options(context_options);
+    let rpc_server = match rate_limiter {
+        Some(rate_limiter) => rpc_server.with_rate_limiter(Arc::new(rate_limiter)),
+        None => rpc_server,
+    };
+    let (rpc_handle, local_addr) = rpc_server.run().await.context("Starting the RPC server")?;
 
     info!("📡 HTTP-RPC server started on: {}", local_addr);
 
@@ -177,6 +220,87 @@ Hint: Make sure the provided ethereum.url and ethereum.password are good.",
     Ok(())
 }
 
+/// Sets up the optional features of the v0.2 RPC API which are enabled in `config`.
+fn rpc_context_options(
+    config: &config::Configuration,
+    storage: &Storage,
+) -> anyhow::Result<rpc::v02::ContextOptions> {
+    use rpc::v02::{
+        audit::FileAuditSink, class_source::ClassSources, fee_budget::FeeBudget,
+        max_fee_check::MaxFeeCheck, submission_store::SubmissionStore,
+    };
+
+    let mut options = rpc::v02::ContextOptions {
+        felt_representation: config.felt_representation,
+        max_fee_cap: config.max_fee_cap,
+        class_validation: rpc::v02::types::ValidationOptions {
+            max_program_felts: config.class_max_program_felts,
+            strict: config.class_validation_strict,
+            check_abi_entry_points: config.class_check_abi,
+            check_program_schema: config.class_check_program_schema,
+        },
+        check_signatures: config.check_signatures,
+        offline: config.sequencer_offline,
+        ..Default::default()
+    };
+
+    if let Some(path) = &config.audit_log {
+        let sink = FileAuditSink::open(path).context("Opening the RPC audit log")?;
+        options.audit_sink = Some(Arc::new(sink));
+    }
+    if let Some(path) = &config.replay_log {
+        let log = sequencer::replay::ReplayLog::open(path).context("Opening the replay log")?;
+        options.replay_log = Some(Arc::new(log));
+    }
+    if config.submission_store {
+        options.submission_store = Some(Arc::new(SubmissionStore::new(storage.clone())));
+    }
+    if let Some(budget) = config.fee_budget {
+        options.fee_budget = Some(Arc::new(FeeBudget::new(budget, config.fee_budget_window)));
+    }
+    if let Some(bps) = config.max_fee_check_bps {
+        options.max_fee_check = Some(MaxFeeCheck {
+            safety_factor_bps: bps,
+            reject: config.max_fee_check_reject,
+        });
+    }
+    if !config.class_source_schemes.is_empty() {
+        let class_sources = ClassSources::new(
+            config.class_source_schemes.clone(),
+            config::CLASS_SOURCE_MAX_SIZE,
+        )
+        .context("Creating the class source client")?
+        .with_allowed_hosts(config.class_source_hosts.clone());
+        options.class_sources = Some(Arc::new(class_sources));
+    }
+
+    Ok(options)
+}
+
+/// Limits calls to the RPC methods which have a rate limit in `config`. Returns [None] if no
+/// method is limited.
+fn rpc_rate_limiter(config: &config::Configuration) -> Option<rpc::v02::rate_limit::RateLimiter> {
+    use rpc::v02::rate_limit::{MethodLimit, RateLimiter};
+
+    if config.rate_limits.is_empty() {
+        return None;
+    }
+
+    let limiter = config
+        .rate_limits
+        .iter()
+        .fold(RateLimiter::new(), |limiter, limit| {
+            limiter.with_limit(
+                limit.method.clone(),
+                MethodLimit {
+                    capacity: limit.capacity,
+                    per_second: f64::from(limit.per_second_bps) / 10_000.0,
+                },
+            )
+        });
+    Some(limiter)
+}
+
 /// Verifies that the database matches the expected chain; throws an error if it does not.
 fn verify_database_chain(storage: &Storage, expected: core::Chain) -> anyhow::Result<()> {
     use pathfinder_lib::storage::StarknetBlocksTable;
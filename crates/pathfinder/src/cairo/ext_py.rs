@@ -78,6 +78,42 @@ impl Handle {
         }
     }
 
+    /// Runs the constructor of `deploy` on the python cairo-lang executors without storing
+    /// anything, returning the address the contract would be deployed at.
+    ///
+    /// A reverting constructor fails with [CallFailure::ExecutionFailed].
+    #[cfg(feature = "local-preflight")]
+    pub async fn simulate_deploy(
+        &self,
+        deploy: add_transaction::Deploy,
+        at_block: BlockHashNumberOrLatest,
+        diffs: Option<Arc<StateUpdate>>,
+    ) -> Result<crate::core::ContractAddress, CallFailure> {
+        use tracing::field::Empty;
+        let (response, rx) = oneshot::channel();
+
+        let continued_span = tracing::info_span!("ext_py_simulate_deploy", pid = Empty);
+
+        self.command_tx
+            .send((
+                Command::SimulateDeploy {
+                    transaction: add_transaction::AddTransaction::Deploy(deploy),
+                    at_block,
+                    chain: self.chain,
+                    diffs,
+                    response,
+                },
+                continued_span,
+            ))
+            .await
+            .map_err(|_| CallFailure::Shutdown)?;
+
+        match rx.await {
+            Ok(x) => x,
+            Err(_closed) => Err(CallFailure::Shutdown),
+        }
+    }
+
     /// Fee estimation is a superset of call with the call result value discarded.
     ///
     /// Returns the fee as a three components.
@@ -106,7 +142,7 @@ impl Handle {
                 add_transaction::AddTransaction::DeployAccount(add_transaction::DeployAccount {
                     version: tx.version,
                     max_fee: tx.max_fee,
-                    signature: tx.signature,
+                    signature: tx.signature.0,
                     nonce: tx.nonce,
                     class_hash: tx.class_hash,
                     contract_address_salt: tx.contract_address_salt,
@@ -117,36 +153,44 @@ impl Handle {
                 add_transaction::AddTransaction::Declare(add_transaction::Declare {
                     version: tx.version,
                     max_fee: tx.max_fee,
-                    signature: tx.signature,
+                    signature: tx.signature.0,
                     contract_class: tx.contract_class.try_into().map_err(|_| {
                         CallFailure::Internal("contract class serialization failure")
                     })?,
                     sender_address: tx.sender_address,
                     nonce: tx.nonce,
+                    compiled_class_hash: tx.compiled_class_hash,
                 })
             }
             BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V0(tx)) => {
                 add_transaction::AddTransaction::Invoke(add_transaction::InvokeFunction {
                     version: tx.version,
                     max_fee: tx.max_fee,
-                    signature: tx.signature,
+                    signature: tx.signature.0,
                     nonce: None,
                     contract_address: tx.contract_address,
                     entry_point_selector: Some(tx.entry_point_selector),
                     calldata: tx.calldata,
+                    resource_bounds: None,
                 })
             }
             BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V1(tx)) => {
                 add_transaction::AddTransaction::Invoke(add_transaction::InvokeFunction {
                     version: tx.version,
                     max_fee: tx.max_fee,
-                    signature: tx.signature,
+                    signature: tx.signature.0,
                     nonce: Some(tx.nonce),
                     contract_address: tx.sender_address,
                     entry_point_selector: None,
                     calldata: tx.calldata,
+                    resource_bounds: None,
                 })
             }
+            BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V3(_)) => {
+                return Err(CallFailure::Internal(
+                    "fee estimation of v3 transactions is not supported",
+                ));
+            }
         };
 
         self.command_tx
@@ -253,6 +297,14 @@ enum Command {
         diffs: Option<Arc<StateUpdate>>,
         response: oneshot::Sender<Result<FeeEstimate, CallFailure>>,
     },
+    #[cfg(feature = "local-preflight")]
+    SimulateDeploy {
+        transaction: add_transaction::AddTransaction,
+        at_block: BlockHashNumberOrLatest,
+        chain: UsedChain,
+        diffs: Option<Arc<StateUpdate>>,
+        response: oneshot::Sender<Result<crate::core::ContractAddress, CallFailure>>,
+    },
 }
 
 impl Command {
@@ -261,6 +313,8 @@ impl Command {
         match self {
             Call { response, .. } => response.is_closed(),
             EstimateFee { response, .. } => response.is_closed(),
+            #[cfg(feature = "local-preflight")]
+            SimulateDeploy { response, .. } => response.is_closed(),
         }
     }
 
@@ -269,6 +323,8 @@ impl Command {
         match self {
             Call { response, .. } => response.send(Err(err)).map_err(|e| e.unwrap_err()),
             EstimateFee { response, .. } => response.send(Err(err)).map_err(|e| e.unwrap_err()),
+            #[cfg(feature = "local-preflight")]
+            SimulateDeploy { response, .. } => response.send(Err(err)).map_err(|e| e.unwrap_err()),
         }
     }
 
@@ -277,6 +333,8 @@ impl Command {
         match self {
             Call { response, .. } => response.closed().await,
             EstimateFee { response, .. } => response.closed().await,
+            #[cfg(feature = "local-preflight")]
+            SimulateDeploy { response, .. } => response.closed().await,
         }
     }
 }
@@ -630,6 +688,67 @@ mod tests {
         jh.await.unwrap();
     }
 
+    #[cfg(feature = "local-preflight")]
+    #[test_log::test(tokio::test)]
+    async fn preflight_deploy_with_reverting_constructor() {
+        use crate::sequencer::deploy::preflight_deploy_locally;
+        use crate::sequencer::request::add_transaction::AddTransaction;
+
+        let db_file = tempfile::NamedTempFile::new().unwrap();
+
+        let s = crate::storage::Storage::migrate(
+            PathBuf::from(db_file.path()),
+            crate::storage::JournalMode::WAL,
+        )
+        .unwrap();
+
+        let mut conn = s.connection().unwrap();
+        conn.execute("PRAGMA foreign_keys = off", []).unwrap();
+
+        let tx = conn.transaction().unwrap();
+
+        deploy_test_contract_in_block_one(&tx);
+
+        tx.commit().unwrap();
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        let (handle, jh) = super::start(
+            PathBuf::from(db_file.path()),
+            std::num::NonZeroUsize::new(1).unwrap(),
+            async move {
+                let _ = shutdown_rx.await;
+            },
+            crate::core::Chain::Testnet,
+        )
+        .await
+        .unwrap();
+
+        let json = include_bytes!("../../resources/deploy_openzeppelin_account.json");
+        let deploy = match serde_json::from_slice::<AddTransaction>(json).unwrap() {
+            AddTransaction::Deploy(deploy) => deploy,
+            _ => unreachable!(),
+        };
+
+        let result = preflight_deploy_locally(&handle, deploy.clone())
+            .await
+            .unwrap();
+        assert!(result.success, "{result:?}");
+
+        // The account's constructor expects the public key.
+        let deploy = crate::sequencer::request::add_transaction::Deploy {
+            constructor_calldata: vec![],
+            ..deploy
+        };
+        let result = preflight_deploy_locally(&handle, deploy).await.unwrap();
+        assert!(!result.success);
+        assert!(result.revert_reason.is_some());
+
+        shutdown_tx.send(()).unwrap();
+
+        jh.await.unwrap();
+    }
+
     #[test_log::test(tokio::test)]
     async fn call_with_unknown_contract() {
         let db_file = tempfile::NamedTempFile::new().unwrap();
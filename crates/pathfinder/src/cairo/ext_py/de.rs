@@ -23,12 +23,17 @@ pub(crate) struct ChildResponse<'a> {
     output: Option<OutputValue>,
 }
 
-/// Deserializes either the call output value or the fee estimate.
+/// Deserializes either the call output value, the fee estimate or the address of a simulated
+/// deploy.
 #[derive(serde::Deserialize, Debug)]
 #[serde(untagged)]
 pub(crate) enum OutputValue {
     Call(Vec<CallResultValue>),
     Fee(FeeEstimate),
+    #[cfg(feature = "local-preflight")]
+    Deploy {
+        contract_address: crate::core::ContractAddress,
+    },
 }
 
 impl<'a> ChildResponse<'a> {
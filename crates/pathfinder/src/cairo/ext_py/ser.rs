@@ -27,6 +27,13 @@ pub(crate) enum ChildCommand<'a> {
         gas_price: &'a web3::types::H256,
         transaction: &'a crate::sequencer::request::add_transaction::AddTransaction,
     },
+    #[cfg(feature = "local-preflight")]
+    SimulateDeploy {
+        #[serde(flatten)]
+        common: CommonProperties<'a>,
+
+        transaction: &'a crate::sequencer::request::add_transaction::AddTransaction,
+    },
 }
 
 #[serde_with::serde_as]
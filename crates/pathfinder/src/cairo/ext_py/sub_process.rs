@@ -366,6 +366,23 @@ async fn process(
             gas_price: gas_price.as_price(),
             transaction,
         },
+        #[cfg(feature = "local-preflight")]
+        Command::SimulateDeploy {
+            transaction,
+            at_block,
+            chain,
+            diffs: maybe_diffs,
+            ..
+        } => ChildCommand::SimulateDeploy {
+            common: CommonProperties {
+                at_block,
+                chain: *chain,
+                pending_updates: maybe_diffs.as_ref().map(|x| &**x).into(),
+                pending_deployed: maybe_diffs.as_ref().map(|x| &**x).into(),
+                pending_nonces: maybe_diffs.as_ref().map(|x| &**x).into(),
+            },
+            transaction,
+        },
     };
 
     let mut cursor = std::io::Cursor::new(command_buffer);
@@ -439,12 +456,17 @@ async fn process(
         (Command::EstimateFee { response, .. }, Ok(OutputValue::Fee(x))) => {
             let _ = response.send(Ok(x));
         }
-        (command @ Command::Call { .. }, Err(fail))
-        | (command @ Command::EstimateFee { .. }, Err(fail)) => {
+        #[cfg(feature = "local-preflight")]
+        (
+            Command::SimulateDeploy { response, .. },
+            Ok(OutputValue::Deploy { contract_address }),
+        ) => {
+            let _ = response.send(Ok(contract_address));
+        }
+        (command, Err(fail)) => {
             let _ = command.fail(fail);
         }
-        (command @ Command::Call { .. }, output @ Ok(OutputValue::Fee(_)))
-        | (command @ Command::EstimateFee { .. }, output @ Ok(OutputValue::Call(_))) => {
+        (command, output @ Ok(_)) => {
             error!(?command, ?output, "python script mixed response to command");
             let _ = command.fail(CallFailure::Internal("mixed response"));
         }
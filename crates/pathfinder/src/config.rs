@@ -3,13 +3,25 @@ mod builder;
 mod cli;
 mod file;
 
-use std::{fmt::Display, net::SocketAddr, path::PathBuf, str::FromStr};
+use std::{fmt::Display, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
 
 use enum_iterator::IntoEnumIterator;
 use reqwest::Url;
 
+use crate::core::Fee;
+use crate::rpc::v02::representation::FeltRepresentation;
+
 const DEFAULT_HTTP_RPC_ADDR: &str = "127.0.0.1:9545";
 
+/// The default window of the [fee budget](Configuration::fee_budget).
+const DEFAULT_FEE_BUDGET_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// The default [batch deploy concurrency](Configuration::batch_deploy_concurrency).
+const DEFAULT_BATCH_DEPLOY_CONCURRENCY: usize = 8;
+
+/// The size limit of classes fetched from [class sources](Configuration::class_source_schemes).
+pub const CLASS_SOURCE_MAX_SIZE: usize = 64 * 1024 * 1024;
+
 /// Possible configuration options.
 #[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, IntoEnumIterator)]
 pub enum ConfigOption {
@@ -33,6 +45,54 @@ pub enum ConfigOption {
     MonitorAddress,
     /// Chooses Integration network instead of testnet.
     Integration,
+    /// Comma separated names of RPC methods to disable.
+    DisabledRpcMethods,
+    /// Path of the audit log of submitted transactions.
+    RpcAuditLog,
+    /// Path of the replay log of submitted deploys.
+    RpcReplayLog,
+    /// Enable storing the transactions submitted through the node.
+    RpcSubmissionStore,
+    /// Representation of field elements in RPC outputs.
+    RpcFeltRepresentation,
+    /// Largest `max_fee` of submitted transactions.
+    RpcMaxFeeCap,
+    /// Total `max_fee` of the transactions submitted within the fee budget window.
+    RpcFeeBudget,
+    /// Length of the fee budget window in seconds.
+    RpcFeeBudgetWindow,
+    /// Factor of the fee estimate which the `max_fee` of submitted transactions must cover.
+    RpcMaxFeeCheck,
+    /// Reject transactions failing the max fee check instead of warning about them.
+    RpcMaxFeeCheckReject,
+    /// Comma separated URL schemes of the class sources of deploys.
+    RpcClassSourceSchemes,
+    /// Comma separated hosts of the class sources of deploys.
+    RpcClassSourceHosts,
+    /// Comma separated rate limits of RPC methods, e.g. `method=capacity/per_second`.
+    RpcRateLimits,
+    /// The listening socket address of the batch deploy endpoint.
+    RpcBatchDeployAddress,
+    /// Number of batch deploy inputs submitted at a time.
+    RpcBatchDeployConcurrency,
+    /// Path of the checkpoint of batch deploys.
+    RpcBatchDeployCheckpoint,
+    /// The maximum number of felts in the programs of submitted classes.
+    RpcClassMaxProgramFelts,
+    /// Whether submitted classes exceeding a limit are rejected instead of warned about.
+    RpcClassValidationStrict,
+    /// Whether the ABI of submitted classes must match their external entry points.
+    RpcClassCheckAbi,
+    /// Whether the programs of submitted classes must match the Cairo 0 program schema.
+    RpcClassCheckProgramSchema,
+    /// Whether the signatures of submitted transactions must consist of `(r, s)` pairs.
+    RpcCheckSignatures,
+    /// Fail all Sequencer requests instead of sending them.
+    SequencerOffline,
+    /// Number of transactions submitted to the Sequencer at a time.
+    SequencerMaxConcurrentSubmissions,
+    /// Sequencer requests taking this many milliseconds are logged.
+    SequencerSlowRequestThreshold,
 }
 
 impl Display for ConfigOption {
@@ -50,6 +110,36 @@ impl Display for ConfigOption {
             ConfigOption::PollPending => f.write_str("Enable pending block polling"),
             ConfigOption::MonitorAddress => f.write_str("Pathfinder monitoring address"),
             ConfigOption::Integration => f.write_str("Select integration network"),
+            ConfigOption::DisabledRpcMethods => f.write_str("Disabled RPC methods"),
+            ConfigOption::RpcAuditLog => f.write_str("RPC audit log"),
+            ConfigOption::RpcReplayLog => f.write_str("RPC replay log"),
+            ConfigOption::RpcSubmissionStore => f.write_str("Enable RPC submission store"),
+            ConfigOption::RpcFeltRepresentation => f.write_str("RPC field element representation"),
+            ConfigOption::RpcMaxFeeCap => f.write_str("RPC max fee cap"),
+            ConfigOption::RpcFeeBudget => f.write_str("RPC fee budget"),
+            ConfigOption::RpcFeeBudgetWindow => f.write_str("RPC fee budget window"),
+            ConfigOption::RpcMaxFeeCheck => f.write_str("RPC max fee check factor"),
+            ConfigOption::RpcMaxFeeCheckReject => f.write_str("Reject on RPC max fee check"),
+            ConfigOption::RpcClassSourceSchemes => f.write_str("RPC class source schemes"),
+            ConfigOption::RpcClassSourceHosts => f.write_str("RPC class source hosts"),
+            ConfigOption::RpcRateLimits => f.write_str("RPC rate limits"),
+            ConfigOption::RpcBatchDeployAddress => f.write_str("RPC batch deploy address"),
+            ConfigOption::RpcBatchDeployConcurrency => f.write_str("RPC batch deploy concurrency"),
+            ConfigOption::RpcBatchDeployCheckpoint => f.write_str("RPC batch deploy checkpoint"),
+            ConfigOption::RpcClassMaxProgramFelts => f.write_str("RPC class max program felts"),
+            ConfigOption::RpcClassValidationStrict => f.write_str("RPC class validation strict"),
+            ConfigOption::RpcClassCheckAbi => f.write_str("RPC class check ABI"),
+            ConfigOption::RpcClassCheckProgramSchema => {
+                f.write_str("RPC class check program schema")
+            }
+            ConfigOption::RpcCheckSignatures => f.write_str("RPC check signatures"),
+            ConfigOption::SequencerOffline => f.write_str("Sequencer offline mode"),
+            ConfigOption::SequencerMaxConcurrentSubmissions => {
+                f.write_str("Sequencer max concurrent submissions")
+            }
+            ConfigOption::SequencerSlowRequestThreshold => {
+                f.write_str("Sequencer slow request threshold")
+            }
         }
     }
 }
@@ -63,6 +153,17 @@ pub struct EthereumConfig {
     pub password: Option<String>,
 }
 
+/// The [rate limit](Configuration::rate_limits) of an RPC method.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodRateLimit {
+    /// Name of the method, e.g. `starknet_addDeployTransaction`.
+    pub method: String,
+    /// How many calls are served in a burst.
+    pub capacity: u32,
+    /// How many calls are added back to the burst per second, in basis points.
+    pub per_second_bps: u32,
+}
+
 /// Node configuration options.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Configuration {
@@ -84,6 +185,61 @@ pub struct Configuration {
     pub monitoring_addr: Option<SocketAddr>,
     /// Select integration network.
     pub integration: bool,
+    /// Names of RPC methods which are not served, e.g. `starknet_addDeployTransaction`.
+    pub disabled_rpc_methods: Vec<String>,
+    /// Path of the audit log of the transactions submitted through the node. Disabled if not set.
+    pub audit_log: Option<PathBuf>,
+    /// Path of the replay log of the deploys submitted through the node. Disabled if not set.
+    pub replay_log: Option<PathBuf>,
+    /// Store the transactions submitted through the node in the data directory.
+    pub submission_store: bool,
+    /// Representation of field elements in v0.2 RPC outputs.
+    pub felt_representation: FeltRepresentation,
+    /// Transactions with a larger `max_fee` are rejected. Unlimited if not set.
+    pub max_fee_cap: Option<Fee>,
+    /// Limits the total `max_fee` of the transactions submitted within
+    /// [fee_budget_window](Self::fee_budget_window). Unlimited if not set.
+    pub fee_budget: Option<Fee>,
+    /// The rolling window of the [fee_budget](Self::fee_budget).
+    pub fee_budget_window: Duration,
+    /// Factor of a live fee estimate which the `max_fee` of submitted transactions must cover,
+    /// in basis points. Not checked if not set.
+    pub max_fee_check_bps: Option<u32>,
+    /// Reject transactions failing the [max fee check](Self::max_fee_check_bps) instead of
+    /// only warning about them.
+    pub max_fee_check_reject: bool,
+    /// URL schemes of the class sources which deploys may reference instead of inlining their
+    /// class. Class sources are disabled if empty.
+    pub class_source_schemes: Vec<String>,
+    /// Hosts of the class sources, including their subdomains. Any public host is allowed if
+    /// empty.
+    pub class_source_hosts: Vec<String>,
+    /// Rate limits of RPC methods. Methods without a limit are never throttled.
+    pub rate_limits: Vec<MethodRateLimit>,
+    /// The listening address and port of the batch deploy endpoint. Disabled if not set.
+    pub batch_deploy_addr: Option<SocketAddr>,
+    /// The number of inputs of a batch deploy which are submitted at a time.
+    pub batch_deploy_concurrency: std::num::NonZeroUsize,
+    /// Path of the checkpoint which lets interrupted batch deploys resume without deploying
+    /// their inputs twice. Disabled if not set.
+    pub batch_deploy_checkpoint: Option<PathBuf>,
+    /// Submitted classes with programs of more felts are warned about, or rejected if
+    /// [Configuration::class_validation_strict] is set.
+    pub class_max_program_felts: Option<usize>,
+    /// Reject submitted classes exceeding a limit instead of only warning about them.
+    pub class_validation_strict: bool,
+    /// Reject submitted classes whose ABI functions do not match their external entry points.
+    pub class_check_abi: bool,
+    /// Reject submitted classes whose programs do not match the Cairo 0 program schema.
+    pub class_check_program_schema: bool,
+    /// Reject submitted transactions whose signatures are empty or not made of `(r, s)` pairs.
+    pub check_signatures: bool,
+    /// Fail all Sequencer requests instead of sending them.
+    pub sequencer_offline: bool,
+    /// Number of transactions submitted to the Sequencer at a time. Unlimited if not set.
+    pub sequencer_max_concurrent_submissions: Option<std::num::NonZeroUsize>,
+    /// Sequencer requests taking at least this long are logged. Disabled if not set.
+    pub sequencer_slow_request_threshold: Option<Duration>,
 }
 
 impl Configuration {
@@ -1,9 +1,11 @@
 //! Provides [ConfigBuilder] which is a convenient and safe way of collecting
 //! configuration parameters from various sources and combining them into one.
 
-use crate::config::{ConfigOption, Configuration, EthereumConfig};
+use crate::config::{ConfigOption, Configuration, EthereumConfig, MethodRateLimit};
+use crate::core::Fee;
+use crate::rpc::v02::representation::FeltRepresentation;
 use reqwest::Url;
-use std::{collections::HashMap, net::SocketAddr, path::PathBuf, str::FromStr};
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
 
 /// A convenient way of collecting and merging configuration options.
 ///
@@ -90,6 +92,62 @@ Hint: Register your own account or run your own Ethereum node and put the real U
             })
             .transpose()?;
         let integration = self.take(ConfigOption::Integration).is_some();
+        let disabled_rpc_methods = self
+            .take(ConfigOption::DisabledRpcMethods)
+            .map(|methods| parse_list(&methods))
+            .unwrap_or_default();
+        let audit_log = self.take(ConfigOption::RpcAuditLog).map(PathBuf::from);
+        let replay_log = self.take(ConfigOption::RpcReplayLog).map(PathBuf::from);
+        let max_fee_cap = self
+            .take(ConfigOption::RpcMaxFeeCap)
+            .map(|fee| parse_fee(ConfigOption::RpcMaxFeeCap, &fee))
+            .transpose()?;
+        let fee_budget = self
+            .take(ConfigOption::RpcFeeBudget)
+            .map(|fee| parse_fee(ConfigOption::RpcFeeBudget, &fee))
+            .transpose()?;
+        let max_fee_check_bps = self
+            .take(ConfigOption::RpcMaxFeeCheck)
+            .map(|factor| parse_basis_points(ConfigOption::RpcMaxFeeCheck, &factor))
+            .transpose()?;
+        let class_source_schemes = self
+            .take(ConfigOption::RpcClassSourceSchemes)
+            .map(|schemes| parse_list(&schemes))
+            .unwrap_or_default();
+        let class_source_hosts = self
+            .take(ConfigOption::RpcClassSourceHosts)
+            .map(|hosts| parse_list(&hosts))
+            .unwrap_or_default();
+        let rate_limits = self
+            .take(ConfigOption::RpcRateLimits)
+            .map(|limits| {
+                parse_list(&limits)
+                    .iter()
+                    .map(|limit| parse_rate_limit(ConfigOption::RpcRateLimits, limit))
+                    .collect::<std::io::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let sequencer_max_concurrent_submissions = self
+            .take(ConfigOption::SequencerMaxConcurrentSubmissions)
+            .map(|num| {
+                let num =
+                    parse_number::<usize>(ConfigOption::SequencerMaxConcurrentSubmissions, &num)?;
+                std::num::NonZeroUsize::new(num).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Number of concurrent submissions must be non-zero".to_owned(),
+                    )
+                })
+            })
+            .transpose()?;
+        let sequencer_slow_request_threshold = self
+            .take(ConfigOption::SequencerSlowRequestThreshold)
+            .map(|millis| {
+                parse_number(ConfigOption::SequencerSlowRequestThreshold, &millis)
+                    .map(Duration::from_millis)
+            })
+            .transpose()?;
 
         // Optional parameters with defaults.
         let data_directory = self
@@ -163,6 +221,88 @@ Hint: Register your own account or run your own Ethereum node and put the real U
             None => Ok(false),
         }?;
 
+        let submission_store = self
+            .take(ConfigOption::RpcSubmissionStore)
+            .map(|enable| parse_bool(ConfigOption::RpcSubmissionStore, &enable))
+            .transpose()?
+            .unwrap_or(false);
+        let felt_representation = match self.take(ConfigOption::RpcFeltRepresentation) {
+            Some(representation) => match representation.to_lowercase().as_str() {
+                "hex" => Ok(FeltRepresentation::Hex),
+                "decimal" => Ok(FeltRepresentation::Decimal),
+                _ => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "Invalid value '{}' for the field element representation option, must be hex|decimal",
+                        representation
+                    ),
+                )),
+            },
+            None => Ok(FeltRepresentation::default()),
+        }?;
+        let fee_budget_window = self
+            .take(ConfigOption::RpcFeeBudgetWindow)
+            .map(|secs| {
+                parse_number(ConfigOption::RpcFeeBudgetWindow, &secs).map(Duration::from_secs)
+            })
+            .transpose()?
+            .unwrap_or(super::DEFAULT_FEE_BUDGET_WINDOW);
+        let max_fee_check_reject = self
+            .take(ConfigOption::RpcMaxFeeCheckReject)
+            .map(|enable| parse_bool(ConfigOption::RpcMaxFeeCheckReject, &enable))
+            .transpose()?
+            .unwrap_or(false);
+        let sequencer_offline = self
+            .take(ConfigOption::SequencerOffline)
+            .map(|enable| parse_bool(ConfigOption::SequencerOffline, &enable))
+            .transpose()?
+            .unwrap_or(false);
+
+        let batch_deploy_addr = self
+            .take(ConfigOption::RpcBatchDeployAddress)
+            .map(|addr| parse_number::<SocketAddr>(ConfigOption::RpcBatchDeployAddress, &addr))
+            .transpose()?;
+        let batch_deploy_concurrency = match self.take(ConfigOption::RpcBatchDeployConcurrency) {
+            Some(num) => {
+                let num = parse_number::<usize>(ConfigOption::RpcBatchDeployConcurrency, &num)?;
+                std::num::NonZeroUsize::new(num).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Batch deploy concurrency must be non-zero".to_owned(),
+                    )
+                })?
+            }
+            None => std::num::NonZeroUsize::new(super::DEFAULT_BATCH_DEPLOY_CONCURRENCY).unwrap(),
+        };
+        let batch_deploy_checkpoint = self
+            .take(ConfigOption::RpcBatchDeployCheckpoint)
+            .map(PathBuf::from);
+
+        let class_max_program_felts = self
+            .take(ConfigOption::RpcClassMaxProgramFelts)
+            .map(|num| parse_number(ConfigOption::RpcClassMaxProgramFelts, &num))
+            .transpose()?;
+        let class_validation_strict = self
+            .take(ConfigOption::RpcClassValidationStrict)
+            .map(|enable| parse_bool(ConfigOption::RpcClassValidationStrict, &enable))
+            .transpose()?
+            .unwrap_or(false);
+        let class_check_abi = self
+            .take(ConfigOption::RpcClassCheckAbi)
+            .map(|enable| parse_bool(ConfigOption::RpcClassCheckAbi, &enable))
+            .transpose()?
+            .unwrap_or(false);
+        let class_check_program_schema = self
+            .take(ConfigOption::RpcClassCheckProgramSchema)
+            .map(|enable| parse_bool(ConfigOption::RpcClassCheckProgramSchema, &enable))
+            .transpose()?
+            .unwrap_or(false);
+        let check_signatures = self
+            .take(ConfigOption::RpcCheckSignatures)
+            .map(|enable| parse_bool(ConfigOption::RpcCheckSignatures, &enable))
+            .transpose()?
+            .unwrap_or(false);
+
         Ok(Configuration {
             ethereum: EthereumConfig {
                 url: eth_url,
@@ -176,6 +316,30 @@ Hint: Register your own account or run your own Ethereum node and put the real U
             poll_pending,
             monitoring_addr,
             integration,
+            disabled_rpc_methods,
+            audit_log,
+            replay_log,
+            submission_store,
+            felt_representation,
+            max_fee_cap,
+            fee_budget,
+            fee_budget_window,
+            max_fee_check_bps,
+            max_fee_check_reject,
+            class_source_schemes,
+            class_source_hosts,
+            rate_limits,
+            batch_deploy_addr,
+            batch_deploy_concurrency,
+            batch_deploy_checkpoint,
+            class_max_program_felts,
+            class_validation_strict,
+            class_check_abi,
+            class_check_program_schema,
+            check_signatures,
+            sequencer_offline,
+            sequencer_max_concurrent_submissions,
+            sequencer_slow_request_threshold,
         })
     }
 
@@ -195,6 +359,88 @@ Hint: Register your own account or run your own Ethereum node and put the real U
     }
 }
 
+fn invalid_value(
+    option: ConfigOption,
+    value: &str,
+    reason: impl std::fmt::Display,
+) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Invalid value '{}' for {}: {}", value, option, reason),
+    )
+}
+
+/// Parses a comma separated list, ignoring empty entries.
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+fn parse_bool(option: ConfigOption, value: &str) -> std::io::Result<bool> {
+    match value.to_lowercase().as_str() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(invalid_value(option, value, "must be true|false")),
+    }
+}
+
+fn parse_number<T>(option: ConfigOption, value: &str) -> std::io::Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err| invalid_value(option, value, err))
+}
+
+/// Parses a decimal amount of wei.
+fn parse_fee(option: ConfigOption, value: &str) -> std::io::Result<Fee> {
+    parse_number::<u128>(option, value).map(|fee| Fee(fee.to_be_bytes().into()))
+}
+
+/// Parses a factor with up to four decimals, e.g. `1.25`, into basis points.
+fn parse_basis_points(option: ConfigOption, value: &str) -> std::io::Result<u32> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 4 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid_value(
+            option,
+            value,
+            "must have at most four decimals",
+        ));
+    }
+
+    let whole = parse_number::<u32>(option, whole)?;
+    let fraction = format!("{:0<4}", fraction);
+    let fraction = parse_number::<u32>(option, &fraction)?;
+
+    whole
+        .checked_mul(10_000)
+        .and_then(|bps| bps.checked_add(fraction))
+        .ok_or_else(|| invalid_value(option, value, "too large"))
+}
+
+/// Parses the rate limit of a method, e.g. `starknet_addDeployTransaction=10/0.5` for bursts of
+/// ten calls refilled at one call every two seconds.
+fn parse_rate_limit(option: ConfigOption, value: &str) -> std::io::Result<MethodRateLimit> {
+    let (method, limit) = value
+        .split_once('=')
+        .ok_or_else(|| invalid_value(option, value, "must be method=capacity/per_second"))?;
+    let (capacity, per_second) = limit
+        .split_once('/')
+        .ok_or_else(|| invalid_value(option, value, "must be method=capacity/per_second"))?;
+
+    Ok(MethodRateLimit {
+        method: method.trim().to_owned(),
+        capacity: parse_number(option, capacity.trim())?,
+        per_second_bps: parse_basis_points(option, per_second.trim())?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use enum_iterator::IntoEnumIterator;
@@ -373,6 +619,173 @@ mod tests {
                 let config = builder_with_all_required().try_build().unwrap();
                 assert_eq!(config.sqlite_wal, expected);
             }
+
+            #[test]
+            fn disabled_rpc_methods() {
+                let config = builder_with_all_required().try_build().unwrap();
+                assert!(config.disabled_rpc_methods.is_empty());
+            }
+
+            #[test]
+            fn transaction_safeguards_are_disabled() {
+                let config = builder_with_all_required().try_build().unwrap();
+                assert_eq!(config.max_fee_cap, None);
+                assert_eq!(config.fee_budget, None);
+                assert_eq!(
+                    config.fee_budget_window,
+                    crate::config::DEFAULT_FEE_BUDGET_WINDOW
+                );
+                assert_eq!(config.max_fee_check_bps, None);
+                assert!(!config.max_fee_check_reject);
+                assert!(config.class_source_schemes.is_empty());
+                assert!(config.class_source_hosts.is_empty());
+                assert!(config.rate_limits.is_empty());
+                assert_eq!(config.class_max_program_felts, None);
+                assert!(!config.class_validation_strict);
+                assert!(!config.class_check_abi);
+                assert!(!config.class_check_program_schema);
+                assert!(!config.check_signatures);
+            }
+
+            #[test]
+            fn batch_deploy_is_disabled() {
+                let config = builder_with_all_required().try_build().unwrap();
+                assert_eq!(config.batch_deploy_addr, None);
+                assert_eq!(
+                    config.batch_deploy_concurrency.get(),
+                    crate::config::DEFAULT_BATCH_DEPLOY_CONCURRENCY
+                );
+                assert_eq!(config.batch_deploy_checkpoint, None);
+            }
+
+            #[test]
+            fn sequencer_is_online() {
+                let config = builder_with_all_required().try_build().unwrap();
+                assert!(!config.sequencer_offline);
+                assert_eq!(config.sequencer_max_concurrent_submissions, None);
+                assert_eq!(config.sequencer_slow_request_threshold, None);
+            }
+        }
+
+        #[test]
+        fn max_fee_check() {
+            for (factor, bps) in [
+                ("1", 10_000),
+                ("1.5", 15_000),
+                ("0.0001", 1),
+                ("2.25", 22_500),
+            ] {
+                let config = builder_with_all_required()
+                    .with(ConfigOption::RpcMaxFeeCheck, Some(factor.to_owned()))
+                    .try_build()
+                    .unwrap();
+                assert_eq!(config.max_fee_check_bps, Some(bps), "{factor}");
+            }
+
+            for factor in ["1.00001", "-1", "1.-5", "one"] {
+                let result = builder_with_all_required()
+                    .with(ConfigOption::RpcMaxFeeCheck, Some(factor.to_owned()))
+                    .try_build();
+                assert!(result.is_err(), "{factor}");
+            }
+        }
+
+        #[test]
+        fn fee_budget() {
+            let config = builder_with_all_required()
+                .with(ConfigOption::RpcFeeBudget, Some("1000000".to_owned()))
+                .with(ConfigOption::RpcFeeBudgetWindow, Some("60".to_owned()))
+                .try_build()
+                .unwrap();
+            assert_eq!(
+                config.fee_budget,
+                Some(Fee(1_000_000u128.to_be_bytes().into()))
+            );
+            assert_eq!(config.fee_budget_window, Duration::from_secs(60));
+        }
+
+        #[test]
+        fn rate_limits() {
+            let config = builder_with_all_required()
+                .with(
+                    ConfigOption::RpcRateLimits,
+                    Some("starknet_addDeployTransaction=10/0.5, starknet_call = 100/20".to_owned()),
+                )
+                .try_build()
+                .unwrap();
+            assert_eq!(
+                config.rate_limits,
+                vec![
+                    MethodRateLimit {
+                        method: "starknet_addDeployTransaction".to_owned(),
+                        capacity: 10,
+                        per_second_bps: 5_000,
+                    },
+                    MethodRateLimit {
+                        method: "starknet_call".to_owned(),
+                        capacity: 100,
+                        per_second_bps: 200_000,
+                    },
+                ]
+            );
+
+            for limits in ["starknet_call", "starknet_call=10", "starknet_call=ten/1"] {
+                let result = builder_with_all_required()
+                    .with(ConfigOption::RpcRateLimits, Some(limits.to_owned()))
+                    .try_build();
+                assert!(result.is_err(), "{limits}");
+            }
+        }
+
+        #[test]
+        fn felt_representation() {
+            let config = builder_with_all_required()
+                .with(
+                    ConfigOption::RpcFeltRepresentation,
+                    Some("Decimal".to_owned()),
+                )
+                .try_build()
+                .unwrap();
+            assert_eq!(config.felt_representation, FeltRepresentation::Decimal);
+
+            let result = builder_with_all_required()
+                .with(
+                    ConfigOption::RpcFeltRepresentation,
+                    Some("octal".to_owned()),
+                )
+                .try_build();
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn sequencer_max_concurrent_submissions_must_be_non_zero() {
+            let result = builder_with_all_required()
+                .with(
+                    ConfigOption::SequencerMaxConcurrentSubmissions,
+                    Some("0".to_owned()),
+                )
+                .try_build();
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn disabled_rpc_methods() {
+            let config = builder_with_all_required()
+                .with(
+                    ConfigOption::DisabledRpcMethods,
+                    Some(
+                        "starknet_addDeployTransaction, starknet_addInvokeTransaction,".to_owned(),
+                    ),
+                )
+                .try_build()
+                .unwrap();
+            assert_eq!(
+                config.disabled_rpc_methods,
+                vec![
+                    "starknet_addDeployTransaction".to_owned(),
+                    "starknet_addInvokeTransaction".to_owned()
+                ]
+            );
         }
     }
 }
@@ -17,6 +17,30 @@ const SQLITE_WAL: &str = "sqlite-wal";
 const POLL_PENDING: &str = "poll-pending";
 const MONITOR_ADDRESS: &str = "monitor-address";
 const INTEGRATION: &str = "integration";
+const RPC_DISABLED_METHODS: &str = "rpc-disabled-methods";
+const RPC_AUDIT_LOG: &str = "rpc-audit-log";
+const RPC_REPLAY_LOG: &str = "rpc-replay-log";
+const RPC_SUBMISSION_STORE: &str = "rpc-submission-store";
+const RPC_FELT_REPRESENTATION: &str = "rpc-felt-representation";
+const RPC_MAX_FEE_CAP: &str = "rpc-max-fee-cap";
+const RPC_FEE_BUDGET: &str = "rpc-fee-budget";
+const RPC_FEE_BUDGET_WINDOW: &str = "rpc-fee-budget-window";
+const RPC_MAX_FEE_CHECK: &str = "rpc-max-fee-check";
+const RPC_MAX_FEE_CHECK_REJECT: &str = "rpc-max-fee-check-reject";
+const RPC_CLASS_SOURCE_SCHEMES: &str = "rpc-class-source-schemes";
+const RPC_CLASS_SOURCE_HOSTS: &str = "rpc-class-source-hosts";
+const RPC_RATE_LIMITS: &str = "rpc-rate-limits";
+const RPC_BATCH_DEPLOY_ADDRESS: &str = "rpc-batch-deploy-address";
+const RPC_BATCH_DEPLOY_CONCURRENCY: &str = "rpc-batch-deploy-concurrency";
+const RPC_BATCH_DEPLOY_CHECKPOINT: &str = "rpc-batch-deploy-checkpoint";
+const RPC_CLASS_MAX_PROGRAM_FELTS: &str = "rpc-class-max-program-felts";
+const RPC_CLASS_VALIDATION_STRICT: &str = "rpc-class-validation-strict";
+const RPC_CLASS_CHECK_ABI: &str = "rpc-class-check-abi";
+const RPC_CLASS_CHECK_PROGRAM_SCHEMA: &str = "rpc-class-check-program-schema";
+const RPC_CHECK_SIGNATURES: &str = "rpc-check-signatures";
+const SEQUENCER_OFFLINE: &str = "sequencer-offline";
+const SEQUENCER_MAX_CONCURRENT_SUBMISSIONS: &str = "sequencer-max-concurrent-submissions";
+const SEQUENCER_SLOW_REQUEST_THRESHOLD: &str = "sequencer-slow-request-threshold";
 
 /// Parses the cmd line arguments and returns the optional
 /// configuration file's path and the specified configuration options.
@@ -53,6 +77,50 @@ where
     let monitor_address = args.value_of(MONITOR_ADDRESS).map(|s| s.to_owned());
     // Hack around our builder requiring Strings, but this arg just needs to be present.
     let integration = args.is_present(INTEGRATION).then_some(String::new());
+    let disabled_rpc_methods = args.value_of(RPC_DISABLED_METHODS).map(|s| s.to_owned());
+    let rpc_audit_log = args.value_of(RPC_AUDIT_LOG).map(|s| s.to_owned());
+    let rpc_replay_log = args.value_of(RPC_REPLAY_LOG).map(|s| s.to_owned());
+    let rpc_submission_store = args.value_of(RPC_SUBMISSION_STORE).map(|s| s.to_owned());
+    let rpc_felt_representation = args.value_of(RPC_FELT_REPRESENTATION).map(|s| s.to_owned());
+    let rpc_max_fee_cap = args.value_of(RPC_MAX_FEE_CAP).map(|s| s.to_owned());
+    let rpc_fee_budget = args.value_of(RPC_FEE_BUDGET).map(|s| s.to_owned());
+    let rpc_fee_budget_window = args.value_of(RPC_FEE_BUDGET_WINDOW).map(|s| s.to_owned());
+    let rpc_max_fee_check = args.value_of(RPC_MAX_FEE_CHECK).map(|s| s.to_owned());
+    let rpc_max_fee_check_reject = args
+        .value_of(RPC_MAX_FEE_CHECK_REJECT)
+        .map(|s| s.to_owned());
+    let rpc_class_source_schemes = args
+        .value_of(RPC_CLASS_SOURCE_SCHEMES)
+        .map(|s| s.to_owned());
+    let rpc_class_source_hosts = args.value_of(RPC_CLASS_SOURCE_HOSTS).map(|s| s.to_owned());
+    let rpc_rate_limits = args.value_of(RPC_RATE_LIMITS).map(|s| s.to_owned());
+    let rpc_batch_deploy_address = args
+        .value_of(RPC_BATCH_DEPLOY_ADDRESS)
+        .map(|s| s.to_owned());
+    let rpc_batch_deploy_concurrency = args
+        .value_of(RPC_BATCH_DEPLOY_CONCURRENCY)
+        .map(|s| s.to_owned());
+    let rpc_batch_deploy_checkpoint = args
+        .value_of(RPC_BATCH_DEPLOY_CHECKPOINT)
+        .map(|s| s.to_owned());
+    let rpc_class_max_program_felts = args
+        .value_of(RPC_CLASS_MAX_PROGRAM_FELTS)
+        .map(|s| s.to_owned());
+    let rpc_class_validation_strict = args
+        .value_of(RPC_CLASS_VALIDATION_STRICT)
+        .map(|s| s.to_owned());
+    let rpc_class_check_abi = args.value_of(RPC_CLASS_CHECK_ABI).map(|s| s.to_owned());
+    let rpc_class_check_program_schema = args
+        .value_of(RPC_CLASS_CHECK_PROGRAM_SCHEMA)
+        .map(|s| s.to_owned());
+    let rpc_check_signatures = args.value_of(RPC_CHECK_SIGNATURES).map(|s| s.to_owned());
+    let sequencer_offline = args.value_of(SEQUENCER_OFFLINE).map(|s| s.to_owned());
+    let sequencer_max_concurrent_submissions = args
+        .value_of(SEQUENCER_MAX_CONCURRENT_SUBMISSIONS)
+        .map(|s| s.to_owned());
+    let sequencer_slow_request_threshold = args
+        .value_of(SEQUENCER_SLOW_REQUEST_THRESHOLD)
+        .map(|s| s.to_owned());
 
     let cfg = ConfigBuilder::default()
         .with(ConfigOption::EthereumHttpUrl, ethereum_url)
@@ -64,7 +132,58 @@ where
         .with(ConfigOption::EnableSQLiteWriteAheadLogging, sqlite_wal)
         .with(ConfigOption::PollPending, poll_pending)
         .with(ConfigOption::MonitorAddress, monitor_address)
-        .with(ConfigOption::Integration, integration);
+        .with(ConfigOption::Integration, integration)
+        .with(ConfigOption::DisabledRpcMethods, disabled_rpc_methods)
+        .with(ConfigOption::RpcAuditLog, rpc_audit_log)
+        .with(ConfigOption::RpcReplayLog, rpc_replay_log)
+        .with(ConfigOption::RpcSubmissionStore, rpc_submission_store)
+        .with(ConfigOption::RpcFeltRepresentation, rpc_felt_representation)
+        .with(ConfigOption::RpcMaxFeeCap, rpc_max_fee_cap)
+        .with(ConfigOption::RpcFeeBudget, rpc_fee_budget)
+        .with(ConfigOption::RpcFeeBudgetWindow, rpc_fee_budget_window)
+        .with(ConfigOption::RpcMaxFeeCheck, rpc_max_fee_check)
+        .with(ConfigOption::RpcMaxFeeCheckReject, rpc_max_fee_check_reject)
+        .with(
+            ConfigOption::RpcClassSourceSchemes,
+            rpc_class_source_schemes,
+        )
+        .with(ConfigOption::RpcClassSourceHosts, rpc_class_source_hosts)
+        .with(ConfigOption::RpcRateLimits, rpc_rate_limits)
+        .with(
+            ConfigOption::RpcBatchDeployAddress,
+            rpc_batch_deploy_address,
+        )
+        .with(
+            ConfigOption::RpcBatchDeployConcurrency,
+            rpc_batch_deploy_concurrency,
+        )
+        .with(
+            ConfigOption::RpcBatchDeployCheckpoint,
+            rpc_batch_deploy_checkpoint,
+        )
+        .with(
+            ConfigOption::RpcClassMaxProgramFelts,
+            rpc_class_max_program_felts,
+        )
+        .with(
+            ConfigOption::RpcClassValidationStrict,
+            rpc_class_validation_strict,
+        )
+        .with(ConfigOption::RpcClassCheckAbi, rpc_class_check_abi)
+        .with(
+            ConfigOption::RpcClassCheckProgramSchema,
+            rpc_class_check_program_schema,
+        )
+        .with(ConfigOption::RpcCheckSignatures, rpc_check_signatures)
+        .with(ConfigOption::SequencerOffline, sequencer_offline)
+        .with(
+            ConfigOption::SequencerMaxConcurrentSubmissions,
+            sequencer_max_concurrent_submissions,
+        )
+        .with(
+            ConfigOption::SequencerSlowRequestThreshold,
+            sequencer_slow_request_threshold,
+        );
 
     Ok((config_filepath, cfg))
 }
@@ -168,6 +287,215 @@ Examples:
                 .value_name("IP:PORT")
                 .env("PATHFINDER_MONITOR_ADDRESS")
         )
+        .arg(
+            Arg::new(RPC_DISABLED_METHODS)
+                .long(RPC_DISABLED_METHODS)
+                .help("Comma separated RPC methods to disable")
+                .long_help("Calls to these RPC methods fail with 'Method not found', e.g. to disable transaction submission with 'starknet_addDeployTransaction,starknet_addInvokeTransaction'. Unknown methods fail the startup.")
+                .takes_value(true)
+                .value_name("METHODS")
+                .env("PATHFINDER_RPC_DISABLED_METHODS")
+        )
+        .arg(
+            Arg::new(RPC_AUDIT_LOG)
+                .long(RPC_AUDIT_LOG)
+                .help("Audit log of submitted transactions")
+                .long_help("Appends a record of every transaction submitted through the RPC API to this file.")
+                .takes_value(true)
+                .value_name("PATH")
+                .env("PATHFINDER_RPC_AUDIT_LOG")
+        )
+        .arg(
+            Arg::new(RPC_REPLAY_LOG)
+                .long(RPC_REPLAY_LOG)
+                .help("Replay log of submitted deploys")
+                .long_help("Appends every deploy submitted through the RPC API to this file, so that they can be replayed on another network.")
+                .takes_value(true)
+                .value_name("PATH")
+                .env("PATHFINDER_RPC_REPLAY_LOG")
+        )
+        .arg(
+            Arg::new(RPC_SUBMISSION_STORE)
+                .long(RPC_SUBMISSION_STORE)
+                .help("Store submitted transactions")
+                .long_help("Stores the transactions submitted through the RPC API in the data directory.")
+                .takes_value(true)
+                .value_name("TRUE/FALSE")
+                .env("PATHFINDER_RPC_SUBMISSION_STORE")
+        )
+        .arg(
+            Arg::new(RPC_FELT_REPRESENTATION)
+                .long(RPC_FELT_REPRESENTATION)
+                .help("Representation of field elements in RPC outputs")
+                .long_help("Field elements in the outputs of the v0.2 RPC API are hex strings as required by the specification, or decimal strings for clients which cannot handle hex. [default: hex]")
+                .takes_value(true)
+                .value_name("HEX/DECIMAL")
+                .env("PATHFINDER_RPC_FELT_REPRESENTATION")
+        )
+        .arg(
+            Arg::new(RPC_MAX_FEE_CAP)
+                .long(RPC_MAX_FEE_CAP)
+                .help("Largest max fee of submitted transactions")
+                .long_help("Transactions with a larger max fee, in wei, are rejected without being submitted.")
+                .takes_value(true)
+                .value_name("WEI")
+                .env("PATHFINDER_RPC_MAX_FEE_CAP")
+        )
+        .arg(
+            Arg::new(RPC_FEE_BUDGET)
+                .long(RPC_FEE_BUDGET)
+                .help("Total max fee of the transactions submitted within the fee budget window")
+                .long_help("Transactions which would take the total max fee, in wei, of the transactions submitted within the fee budget window over this budget are rejected without being submitted.")
+                .takes_value(true)
+                .value_name("WEI")
+                .env("PATHFINDER_RPC_FEE_BUDGET")
+        )
+        .arg(
+            Arg::new(RPC_FEE_BUDGET_WINDOW)
+                .long(RPC_FEE_BUDGET_WINDOW)
+                .help("Length of the fee budget window in seconds [default: 3600]")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .env("PATHFINDER_RPC_FEE_BUDGET_WINDOW")
+        )
+        .arg(
+            Arg::new(RPC_MAX_FEE_CHECK)
+                .long(RPC_MAX_FEE_CHECK)
+                .help("Compare the max fee of submitted transactions to a fee estimate")
+                .long_help("Transactions whose max fee does not cover a live fee estimate times this factor, e.g. 1.5, are logged, or rejected if rpc-max-fee-check-reject is set.")
+                .takes_value(true)
+                .value_name("FACTOR")
+                .env("PATHFINDER_RPC_MAX_FEE_CHECK")
+        )
+        .arg(
+            Arg::new(RPC_MAX_FEE_CHECK_REJECT)
+                .long(RPC_MAX_FEE_CHECK_REJECT)
+                .help("Reject transactions failing the max fee check")
+                .takes_value(true)
+                .value_name("TRUE/FALSE")
+                .env("PATHFINDER_RPC_MAX_FEE_CHECK_REJECT")
+        )
+        .arg(
+            Arg::new(RPC_CLASS_SOURCE_SCHEMES)
+                .long(RPC_CLASS_SOURCE_SCHEMES)
+                .help("Comma separated URL schemes of class sources")
+                .long_help("Deploys may reference their class by a URL of one of these schemes, e.g. 'https', instead of inlining it. Disabled if not set.")
+                .takes_value(true)
+                .value_name("SCHEMES")
+                .env("PATHFINDER_RPC_CLASS_SOURCE_SCHEMES")
+        )
+        .arg(
+            Arg::new(RPC_CLASS_SOURCE_HOSTS)
+                .long(RPC_CLASS_SOURCE_HOSTS)
+                .help("Comma separated hosts of class sources")
+                .long_help("Class sources are only fetched from these hosts and their subdomains. Any host except for loopback and private addresses is allowed if not set.")
+                .takes_value(true)
+                .value_name("HOSTS")
+                .env("PATHFINDER_RPC_CLASS_SOURCE_HOSTS")
+        )
+        .arg(
+            Arg::new(RPC_RATE_LIMITS)
+                .long(RPC_RATE_LIMITS)
+                .help("Comma separated rate limits of RPC methods")
+                .long_help("Limits calls to RPC methods, e.g. 'starknet_addDeployTransaction=10/0.5' serves bursts of 10 deploys and one more every 2 seconds. Methods without a limit are never throttled.")
+                .takes_value(true)
+                .value_name("METHOD=CAPACITY/PER_SECOND")
+                .env("PATHFINDER_RPC_RATE_LIMITS")
+        )
+        .arg(
+            Arg::new(RPC_BATCH_DEPLOY_ADDRESS)
+                .long(RPC_BATCH_DEPLOY_ADDRESS)
+                .help("Batch deploy listening address")
+                .long_help("The address at which pathfinder serves newline-delimited deploy inputs at 'POST /deploy/batch'. Disabled if not set.")
+                .takes_value(true)
+                .value_name("IP:PORT")
+                .env("PATHFINDER_RPC_BATCH_DEPLOY_ADDRESS")
+        )
+        .arg(
+            Arg::new(RPC_BATCH_DEPLOY_CONCURRENCY)
+                .long(RPC_BATCH_DEPLOY_CONCURRENCY)
+                .help("Number of batch deploy inputs submitted at a time")
+                .takes_value(true)
+                .value_name("NUM")
+                .env("PATHFINDER_RPC_BATCH_DEPLOY_CONCURRENCY")
+        )
+        .arg(
+            Arg::new(RPC_BATCH_DEPLOY_CHECKPOINT)
+                .long(RPC_BATCH_DEPLOY_CHECKPOINT)
+                .help("Path of the batch deploy checkpoint")
+                .long_help("Batch deploy inputs recorded in this file are not submitted again, so that an interrupted batch can be resumed. Disabled if not set.")
+                .takes_value(true)
+                .value_name("PATH")
+                .env("PATHFINDER_RPC_BATCH_DEPLOY_CHECKPOINT")
+        )
+        .arg(
+            Arg::new(RPC_CLASS_MAX_PROGRAM_FELTS)
+                .long(RPC_CLASS_MAX_PROGRAM_FELTS)
+                .help("Number of felts the programs of submitted classes may have")
+                .long_help("Classes of declares and deploys with larger programs are warned about, or rejected if rpc-class-validation-strict is set. Defaults to unlimited.")
+                .takes_value(true)
+                .value_name("FELTS")
+                .env("PATHFINDER_RPC_CLASS_MAX_PROGRAM_FELTS")
+        )
+        .arg(
+            Arg::new(RPC_CLASS_VALIDATION_STRICT)
+                .long(RPC_CLASS_VALIDATION_STRICT)
+                .help("Reject submitted classes exceeding a limit instead of warning about them")
+                .takes_value(true)
+                .value_name("BOOL")
+                .env("PATHFINDER_RPC_CLASS_VALIDATION_STRICT")
+        )
+        .arg(
+            Arg::new(RPC_CLASS_CHECK_ABI)
+                .long(RPC_CLASS_CHECK_ABI)
+                .help("Reject submitted classes whose ABI does not match their external entry points")
+                .takes_value(true)
+                .value_name("BOOL")
+                .env("PATHFINDER_RPC_CLASS_CHECK_ABI")
+        )
+        .arg(
+            Arg::new(RPC_CLASS_CHECK_PROGRAM_SCHEMA)
+                .long(RPC_CLASS_CHECK_PROGRAM_SCHEMA)
+                .help("Reject submitted classes whose programs do not match the Cairo 0 program schema")
+                .long_help("Costly for large programs, since each program is decompressed and parsed.")
+                .takes_value(true)
+                .value_name("BOOL")
+                .env("PATHFINDER_RPC_CLASS_CHECK_PROGRAM_SCHEMA")
+        )
+        .arg(
+            Arg::new(RPC_CHECK_SIGNATURES)
+                .long(RPC_CHECK_SIGNATURES)
+                .help("Reject submitted transactions whose signatures are not made of (r, s) pairs")
+                .long_help("Only enable this if all accounts submitting through this node use the standard signature layout. Other account contracts may accept signatures of any shape.")
+                .takes_value(true)
+                .value_name("BOOL")
+                .env("PATHFINDER_RPC_CHECK_SIGNATURES")
+        )
+        .arg(
+            Arg::new(SEQUENCER_OFFLINE)
+                .long(SEQUENCER_OFFLINE)
+                .help("Fail all Sequencer requests and class source fetches instead of sending them")
+                .takes_value(true)
+                .value_name("TRUE/FALSE")
+                .env("PATHFINDER_SEQUENCER_OFFLINE")
+        )
+        .arg(
+            Arg::new(SEQUENCER_MAX_CONCURRENT_SUBMISSIONS)
+                .long(SEQUENCER_MAX_CONCURRENT_SUBMISSIONS)
+                .help("Number of transactions submitted to the Sequencer at a time")
+                .long_help("Further submissions wait until earlier ones complete. Unlimited if not set.")
+                .takes_value(true)
+                .value_name("NUM")
+                .env("PATHFINDER_SEQUENCER_MAX_CONCURRENT_SUBMISSIONS")
+        )
+        .arg(
+            Arg::new(SEQUENCER_SLOW_REQUEST_THRESHOLD)
+                .long(SEQUENCER_SLOW_REQUEST_THRESHOLD)
+                .help("Log Sequencer requests taking this many milliseconds")
+                .takes_value(true)
+                .value_name("MILLISECONDS")
+                .env("PATHFINDER_SEQUENCER_SLOW_REQUEST_THRESHOLD")
+        )
         .arg(
             Arg::new(INTEGRATION)
                 .long(INTEGRATION)
@@ -198,6 +526,30 @@ mod tests {
         env::remove_var("PATHFINDER_SQLITE_WAL");
         env::remove_var("PATHFINDER_POLL_PENDING");
         env::remove_var("PATHFINDER_MONITOR_ADDRESS");
+        env::remove_var("PATHFINDER_RPC_DISABLED_METHODS");
+        env::remove_var("PATHFINDER_RPC_AUDIT_LOG");
+        env::remove_var("PATHFINDER_RPC_REPLAY_LOG");
+        env::remove_var("PATHFINDER_RPC_SUBMISSION_STORE");
+        env::remove_var("PATHFINDER_RPC_FELT_REPRESENTATION");
+        env::remove_var("PATHFINDER_RPC_MAX_FEE_CAP");
+        env::remove_var("PATHFINDER_RPC_FEE_BUDGET");
+        env::remove_var("PATHFINDER_RPC_FEE_BUDGET_WINDOW");
+        env::remove_var("PATHFINDER_RPC_MAX_FEE_CHECK");
+        env::remove_var("PATHFINDER_RPC_MAX_FEE_CHECK_REJECT");
+        env::remove_var("PATHFINDER_RPC_CLASS_SOURCE_SCHEMES");
+        env::remove_var("PATHFINDER_RPC_CLASS_SOURCE_HOSTS");
+        env::remove_var("PATHFINDER_RPC_RATE_LIMITS");
+        env::remove_var("PATHFINDER_RPC_BATCH_DEPLOY_ADDRESS");
+        env::remove_var("PATHFINDER_RPC_BATCH_DEPLOY_CONCURRENCY");
+        env::remove_var("PATHFINDER_RPC_BATCH_DEPLOY_CHECKPOINT");
+        env::remove_var("PATHFINDER_RPC_CLASS_MAX_PROGRAM_FELTS");
+        env::remove_var("PATHFINDER_RPC_CLASS_VALIDATION_STRICT");
+        env::remove_var("PATHFINDER_RPC_CLASS_CHECK_ABI");
+        env::remove_var("PATHFINDER_RPC_CLASS_CHECK_PROGRAM_SCHEMA");
+        env::remove_var("PATHFINDER_RPC_CHECK_SIGNATURES");
+        env::remove_var("PATHFINDER_SEQUENCER_OFFLINE");
+        env::remove_var("PATHFINDER_SEQUENCER_MAX_CONCURRENT_SUBMISSIONS");
+        env::remove_var("PATHFINDER_SEQUENCER_SLOW_REQUEST_THRESHOLD");
     }
 
     #[test]
@@ -415,6 +767,566 @@ mod tests {
         assert_eq!(cfg.take(ConfigOption::MonitorAddress), Some(value));
     }
 
+    #[test]
+    fn rpc_disabled_methods_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-disabled-methods", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::DisabledRpcMethods), Some(value));
+    }
+
+    #[test]
+    fn rpc_disabled_methods_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_DISABLED_METHODS", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::DisabledRpcMethods), Some(value));
+    }
+
+    #[test]
+    fn rpc_audit_log_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-audit-log", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcAuditLog), Some(value));
+    }
+
+    #[test]
+    fn rpc_audit_log_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_AUDIT_LOG", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcAuditLog), Some(value));
+    }
+
+    #[test]
+    fn rpc_replay_log_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-replay-log", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcReplayLog), Some(value));
+    }
+
+    #[test]
+    fn rpc_replay_log_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_REPLAY_LOG", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcReplayLog), Some(value));
+    }
+
+    #[test]
+    fn rpc_submission_store_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-submission-store", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcSubmissionStore), Some(value));
+    }
+
+    #[test]
+    fn rpc_submission_store_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_SUBMISSION_STORE", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcSubmissionStore), Some(value));
+    }
+
+    #[test]
+    fn rpc_felt_representation_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-felt-representation", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeltRepresentation), Some(value));
+    }
+
+    #[test]
+    fn rpc_felt_representation_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_FELT_REPRESENTATION", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeltRepresentation), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_cap_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-max-fee-cap", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCap), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_cap_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_MAX_FEE_CAP", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCap), Some(value));
+    }
+
+    #[test]
+    fn rpc_fee_budget_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-fee-budget", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeeBudget), Some(value));
+    }
+
+    #[test]
+    fn rpc_fee_budget_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_FEE_BUDGET", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeeBudget), Some(value));
+    }
+
+    #[test]
+    fn rpc_fee_budget_window_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-fee-budget-window", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeeBudgetWindow), Some(value));
+    }
+
+    #[test]
+    fn rpc_fee_budget_window_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_FEE_BUDGET_WINDOW", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeeBudgetWindow), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_check_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-max-fee-check", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCheck), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_check_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_MAX_FEE_CHECK", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCheck), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_check_reject_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-max-fee-check-reject", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCheckReject), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_check_reject_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_MAX_FEE_CHECK_REJECT", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCheckReject), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_source_schemes_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-class-source-schemes", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassSourceSchemes), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_source_schemes_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_CLASS_SOURCE_SCHEMES", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassSourceSchemes), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_source_hosts_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-class-source-hosts", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassSourceHosts), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_source_hosts_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_CLASS_SOURCE_HOSTS", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassSourceHosts), Some(value));
+    }
+
+    #[test]
+    fn rpc_rate_limits_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-rate-limits", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcRateLimits), Some(value));
+    }
+
+    #[test]
+    fn rpc_rate_limits_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_RATE_LIMITS", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcRateLimits), Some(value));
+    }
+
+    #[test]
+    fn rpc_batch_deploy_address_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-batch-deploy-address", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcBatchDeployAddress), Some(value));
+    }
+
+    #[test]
+    fn rpc_batch_deploy_address_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_BATCH_DEPLOY_ADDRESS", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcBatchDeployAddress), Some(value));
+    }
+
+    #[test]
+    fn rpc_batch_deploy_concurrency_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-batch-deploy-concurrency", &value]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcBatchDeployConcurrency),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_batch_deploy_concurrency_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_BATCH_DEPLOY_CONCURRENCY", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcBatchDeployConcurrency),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_batch_deploy_checkpoint_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-batch-deploy-checkpoint", &value]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcBatchDeployCheckpoint),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_batch_deploy_checkpoint_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_BATCH_DEPLOY_CHECKPOINT", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcBatchDeployCheckpoint),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_class_max_program_felts_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-class-max-program-felts", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassMaxProgramFelts), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_max_program_felts_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_CLASS_MAX_PROGRAM_FELTS", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassMaxProgramFelts), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_validation_strict_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-class-validation-strict", &value]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcClassValidationStrict),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_class_validation_strict_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_CLASS_VALIDATION_STRICT", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcClassValidationStrict),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_class_check_abi_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-class-check-abi", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassCheckAbi), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_check_abi_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_CLASS_CHECK_ABI", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassCheckAbi), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_check_program_schema_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) =
+            parse_args(vec!["bin name", "--rpc-class-check-program-schema", &value]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcClassCheckProgramSchema),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_class_check_program_schema_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_CLASS_CHECK_PROGRAM_SCHEMA", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcClassCheckProgramSchema),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_check_signatures_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--rpc-check-signatures", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcCheckSignatures), Some(value));
+    }
+
+    #[test]
+    fn rpc_check_signatures_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_RPC_CHECK_SIGNATURES", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcCheckSignatures), Some(value));
+    }
+
+    #[test]
+    fn sequencer_offline_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--sequencer-offline", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::SequencerOffline), Some(value));
+    }
+
+    #[test]
+    fn sequencer_offline_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_SEQUENCER_OFFLINE", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::SequencerOffline), Some(value));
+    }
+
+    #[test]
+    fn sequencer_max_concurrent_submissions_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec![
+            "bin name",
+            "--sequencer-max-concurrent-submissions",
+            &value,
+        ])
+        .unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerMaxConcurrentSubmissions),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn sequencer_max_concurrent_submissions_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_SEQUENCER_MAX_CONCURRENT_SUBMISSIONS", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerMaxConcurrentSubmissions),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn sequencer_slow_request_threshold_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec![
+            "bin name",
+            "--sequencer-slow-request-threshold",
+            &value,
+        ])
+        .unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerSlowRequestThreshold),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn sequencer_slow_request_threshold_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_SEQUENCER_SLOW_REQUEST_THRESHOLD", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerSlowRequestThreshold),
+            Some(value)
+        );
+    }
+
     #[test]
     fn empty_config() {
         let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
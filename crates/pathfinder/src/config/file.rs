@@ -26,6 +26,54 @@ struct FileConfig {
     poll_pending: Option<String>,
     #[serde(rename = "monitor-address")]
     monitor_address: Option<String>,
+    #[serde(rename = "rpc-disabled-methods")]
+    rpc_disabled_methods: Option<String>,
+    #[serde(rename = "rpc-audit-log")]
+    rpc_audit_log: Option<String>,
+    #[serde(rename = "rpc-replay-log")]
+    rpc_replay_log: Option<String>,
+    #[serde(rename = "rpc-submission-store")]
+    rpc_submission_store: Option<String>,
+    #[serde(rename = "rpc-felt-representation")]
+    rpc_felt_representation: Option<String>,
+    #[serde(rename = "rpc-max-fee-cap")]
+    rpc_max_fee_cap: Option<String>,
+    #[serde(rename = "rpc-fee-budget")]
+    rpc_fee_budget: Option<String>,
+    #[serde(rename = "rpc-fee-budget-window")]
+    rpc_fee_budget_window: Option<String>,
+    #[serde(rename = "rpc-max-fee-check")]
+    rpc_max_fee_check: Option<String>,
+    #[serde(rename = "rpc-max-fee-check-reject")]
+    rpc_max_fee_check_reject: Option<String>,
+    #[serde(rename = "rpc-class-source-schemes")]
+    rpc_class_source_schemes: Option<String>,
+    #[serde(rename = "rpc-class-source-hosts")]
+    rpc_class_source_hosts: Option<String>,
+    #[serde(rename = "rpc-rate-limits")]
+    rpc_rate_limits: Option<String>,
+    #[serde(rename = "rpc-batch-deploy-address")]
+    rpc_batch_deploy_address: Option<String>,
+    #[serde(rename = "rpc-batch-deploy-concurrency")]
+    rpc_batch_deploy_concurrency: Option<String>,
+    #[serde(rename = "rpc-batch-deploy-checkpoint")]
+    rpc_batch_deploy_checkpoint: Option<String>,
+    #[serde(rename = "rpc-class-max-program-felts")]
+    rpc_class_max_program_felts: Option<String>,
+    #[serde(rename = "rpc-class-validation-strict")]
+    rpc_class_validation_strict: Option<String>,
+    #[serde(rename = "rpc-class-check-abi")]
+    rpc_class_check_abi: Option<String>,
+    #[serde(rename = "rpc-class-check-program-schema")]
+    rpc_class_check_program_schema: Option<String>,
+    #[serde(rename = "rpc-check-signatures")]
+    rpc_check_signatures: Option<String>,
+    #[serde(rename = "sequencer-offline")]
+    sequencer_offline: Option<String>,
+    #[serde(rename = "sequencer-max-concurrent-submissions")]
+    sequencer_max_concurrent_submissions: Option<String>,
+    #[serde(rename = "sequencer-slow-request-threshold")]
+    sequencer_slow_request_threshold: Option<String>,
 }
 
 impl FileConfig {
@@ -44,6 +92,66 @@ impl FileConfig {
         .with(ConfigOption::EnableSQLiteWriteAheadLogging, self.sqlite_wal)
         .with(ConfigOption::PollPending, self.poll_pending)
         .with(ConfigOption::MonitorAddress, self.monitor_address)
+        .with(ConfigOption::DisabledRpcMethods, self.rpc_disabled_methods)
+        .with(ConfigOption::RpcAuditLog, self.rpc_audit_log)
+        .with(ConfigOption::RpcReplayLog, self.rpc_replay_log)
+        .with(ConfigOption::RpcSubmissionStore, self.rpc_submission_store)
+        .with(
+            ConfigOption::RpcFeltRepresentation,
+            self.rpc_felt_representation,
+        )
+        .with(ConfigOption::RpcMaxFeeCap, self.rpc_max_fee_cap)
+        .with(ConfigOption::RpcFeeBudget, self.rpc_fee_budget)
+        .with(ConfigOption::RpcFeeBudgetWindow, self.rpc_fee_budget_window)
+        .with(ConfigOption::RpcMaxFeeCheck, self.rpc_max_fee_check)
+        .with(
+            ConfigOption::RpcMaxFeeCheckReject,
+            self.rpc_max_fee_check_reject,
+        )
+        .with(
+            ConfigOption::RpcClassSourceSchemes,
+            self.rpc_class_source_schemes,
+        )
+        .with(
+            ConfigOption::RpcClassSourceHosts,
+            self.rpc_class_source_hosts,
+        )
+        .with(ConfigOption::RpcRateLimits, self.rpc_rate_limits)
+        .with(
+            ConfigOption::RpcBatchDeployAddress,
+            self.rpc_batch_deploy_address,
+        )
+        .with(
+            ConfigOption::RpcBatchDeployConcurrency,
+            self.rpc_batch_deploy_concurrency,
+        )
+        .with(
+            ConfigOption::RpcBatchDeployCheckpoint,
+            self.rpc_batch_deploy_checkpoint,
+        )
+        .with(
+            ConfigOption::RpcClassMaxProgramFelts,
+            self.rpc_class_max_program_felts,
+        )
+        .with(
+            ConfigOption::RpcClassValidationStrict,
+            self.rpc_class_validation_strict,
+        )
+        .with(ConfigOption::RpcClassCheckAbi, self.rpc_class_check_abi)
+        .with(
+            ConfigOption::RpcClassCheckProgramSchema,
+            self.rpc_class_check_program_schema,
+        )
+        .with(ConfigOption::RpcCheckSignatures, self.rpc_check_signatures)
+        .with(ConfigOption::SequencerOffline, self.sequencer_offline)
+        .with(
+            ConfigOption::SequencerMaxConcurrentSubmissions,
+            self.sequencer_max_concurrent_submissions,
+        )
+        .with(
+            ConfigOption::SequencerSlowRequestThreshold,
+            self.sequencer_slow_request_threshold,
+        )
     }
 }
 
@@ -156,6 +264,216 @@ password = "{}""#,
         assert_eq!(cfg.take(ConfigOption::MonitorAddress), Some(value));
     }
 
+    #[test]
+    fn rpc_disabled_methods() {
+        let value = "starknet_addDeployTransaction".to_owned();
+        let toml = format!(r#"rpc-disabled-methods = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::DisabledRpcMethods), Some(value));
+    }
+
+    #[test]
+    fn rpc_audit_log() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-audit-log = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcAuditLog), Some(value));
+    }
+
+    #[test]
+    fn rpc_replay_log() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-replay-log = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcReplayLog), Some(value));
+    }
+
+    #[test]
+    fn rpc_submission_store() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-submission-store = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcSubmissionStore), Some(value));
+    }
+
+    #[test]
+    fn rpc_felt_representation() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-felt-representation = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeltRepresentation), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_cap() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-max-fee-cap = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCap), Some(value));
+    }
+
+    #[test]
+    fn rpc_fee_budget() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-fee-budget = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeeBudget), Some(value));
+    }
+
+    #[test]
+    fn rpc_fee_budget_window() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-fee-budget-window = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcFeeBudgetWindow), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_check() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-max-fee-check = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCheck), Some(value));
+    }
+
+    #[test]
+    fn rpc_max_fee_check_reject() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-max-fee-check-reject = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxFeeCheckReject), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_source_schemes() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-class-source-schemes = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassSourceSchemes), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_source_hosts() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-class-source-hosts = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassSourceHosts), Some(value));
+    }
+
+    #[test]
+    fn rpc_rate_limits() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-rate-limits = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcRateLimits), Some(value));
+    }
+
+    #[test]
+    fn rpc_batch_deploy_address() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-batch-deploy-address = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcBatchDeployAddress), Some(value));
+    }
+
+    #[test]
+    fn rpc_batch_deploy_concurrency() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-batch-deploy-concurrency = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcBatchDeployConcurrency),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_batch_deploy_checkpoint() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-batch-deploy-checkpoint = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcBatchDeployCheckpoint),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_class_max_program_felts() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-class-max-program-felts = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassMaxProgramFelts), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_validation_strict() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-class-validation-strict = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcClassValidationStrict),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_class_check_abi() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-class-check-abi = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcClassCheckAbi), Some(value));
+    }
+
+    #[test]
+    fn rpc_class_check_program_schema() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-class-check-program-schema = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcClassCheckProgramSchema),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn rpc_check_signatures() {
+        let value = "value".to_owned();
+        let toml = format!(r#"rpc-check-signatures = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcCheckSignatures), Some(value));
+    }
+
+    #[test]
+    fn sequencer_offline() {
+        let value = "value".to_owned();
+        let toml = format!(r#"sequencer-offline = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::SequencerOffline), Some(value));
+    }
+
+    #[test]
+    fn sequencer_max_concurrent_submissions() {
+        let value = "value".to_owned();
+        let toml = format!(r#"sequencer-max-concurrent-submissions = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerMaxConcurrentSubmissions),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn sequencer_slow_request_threshold() {
+        let value = "value".to_owned();
+        let toml = format!(r#"sequencer-slow-request-threshold = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerSlowRequestThreshold),
+            Some(value)
+        );
+    }
+
     #[test]
     fn empty_config() {
         let cfg = config_from_str("").unwrap();
@@ -28,11 +28,29 @@ impl ContractNonce {
 #[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct ContractAddressSalt(pub StarkHash);
 
+impl ContractAddressSalt {
+    /// Derives a salt from `seed`, which makes deploy addresses reproducible from a memorable
+    /// seed, e.g. across environments.
+    ///
+    /// This is the Keccak256 digest of `seed` truncated to 250 bits, the same hash as is used
+    /// for [entry point selectors](EntryPoint::hashed).
+    pub fn from_seed(seed: &[u8]) -> Self {
+        use sha3::Digest;
+        ContractAddressSalt(crate::state::class_hash::truncated_keccak(
+            <[u8; 32]>::from(sha3::Keccak256::digest(seed)),
+        ))
+    }
+}
+
 /// The hash of a StarkNet contract. This is a hash over a class'
 /// deployment properties e.g. code and ABI.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
 pub struct ClassHash(pub StarkHash);
 
+/// The hash of a compiled (CASM) Cairo 1 class, which is declared along with its Sierra class.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+pub struct CasmHash(pub StarkHash);
+
 /// A StarkNet contract's state hash. This is the value stored
 /// in the global state tree.
 #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -142,7 +160,7 @@ macros::i64_backed_u64::new_get_partialeq!(StarknetBlockTimestamp);
 macros::i64_backed_u64::serdes!(StarknetBlockTimestamp);
 
 /// A StarkNet transaction hash.
-#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StarknetTransactionHash(pub StarkHash);
 
 /// A StarkNet transaction index.
@@ -217,6 +235,19 @@ impl TransactionVersion {
         u128::from_be_bytes(lower.try_into().expect("slice should be the right length"))
     }
 
+    /// Returns this version with QUERY_VERSION_BASE added, which marks the transaction as
+    /// only valid for calls, fee estimates and simulations.
+    pub fn with_query_only(self) -> Self {
+        let mut bytes = self.0.to_fixed_bytes();
+        bytes[15] |= 1;
+        Self(H256(bytes))
+    }
+
+    /// Checks if QUERY_VERSION_BASE is part of the version.
+    pub fn is_query_only(&self) -> bool {
+        self.0.as_bytes()[15] & 1 == 1
+    }
+
     pub const ZERO: Self = Self(H256::zero());
     pub const ONE: Self = Self(H256(hex_literal::hex!(
         "0000000000000000000000000000000000000000000000000000000000000001"
@@ -421,6 +452,16 @@ impl Chain {
             Chain::Integration => StarkHash::from_u128(0x534E5F494E544547524154494F4E),
         }
     }
+
+    /// The chain id which transactions of this chain are hashed and executed with. Differs from
+    /// the [starknet_chain_id](Self::starknet_chain_id) for [Chain::Integration], which
+    /// executes transactions using the Görli chain id.
+    pub const fn execution_chain_id(&self) -> StarkHash {
+        match self {
+            Chain::Integration => Chain::Testnet.starknet_chain_id(),
+            other => other.starknet_chain_id(),
+        }
+    }
 }
 
 impl std::fmt::Display for Chain {
@@ -442,6 +483,7 @@ macros::fmt::thin_debug!(StarknetTransactionHash);
 macros::starkhash::to_from_sql!(StarknetTransactionHash);
 
 macros::fmt::thin_debug!(ClassHash);
+macros::fmt::thin_debug!(CasmHash);
 macros::starkhash::to_from_sql!(ClassHash);
 
 macros::starkhash::common_newtype!(
@@ -474,6 +516,60 @@ macros::fmt::thin_display!(StarknetBlockTimestamp);
 
 #[cfg(test)]
 mod tests {
+    mod query_version {
+        use super::super::TransactionVersion;
+
+        #[test]
+        fn with_query_only() {
+            assert_eq!(
+                TransactionVersion::ZERO.with_query_only(),
+                TransactionVersion::ZERO_WITH_QUERY_VERSION
+            );
+            assert_eq!(
+                TransactionVersion::ONE.with_query_only(),
+                TransactionVersion::ONE_WITH_QUERY_VERSION
+            );
+            // Adding the base twice must not carry into higher bits.
+            assert_eq!(
+                TransactionVersion::ONE_WITH_QUERY_VERSION.with_query_only(),
+                TransactionVersion::ONE_WITH_QUERY_VERSION
+            );
+        }
+
+        #[test]
+        fn is_query_only() {
+            assert!(!TransactionVersion::ZERO.is_query_only());
+            assert!(!TransactionVersion::ONE.is_query_only());
+            assert!(TransactionVersion::ZERO_WITH_QUERY_VERSION.is_query_only());
+            assert!(TransactionVersion::ONE_WITH_QUERY_VERSION.is_query_only());
+        }
+    }
+
+    mod salt_from_seed {
+        use super::super::ContractAddressSalt;
+
+        #[test]
+        fn is_reproducible() {
+            assert_eq!(
+                ContractAddressSalt::from_seed(b"my deploy"),
+                ContractAddressSalt::from_seed(b"my deploy")
+            );
+            assert_ne!(
+                ContractAddressSalt::from_seed(b"my deploy"),
+                ContractAddressSalt::from_seed(b"my other deploy")
+            );
+        }
+
+        #[test]
+        fn is_in_range() {
+            for seed in [&b""[..], b"my deploy", &[0xff; 64]] {
+                let salt = ContractAddressSalt::from_seed(seed);
+                // Truncated to 250 bits.
+                assert!(salt.0.to_be_bytes()[0] <= 0x03, "{:?}", salt);
+            }
+        }
+    }
+
     mod block_id_serde {
         use super::super::BlockId;
 
@@ -6,6 +6,7 @@ use std::{
     time::Duration,
 };
 use tokio_retry::{strategy::ExponentialBackoff, Retry as TokioRetry, RetryIf as TokioRetryIf};
+use tracing::Instrument;
 
 pub struct Retry<T, E, Fut, FutureFactory>
 where
@@ -76,6 +77,62 @@ where
         )
         .await
     }
+
+    /// Retry the future on every error for which `backoff` returns a [Backoff] other than
+    /// [Backoff::Stop], until the future returns:
+    /// - an `Ok()` value
+    /// - an `Err()` value for which `backoff` returns [Backoff::Stop].
+    ///
+    /// Each attempt runs in its own `attempt` span, which records the attempt number and the
+    /// delay before it, so that all attempts are grouped under the caller's span. `backoff` is
+    /// called within the span of the failed attempt.
+    pub async fn with_backoff<B>(self, mut backoff: B) -> Result<T, E>
+    where
+        B: FnMut(&E) -> Backoff,
+    {
+        let Self {
+            mut future_factory,
+            strategy,
+        } = self;
+        let mut exponential = MaybeLimited::from(strategy);
+
+        let mut attempt = 1u32;
+        let mut delay = Duration::ZERO;
+        loop {
+            let span =
+                tracing::debug_span!("attempt", attempt, delay_ms = delay.as_millis() as u64);
+
+            let e = match future_factory().instrument(span.clone()).await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+
+            let next_delay = match span.in_scope(|| backoff(&e)) {
+                Backoff::Stop => None,
+                Backoff::Fixed(delay) => Some(delay),
+                Backoff::Exponential => exponential.next(),
+            };
+            delay = match next_delay {
+                Some(delay) => delay,
+                None => return Err(e),
+            };
+            attempt += 1;
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Decides how a failed attempt is retried by [Retry::with_backoff].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Backoff {
+    /// The error is returned.
+    Stop,
+    /// Retried after a constant delay, which neither advances the exponential backoff nor
+    /// counts towards the [max_num_retries](Retry::max_num_retries).
+    Fixed(Duration),
+    /// Retried after the next exponential backoff, unless the retries are exhausted.
+    Exponential,
 }
 
 struct Strategy {
@@ -352,4 +409,66 @@ mod tests {
             assert_eq!(uut.call_count(), 3);
         }
     }
+
+    mod with_backoff {
+        use super::*;
+        use crate::retry::Backoff;
+
+        #[tokio::test]
+        async fn fixed_delay_does_not_advance_the_backoff() {
+            let uut = Uut::new([
+                Err(Failure::Retryable),
+                Err(Failure::Fatal),
+                Err(Failure::Retryable),
+                Ok(Success),
+            ]);
+            Retry::exponential(|| uut.do_work(), NonZeroU64::new(2).unwrap())
+                .factor(NonZeroU64::new(10).unwrap())
+                .with_backoff(|e| match e {
+                    Failure::Retryable => Backoff::Exponential,
+                    Failure::Fatal => Backoff::Fixed(Duration::from_millis(200)),
+                })
+                .await
+                .unwrap();
+            assert_eq!(uut.call_count(), 4);
+            // ~40ms (2^2*10), the fixed delay in between didn't count.
+            uut.expect_last_delay(40).unwrap();
+        }
+
+        #[tokio::test]
+        async fn until_stop() {
+            let uut = Uut::new([
+                Err(Failure::Retryable),
+                Err(Failure::Retryable),
+                Err(Failure::Fatal),
+                Ok(Success),
+            ]);
+            assert_eq!(
+                Retry::exponential(|| uut.do_work(), NonZeroU64::new(1).unwrap())
+                    .with_backoff(|e| match e {
+                        Failure::Retryable => Backoff::Exponential,
+                        Failure::Fatal => Backoff::Stop,
+                    })
+                    .await
+                    .unwrap_err(),
+                Failure::Fatal
+            );
+            assert_eq!(uut.call_count(), 3);
+        }
+
+        #[tokio::test]
+        async fn reach_max_num_retries() {
+            let uut = Uut::new([Err(Failure::Retryable); 4]);
+            assert_eq!(
+                Retry::exponential(|| uut.do_work(), NonZeroU64::new(1).unwrap())
+                    .max_num_retries(NonZeroUsize::new(2).unwrap())
+                    .with_backoff(|_| Backoff::Exponential)
+                    .await
+                    .unwrap_err(),
+                Failure::Retryable
+            );
+            // Retry limit of 2 means 3 tries altogether
+            assert_eq!(uut.call_count(), 3);
+        }
+    }
 }
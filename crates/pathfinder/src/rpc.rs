@@ -15,13 +15,21 @@ use jsonrpsee::{
     http_server::{HttpServerBuilder, HttpServerHandle, RpcModule},
 };
 
-use std::{net::SocketAddr, result::Result};
+use std::{
+    collections::{BTreeSet, HashSet},
+    net::SocketAddr,
+    result::Result,
+    sync::Arc,
+};
 use v01::api::RpcApi;
 
 pub struct RpcServer {
     addr: SocketAddr,
     api: RpcApi,
     middleware: MaybeRpcMetricsMiddleware,
+    disabled_methods: HashSet<String>,
+    rate_limiter: Option<Arc<v02::rate_limit::RateLimiter>>,
+    context_options: v02::ContextOptions,
 }
 
 impl RpcServer {
@@ -30,6 +38,9 @@ impl RpcServer {
             addr,
             api,
             middleware: MaybeRpcMetricsMiddleware::NoOp,
+            disabled_methods: HashSet::new(),
+            rate_limiter: None,
+            context_options: Default::default(),
         }
     }
 
@@ -40,6 +51,36 @@ impl RpcServer {
         }
     }
 
+    /// Disables the methods with these names in all API versions, e.g. to only serve reads.
+    ///
+    /// Calls to disabled methods fail with `MethodNotFound`. [run](Self::run) fails if a name
+    /// is not a method of any API version, which is likely a typo.
+    pub fn with_disabled_methods(self, methods: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            disabled_methods: methods.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Throttles calls to the methods which `rate_limiter` has limits for, in all API versions.
+    ///
+    /// Throttled calls fail with a rate limit error. The API versions share the limit of a method.
+    pub fn with_rate_limiter(self, rate_limiter: Arc<v02::rate_limit::RateLimiter>) -> Self {
+        Self {
+            rate_limiter: Some(rate_limiter),
+            ..self
+        }
+    }
+
+    /// Enables the optional features of the v0.2 API, e.g. an audit log of the submitted
+    /// transactions.
+    pub fn with_context_options(self, context_options: v02::ContextOptions) -> Self {
+        Self {
+            context_options,
+            ..self
+        }
+    }
+
     /// Starts the HTTP-RPC server.
     pub async fn run(self) -> Result<(HttpServerHandle, SocketAddr), anyhow::Error> {
         let server = HttpServerBuilder::default()
@@ -67,14 +108,24 @@ Hint: If you are looking to run two instances of pathfinder, you must configure
             })?;
         let local_addr = server.local_addr()?;
 
-        let context_v02 = (&self.api).into();
+        let api = match self.rate_limiter {
+            Some(rate_limiter) => self.api.with_rate_limiter(rate_limiter),
+            None => self.api,
+        };
+        let context_v02 = v02::RpcContext::from(&api).with_options(self.context_options);
 
-        let mut module_v01 = v01::RpcModuleWrapper::new(RpcModule::new(self.api));
+        let mut module_v01 = v01::RpcModuleWrapper::new(RpcModule::new(api))
+            .with_disabled_methods(self.disabled_methods.clone());
         v01::register_all_methods(&mut module_v01)?;
+
+        let mut method_names = module_v01.method_names().clone();
+        method_names.extend(v02::method_names(context_v02.clone())?);
+        check_disabled_methods(&self.disabled_methods, &method_names)?;
+
         let module_v01: Methods = module_v01.into_inner().into();
 
         let mut module_v02 = RpcModule::new(context_v02);
-        v02::register_all_methods(&mut module_v02)?;
+        v02::register_all_methods(&mut module_v02, &self.disabled_methods)?;
         let module_v02 = module_v02.into();
 
         Ok(server
@@ -86,6 +137,28 @@ Hint: If you are looking to run two instances of pathfinder, you must configure
     }
 }
 
+/// Fails if any of `disabled_methods` is not one of `method_names`, listing the valid names.
+fn check_disabled_methods(
+    disabled_methods: &HashSet<String>,
+    method_names: &HashSet<&'static str>,
+) -> anyhow::Result<()> {
+    let unknown = disabled_methods
+        .iter()
+        .filter(|method| !method_names.contains(method.as_str()))
+        .map(String::as_str)
+        .collect::<BTreeSet<_>>();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    let valid = method_names.iter().copied().collect::<BTreeSet<_>>();
+    anyhow::bail!(
+        "Unknown RPC methods to disable: {}. Valid methods are: {}",
+        unknown.into_iter().collect::<Vec<_>>().join(", "),
+        valid.into_iter().collect::<Vec<_>>().join(", ")
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
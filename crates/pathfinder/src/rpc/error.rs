@@ -4,6 +4,9 @@
 //! by each JSON-RPC method to trivially create its subset of [RpcError] along with the boilerplate involved.
 #![macro_use]
 
+use crate::core::{ClassHash, ContractAddress, Fee, StarknetTransactionHash, TransactionVersion};
+use crate::rpc::v02::types::ClassKind;
+
 /// The StarkNet JSON-RPC error variants.
 #[derive(thiserror::Error, Debug)]
 pub enum RpcError {
@@ -33,10 +36,94 @@ pub enum RpcError {
     ContractError,
     #[error("Invalid contract class")]
     InvalidContractClass,
+    /// Not part of the specification, the transaction's `max_fee` exceeds the largest one the
+    /// node is configured to submit.
+    #[error("Max fee {:#x} exceeds the configured cap of {:#x}", .max_fee.0, .cap.0)]
+    FeeExceedsCap { max_fee: Fee, cap: Fee },
+    /// Not part of the specification, submitting the transaction would spend more than is left
+    /// of the node's fee budget for the current window.
+    #[error("Max fee {:#x} exceeds the remaining fee budget of {:#x}", .max_fee.0, .remaining.0)]
+    FeeBudgetExceeded { max_fee: Fee, remaining: Fee },
+    /// Not part of the specification, the transaction's `max_fee` is below the live fee
+    /// estimate times the configured safety factor, so the sequencer would likely reject it.
+    #[error(
+        "Max fee {:#x} is below the required {:#x}, the estimated fee times the safety factor",
+        .max_fee.0,
+        .required.0
+    )]
+    MaxFeeBelowEstimate { max_fee: Fee, required: Fee },
+    /// Not part of the specification, the class is of a kind which the transaction's version
+    /// cannot carry, e.g. a Sierra class in a version 1 declare.
+    #[error(
+        "{class_version} classes cannot be submitted with transaction version {:#x}",
+        .tx_version.without_query_version()
+    )]
+    IncompatibleClassVersion {
+        class_version: ClassKind,
+        tx_version: TransactionVersion,
+    },
+    /// Not part of the specification, a contract already exists at the address the deploy
+    /// would create. Only checked if the caller asked for it.
+    #[error("A contract is already deployed at {}", .address.get())]
+    AlreadyDeployed { address: ContractAddress },
+    /// [RpcError::ClassHashNotFound] along with the offending class hash.
+    #[error("Class hash {} not found", .class_hash.0)]
+    UndeclaredClass { class_hash: ClassHash },
+    /// Not part of the specification, the hash the node computed for the transaction differs
+    /// from the one the caller expected, e.g. because it was hashed for another chain.
+    #[error("Expected transaction hash {} but computed {}", .expected.0, .computed.0)]
+    TransactionHashMismatch {
+        expected: StarknetTransactionHash,
+        computed: StarknetTransactionHash,
+    },
+    /// Not part of the specification, DEPLOY transactions only carry Cairo 0 classes.
+    #[error("Sierra classes cannot be deployed using DEPLOY transactions, declare the class and deploy it using a DEPLOY_ACCOUNT transaction or an account instead")]
+    SierraNotSupportedForDeploy,
+    /// Not part of the specification, the class referenced by a deploy could not be fetched.
+    #[error("Failed to fetch the class: {reason}")]
+    ClassSourceFailed { reason: String },
+    /// Not part of the specification, the class of a declare or deploy failed the node's
+    /// configured checks and was not submitted.
+    #[error("Class failed validation: {reason}")]
+    ClassValidationFailed { reason: String },
+    /// Not part of the specification, calls to the method exceeded its configured rate limit.
+    #[error("Rate limit of {method} exceeded, retry after {}ms", .retry_after.as_millis())]
+    RateLimited {
+        method: &'static str,
+        retry_after: std::time::Duration,
+    },
     #[error(transparent)]
     Internal(anyhow::Error),
 }
 
+/// The implementation defined server error codes, for errors which the specification has no
+/// equivalent of.
+///
+/// All of them are allocated here, so that the compiler rejects two errors sharing a code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ServerErrorCode {
+    FeeExceedsCap = -32001,
+    GatewayMaintenance = -32002,
+    GatewayTimeout = -32003,
+    IncompatibleClassVersion = -32004,
+    AlreadyDeployed = -32005,
+    SierraNotSupportedForDeploy = -32006,
+    FeeBudgetExceeded = -32007,
+    MaxFeeBelowEstimate = -32008,
+    TransactionHashMismatch = -32009,
+    InvalidNonce = -32010,
+    ClassSourceFailed = -32011,
+    RateLimited = -32012,
+    ClassValidationFailed = -32013,
+}
+
+impl ServerErrorCode {
+    pub const fn code(self) -> i32 {
+        self as i32
+    }
+}
+
 impl RpcError {
     pub fn code(&self) -> i32 {
         match self {
@@ -47,12 +134,28 @@ impl RpcError {
             RpcError::BlockNotFound => 24,
             RpcError::TxnHashNotFound => 25,
             RpcError::InvalidTxnIndex => 27,
-            RpcError::ClassHashNotFound => 28,
+            RpcError::ClassHashNotFound | RpcError::UndeclaredClass { .. } => 28,
             RpcError::PageSizeTooBig => 31,
             RpcError::NoBlocks => 32,
             RpcError::InvalidContinuationToken => 33,
             RpcError::ContractError => 40,
             RpcError::InvalidContractClass => 50,
+            RpcError::FeeExceedsCap { .. } => ServerErrorCode::FeeExceedsCap.code(),
+            RpcError::IncompatibleClassVersion { .. } => {
+                ServerErrorCode::IncompatibleClassVersion.code()
+            }
+            RpcError::AlreadyDeployed { .. } => ServerErrorCode::AlreadyDeployed.code(),
+            RpcError::SierraNotSupportedForDeploy => {
+                ServerErrorCode::SierraNotSupportedForDeploy.code()
+            }
+            RpcError::FeeBudgetExceeded { .. } => ServerErrorCode::FeeBudgetExceeded.code(),
+            RpcError::MaxFeeBelowEstimate { .. } => ServerErrorCode::MaxFeeBelowEstimate.code(),
+            RpcError::TransactionHashMismatch { .. } => {
+                ServerErrorCode::TransactionHashMismatch.code()
+            }
+            RpcError::ClassSourceFailed { .. } => ServerErrorCode::ClassSourceFailed.code(),
+            RpcError::RateLimited { .. } => ServerErrorCode::RateLimited.code(),
+            RpcError::ClassValidationFailed { .. } => ServerErrorCode::ClassValidationFailed.code(),
             RpcError::Internal(_) => jsonrpsee::types::error::ErrorCode::InternalError.code(),
         }
     }
@@ -60,8 +163,27 @@ impl RpcError {
 
 impl From<RpcError> for jsonrpsee::core::error::Error {
     fn from(err: RpcError) -> Self {
+        use crate::sequencer::error::{
+            gateway_maintenance_error, gateway_timeout_error, invalid_nonce_error, SequencerError,
+        };
         use jsonrpsee::types::error::{CallError, ErrorObject};
 
+        // Advise clients to retry later instead of reporting an internal error.
+        if let RpcError::Internal(e) = &err {
+            match e.downcast_ref() {
+                Some(SequencerError::GatewayMaintenance { message }) => {
+                    return gateway_maintenance_error(message)
+                }
+                Some(SequencerError::Timeout { .. }) => return gateway_timeout_error(),
+                Some(SequencerError::StarknetError(e)) => {
+                    if let Some(nonce) = e.invalid_nonce() {
+                        return invalid_nonce_error(nonce, &e.message);
+                    }
+                }
+                _ => {}
+            }
+        }
+
         CallError::Custom(ErrorObject::owned(err.code(), err.to_string(), None::<()>)).into()
     }
 }
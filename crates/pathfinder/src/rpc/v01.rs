@@ -1,20 +1,44 @@
+use std::collections::HashSet;
+
 use self::api::RpcApi;
+use super::v02::rate_limit::RateLimiter;
 
 pub mod api;
 pub mod types;
 
 /// Helper wrapper for attaching spans to rpc method implementations
-pub struct RpcModuleWrapper<Context>(jsonrpsee::RpcModule<Context>);
+pub struct RpcModuleWrapper<Context> {
+    module: jsonrpsee::RpcModule<Context>,
+    disabled_methods: HashSet<String>,
+    /// All methods registered so far, including disabled ones.
+    method_names: HashSet<&'static str>,
+}
 
 impl<Context: Send + Sync + 'static> RpcModuleWrapper<Context> {
     pub fn new(context: jsonrpsee::RpcModule<Context>) -> Self {
-        Self(context)
+        Self {
+            module: context,
+            disabled_methods: HashSet::new(),
+            method_names: HashSet::new(),
+        }
+    }
+
+    /// Methods with these names are not registered, so that calls to them fail with
+    /// `MethodNotFound`.
+    pub fn with_disabled_methods(self, disabled_methods: HashSet<String>) -> Self {
+        Self {
+            disabled_methods,
+            ..self
+        }
     }
 
     /// This wrapper helper adds a tracing span around all rpc methods with name = method_name.
     ///
     /// It could do more, for example trace the outputs, durations.
     ///
+    /// Disabled methods are silently skipped, and calls exceeding the rate limit of their method
+    /// fail before reaching `callback`.
+    ///
     /// This is the only one method provided at the moment, because it's the only one used. If you
     /// need to use some other `register_*` method from [`jsonrpsee::RpcModule`], just add it to
     /// this wrapper.
@@ -22,10 +46,7 @@ impl<Context: Send + Sync + 'static> RpcModuleWrapper<Context> {
         &mut self,
         method_name: &'static str,
         callback: Fun,
-    ) -> Result<
-        jsonrpsee::core::server::rpc_module::MethodResourcesBuilder<'_>,
-        jsonrpsee::core::Error,
-    >
+    ) -> Result<(), jsonrpsee::core::Error>
     where
         R: ::serde::Serialize + Send + Sync + 'static,
         Fut: std::future::Future<Output = Result<R, jsonrpsee::core::Error>> + Send,
@@ -34,21 +55,51 @@ impl<Context: Send + Sync + 'static> RpcModuleWrapper<Context> {
             + Send
             + Sync
             + 'static,
+        Context: RateLimitedContext,
     {
         use tracing::Instrument;
 
+        self.method_names.insert(method_name);
+        if self.disabled_methods.contains(method_name) {
+            return Ok(());
+        }
+
         metrics::register_counter!("rpc_method_calls_total", "method" => method_name);
         metrics::register_counter!("rpc_method_calls_failed_total", "method" => method_name);
 
-        self.0.register_async_method(method_name, move |p, c| {
-            // why info here? it's the same used in warp tracing filter for example.
-            let span = tracing::info_span!("rpc_method", name = method_name);
-            callback(p, c).instrument(span)
-        })
+        self.module
+            .register_async_method(method_name, move |p, c| {
+                // why info here? it's the same used in warp tracing filter for example.
+                let span = tracing::info_span!("rpc_method", name = method_name);
+                async move {
+                    if let Some(limiter) = c.rate_limiter() {
+                        limiter.check_call(method_name)?;
+                    }
+                    callback(p, c).await
+                }
+                .instrument(span)
+            })
+            .map(|_| ())
+    }
+
+    /// The names of all methods registered so far, including disabled ones.
+    pub fn method_names(&self) -> &HashSet<&'static str> {
+        &self.method_names
     }
 
     pub fn into_inner(self) -> jsonrpsee::RpcModule<Context> {
-        self.0
+        self.module
+    }
+}
+
+/// The context of an [RpcModuleWrapper], which may throttle calls to its methods.
+pub trait RateLimitedContext {
+    fn rate_limiter(&self) -> Option<&RateLimiter>;
+}
+
+impl RateLimitedContext for RpcApi {
+    fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_deref()
     }
 }
 
@@ -2065,6 +2116,40 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn disabled_method_is_not_found() {
+        use crate::rpc::v01::types::reply::DeployTransactionResult;
+        use jsonrpsee::types::error::{CallError, ErrorCode};
+
+        let storage = Storage::in_memory().unwrap();
+        let sequencer = Client::new(Chain::Testnet).unwrap();
+        let sync_state = Arc::new(SyncState::default());
+        let api = RpcApi::new(storage, sequencer, Chain::Testnet, sync_state);
+
+        let (__handle, addr) = RpcServer::new(*LOCALHOST, api)
+            .with_disabled_methods(["starknet_addDeployTransaction".to_owned()])
+            .run()
+            .await
+            .unwrap();
+
+        let error = client(addr)
+            .request::<DeployTransactionResult>("starknet_addDeployTransaction", rpc_params!())
+            .await
+            .unwrap_err();
+        assert_matches!(
+            error,
+            jsonrpsee::core::Error::Call(CallError::Custom(error)) => {
+                assert_eq!(error.code(), ErrorCode::MethodNotFound.code())
+            }
+        );
+
+        // Other methods are still served.
+        client(addr)
+            .request::<String>("starknet_chainId", rpc_params!())
+            .await
+            .unwrap();
+    }
+
     mod syncing {
         use crate::rpc::v01::types::reply::{syncing, Syncing};
         use pretty_assertions::assert_eq;
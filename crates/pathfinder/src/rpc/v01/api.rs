@@ -7,9 +7,12 @@ use crate::rpc::{
         },
         request::{Call, ContractCall, EventFilter},
     },
-    v02::types::request::{
-        BroadcastedInvokeTransaction, BroadcastedInvokeTransactionV0,
-        BroadcastedInvokeTransactionV1, BroadcastedTransaction,
+    v02::{
+        rate_limit::RateLimiter,
+        types::request::{
+            BroadcastedInvokeTransaction, BroadcastedInvokeTransactionV0,
+            BroadcastedInvokeTransactionV1, BroadcastedTransaction, Signature,
+        },
     },
 };
 use crate::{
@@ -51,6 +54,7 @@ pub struct RpcApi {
     pub shared_gas_price: Option<gas_price::Cached>,
     pub sync_state: Arc<SyncState>,
     pub pending_data: Option<PendingData>,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 #[derive(Debug)]
@@ -89,6 +93,7 @@ impl RpcApi {
             shared_gas_price: None,
             sync_state,
             pending_data: None,
+            rate_limiter: None,
         }
     }
 
@@ -113,6 +118,14 @@ impl RpcApi {
         }
     }
 
+    /// Throttles calls to the methods which `rate_limiter` has limits for.
+    pub fn with_rate_limiter(self, rate_limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            rate_limiter: Some(rate_limiter),
+            ..self
+        }
+    }
+
     /// Returns [PendingData]; errors if [RpcApi] was not configured with one.
     ///
     /// This is useful for queries to access pending data or return an error via `?` if it
@@ -1312,6 +1325,7 @@ impl RpcApi {
                 call.contract_address,
                 call.entry_point_selector,
                 call.calldata,
+                None,
             )
             .await?;
         Ok(InvokeTransactionResult {
@@ -1348,6 +1362,7 @@ impl RpcApi {
                 contract_class,
                 // actual address dumped from a `starknet declare` call
                 sender_address,
+                None,
                 token,
             )
             .await?;
@@ -1377,6 +1392,7 @@ impl RpcApi {
                 constructor_calldata,
                 contract_definition,
                 token,
+                None,
             )
             .await?;
         Ok(DeployTransactionResult {
@@ -1399,7 +1415,7 @@ impl RpcApi {
                 BroadcastedInvokeTransactionV0 {
                     version: request.version,
                     max_fee: request.max_fee,
-                    signature: request.signature,
+                    signature: Signature(request.signature),
                     nonce: None,
                     contract_address: request.contract_address,
                     entry_point_selector: request.entry_point_selector.ok_or_else(|| {
@@ -1414,7 +1430,7 @@ impl RpcApi {
                 BroadcastedInvokeTransactionV1 {
                     version: request.version,
                     max_fee: request.max_fee,
-                    signature: request.signature,
+                    signature: Signature(request.signature),
                     nonce: request.nonce,
                     sender_address: request.contract_address,
                     calldata: request.calldata,
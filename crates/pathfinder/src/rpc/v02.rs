@@ -1,13 +1,26 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use super::error::RpcError;
 use crate::cairo::ext_py;
 use crate::rpc::gas_price;
-use crate::{core::Chain, state::SyncState};
+use crate::{
+    core::{Chain, Fee},
+    state::SyncState,
+};
 use crate::{state::PendingData, storage::Storage};
 
+pub mod audit;
+pub mod batch_deploy;
+pub mod class_source;
 mod common;
+pub mod fee_budget;
+pub mod max_fee_check;
 pub mod method;
+pub mod rate_limit;
+pub mod representation;
+pub mod signer;
+pub mod submission_store;
 pub mod types;
 
 type SequencerClient = crate::sequencer::Client;
@@ -21,6 +34,48 @@ pub struct RpcContext {
     pub call_handle: Option<ext_py::Handle>,
     pub eth_gas_price: Option<gas_price::Cached>,
     pub sequencer: SequencerClient,
+    pub audit_sink: Option<Arc<dyn audit::AuditSink>>,
+    /// Records every `DEPLOY` submission so that it can be replayed later.
+    pub replay_log: Option<Arc<crate::sequencer::replay::ReplayLog>>,
+    /// Durably records the transactions submitted through this node.
+    pub submission_store: Option<Arc<submission_store::SubmissionStore>>,
+    pub felt_representation: representation::FeltRepresentation,
+    /// Transactions with a larger `max_fee` are rejected without being submitted.
+    pub max_fee_cap: Option<Fee>,
+    /// Limits the total `max_fee` of transactions submitted within a rolling window.
+    pub fee_budget: Option<Arc<fee_budget::FeeBudget>>,
+    /// Compares the `max_fee` of transactions to a live estimate before submitting them.
+    pub max_fee_check: Option<max_fee_check::MaxFeeCheck>,
+    /// Fetches the classes of deploys which reference them instead of inlining them.
+    pub class_sources: Option<Arc<class_source::ClassSources>>,
+    /// The checks classes of declares and deploys must pass before they are submitted.
+    pub class_validation: types::ValidationOptions,
+    /// Rejects transactions whose signatures are not made of `(r, s)` pairs, see
+    /// [Signature::validate](types::request::Signature::validate).
+    pub check_signatures: bool,
+    /// Throttles calls to the methods it has limits for.
+    pub rate_limiter: Option<Arc<rate_limit::RateLimiter>>,
+    /// Fails methods with [OfflineMode](crate::sequencer::error::SequencerError::OfflineMode)
+    /// where they would reach the network, so that only their local checks and computations run.
+    pub offline: bool,
+}
+
+/// The optional features of an [RpcContext], which are all disabled by default.
+///
+/// See the [RpcContext] field of the same name for what each of them does.
+#[derive(Clone, Default)]
+pub struct ContextOptions {
+    pub audit_sink: Option<Arc<dyn audit::AuditSink>>,
+    pub replay_log: Option<Arc<crate::sequencer::replay::ReplayLog>>,
+    pub submission_store: Option<Arc<submission_store::SubmissionStore>>,
+    pub felt_representation: representation::FeltRepresentation,
+    pub max_fee_cap: Option<Fee>,
+    pub fee_budget: Option<Arc<fee_budget::FeeBudget>>,
+    pub max_fee_check: Option<max_fee_check::MaxFeeCheck>,
+    pub class_sources: Option<Arc<class_source::ClassSources>>,
+    pub class_validation: types::ValidationOptions,
+    pub check_signatures: bool,
+    pub offline: bool,
 }
 
 impl RpcContext {
@@ -38,6 +93,18 @@ impl RpcContext {
             call_handle: None,
             eth_gas_price: None,
             sequencer,
+            audit_sink: None,
+            replay_log: None,
+            submission_store: None,
+            felt_representation: Default::default(),
+            max_fee_cap: None,
+            fee_budget: None,
+            max_fee_check: None,
+            class_sources: None,
+            class_validation: Default::default(),
+            check_signatures: false,
+            rate_limiter: None,
+            offline: false,
         }
     }
 
@@ -89,6 +156,179 @@ impl RpcContext {
             ..self
         }
     }
+
+    pub fn with_audit_sink(self, audit_sink: Arc<dyn audit::AuditSink>) -> Self {
+        Self {
+            audit_sink: Some(audit_sink),
+            ..self
+        }
+    }
+
+    pub fn with_replay_log(self, replay_log: Arc<crate::sequencer::replay::ReplayLog>) -> Self {
+        Self {
+            replay_log: Some(replay_log),
+            ..self
+        }
+    }
+
+    pub fn with_submission_store(
+        self,
+        submission_store: Arc<submission_store::SubmissionStore>,
+    ) -> Self {
+        Self {
+            submission_store: Some(submission_store),
+            ..self
+        }
+    }
+
+    pub fn with_max_fee_cap(self, max_fee_cap: Fee) -> Self {
+        Self {
+            max_fee_cap: Some(max_fee_cap),
+            ..self
+        }
+    }
+
+    pub fn with_fee_budget(self, fee_budget: Arc<fee_budget::FeeBudget>) -> Self {
+        Self {
+            fee_budget: Some(fee_budget),
+            ..self
+        }
+    }
+
+    pub fn with_max_fee_check(self, max_fee_check: max_fee_check::MaxFeeCheck) -> Self {
+        Self {
+            max_fee_check: Some(max_fee_check),
+            ..self
+        }
+    }
+
+    pub fn with_class_sources(self, class_sources: Arc<class_source::ClassSources>) -> Self {
+        Self {
+            class_sources: Some(class_sources),
+            ..self
+        }
+    }
+
+    pub fn with_class_validation(self, class_validation: types::ValidationOptions) -> Self {
+        Self {
+            class_validation,
+            ..self
+        }
+    }
+
+    pub fn with_signature_checks(self) -> Self {
+        Self {
+            check_signatures: true,
+            ..self
+        }
+    }
+
+    pub fn with_rate_limiter(self, rate_limiter: Arc<rate_limit::RateLimiter>) -> Self {
+        Self {
+            rate_limiter: Some(rate_limiter),
+            ..self
+        }
+    }
+
+    pub fn with_offline(self) -> Self {
+        Self {
+            offline: true,
+            ..self
+        }
+    }
+
+    /// Replaces the optional features of this context with `options`.
+    pub fn with_options(self, options: ContextOptions) -> Self {
+        Self {
+            audit_sink: options.audit_sink,
+            replay_log: options.replay_log,
+            submission_store: options.submission_store,
+            felt_representation: options.felt_representation,
+            max_fee_cap: options.max_fee_cap,
+            fee_budget: options.fee_budget,
+            max_fee_check: options.max_fee_check,
+            class_sources: options.class_sources,
+            class_validation: options.class_validation,
+            check_signatures: options.check_signatures,
+            offline: options.offline,
+            ..self
+        }
+    }
+
+    /// Returns the [RpcError::RateLimited] error if calling `method_name` exceeds its
+    /// [rate limit](Self::rate_limiter).
+    fn rate_limited(&self, method_name: &'static str) -> Result<(), jsonrpsee::core::Error> {
+        match &self.rate_limiter {
+            Some(limiter) => limiter.check_call(method_name),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the [class_validation](Self::class_validation) checks on `class`, off the runtime
+    /// since they may decompress the whole program.
+    ///
+    /// Only fails with the outer error if the checks could not be run.
+    pub(crate) async fn validate_class(
+        &self,
+        class: &types::ContractClass,
+    ) -> anyhow::Result<Result<(), types::ContractClassError>> {
+        use anyhow::Context;
+
+        let options = self.class_validation;
+        if options == types::ValidationOptions::default() {
+            return Ok(Ok(()));
+        }
+
+        let class = class.clone();
+        tokio::task::spawn_blocking(move || class.validate(&options))
+            .await
+            .context("Joining class validation")
+    }
+
+    /// Runs [Signature::validate](types::request::Signature::validate) on `signature` if
+    /// [check_signatures](Self::check_signatures) is set.
+    pub(crate) fn validate_signature(
+        &self,
+        signature: &types::request::Signature,
+    ) -> Result<(), types::request::SignatureError> {
+        match self.check_signatures {
+            true => signature.validate(),
+            false => Ok(()),
+        }
+    }
+
+    /// Fails with [OfflineMode](crate::sequencer::error::SequencerError::OfflineMode) if this
+    /// context is [offline](Self::offline). Called before anything is sent over the network.
+    pub(crate) fn ensure_online(&self) -> anyhow::Result<()> {
+        match self.offline {
+            true => Err(crate::sequencer::error::SequencerError::OfflineMode.into()),
+            false => Ok(()),
+        }
+    }
+
+    /// Commits `max_fee` to the [fee_budget](Self::fee_budget), or returns the remaining budget
+    /// if `max_fee` exceeds it.
+    pub(crate) fn exceeded_fee_budget(&self, max_fee: Fee) -> Option<Fee> {
+        self.fee_budget
+            .as_ref()
+            .and_then(|budget| budget.commit(max_fee).err())
+    }
+
+    /// Returns the [max_fee_cap](Self::max_fee_cap) if `max_fee` exceeds it.
+    pub(crate) fn exceeded_fee_cap(&self, max_fee: Fee) -> Option<Fee> {
+        self.max_fee_cap.filter(|cap| max_fee.0 > cap.0)
+    }
+
+    /// Opts into a non-default representation of field elements in method outputs.
+    pub fn with_felt_representation(
+        self,
+        felt_representation: representation::FeltRepresentation,
+    ) -> Self {
+        Self {
+            felt_representation,
+            ..self
+        }
+    }
 }
 
 // FIXME
@@ -104,11 +344,23 @@ impl From<&super::v01::api::RpcApi> for RpcContext {
             call_handle: v01.call_handle.clone(),
             eth_gas_price: v01.shared_gas_price.clone(),
             sequencer: v01.sequencer.clone(),
+            audit_sink: None,
+            replay_log: None,
+            submission_store: None,
+            felt_representation: Default::default(),
+            max_fee_cap: None,
+            fee_budget: None,
+            max_fee_check: None,
+            class_sources: None,
+            class_validation: Default::default(),
+            rate_limiter: v01.rate_limiter.clone(),
+            offline: false,
         }
     }
 }
 
-/// Registers a JSON-RPC method with the [RpcModule<RpcContext>](jsonrpsee::RpcModule).
+/// Registers a JSON-RPC method with the [RpcModule<RpcContext>](jsonrpsee::RpcModule), unless it
+/// is one of `disabled_methods`.
 ///
 /// An example signature for `method` is:
 /// ```ignore
@@ -116,6 +368,7 @@ impl From<&super::v01::api::RpcApi> for RpcContext {
 /// ```
 fn register_method<Input, Output, Error, MethodFuture, Method>(
     module: &mut jsonrpsee::RpcModule<RpcContext>,
+    disabled_methods: &HashSet<String>,
     method_name: &'static str,
     method: Method,
 ) -> anyhow::Result<()>
@@ -130,17 +383,24 @@ where
     use jsonrpsee::types::Params;
     use tracing::Instrument;
 
+    if disabled_methods.contains(method_name) {
+        return Ok(());
+    }
+
     metrics::register_counter!("rpc_method_calls_total", "method" => method_name);
 
     let method_callback = move |params: Params<'static>, context: Arc<RpcContext>| {
         // why info here? it's the same used in warp tracing filter for example.
         let span = tracing::info_span!("rpc_method", name = method_name);
         async move {
+            context.rate_limited(method_name)?;
             let input = params.parse::<Input>()?;
-            method((*context).clone(), input).await.map_err(|err| {
+            let representation = context.felt_representation;
+            let output = method((*context).clone(), input).await.map_err(|err| {
                 let rpc_err: RpcError = err.into();
                 jsonrpsee::core::Error::from(rpc_err)
-            })
+            })?;
+            encode_output(representation, output)
         }
         .instrument(span)
     };
@@ -152,7 +412,8 @@ where
     Ok(())
 }
 
-/// Registers a JSON-RPC method with the [RpcModule<RpcContext>](jsonrpsee::RpcModule).
+/// Registers a JSON-RPC method with the [RpcModule<RpcContext>](jsonrpsee::RpcModule), unless it
+/// is one of `disabled_methods`.
 ///
 /// An example signature for `method` is:
 /// ```ignore
@@ -160,6 +421,7 @@ where
 /// ```
 fn register_method_with_no_input<Output, Error, MethodFuture, Method>(
     module: &mut jsonrpsee::RpcModule<RpcContext>,
+    disabled_methods: &HashSet<String>,
     method_name: &'static str,
     method: Method,
 ) -> anyhow::Result<()>
@@ -172,16 +434,23 @@ where
     use anyhow::Context;
     use tracing::Instrument;
 
+    if disabled_methods.contains(method_name) {
+        return Ok(());
+    }
+
     metrics::register_counter!("rpc_method_calls_total", "method" => method_name);
 
     let method_callback = move |_params, context: Arc<RpcContext>| {
         // why info here? it's the same used in warp tracing filter for example.
         let span = tracing::info_span!("rpc_method", name = method_name);
         async move {
-            method((*context).clone()).await.map_err(|err| {
+            context.rate_limited(method_name)?;
+            let representation = context.felt_representation;
+            let output = method((*context).clone()).await.map_err(|err| {
                 let rpc_err: RpcError = err.into();
                 jsonrpsee::core::Error::from(rpc_err)
-            })
+            })?;
+            encode_output(representation, output)
         }
         .instrument(span)
     };
@@ -193,105 +462,251 @@ where
     Ok(())
 }
 
-// Registers all methods for the v0.2 API
-pub fn register_all_methods(module: &mut jsonrpsee::RpcModule<RpcContext>) -> anyhow::Result<()> {
-    register_method(module, "starknet_call", method::call::call)?;
-    register_method_with_no_input(module, "starknet_chainId", method::chain_id::chain_id)?;
+fn encode_output<Output: ::serde::Serialize>(
+    representation: representation::FeltRepresentation,
+    output: Output,
+) -> Result<representation::Encoded<Output>, jsonrpsee::core::Error> {
+    representation.encode(output).map_err(|e| {
+        let rpc_err = RpcError::Internal(anyhow::Error::new(e).context("Encoding output"));
+        jsonrpsee::core::Error::from(rpc_err)
+    })
+}
+
+/// The names of all methods of the v0.2 API, including those which are disabled.
+pub fn method_names(context: RpcContext) -> anyhow::Result<Vec<&'static str>> {
+    let mut module = jsonrpsee::RpcModule::new(context);
+    register_all_methods(&mut module, &HashSet::new())?;
+    Ok(module.method_names().collect())
+}
+
+/// Registers all methods for the v0.2 API, except for those in `disabled_methods`.
+pub fn register_all_methods(
+    module: &mut jsonrpsee::RpcModule<RpcContext>,
+    disabled_methods: &HashSet<String>,
+) -> anyhow::Result<()> {
     register_method(
         module,
+        disabled_methods,
+        "starknet_call",
+        method::call::call,
+    )?;
+    register_method_with_no_input(
+        module,
+        disabled_methods,
+        "starknet_chainId",
+        method::chain_id::chain_id,
+    )?;
+    register_method(
+        module,
+        disabled_methods,
         "starknet_getBlockWithTxHashes",
         method::get_block::get_block_with_tx_hashes,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_getBlockWithTxs",
         method::get_block::get_block_with_txs,
     )?;
-    register_method(module, "starknet_getClass", method::get_class::get_class)?;
     register_method(
         module,
+        disabled_methods,
+        "starknet_getClass",
+        method::get_class::get_class,
+    )?;
+    register_method(
+        module,
+        disabled_methods,
         "starknet_getClassAt",
         method::get_class_at::get_class_at,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_getClassHashAt",
         method::get_class_hash_at::get_class_hash_at,
     )?;
-    register_method(module, "starknet_getEvents", method::get_events::get_events)?;
     register_method(
         module,
+        disabled_methods,
+        "starknet_getEvents",
+        method::get_events::get_events,
+    )?;
+    register_method(
+        module,
+        disabled_methods,
         "starknet_estimateFee",
         method::estimate_fee::estimate_fee,
     )?;
-    register_method(module, "starknet_getNonce", method::get_nonce::get_nonce)?;
+    register_method(
+        module,
+        disabled_methods,
+        "starknet_getNonce",
+        method::get_nonce::get_nonce,
+    )?;
     register_method_with_no_input(
         module,
+        disabled_methods,
         "starknet_pendingTransactions",
         method::pending_transactions::pending_transactions,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_getStateUpdate",
         method::get_state_update::get_state_update,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_getStorageAt",
         method::get_storage_at::get_storage_at,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_getTransactionByHash",
         method::get_transaction_by_hash::get_transaction_by_hash,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_getTransactionByBlockIdAndIndex",
         method::get_transaction_by_block_id_and_index::get_transaction_by_block_id_and_index,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_getTransactionReceipt",
         method::get_transaction_receipt::get_transaction_receipt,
     )?;
-    register_method_with_no_input(module, "starknet_syncing", method::syncing::syncing)?;
+    register_method_with_no_input(
+        module,
+        disabled_methods,
+        "starknet_syncing",
+        method::syncing::syncing,
+    )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_getBlockTransactionCount",
         method::get_block_transaction_count::get_block_transaction_count,
     )?;
     register_method_with_no_input(
         module,
+        disabled_methods,
         "starknet_blockHashAndNumber",
         method::block_hash_and_number::block_hash_and_number,
     )?;
     register_method_with_no_input(
         module,
+        disabled_methods,
         "starknet_blockNumber",
         method::block_hash_and_number::block_number,
     )?;
 
     register_method(
         module,
+        disabled_methods,
         "starknet_addInvokeTransaction",
         method::add_invoke_transaction::add_invoke_transaction,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_addDeclareTransaction",
         method::add_declare_transaction::add_declare_transaction,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_addDeployTransaction",
         method::add_deploy_transaction::add_deploy_transaction,
     )?;
     register_method(
         module,
+        disabled_methods,
         "starknet_addDeployAccountTransaction",
         method::add_deploy_account_transaction::add_deploy_account_transaction,
     )?;
 
+    register_method_with_no_input(
+        module,
+        disabled_methods,
+        "pathfinder_sequencerConfig",
+        method::sequencer_config::sequencer_config,
+    )?;
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RpcContext;
+    use crate::rpc::v01::api::RpcApi;
+    use crate::rpc::RpcServer;
+
+    fn api() -> RpcApi {
+        let context = RpcContext::for_tests();
+        RpcApi::new(
+            context.storage,
+            context.sequencer,
+            context.chain,
+            context.sync_status,
+        )
+    }
+
+    #[tokio::test]
+    async fn disabled_method_is_not_found() {
+        let (_handle, addr) = RpcServer::new(*crate::rpc::tests::LOCALHOST, api())
+            .with_disabled_methods(["starknet_addDeployTransaction".to_owned()])
+            .run()
+            .await
+            .unwrap();
+
+        let client = reqwest::Client::new();
+        let call = |method: &'static str| {
+            let request = client
+                .post(format!("http://{addr}/rpc/v0.2"))
+                .json(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 0,
+                    "method": method,
+                    "params": {},
+                }));
+            async move {
+                let response: serde_json::Value =
+                    request.send().await.unwrap().json().await.unwrap();
+                response
+            }
+        };
+
+        let response = call("starknet_addDeployTransaction").await;
+        assert_eq!(response["error"]["code"], -32601, "{response}");
+        // Other methods are still served.
+        let response = call("starknet_chainId").await;
+        assert!(response["result"].is_string(), "{response}");
+    }
+
+    #[tokio::test]
+    async fn unknown_disabled_method_fails_to_start() {
+        let error = RpcServer::new(*crate::rpc::tests::LOCALHOST, api())
+            .with_disabled_methods([
+                "starknet_addDeployTransaction".to_owned(),
+                "starknet_addDeploytransaction".to_owned(),
+            ])
+            .run()
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            error.starts_with("Unknown RPC methods to disable: starknet_addDeploytransaction."),
+            "{error}"
+        );
+        // The valid methods are listed.
+        assert!(error.contains("starknet_addDeployTransaction"), "{error}");
+        assert!(error.contains("starknet_getEvents"), "{error}");
+        assert!(error.contains("pathfinder_sequencerConfig"), "{error}");
+    }
+}
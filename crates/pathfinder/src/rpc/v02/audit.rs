@@ -0,0 +1,135 @@
+//! Append-only audit log of transactions submitted through this node.
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+
+use crate::core::{ContractAddress, StarknetTransactionHash};
+
+/// A single submission attempt.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AuditRecord {
+    /// Seconds since the UNIX epoch.
+    pub timestamp: u64,
+    /// The JSON-RPC method used for the submission.
+    pub method: String,
+    /// Only present if the sequencer accepted the submission.
+    pub transaction_hash: Option<StarknetTransactionHash>,
+    /// Only present if the sequencer accepted the submission.
+    pub contract_address: Option<ContractAddress>,
+    pub success: bool,
+}
+
+impl AuditRecord {
+    /// Creates a record timestamped with the current system time.
+    pub fn now(
+        method: &str,
+        transaction_hash: Option<StarknetTransactionHash>,
+        contract_address: Option<ContractAddress>,
+        success: bool,
+    ) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            timestamp,
+            method: method.to_owned(),
+            transaction_hash,
+            contract_address,
+            success,
+        }
+    }
+}
+
+/// Destination for [AuditRecord]s.
+///
+/// Records are written on a blocking thread, so [record](AuditSink::record) may block.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: AuditRecord) -> anyhow::Result<()>;
+}
+
+/// Appends records to a file, one JSON object per line.
+pub struct FileAuditSink {
+    file: Mutex<std::fs::File>,
+}
+
+impl FileAuditSink {
+    /// Opens the file at `path` for appending, creating it if it does not exist.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Opening audit log {}", path.display()))?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, record: AuditRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(&record).context("Serializing audit record")?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&line).context("Writing audit record")?;
+        // The record must be durable before the submission's result is returned.
+        file.sync_data().context("Syncing audit log")?;
+
+        Ok(())
+    }
+}
+
+/// Writes `record` to the `sink`, if there is one.
+///
+/// Failures are only logged since the transaction has already been submitted at this point.
+pub(crate) async fn record(sink: Option<&Arc<dyn AuditSink>>, record: AuditRecord) {
+    if let Some(sink) = sink {
+        let sink = sink.clone();
+        let result = tokio::task::spawn_blocking(move || sink.record(record))
+            .await
+            .context("Joining audit log writer");
+        if let Err(e) = result.and_then(|result| result) {
+            tracing::error!(reason=?e, "Failed to write audit record");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::starkhash;
+
+    #[test]
+    fn file_sink_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        let first = AuditRecord::now(
+            "starknet_addDeployTransaction",
+            Some(StarknetTransactionHash(starkhash!("01"))),
+            Some(ContractAddress::new_or_panic(starkhash!("02"))),
+            true,
+        );
+        let second = AuditRecord::now("starknet_addDeployTransaction", None, None, false);
+
+        let sink = FileAuditSink::open(&path).unwrap();
+        sink.record(first.clone()).unwrap();
+        drop(sink);
+        // Re-opening must not truncate the existing records.
+        let sink = FileAuditSink::open(&path).unwrap();
+        sink.record(second.clone()).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let records = contents
+            .lines()
+            .map(|line| serde_json::from_str::<AuditRecord>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(records, vec![first, second]);
+    }
+}
@@ -0,0 +1,683 @@
+//! Newline-delimited JSON (NDJSON) batch submission of `DEPLOY` transactions.
+//!
+//! Every line of the request body is the input of `starknet_addDeployTransaction`, using named
+//! parameters. Every line of the response is the result of one input and is streamed back as
+//! soon as its submission completes. Results are therefore unordered, but echo the zero-based
+//! `index` of their input line.
+//!
+//! With a [Checkpoint], inputs which were already submitted by an earlier, possibly interrupted,
+//! batch are skipped instead of being deployed twice.
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::io::{BufRead, Write};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use bytes::Buf;
+use futures::{Stream, StreamExt};
+use warp::Filter;
+
+use super::method::add_deploy_transaction::{
+    add_deploy_transaction, AddDeployTransactionInput, AddDeployTransactionOutput,
+};
+use super::RpcContext;
+use crate::core::{ContractAddress, StarknetTransactionHash};
+use crate::rpc::error::RpcError;
+
+/// Spawns a server which hosts the batch deploy endpoint at `/deploy/batch`.
+pub async fn spawn_server(
+    addr: impl Into<std::net::SocketAddr> + 'static,
+    context: RpcContext,
+    concurrency: NonZeroUsize,
+    checkpoint: Option<Arc<Checkpoint>>,
+) -> tokio::task::JoinHandle<()> {
+    let server = warp::serve(route(context, concurrency, checkpoint));
+    let server = server.bind(addr);
+
+    tokio::spawn(async move { server.await })
+}
+
+/// Accepts NDJSON deploy inputs at `POST /deploy/batch`, submitting at most `concurrency`
+/// of them at a time.
+///
+/// The request must have a `Content-Length` of at most [MAX_BODY_SIZE] bytes, and lines larger
+/// than [MAX_LINE_SIZE] bytes fail without being deployed.
+///
+/// Inputs recorded in `checkpoint` are skipped, and successful submissions are recorded in it.
+pub fn route(
+    context: RpcContext,
+    concurrency: NonZeroUsize,
+    checkpoint: Option<Arc<Checkpoint>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("deploy" / "batch"))
+        .and(warp::body::content_length_limit(MAX_BODY_SIZE))
+        .and(warp::body::stream())
+        .map(move |body| {
+            let results = deploy_all(
+                context.clone(),
+                lines(Box::pin(body), MAX_LINE_SIZE),
+                concurrency.get(),
+                checkpoint.clone(),
+            )
+            .map(|output| {
+                let mut line = serde_json::to_vec(&output).expect("output is serializable");
+                line.push(b'\n');
+                Ok::<_, Infallible>(line)
+            });
+
+            warp::http::Response::builder()
+                .header(warp::http::header::CONTENT_TYPE, "application/x-ndjson")
+                .body(warp::hyper::Body::wrap_stream(results))
+        })
+}
+
+#[derive(serde::Serialize)]
+struct BatchOutput {
+    /// Index of the input line this is the result of.
+    index: usize,
+    #[serde(flatten)]
+    result: BatchResult,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum BatchResult {
+    Result(AddDeployTransactionOutput),
+    Error(BatchError),
+    /// The input was already submitted according to the [Checkpoint].
+    Skipped(Submission),
+    /// The input is being submitted for another line, or an earlier batch was interrupted while
+    /// submitting it. It is not submitted again, since it may have been submitted already.
+    Unconfirmed {
+        content_hash: String,
+    },
+}
+
+/// Records which batch inputs were submitted, so that an interrupted batch can be resumed
+/// without deploying any of its inputs twice.
+///
+/// Inputs are identified by the Keccak256 hash of their line, ignoring surrounding whitespace.
+/// Records are appended to the checkpoint file, one JSON object per line. An input is recorded
+/// as submitting before it is submitted, so that an input whose submission was interrupted is
+/// not submitted again either.
+pub struct Checkpoint {
+    file: Arc<Mutex<std::fs::File>>,
+    inputs: Mutex<HashMap<String, InputState>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct Submission {
+    transaction_hash: StarknetTransactionHash,
+    contract_address: ContractAddress,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum InputState {
+    /// The input is being submitted, or the batch was interrupted while submitting it.
+    Submitting,
+    Submitted(Submission),
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum CheckpointRecord {
+    /// Recorded before submitting the input.
+    Submitting { content_hash: String },
+    Submitted {
+        content_hash: String,
+        transaction_hash: StarknetTransactionHash,
+        contract_address: ContractAddress,
+    },
+    /// The input was rejected without being submitted, so it may be submitted again.
+    Rejected { content_hash: String },
+}
+
+impl Checkpoint {
+    /// Reads the inputs recorded in the file at `path`, creating it if it does not exist.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Opening checkpoint {}", path.display()))?;
+
+        let mut inputs = HashMap::new();
+        for (i, line) in std::io::BufReader::new(&file).lines().enumerate() {
+            let line = line.context("Reading checkpoint")?;
+            // A line may be incomplete if the batch was interrupted while writing it.
+            match serde_json::from_str::<CheckpointRecord>(&line) {
+                Ok(CheckpointRecord::Submitting { content_hash }) => {
+                    inputs.insert(content_hash, InputState::Submitting);
+                }
+                Ok(CheckpointRecord::Submitted {
+                    content_hash,
+                    transaction_hash,
+                    contract_address,
+                }) => {
+                    let submission = Submission {
+                        transaction_hash,
+                        contract_address,
+                    };
+                    inputs.insert(content_hash, InputState::Submitted(submission));
+                }
+                Ok(CheckpointRecord::Rejected { content_hash }) => {
+                    inputs.remove(&content_hash);
+                }
+                Err(_) if line.trim().is_empty() => {}
+                Err(e) => {
+                    tracing::warn!(line=%(i + 1), reason=%e, "Ignoring invalid checkpoint record")
+                }
+            }
+        }
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            inputs: Mutex::new(inputs),
+        })
+    }
+
+    /// Claims the input with `content_hash` for submission, unless it was claimed before.
+    ///
+    /// Claiming is atomic, so that only one of several identical inputs is submitted.
+    fn claim(&self, content_hash: &str) -> Option<InputState> {
+        let mut inputs = self.inputs.lock().unwrap();
+        match inputs.get(content_hash) {
+            Some(state) => Some(state.clone()),
+            None => {
+                inputs.insert(content_hash.to_owned(), InputState::Submitting);
+                None
+            }
+        }
+    }
+
+    /// Durably records that the claimed input with `content_hash` is about to be submitted.
+    ///
+    /// The claim is released if this fails, since the input was not submitted.
+    async fn record_submitting(&self, content_hash: &str) -> anyhow::Result<()> {
+        let record = CheckpointRecord::Submitting {
+            content_hash: content_hash.to_owned(),
+        };
+        let result = self.append(&record).await;
+        if result.is_err() {
+            self.inputs.lock().unwrap().remove(content_hash);
+        }
+        result
+    }
+
+    async fn record_submitted(
+        &self,
+        content_hash: String,
+        submission: Submission,
+    ) -> anyhow::Result<()> {
+        self.inputs.lock().unwrap().insert(
+            content_hash.clone(),
+            InputState::Submitted(submission.clone()),
+        );
+
+        let record = CheckpointRecord::Submitted {
+            content_hash,
+            transaction_hash: submission.transaction_hash,
+            contract_address: submission.contract_address,
+        };
+        self.append(&record).await
+    }
+
+    /// Releases the claim of an input which was rejected without being submitted.
+    async fn record_rejected(&self, content_hash: String) -> anyhow::Result<()> {
+        self.inputs.lock().unwrap().remove(&content_hash);
+
+        self.append(&CheckpointRecord::Rejected { content_hash })
+            .await
+    }
+
+    async fn append(&self, record: &CheckpointRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(record).context("Serializing checkpoint record")?;
+        line.push(b'\n');
+
+        let file = self.file.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut file = file.lock().unwrap();
+            file.write_all(&line).context("Writing checkpoint record")?;
+            file.sync_data().context("Syncing checkpoint")
+        })
+        .await
+        .context("Joining checkpoint writer")?
+    }
+}
+
+/// Identifies an input line in a [Checkpoint].
+fn content_hash(line: &[u8]) -> String {
+    use sha3::Digest;
+
+    let line = std::str::from_utf8(line).map(str::trim).unwrap_or_default();
+    hex::encode(sha3::Keccak256::digest(line.as_bytes()))
+}
+
+/// Mirrors the JSON-RPC error object.
+#[derive(serde::Serialize)]
+struct BatchError {
+    code: i32,
+    message: String,
+}
+
+impl From<RpcError> for BatchError {
+    fn from(e: RpcError) -> Self {
+        Self {
+            code: e.code(),
+            message: e.to_string(),
+        }
+    }
+}
+
+fn deploy_all(
+    context: RpcContext,
+    lines: impl Stream<Item = Result<Vec<u8>, LineError>>,
+    concurrency: usize,
+    checkpoint: Option<Arc<Checkpoint>>,
+) -> impl Stream<Item = BatchOutput> {
+    lines
+        .enumerate()
+        .filter(|(_, line)| {
+            let blank = matches!(line, Ok(line) if line.iter().all(u8::is_ascii_whitespace));
+            futures::future::ready(!blank)
+        })
+        .map(move |(index, line)| {
+            let context = context.clone();
+            let checkpoint = checkpoint.clone();
+            async move {
+                let result = match checkpoint {
+                    Some(checkpoint) => deploy_once(context, &checkpoint, line).await,
+                    None => into_result(deploy(context, line).await),
+                };
+                BatchOutput { index, result }
+            }
+        })
+        .buffer_unordered(concurrency)
+}
+
+async fn deploy(
+    context: RpcContext,
+    line: Result<Vec<u8>, LineError>,
+) -> Result<AddDeployTransactionOutput, BatchError> {
+    let line = line.map_err(|e| match e {
+        LineError::Body(e) => BatchError::from(RpcError::Internal(
+            anyhow::Error::new(e).context("Reading request body"),
+        )),
+        LineError::TooLong => BatchError {
+            code: jsonrpsee::types::error::ErrorCode::InvalidRequest.code(),
+            message: format!("Line exceeds {MAX_LINE_SIZE} bytes"),
+        },
+    })?;
+
+    let input =
+        serde_json::from_slice::<AddDeployTransactionInput>(&line).map_err(|e| BatchError {
+            code: jsonrpsee::types::error::ErrorCode::InvalidParams.code(),
+            message: format!("Invalid params: {e}"),
+        })?;
+
+    add_deploy_transaction(context, input)
+        .await
+        .map_err(|e| BatchError::from(RpcError::from(e)))
+}
+
+/// Deploys `line` unless `checkpoint` records it as submitted, or as being submitted, already.
+async fn deploy_once(
+    context: RpcContext,
+    checkpoint: &Checkpoint,
+    line: Result<Vec<u8>, LineError>,
+) -> BatchResult {
+    let content_hash = match &line {
+        Ok(line) => content_hash(line),
+        // Reported by `deploy`.
+        Err(_) => return into_result(deploy(context, line).await),
+    };
+
+    match checkpoint.claim(&content_hash) {
+        Some(InputState::Submitted(submission)) => return BatchResult::Skipped(submission),
+        Some(InputState::Submitting) => return BatchResult::Unconfirmed { content_hash },
+        None => {}
+    }
+
+    if let Err(e) = checkpoint.record_submitting(&content_hash).await {
+        return BatchResult::Error(RpcError::Internal(e).into());
+    }
+
+    let result = deploy(context, line).await;
+    // Recording the outcome is only logged, since the deploy was submitted or rejected
+    // regardless. The input stays recorded as submitting if this fails.
+    let recorded = match &result {
+        Ok(output) => {
+            let submission = Submission {
+                transaction_hash: output.transaction_hash,
+                contract_address: output.contract_address,
+            };
+            checkpoint.record_submitted(content_hash, submission).await
+        }
+        // Internal errors include failed requests, which may have been submitted regardless.
+        Err(e) if e.code == jsonrpsee::types::error::ErrorCode::InternalError.code() => Ok(()),
+        Err(_) => checkpoint.record_rejected(content_hash).await,
+    };
+    if let Err(e) = recorded {
+        tracing::error!(reason=?e, "Failed to record batch deploy in checkpoint");
+    }
+
+    into_result(result)
+}
+
+fn into_result(result: Result<AddDeployTransactionOutput, BatchError>) -> BatchResult {
+    match result {
+        Ok(output) => BatchResult::Result(output),
+        Err(e) => BatchResult::Error(e),
+    }
+}
+
+/// Lines longer than this, in bytes, are rejected without buffering all of them.
+const MAX_LINE_SIZE: usize = 10 * 1024 * 1024;
+
+/// Request bodies larger than this, in bytes, are rejected before reading them.
+const MAX_BODY_SIZE: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug)]
+enum LineError {
+    Body(warp::Error),
+    /// The line exceeds the maximum line size.
+    TooLong,
+}
+
+/// The state of [lines].
+struct LineBuffer<S> {
+    body: S,
+    buffer: Vec<u8>,
+    /// The prefix of `buffer` which is known to not contain a newline.
+    scanned: usize,
+    /// The rest of a line which exceeded the maximum size is being skipped.
+    skipping: bool,
+    eof: bool,
+}
+
+/// Splits `body` into lines as its chunks arrive. The newline itself is kept.
+///
+/// Lines longer than `max_line_size` bytes are reported as [LineError::TooLong] and skipped.
+fn lines<B: Buf>(
+    body: impl Stream<Item = Result<B, warp::Error>> + Unpin,
+    max_line_size: usize,
+) -> impl Stream<Item = Result<Vec<u8>, LineError>> {
+    let state = LineBuffer {
+        body,
+        buffer: Vec::new(),
+        scanned: 0,
+        skipping: false,
+        eof: false,
+    };
+
+    futures::stream::unfold(state, move |mut state| async move {
+        loop {
+            let newline = state.buffer[state.scanned..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map(|position| state.scanned + position);
+            match newline {
+                Some(position) => {
+                    let line: Vec<u8> = state.buffer.drain(..=position).collect();
+                    state.scanned = 0;
+                    if std::mem::take(&mut state.skipping) {
+                        // Already reported when it exceeded the maximum size.
+                        continue;
+                    }
+                    if position > max_line_size {
+                        return Some((Err(LineError::TooLong), state));
+                    }
+                    return Some((Ok(line), state));
+                }
+                None => state.scanned = state.buffer.len(),
+            }
+
+            if state.skipping {
+                state.buffer.clear();
+                state.scanned = 0;
+            } else if state.buffer.len() > max_line_size {
+                state.buffer.clear();
+                state.scanned = 0;
+                state.skipping = true;
+                return Some((Err(LineError::TooLong), state));
+            }
+
+            if state.eof {
+                if state.buffer.is_empty() {
+                    return None;
+                }
+                let line = std::mem::take(&mut state.buffer);
+                state.scanned = 0;
+                return Some((Ok(line), state));
+            }
+
+            match state.body.next().await {
+                Some(Ok(mut chunk)) => {
+                    while chunk.has_remaining() {
+                        let part = chunk.chunk();
+                        let len = part.len();
+                        state.buffer.extend_from_slice(part);
+                        chunk.advance(len);
+                    }
+                }
+                Some(Err(e)) => {
+                    // Whatever was buffered is incomplete.
+                    state.buffer.clear();
+                    state.scanned = 0;
+                    state.eof = true;
+                    return Some((Err(LineError::Body(e)), state));
+                }
+                None => state.eof = true,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::v02::types::ContractClass;
+    use crate::sequencer::test_utils::serve_gateway;
+
+    #[tokio::test]
+    async fn long_lines_are_skipped() {
+        let chunks = [
+            "short\nlong",
+            "er than",
+            " ten\nshort",
+            " too\nlast line is long",
+        ];
+        let body = futures::stream::iter(chunks)
+            .map(|chunk| Ok::<_, warp::Error>(bytes::Bytes::from_static(chunk.as_bytes())));
+
+        let lines = lines(body, 10)
+            .map(|line| line.map_err(|e| format!("{e:?}")))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            lines,
+            vec![
+                Ok(b"short\n".to_vec()),
+                Err("TooLong".to_owned()),
+                Ok(b"short too\n".to_vec()),
+                Err("TooLong".to_owned()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn results_echo_the_input_index() {
+        let sequencer_route = warp::any().map(|| {
+            warp::reply::json(&serde_json::json!({
+                "code": "TRANSACTION_RECEIVED",
+                "transaction_hash": "0x3de4caad951e30581554b92ed5dfc29732dca360740598105d6b7cee7afd94f",
+                "address": "0x159519a16ee4370a05009e584855a29f4f1914326283201356f7650290f7789"
+            }))
+        });
+        let (_jh, url) = serve_gateway(sequencer_route);
+
+        let sequencer = crate::sequencer::Client::with_url(url).unwrap();
+        let context = RpcContext {
+            sequencer,
+            ..RpcContext::for_tests()
+        };
+
+        let definition = zstd::decode_all(std::io::Cursor::new(include_bytes!(
+            "../../../fixtures/contract_definition.json.zst"
+        )))
+        .unwrap();
+        let contract_class = ContractClass::from_definition_bytes(&definition).unwrap();
+        let input = serde_json::json!({
+            "deploy_transaction": {
+                "type": "DEPLOY",
+                "version": "0x0",
+                "constructor_calldata": [],
+                "contract_address_salt": "0x1234",
+                "contract_class": contract_class,
+            }
+        })
+        .to_string();
+        let body = format!("{input}\n{{\"invalid\": true}}\n{input}");
+
+        let filter = route(context, NonZeroUsize::new(2).unwrap(), None);
+        let response = warp::test::request()
+            .method("POST")
+            .path("/deploy/batch")
+            .body(body)
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), http::StatusCode::OK);
+
+        let mut results = std::str::from_utf8(response.body())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        results.sort_by_key(|result| result["index"].as_u64());
+
+        let expected_result = serde_json::json!({
+            "transaction_hash": "0x3de4caad951e30581554b92ed5dfc29732dca360740598105d6b7cee7afd94f",
+            "contract_address": "0x159519a16ee4370a05009e584855a29f4f1914326283201356f7650290f7789",
+            "code": "TRANSACTION_RECEIVED"
+        });
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["index"], 0);
+        assert_eq!(results[0]["result"], expected_result);
+        assert_eq!(results[1]["index"], 1);
+        assert_eq!(
+            results[1]["error"]["code"],
+            jsonrpsee::types::error::ErrorCode::InvalidParams.code()
+        );
+        assert_eq!(results[2]["index"], 2);
+        assert_eq!(results[2]["result"], expected_result);
+    }
+
+    #[tokio::test]
+    async fn resumed_batch_skips_submitted_inputs() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let received = Arc::new(AtomicUsize::new(0));
+        let received_clone = received.clone();
+        let sequencer_route = warp::any().map(move || {
+            let count = received_clone.fetch_add(1, Ordering::Relaxed);
+            warp::reply::json(&serde_json::json!({
+                "code": "TRANSACTION_RECEIVED",
+                "transaction_hash": format!("0x{:x}", count + 1),
+                "address": "0x159519a16ee4370a05009e584855a29f4f1914326283201356f7650290f7789"
+            }))
+        });
+        let (_jh, url) = serve_gateway(sequencer_route);
+
+        let sequencer = crate::sequencer::Client::with_url(url).unwrap();
+        let context = RpcContext {
+            sequencer,
+            ..RpcContext::for_tests()
+        };
+
+        let definition = zstd::decode_all(std::io::Cursor::new(include_bytes!(
+            "../../../fixtures/contract_definition.json.zst"
+        )))
+        .unwrap();
+        let contract_class = ContractClass::from_definition_bytes(&definition).unwrap();
+        let input = |salt: &str| {
+            serde_json::json!({
+                "deploy_transaction": {
+                    "type": "DEPLOY",
+                    "version": "0x0",
+                    "constructor_calldata": [],
+                    "contract_address_salt": salt,
+                    "contract_class": contract_class,
+                }
+            })
+            .to_string()
+        };
+        let (first, second) = (input("0x1"), input("0x2"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.ndjson");
+        let submit = |body: String| {
+            let context = context.clone();
+            let checkpoint = Arc::new(Checkpoint::open(&path).unwrap());
+            async move {
+                let filter = route(context, NonZeroUsize::new(2).unwrap(), Some(checkpoint));
+                let response = warp::test::request()
+                    .method("POST")
+                    .path("/deploy/batch")
+                    .body(body)
+                    .reply(&filter)
+                    .await;
+                let mut results = std::str::from_utf8(response.body())
+                    .unwrap()
+                    .lines()
+                    .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+                    .collect::<Vec<_>>();
+                results.sort_by_key(|result| result["index"].as_u64());
+                results
+            }
+        };
+
+        // The batch is interrupted after its first input.
+        let results = submit(format!("{first}\n")).await;
+        assert_eq!(results[0]["result"]["transaction_hash"], "0x1");
+        assert_eq!(received.load(Ordering::Relaxed), 1);
+
+        // Resuming with a reopened checkpoint only submits the second input.
+        let results = submit(format!("{first}\n{second}\n")).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["skipped"]["transaction_hash"], "0x1");
+        assert_eq!(results[1]["result"]["transaction_hash"], "0x2");
+        assert_eq!(received.load(Ordering::Relaxed), 2);
+
+        // An input whose submission was interrupted is not resubmitted, and neither is the
+        // duplicate of an input.
+        let third = input("0x3");
+        let record = serde_json::json!({
+            "state": "submitting",
+            "content_hash": content_hash(third.as_bytes()),
+        });
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(format!("{record}\n").as_bytes())
+            .unwrap();
+
+        let fourth = input("0x4");
+        let results = submit(format!("{third}\n{fourth}\n{fourth}\n")).await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0]["unconfirmed"]["content_hash"],
+            content_hash(third.as_bytes())
+        );
+        let submitted = results[1..]
+            .iter()
+            .filter(|result| result["result"].is_object())
+            .count();
+        assert_eq!(submitted, 1, "{results:?}");
+        assert_eq!(received.load(Ordering::Relaxed), 3);
+    }
+}
@@ -0,0 +1,260 @@
+//! Fetching of the classes of deploys from an external source, e.g. an artifact store, instead
+//! of inlining them in every request.
+use std::net::IpAddr;
+use std::time::Duration;
+
+use reqwest::Url;
+
+/// Fetching a class is aborted after this long.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Fetches classes from the URLs given as the `class_source` of a deploy.
+///
+/// Only URLs of the allowed schemes and hosts are fetched, since the node would otherwise fetch
+/// whatever its callers point it at, e.g. local files or internal services. For the same reason
+/// redirects are not followed.
+#[derive(Clone, Debug)]
+pub struct ClassSources {
+    client: reqwest::Client,
+    /// The lowercase schemes of the URLs which are fetched, e.g. `https`.
+    allowed_schemes: Vec<String>,
+    /// The lowercase hosts which are fetched from, including their subdomains. Any host is
+    /// allowed if empty, except for loopback and private addresses.
+    allowed_hosts: Vec<String>,
+    /// Classes larger than this many bytes are aborted while being received.
+    max_size: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClassSourceError {
+    #[error("fetching classes from a class source is disabled")]
+    Disabled,
+    #[error("invalid class source URL: {0}")]
+    InvalidUrl(String),
+    #[error("class source scheme {0} is not allowed")]
+    SchemeNotAllowed(String),
+    #[error("class source host {0} is not allowed")]
+    HostNotAllowed(String),
+    #[error("class source redirected to another location")]
+    Redirected,
+    #[error("class source exceeds the limit of {limit} bytes")]
+    TooLarge { limit: usize },
+    #[error("fetching class source failed: {0}")]
+    Request(#[from] reqwest::Error),
+}
+
+impl ClassSources {
+    pub fn new(allowed_schemes: Vec<String>, max_size: usize) -> reqwest::Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(FETCH_TIMEOUT)
+                // A redirect would go to an unchecked URL.
+                .redirect(reqwest::redirect::Policy::none())
+                .build()?,
+            allowed_schemes: allowed_schemes
+                .into_iter()
+                .map(|scheme| scheme.to_ascii_lowercase())
+                .collect(),
+            allowed_hosts: Vec::new(),
+            max_size,
+        })
+    }
+
+    /// Only fetches from these hosts and their subdomains, which may include otherwise
+    /// rejected loopback and private addresses.
+    ///
+    /// Without an allowlist, names which resolve to private addresses are fetched from.
+    pub fn with_allowed_hosts(self, allowed_hosts: Vec<String>) -> Self {
+        Self {
+            allowed_hosts: allowed_hosts
+                .into_iter()
+                .map(|host| host.trim_start_matches('.').to_ascii_lowercase())
+                .collect(),
+            ..self
+        }
+    }
+
+    fn check_host(&self, url: &Url) -> Result<(), ClassSourceError> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| ClassSourceError::InvalidUrl(format!("{url} has no host")))?
+            .to_ascii_lowercase();
+
+        let allowed = if self.allowed_hosts.is_empty() {
+            !is_internal(url)
+        } else {
+            self.allowed_hosts
+                .iter()
+                .any(|allowed| host == *allowed || host.ends_with(&format!(".{allowed}")))
+        };
+
+        match allowed {
+            true => Ok(()),
+            false => Err(ClassSourceError::HostNotAllowed(host)),
+        }
+    }
+
+    /// Returns the body of a successful response to `GET url`.
+    pub(crate) async fn fetch(&self, url: &str) -> Result<bytes::Bytes, ClassSourceError> {
+        let url =
+            reqwest::Url::parse(url).map_err(|e| ClassSourceError::InvalidUrl(e.to_string()))?;
+        if !self.allowed_schemes.iter().any(|s| s == url.scheme()) {
+            return Err(ClassSourceError::SchemeNotAllowed(url.scheme().to_owned()));
+        }
+        self.check_host(&url)?;
+
+        let response = self.client.get(url).send().await?;
+        if response.status().is_redirection() {
+            return Err(ClassSourceError::Redirected);
+        }
+        let mut response = response.error_for_status()?;
+
+        let limit = self.max_size;
+        if matches!(response.content_length(), Some(length) if length > limit as u64) {
+            return Err(ClassSourceError::TooLarge { limit });
+        }
+
+        let mut body = bytes::BytesMut::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(ClassSourceError::TooLarge { limit });
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body.freeze())
+    }
+}
+
+/// Whether the host of `url` is `localhost` or a loopback, private, link-local or unspecified
+/// address.
+fn is_internal(url: &Url) -> bool {
+    let host = match url.host_str() {
+        Some(host) => host.to_ascii_lowercase(),
+        None => return true,
+    };
+
+    // IPv6 hosts are enclosed in brackets.
+    match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) => is_internal_ip(ip),
+        Err(_) => host == "localhost" || host.ends_with(".localhost"),
+    }
+}
+
+fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // Shared address space of carrier-grade NAT, 100.64.0.0/10.
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64)
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            // IPv4-mapped, ::ffff:0:0/96.
+            if let [0, 0, 0, 0, 0, 0xffff, high, low] = segments {
+                let ip = std::net::Ipv4Addr::from((u32::from(high) << 16) | u32::from(low));
+                return is_internal_ip(IpAddr::V4(ip));
+            }
+            let first = segments[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                // Unique local, fc00::/7.
+                || first & 0xfe00 == 0xfc00
+                // Link-local, fe80::/10.
+                || first & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::test_utils::serve_gateway;
+    use warp::Filter;
+
+    /// Serves `body` at `/class.json`, returning its URL.
+    fn serve(body: &'static str) -> reqwest::Url {
+        let route = warp::path!("class.json").map(move || body);
+        serve_gateway(route).1.join("class.json").unwrap()
+    }
+
+    fn local_sources(max_size: usize) -> ClassSources {
+        ClassSources::new(vec!["http".to_owned()], max_size)
+            .unwrap()
+            .with_allowed_hosts(vec!["127.0.0.1".to_owned()])
+    }
+
+    #[tokio::test]
+    async fn too_large() {
+        let url = serve(r#"{"program": "too large"}"#);
+        let sources = local_sources(8);
+
+        let error = sources.fetch(url.as_str()).await.unwrap_err();
+        assert_matches::assert_matches!(error, ClassSourceError::TooLarge { limit: 8 });
+    }
+
+    #[tokio::test]
+    async fn scheme_not_allowed() {
+        let url = serve("{}");
+        let sources = ClassSources::new(vec!["HTTPS".to_owned()], 1024).unwrap();
+
+        let error = sources.fetch(url.as_str()).await.unwrap_err();
+        assert_matches::assert_matches!(error, ClassSourceError::SchemeNotAllowed(scheme) => assert_eq!(scheme, "http"));
+    }
+
+    #[tokio::test]
+    async fn redirects_are_not_followed() {
+        let target = serve("{}");
+        let route = warp::any().map(move || {
+            warp::redirect::temporary(target.as_str().parse::<warp::http::Uri>().unwrap())
+        });
+        let url = serve_gateway(route).1.join("class.json").unwrap();
+
+        let error = local_sources(1024).fetch(url.as_str()).await.unwrap_err();
+        assert_matches::assert_matches!(error, ClassSourceError::Redirected);
+    }
+
+    #[tokio::test]
+    async fn internal_hosts_are_rejected_without_allowlist() {
+        let sources = ClassSources::new(vec!["http".to_owned()], 1024).unwrap();
+
+        for url in [
+            "http://127.0.0.1:9545/class.json",
+            "http://localhost/class.json",
+            "http://10.1.2.3/class.json",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/class.json",
+            "http://[::ffff:192.168.0.1]/class.json",
+            "http://[fd00::1]/class.json",
+        ] {
+            let error = sources.fetch(url).await.unwrap_err();
+            assert_matches::assert_matches!(error, ClassSourceError::HostNotAllowed(_), "{url}");
+        }
+    }
+
+    #[tokio::test]
+    async fn hosts_outside_allowlist_are_rejected() {
+        let url = serve("{}");
+        let sources = ClassSources::new(vec!["http".to_owned()], 1024)
+            .unwrap()
+            .with_allowed_hosts(vec!["artifacts.example".to_owned()]);
+
+        let error = sources.fetch(url.as_str()).await.unwrap_err();
+        assert_matches::assert_matches!(error, ClassSourceError::HostNotAllowed(host) => assert_eq!(host, "127.0.0.1"));
+
+        assert!(sources
+            .check_host(&Url::parse("https://eu.artifacts.example/class.json").unwrap())
+            .is_ok());
+        assert!(sources
+            .check_host(&Url::parse("https://notartifacts.example/class.json").unwrap())
+            .is_err());
+    }
+}
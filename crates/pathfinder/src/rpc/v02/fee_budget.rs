@@ -0,0 +1,109 @@
+//! Limiting of the fees committed to submissions within a rolling time window.
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::core::Fee;
+
+/// Tracks the `max_fee` of submissions within the last `window` and rejects submissions which
+/// would take the total over `budget`.
+///
+/// A `max_fee` counts against the budget as soon as it is committed, whether or not the
+/// submission then succeeds.
+#[derive(Debug)]
+pub struct FeeBudget {
+    budget: u128,
+    window: Duration,
+    /// The committed fees and when they were committed, the oldest first.
+    committed: Mutex<VecDeque<(Instant, u128)>>,
+}
+
+impl FeeBudget {
+    pub fn new(budget: Fee, window: Duration) -> Self {
+        Self {
+            budget: to_u128(budget),
+            window,
+            committed: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Commits `max_fee` to a submission, or returns the remaining budget if `max_fee`
+    /// exceeds it.
+    pub(crate) fn commit(&self, max_fee: Fee) -> Result<(), Fee> {
+        self.commit_at(max_fee, Instant::now())
+    }
+
+    fn commit_at(&self, max_fee: Fee, now: Instant) -> Result<(), Fee> {
+        let mut committed = self.committed.lock().unwrap();
+        while let Some((at, _)) = committed.front() {
+            if now.saturating_duration_since(*at) < self.window {
+                break;
+            }
+            committed.pop_front();
+        }
+
+        let spent = committed
+            .iter()
+            .fold(0u128, |spent, (_, fee)| spent.saturating_add(*fee));
+        let remaining = self.budget.saturating_sub(spent);
+        let max_fee = to_u128(max_fee);
+        if max_fee > remaining {
+            return Err(Fee(remaining.to_be_bytes().into()));
+        }
+
+        committed.push_back((now, max_fee));
+        Ok(())
+    }
+}
+
+fn to_u128(fee: Fee) -> u128 {
+    u128::from_be_bytes(fee.0.to_fixed_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee(fee: u128) -> Fee {
+        Fee(fee.to_be_bytes().into())
+    }
+
+    #[test]
+    fn exceeding_the_budget_is_rejected() {
+        let budget = FeeBudget::new(fee(100), Duration::from_secs(60));
+        let now = Instant::now();
+
+        budget.commit_at(fee(40), now).unwrap();
+        budget.commit_at(fee(60), now).unwrap();
+        assert_eq!(budget.commit_at(fee(1), now), Err(fee(0)));
+    }
+
+    #[test]
+    fn rejected_fees_are_not_committed() {
+        let budget = FeeBudget::new(fee(100), Duration::from_secs(60));
+        let now = Instant::now();
+
+        budget.commit_at(fee(70), now).unwrap();
+        assert_eq!(budget.commit_at(fee(50), now), Err(fee(30)));
+        budget.commit_at(fee(30), now).unwrap();
+    }
+
+    #[test]
+    fn fees_leave_the_window() {
+        let budget = FeeBudget::new(fee(100), Duration::from_secs(60));
+        let start = Instant::now();
+
+        budget.commit_at(fee(80), start).unwrap();
+        budget
+            .commit_at(fee(20), start + Duration::from_secs(30))
+            .unwrap();
+        assert_eq!(
+            budget.commit_at(fee(50), start + Duration::from_secs(59)),
+            Err(fee(0))
+        );
+        // Only the second fee is within the window by now.
+        budget
+            .commit_at(fee(80), start + Duration::from_secs(60))
+            .unwrap();
+    }
+}
@@ -0,0 +1,79 @@
+//! Comparison of a transaction's `max_fee` with a live estimate before submitting it.
+use crate::core::{BlockId, Fee};
+use crate::sequencer::request::add_transaction::AddTransaction;
+use crate::sequencer::ClientApi;
+
+/// Estimates the fee of transactions before they are submitted and flags those whose `max_fee`
+/// is below the estimate times the safety factor, which the sequencer would likely reject.
+#[derive(Copy, Clone, Debug)]
+pub struct MaxFeeCheck {
+    /// The `max_fee` must cover the estimate times this factor, in basis points, which allows
+    /// for the fee rising between the estimate and the execution.
+    pub safety_factor_bps: u32,
+    /// Reject flagged transactions instead of only warning about them.
+    pub reject: bool,
+}
+
+impl MaxFeeCheck {
+    /// The `max_fee` required of a transaction whose fee is estimated at `estimate`, rounded up
+    /// and saturating at the largest fee.
+    pub fn required(&self, estimate: u128) -> u128 {
+        crate::sequencer::deploy::multiply_fee(estimate, self.safety_factor_bps)
+    }
+
+    /// Returns the [required](Self::required) `max_fee` if `max_fee` falls short of it and
+    /// flagged transactions are rejected.
+    ///
+    /// Failing estimates are only logged, leaving it to the sequencer to reject such
+    /// transactions.
+    pub(crate) async fn below_estimate(
+        &self,
+        sequencer: &impl ClientApi,
+        transaction: AddTransaction,
+        max_fee: Fee,
+    ) -> Option<Fee> {
+        let estimate = match sequencer
+            .estimate_fee_bulk(vec![transaction], BlockId::Pending)
+            .await
+        {
+            Ok(estimates) => estimates.first()?.overall_fee,
+            Err(e) => {
+                tracing::warn!(reason=%e, "Failed to estimate the fee of a transaction before submitting it");
+                return None;
+            }
+        };
+
+        let max_fee = u128::from_be_bytes(max_fee.0.to_fixed_bytes());
+        let required = self.required(estimate);
+        if max_fee >= required {
+            return None;
+        }
+
+        tracing::warn!(
+            %max_fee,
+            %estimate,
+            %required,
+            "Max fee is below the estimated fee times the safety factor"
+        );
+        self.reject.then(|| Fee(required.to_be_bytes().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MaxFeeCheck;
+
+    #[test]
+    fn required_is_rounded_up() {
+        let check = MaxFeeCheck {
+            safety_factor_bps: 15_000,
+            reject: true,
+        };
+        assert_eq!(check.required(5_000_000_000_000), 7_500_000_000_000);
+        assert_eq!(check.required(3), 5);
+        assert_eq!(check.required(0), 0);
+        // Beyond the precision of floating point numbers.
+        assert_eq!(check.required(u128::MAX / 2 - 1), u128::MAX / 4 * 3);
+        assert_eq!(check.required(u128::MAX), u128::MAX);
+    }
+}
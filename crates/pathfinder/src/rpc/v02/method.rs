@@ -19,4 +19,5 @@ pub(super) mod get_transaction_by_block_id_and_index;
 pub(super) mod get_transaction_by_hash;
 pub(super) mod get_transaction_receipt;
 pub(super) mod pending_transactions;
+pub(super) mod sequencer_config;
 pub(super) mod syncing;
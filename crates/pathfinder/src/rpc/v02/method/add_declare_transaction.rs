@@ -1,11 +1,85 @@
-use crate::core::{ClassHash, StarknetTransactionHash};
+use crate::core::{ClassHash, Fee, StarknetTransactionHash, TransactionVersion};
+use crate::rpc::error::RpcError;
 use crate::rpc::v02::types::request::BroadcastedDeclareTransaction;
+use crate::rpc::v02::types::{ClassKind, ContractClassError, IncompatibleClassVersion};
 use crate::rpc::v02::RpcContext;
 use crate::sequencer::error::SequencerError;
-use crate::sequencer::request::add_transaction::ContractDefinition;
+use crate::sequencer::request::add_transaction::{AddTransaction, ContractDefinition, Declare};
 use crate::sequencer::ClientApi;
 
-crate::rpc::error::generate_rpc_error_subset!(AddDeclareTransactionError: InvalidContractClass);
+// Not generated using `generate_rpc_error_subset!` as it does not support variants with fields.
+#[derive(Debug)]
+pub enum AddDeclareTransactionError {
+    Internal(anyhow::Error),
+    InvalidContractClass,
+    IncompatibleClassVersion {
+        class_version: ClassKind,
+        tx_version: TransactionVersion,
+    },
+    FeeExceedsCap {
+        max_fee: Fee,
+        cap: Fee,
+    },
+    FeeBudgetExceeded {
+        max_fee: Fee,
+        remaining: Fee,
+    },
+    MaxFeeBelowEstimate {
+        max_fee: Fee,
+        required: Fee,
+    },
+    /// The class failed the checks of [RpcContext::class_validation].
+    ClassValidation(ContractClassError),
+}
+
+impl From<ContractClassError> for AddDeclareTransactionError {
+    fn from(e: ContractClassError) -> Self {
+        Self::ClassValidation(e)
+    }
+}
+
+impl From<anyhow::Error> for AddDeclareTransactionError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<IncompatibleClassVersion> for AddDeclareTransactionError {
+    fn from(e: IncompatibleClassVersion) -> Self {
+        Self::IncompatibleClassVersion {
+            class_version: e.class_version,
+            tx_version: e.tx_version,
+        }
+    }
+}
+
+impl From<AddDeclareTransactionError> for RpcError {
+    fn from(e: AddDeclareTransactionError) -> Self {
+        match e {
+            AddDeclareTransactionError::Internal(internal) => Self::Internal(internal),
+            AddDeclareTransactionError::ClassValidation(e) => Self::ClassValidationFailed {
+                reason: e.to_string(),
+            },
+            AddDeclareTransactionError::InvalidContractClass => Self::InvalidContractClass,
+            AddDeclareTransactionError::IncompatibleClassVersion {
+                class_version,
+                tx_version,
+            } => Self::IncompatibleClassVersion {
+                class_version,
+                tx_version,
+            },
+            AddDeclareTransactionError::FeeExceedsCap { max_fee, cap } => {
+                Self::FeeExceedsCap { max_fee, cap }
+            }
+            AddDeclareTransactionError::FeeBudgetExceeded { max_fee, remaining } => {
+                Self::FeeBudgetExceeded { max_fee, remaining }
+            }
+            AddDeclareTransactionError::MaxFeeBelowEstimate { max_fee, required } => {
+                Self::MaxFeeBelowEstimate { max_fee, required }
+            }
+        }
+    }
+}
 
 impl From<SequencerError> for AddDeclareTransactionError {
     fn from(e: SequencerError) -> Self {
@@ -27,6 +101,7 @@ pub enum Transaction {
 }
 
 #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rpc-strict-inputs", serde(deny_unknown_fields))]
 pub struct AddDeclareTransactionInput {
     declare_transaction: Transaction,
     // An undocumented parameter that we forward to the sequencer API
@@ -46,20 +121,57 @@ pub async fn add_declare_transaction(
     input: AddDeclareTransactionInput,
 ) -> Result<AddDeclareTransactionOutput, AddDeclareTransactionError> {
     let Transaction::Declare(tx) = input.declare_transaction;
+    tx.contract_class.kind().check_declare_version(tx.version)?;
+    context.validate_class(&tx.contract_class).await??;
     let contract_definition: ContractDefinition = tx
         .contract_class
         .try_into()
         .map_err(|e| anyhow::anyhow!("Failed to convert contract definition: {}", e))?;
+    if let Some(cap) = context.exceeded_fee_cap(tx.max_fee) {
+        return Err(AddDeclareTransactionError::FeeExceedsCap {
+            max_fee: tx.max_fee,
+            cap,
+        });
+    }
+    context.ensure_online()?;
+    if let Some(check) = &context.max_fee_check {
+        let request = AddTransaction::Declare(Declare {
+            version: tx.version,
+            max_fee: tx.max_fee,
+            signature: tx.signature.0.clone(),
+            contract_class: contract_definition.clone(),
+            sender_address: tx.sender_address,
+            nonce: tx.nonce,
+            compiled_class_hash: tx.compiled_class_hash,
+        });
+        if let Some(required) = check
+            .below_estimate(&context.sequencer, request, tx.max_fee)
+            .await
+        {
+            return Err(AddDeclareTransactionError::MaxFeeBelowEstimate {
+                max_fee: tx.max_fee,
+                required,
+            });
+        }
+    }
+    // Only declares which passed every local check count against the budget.
+    if let Some(remaining) = context.exceeded_fee_budget(tx.max_fee) {
+        return Err(AddDeclareTransactionError::FeeBudgetExceeded {
+            max_fee: tx.max_fee,
+            remaining,
+        });
+    }
 
     let response = context
         .sequencer
         .add_declare_transaction(
             tx.version,
             tx.max_fee,
-            tx.signature,
+            tx.signature.0,
             tx.nonce,
             contract_definition,
             tx.sender_address,
+            tx.compiled_class_hash,
             input.token,
         )
         .await?;
@@ -74,7 +186,7 @@ pub async fn add_declare_transaction(
 mod tests {
     use stark_hash::StarkHash;
 
-    use crate::core::{ContractAddress, Fee, TransactionNonce, TransactionVersion};
+    use crate::core::{CasmHash, ContractAddress, Fee, TransactionNonce, TransactionVersion};
     use crate::rpc::v02::types::request::BroadcastedDeclareTransaction;
     use crate::rpc::v02::types::ContractClass;
     use crate::starkhash;
@@ -103,10 +215,11 @@ mod tests {
             Transaction::Declare(BroadcastedDeclareTransaction {
                 max_fee: Fee(web3::types::H128::from_low_u64_be(1)),
                 version: TransactionVersion::ZERO,
-                signature: vec![],
+                signature: Default::default(),
                 nonce: TransactionNonce(StarkHash::ZERO),
                 contract_class: CONTRACT_CLASS.clone(),
                 sender_address: ContractAddress::new_or_panic(StarkHash::from_u64(1)),
+                compiled_class_hash: None,
             })
         }
 
@@ -166,6 +279,41 @@ mod tests {
             };
             assert_eq!(input, expected);
         }
+
+        #[test]
+        fn v2_with_compiled_class_hash() {
+            use jsonrpsee::types::Params;
+
+            let named = format!(
+                r#"{{
+                    "declare_transaction": {{
+                        "type": "DECLARE",
+                        "version": "0x2",
+                        "max_fee": "0x1",
+                        "signature": [],
+                        "nonce": "0x0",
+                        "contract_class": {},
+                        "sender_address": "0x1",
+                        "compiled_class_hash": "0xcc"
+                    }}
+                }}"#,
+                CONTRACT_CLASS_JSON.clone()
+            );
+            let named = Params::new(Some(&named));
+
+            let input = named.parse::<AddDeclareTransactionInput>().unwrap();
+            let expected = AddDeclareTransactionInput {
+                declare_transaction: Transaction::Declare(BroadcastedDeclareTransaction {
+                    version: TransactionVersion(web3::types::H256::from_low_u64_be(2)),
+                    compiled_class_hash: Some(CasmHash(starkhash!("cc"))),
+                    ..match test_declare_txn() {
+                        Transaction::Declare(tx) => tx,
+                    }
+                }),
+                token: None,
+            };
+            assert_eq!(input, expected);
+        }
     }
 
     #[test_log::test(tokio::test)]
@@ -180,10 +328,11 @@ mod tests {
         let declare_transaction = Transaction::Declare(BroadcastedDeclareTransaction {
             version: TransactionVersion::ZERO,
             max_fee: Fee(Default::default()),
-            signature: vec![],
+            signature: Default::default(),
             nonce: TransactionNonce(Default::default()),
             contract_class: invalid_contract_class,
             sender_address: ContractAddress::new_or_panic(StarkHash::from_u64(1)),
+            compiled_class_hash: None,
         });
 
         let input = AddDeclareTransactionInput {
@@ -194,6 +343,62 @@ mod tests {
         assert_matches::assert_matches!(error, AddDeclareTransactionError::InvalidContractClass);
     }
 
+    #[tokio::test]
+    async fn rejected_declare_does_not_use_fee_budget() {
+        use crate::rpc::v02::fee_budget::FeeBudget;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let max_fee = Fee(web3::types::H128::from_low_u64_be(100));
+        // The sequencer is unreachable, so only local checks pass or fail.
+        let context = RpcContext {
+            sequencer: crate::sequencer::Client::with_url(
+                "http://sequencer.invalid/".parse().unwrap(),
+            )
+            .unwrap(),
+            ..RpcContext::for_tests()
+        }
+        .with_fee_budget(Arc::new(FeeBudget::new(max_fee, Duration::from_secs(3600))));
+        let declare = |version| AddDeclareTransactionInput {
+            declare_transaction: Transaction::Declare(BroadcastedDeclareTransaction {
+                version,
+                max_fee,
+                signature: Default::default(),
+                nonce: TransactionNonce(Default::default()),
+                contract_class: CONTRACT_CLASS.clone(),
+                sender_address: ContractAddress::new_or_panic(StarkHash::from_u64(1)),
+                compiled_class_hash: None,
+            }),
+            token: None,
+        };
+
+        // Cairo 0 classes cannot be declared using version 2.
+        let v2 = TransactionVersion(web3::types::H256::from_low_u64_be(2));
+        let error = add_declare_transaction(context.clone(), declare(v2))
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeclareTransactionError::IncompatibleClassVersion { .. }
+        );
+
+        let error = add_declare_transaction(context.clone(), declare(TransactionVersion::ONE))
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(error, AddDeclareTransactionError::Internal(_));
+
+        let error = add_declare_transaction(context, declare(TransactionVersion::ONE))
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeclareTransactionError::FeeBudgetExceeded { max_fee: m, remaining } => {
+                assert_eq!(m, max_fee);
+                assert_eq!(remaining, Fee(Default::default()));
+            }
+        );
+    }
+
     #[test_log::test(tokio::test)]
     async fn successful_declare() {
         let context = RpcContext::for_tests();
@@ -201,10 +406,11 @@ mod tests {
         let declare_transaction = Transaction::Declare(BroadcastedDeclareTransaction {
             version: TransactionVersion::ZERO,
             max_fee: Fee(Default::default()),
-            signature: vec![],
+            signature: Default::default(),
             nonce: TransactionNonce(Default::default()),
             contract_class: CONTRACT_CLASS.clone(),
             sender_address: ContractAddress::new_or_panic(StarkHash::from_u64(1)),
+            compiled_class_hash: None,
         });
 
         let input = AddDeclareTransactionInput {
@@ -1,9 +1,19 @@
 use anyhow::Context;
 
 use crate::{
-    core::{ContractAddress, StarknetTransactionHash},
-    rpc::v02::{types::request::BroadcastedDeployAccountTransaction, RpcContext},
-    sequencer::ClientApi,
+    core::{ContractAddress, Fee, StarknetTransactionHash},
+    rpc::{
+        error::RpcError,
+        v02::{
+            signer::{build_deploy_account_payload, Signer},
+            types::request::BroadcastedDeployAccountTransaction,
+            RpcContext,
+        },
+    },
+    sequencer::{
+        request::add_transaction::{AddTransaction, DeployAccount},
+        ClientApi,
+    },
 };
 
 #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
@@ -14,6 +24,7 @@ pub enum Transaction {
 }
 
 #[derive(Debug, serde::Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "rpc-strict-inputs", serde(deny_unknown_fields))]
 pub struct AddDeployAccountTransactionInput {
     deploy_account_transaction: Transaction,
 }
@@ -24,19 +35,89 @@ pub struct AddDeployAccountTransactionOutput {
     contract_address: ContractAddress,
 }
 
-crate::rpc::error::generate_rpc_error_subset!(AddDeployAccountTransactionError: ClassHashNotFound);
+// Not generated using `generate_rpc_error_subset!` as it does not support variants with fields.
+#[derive(Debug)]
+pub enum AddDeployAccountTransactionError {
+    Internal(anyhow::Error),
+    ClassHashNotFound,
+    FeeExceedsCap { max_fee: Fee, cap: Fee },
+    FeeBudgetExceeded { max_fee: Fee, remaining: Fee },
+    MaxFeeBelowEstimate { max_fee: Fee, required: Fee },
+}
+
+impl From<anyhow::Error> for AddDeployAccountTransactionError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<AddDeployAccountTransactionError> for RpcError {
+    fn from(e: AddDeployAccountTransactionError) -> Self {
+        match e {
+            AddDeployAccountTransactionError::Internal(internal) => Self::Internal(internal),
+            AddDeployAccountTransactionError::ClassHashNotFound => Self::ClassHashNotFound,
+            AddDeployAccountTransactionError::FeeExceedsCap { max_fee, cap } => {
+                Self::FeeExceedsCap { max_fee, cap }
+            }
+            AddDeployAccountTransactionError::FeeBudgetExceeded { max_fee, remaining } => {
+                Self::FeeBudgetExceeded { max_fee, remaining }
+            }
+            AddDeployAccountTransactionError::MaxFeeBelowEstimate { max_fee, required } => {
+                Self::MaxFeeBelowEstimate { max_fee, required }
+            }
+        }
+    }
+}
 
 pub async fn add_deploy_account_transaction(
     context: RpcContext,
     input: AddDeployAccountTransactionInput,
 ) -> Result<AddDeployAccountTransactionOutput, AddDeployAccountTransactionError> {
     let Transaction::DeployAccount(tx) = input.deploy_account_transaction;
+    context
+        .validate_signature(&tx.signature)
+        .context("Validating signature")?;
+    if let Some(cap) = context.exceeded_fee_cap(tx.max_fee) {
+        return Err(AddDeployAccountTransactionError::FeeExceedsCap {
+            max_fee: tx.max_fee,
+            cap,
+        });
+    }
+    context.ensure_online()?;
+    if let Some(check) = &context.max_fee_check {
+        let request = AddTransaction::DeployAccount(DeployAccount {
+            version: tx.version,
+            max_fee: tx.max_fee,
+            signature: tx.signature.0.clone(),
+            nonce: tx.nonce,
+            class_hash: tx.class_hash,
+            contract_address_salt: tx.contract_address_salt,
+            constructor_calldata: tx.constructor_calldata.clone(),
+        });
+        if let Some(required) = check
+            .below_estimate(&context.sequencer, request, tx.max_fee)
+            .await
+        {
+            return Err(AddDeployAccountTransactionError::MaxFeeBelowEstimate {
+                max_fee: tx.max_fee,
+                required,
+            });
+        }
+    }
+    // Committed last, so that transactions failing any other check do not use up the budget.
+    if let Some(remaining) = context.exceeded_fee_budget(tx.max_fee) {
+        return Err(AddDeployAccountTransactionError::FeeBudgetExceeded {
+            max_fee: tx.max_fee,
+            remaining,
+        });
+    }
+
     let response = context
         .sequencer
         .add_deploy_account(
             tx.version,
             tx.max_fee,
-            tx.signature,
+            tx.signature.0,
             tx.nonce,
             tx.contract_address_salt,
             tx.class_hash,
@@ -51,14 +132,50 @@ pub async fn add_deploy_account_transaction(
     })
 }
 
+/// Submits a DEPLOY_ACCOUNT transaction whose signature is produced by `signer`.
+///
+/// The transaction hash is computed locally and only its signing is delegated. Any
+/// signature already present in `tx` is replaced.
+pub async fn add_deploy_account_transaction_with_signer(
+    context: RpcContext,
+    tx: BroadcastedDeployAccountTransaction,
+    signer: &dyn Signer,
+) -> Result<AddDeployAccountTransactionOutput, AddDeployAccountTransactionError> {
+    let payload = build_deploy_account_payload(
+        tx.version,
+        tx.max_fee,
+        tx.nonce,
+        tx.contract_address_salt,
+        tx.class_hash,
+        tx.constructor_calldata,
+        context.chain,
+    );
+
+    let signature = signer
+        .sign(payload.transaction_hash.0)
+        .await
+        .context("Signing Deploy Account Transaction")?;
+    let tx = payload.with_signature(signature);
+
+    add_deploy_account_transaction(
+        context,
+        AddDeployAccountTransactionInput {
+            deploy_account_transaction: Transaction::DeployAccount(tx),
+        },
+    )
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         core::{
-            CallParam, Chain, ClassHash, ContractAddressSalt, Fee, TransactionNonce,
+            CallParam, Chain, ClassHash, ContractAddressSalt, TransactionNonce,
             TransactionSignatureElem, TransactionVersion,
         },
+        rpc::v02::types::request::Signature,
+        sequencer::test_utils::serve_gateway,
         starkhash,
     };
 
@@ -106,14 +223,14 @@ mod tests {
                         0x91, 0x37, 0x78, 0x13,
                     ]
                     .into()),
-                    signature: vec![
+                    signature: Signature(vec![
                         TransactionSignatureElem(starkhash!(
                             "07dd3a55d94a0de6f3d6c104d7e6c88ec719a82f4e2bbc12587c8c187584d3d5"
                         )),
                         TransactionSignatureElem(starkhash!(
                             "071456dded17015d1234779889d78f3e7c763ddcfd2662b19e7843c7542614f8"
                         )),
-                    ],
+                    ]),
                     nonce: TransactionNonce::ZERO,
 
                     contract_address_salt: ContractAddressSalt(starkhash!(
@@ -152,4 +269,100 @@ mod tests {
 
         assert_eq!(response, expected);
     }
+
+    #[tokio::test]
+    async fn fee_over_cap_is_rejected() {
+        let input = get_input();
+        let Transaction::DeployAccount(tx) = &input.deploy_account_transaction;
+        let max_fee = tx.max_fee;
+        let cap = Fee(web3::types::H128::from_low_u64_be(0xbf391377812));
+        // The sequencer is unreachable, so the transaction must be rejected locally.
+        let context = RpcContext {
+            sequencer: crate::sequencer::Client::with_url(
+                "http://sequencer.invalid/".parse().unwrap(),
+            )
+            .unwrap(),
+            ..RpcContext::for_tests_on(Chain::Integration)
+        }
+        .with_max_fee_cap(cap);
+
+        let error = add_deploy_account_transaction(context, input)
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeployAccountTransactionError::FeeExceedsCap { max_fee: m, cap: c } if m == max_fee && c == cap
+        );
+    }
+
+    #[tokio::test]
+    async fn signature_is_delegated_to_signer() {
+        use crate::sequencer::request::add_transaction::AddTransaction;
+        use stark_hash::StarkHash;
+        use std::sync::{Arc, Mutex};
+        use warp::Filter;
+
+        /// Records the hash it was asked to sign and returns a fixed signature.
+        #[derive(Default)]
+        struct StubSigner(Mutex<Option<StarkHash>>);
+
+        #[async_trait::async_trait]
+        impl Signer for StubSigner {
+            async fn sign(&self, message_hash: StarkHash) -> anyhow::Result<Signature> {
+                *self.0.lock().unwrap() = Some(message_hash);
+                Ok(Signature(vec![
+                    TransactionSignatureElem(starkhash!("0a")),
+                    TransactionSignatureElem(starkhash!("0b")),
+                ]))
+            }
+        }
+
+        let received = Arc::new(Mutex::new(None));
+        let received_clone = received.clone();
+        let route = warp::any()
+            .and(warp::body::json())
+            .map(move |body: AddTransaction| {
+                *received_clone.lock().unwrap() = Some(body);
+                warp::reply::json(&serde_json::json!({
+                    "code": "TRANSACTION_RECEIVED",
+                    "transaction_hash": "0x273fb3c38b20037839d6bad8811cd0afd82f2bc3c95c061eb8f30ce5cedc377",
+                    "address": "0x42ae26ab2b8236242bb384c23e74c69af7204bb2fc711a99da63e0dd6adf33f"
+                }))
+            });
+        let (_jh, url) = serve_gateway(route);
+
+        let context = RpcContext {
+            sequencer: crate::sequencer::Client::with_url(url).unwrap(),
+            ..RpcContext::for_tests_on(Chain::Integration)
+        };
+
+        let Transaction::DeployAccount(mut tx) = get_input().deploy_account_transaction;
+        tx.signature = Signature::default();
+
+        let signer = StubSigner::default();
+        add_deploy_account_transaction_with_signer(context, tx, &signer)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *signer.0.lock().unwrap(),
+            Some(starkhash!(
+                "0273fb3c38b20037839d6bad8811cd0afd82f2bc3c95c061eb8f30ce5cedc377"
+            ))
+        );
+
+        let received = received.lock().unwrap().take().unwrap();
+        match received {
+            AddTransaction::DeployAccount(deploy_account) => {
+                assert_eq!(
+                    deploy_account.signature,
+                    vec![
+                        TransactionSignatureElem(starkhash!("0a")),
+                        TransactionSignatureElem(starkhash!("0b")),
+                    ]
+                );
+            }
+            _ => panic!("Expected a DEPLOY_ACCOUNT transaction"),
+        }
+    }
 }
@@ -1,11 +1,105 @@
-use crate::core::{ContractAddress, StarknetTransactionHash};
+use anyhow::Context;
+
+use crate::core::{
+    BlockId, ClassHash, ContractAddress, StarknetTransactionHash, TransactionVersion,
+};
+use crate::rpc::error::RpcError;
+use crate::rpc::v02::audit::{self, AuditRecord};
+use crate::rpc::v02::class_source::ClassSourceError;
+use crate::rpc::v02::submission_store::{self, SubmissionRecord};
 use crate::rpc::v02::types::request::BroadcastedDeployTransaction;
+use crate::rpc::v02::types::{ClassKind, ContractClassError, IncompatibleClassVersion};
 use crate::rpc::v02::RpcContext;
 use crate::sequencer::error::SequencerError;
-use crate::sequencer::request::add_transaction::ContractDefinition;
+use crate::sequencer::replay::ReplayRecord;
+use crate::sequencer::reply::add_transaction::AddTransactionCode;
+use crate::sequencer::request::add_transaction::{ContractDefinition, Deploy};
 use crate::sequencer::ClientApi;
+use crate::state::transaction_hash::{
+    compute_contract_address, compute_deploy_transaction_hash, ZERO_DEPLOYER_ADDRESS,
+};
+
+// Not generated using `generate_rpc_error_subset!` as it does not support variants with fields.
+#[derive(Debug)]
+pub enum AddDeployTransactionError {
+    Internal(anyhow::Error),
+    InvalidContractClass,
+    IncompatibleClassVersion {
+        class_version: ClassKind,
+        tx_version: TransactionVersion,
+    },
+    AlreadyDeployed {
+        address: ContractAddress,
+    },
+    /// The sequencer does not know the class being deployed.
+    ClassHashNotFound {
+        class_hash: ClassHash,
+    },
+    TransactionHashMismatch {
+        expected: StarknetTransactionHash,
+        computed: StarknetTransactionHash,
+    },
+    /// The legacy deploy endpoint only accepts Cairo 0 classes.
+    SierraNotSupportedForDeploy,
+    ClassSource(ClassSourceError),
+    /// The class failed the checks of [RpcContext::class_validation].
+    ClassValidation(ContractClassError),
+}
+
+impl From<ContractClassError> for AddDeployTransactionError {
+    fn from(e: ContractClassError) -> Self {
+        Self::ClassValidation(e)
+    }
+}
 
-crate::rpc::error::generate_rpc_error_subset!(AddDeployTransactionError: InvalidContractClass);
+impl From<anyhow::Error> for AddDeployTransactionError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<IncompatibleClassVersion> for AddDeployTransactionError {
+    fn from(e: IncompatibleClassVersion) -> Self {
+        Self::IncompatibleClassVersion {
+            class_version: e.class_version,
+            tx_version: e.tx_version,
+        }
+    }
+}
+
+impl From<AddDeployTransactionError> for RpcError {
+    fn from(e: AddDeployTransactionError) -> Self {
+        match e {
+            AddDeployTransactionError::Internal(internal) => Self::Internal(internal),
+            AddDeployTransactionError::ClassValidation(e) => Self::ClassValidationFailed {
+                reason: e.to_string(),
+            },
+            AddDeployTransactionError::InvalidContractClass => Self::InvalidContractClass,
+            AddDeployTransactionError::IncompatibleClassVersion {
+                class_version,
+                tx_version,
+            } => Self::IncompatibleClassVersion {
+                class_version,
+                tx_version,
+            },
+            AddDeployTransactionError::AlreadyDeployed { address } => {
+                Self::AlreadyDeployed { address }
+            }
+            AddDeployTransactionError::ClassHashNotFound { class_hash } => {
+                Self::UndeclaredClass { class_hash }
+            }
+            AddDeployTransactionError::TransactionHashMismatch { expected, computed } => {
+                Self::TransactionHashMismatch { expected, computed }
+            }
+            AddDeployTransactionError::SierraNotSupportedForDeploy => {
+                Self::SierraNotSupportedForDeploy
+            }
+            AddDeployTransactionError::ClassSource(e) => Self::ClassSourceFailed {
+                reason: e.to_string(),
+            },
+        }
+    }
+}
 
 impl From<SequencerError> for AddDeployTransactionError {
     fn from(e: SequencerError) -> Self {
@@ -19,39 +113,215 @@ impl From<SequencerError> for AddDeployTransactionError {
     }
 }
 
-#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
-#[serde(tag = "type")]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Transaction {
-    #[serde(rename = "DEPLOY")]
     Deploy(BroadcastedDeployTransaction),
+    /// A `DEPLOY` of a Sierra class, which is recognized before parsing the class so that it
+    /// can be rejected with [AddDeployTransactionError::SierraNotSupportedForDeploy] instead
+    /// of a parsing error.
+    SierraDeploy,
+    /// A `DEPLOY` without a `contract_class`, which is expected to be fetched from the
+    /// `class_source` of the [AddDeployTransactionInput] and inserted into the transaction.
+    DeployWithoutClass(serde_json::Value),
+}
+
+impl Transaction {
+    /// Parses `transaction`, a [Transaction::DeployWithoutClass], with `contract_class`.
+    fn with_class(
+        mut transaction: serde_json::Value,
+        contract_class: &[u8],
+    ) -> Result<Self, AddDeployTransactionError> {
+        let contract_class = serde_json::from_slice::<serde_json::Value>(contract_class)
+            .map_err(|_| AddDeployTransactionError::InvalidContractClass)?;
+        // Fetched classes are not checked by the caller, so a malformed ABI is reported as
+        // such instead of as a generic parsing error.
+        crate::rpc::v02::types::class::check_abi(contract_class.get("abi"))?;
+        transaction["contract_class"] = contract_class;
+
+        match serde_json::from_value(transaction) {
+            Ok(Self::DeployWithoutClass(_)) | Err(_) => {
+                Err(AddDeployTransactionError::InvalidContractClass)
+            }
+            Ok(transaction) => Ok(transaction),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Transaction {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        #[serde(tag = "type")]
+        enum Tagged {
+            #[serde(rename = "DEPLOY")]
+            Deploy(BroadcastedDeployTransaction),
+        }
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let is_deploy = value.get("type").and_then(|t| t.as_str()) == Some("DEPLOY");
+        if is_deploy && value.get("contract_class").is_none() {
+            return Ok(Self::DeployWithoutClass(value));
+        }
+        let is_sierra_deploy = value.get("type").and_then(|t| t.as_str()) == Some("DEPLOY")
+            && value
+                .get("contract_class")
+                .and_then(|class| class.get("sierra_program"))
+                .is_some();
+        if is_sierra_deploy {
+            return Ok(Self::SierraDeploy);
+        }
+
+        let Tagged::Deploy(tx) = Tagged::deserialize(value).map_err(D::Error::custom)?;
+        Ok(Self::Deploy(tx))
+    }
 }
 
 #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rpc-strict-inputs", serde(deny_unknown_fields))]
 pub struct AddDeployTransactionInput {
     deploy_transaction: Transaction,
     // An undocumented parameter that we forward to the sequencer API
     // A deploy token is required to deploy contracts on Starknet mainnet only.
     #[serde(default)]
     token: Option<String>,
+    // An undocumented parameter which is only used for local logging and is
+    // never forwarded to the sequencer. Allows operators to attribute deploys to callers.
+    #[serde(default)]
+    client_tag: Option<String>,
+    // An undocumented parameter which adds the class hash to the output. Off by default,
+    // since hashing the class is expensive.
+    #[serde(default)]
+    include_class_hash: bool,
+    // An undocumented parameter which rejects the deploy if a contract already exists at
+    // the target address. Off by default, since it costs a class hash computation and a
+    // gateway query before submitting.
+    #[serde(default)]
+    check_not_deployed: bool,
+    // An undocumented parameter which rejects the deploy if its locally computed transaction
+    // hash differs, which catches clients computing hashes differently than the node.
+    #[serde(default)]
+    expected_transaction_hash: Option<StarknetTransactionHash>,
+    // An undocumented parameter with the URL of the class to deploy, which is fetched if the
+    // transaction has no `contract_class`. Saves inlining large classes which are stored
+    // elsewhere, e.g. in an artifact store. Only fetched if the node enables class sources.
+    #[serde(default)]
+    class_source: Option<String>,
+    // An undocumented parameter which lets an oversized deploy take longer than the gateway
+    // timeout of the node, up to its `max_timeout_override`, without raising it for everyone.
+    #[serde(default)]
+    timeout_secs: Option<u64>,
 }
 
 #[derive(serde::Serialize, Debug, PartialEq, Eq)]
 pub struct AddDeployTransactionOutput {
-    transaction_hash: StarknetTransactionHash,
-    contract_address: ContractAddress,
+    pub(crate) transaction_hash: StarknetTransactionHash,
+    pub(crate) contract_address: ContractAddress,
+    // An undocumented field with the gateway's acknowledgement of the transaction,
+    // e.g. `TRANSACTION_RECEIVED`. Saves clients a status query right after submitting.
+    code: AddTransactionCode,
+    // An undocumented field, only present if the `include_class_hash` parameter is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    class_hash: Option<ClassHash>,
 }
 
 pub async fn add_deploy_transaction(
     context: RpcContext,
     input: AddDeployTransactionInput,
 ) -> Result<AddDeployTransactionOutput, AddDeployTransactionError> {
-    let Transaction::Deploy(tx) = input.deploy_transaction;
+    use crate::sequencer::error::StarknetErrorCode::UndeclaredClass;
+
+    let transaction =
+        match input.deploy_transaction {
+            Transaction::DeployWithoutClass(transaction) => {
+                let class_source = input
+                    .class_source
+                    .as_deref()
+                    .ok_or(AddDeployTransactionError::InvalidContractClass)?;
+                let class_sources = context.class_sources.as_deref().ok_or(
+                    AddDeployTransactionError::ClassSource(ClassSourceError::Disabled),
+                )?;
+                context.ensure_online()?;
+                let contract_class = class_sources
+                    .fetch(class_source)
+                    .await
+                    .map_err(AddDeployTransactionError::ClassSource)?;
+                Transaction::with_class(transaction, &contract_class)?
+            }
+            // An inline class takes precedence over a class source.
+            transaction => transaction,
+        };
+    let tx = match transaction {
+        Transaction::Deploy(tx) => tx,
+        Transaction::SierraDeploy => {
+            return Err(AddDeployTransactionError::SierraNotSupportedForDeploy)
+        }
+        Transaction::DeployWithoutClass(_) => unreachable!("The class was fetched above"),
+    };
+    tx.contract_class.kind().check_deploy_version(tx.version)?;
+    context.validate_class(&tx.contract_class).await??;
+    let class_hash = if input.include_class_hash
+        || input.check_not_deployed
+        || input.expected_transaction_hash.is_some()
+    {
+        let contract_class = tx.contract_class.clone();
+        let class_hash = tokio::task::spawn_blocking(move || contract_class.class_hash())
+            .await
+            .context("Joining class hash computation")?
+            .context("Computing class hash")?;
+        Some(class_hash)
+    } else {
+        None
+    };
+
+    if let Some(expected) = input.expected_transaction_hash {
+        let class_hash = class_hash.expect("Class hash is computed for the check");
+        let (_, computed) = compute_deploy_transaction_hash(
+            tx.version,
+            tx.contract_address_salt,
+            &tx.constructor_calldata,
+            class_hash,
+            context.chain.execution_chain_id(),
+        );
+        if computed != expected {
+            return Err(AddDeployTransactionError::TransactionHashMismatch { expected, computed });
+        }
+    }
+
+    context.ensure_online()?;
+    if input.check_not_deployed {
+        let class_hash = class_hash.expect("Class hash is computed for the check");
+        let address = compute_contract_address(
+            tx.contract_address_salt,
+            class_hash,
+            tx.constructor_calldata.iter().map(|param| param.0),
+            ZERO_DEPLOYER_ADDRESS,
+        );
+        check_not_deployed(&context.sequencer, address).await?;
+    }
+
     let contract_definition: ContractDefinition = tx
         .contract_class
         .try_into()
         .map_err(|e| anyhow::anyhow!("Failed to convert contract definition: {}", e))?;
 
-    let response = context
+    if let Some(replay_log) = context.replay_log.as_deref() {
+        let record = ReplayRecord::new(
+            Deploy {
+                version: tx.version,
+                contract_address_salt: tx.contract_address_salt,
+                contract_definition: contract_definition.clone(),
+                constructor_calldata: tx.constructor_calldata.clone(),
+            },
+            input.token.as_deref(),
+        );
+        // Only logged, since failing to record must not prevent the submission.
+        if let Err(e) = replay_log.record(&record) {
+            tracing::error!(reason=?e, "Failed to write replay record");
+        }
+    }
+
+    let result = context
         .sequencer
         .add_deploy_transaction(
             tx.version,
@@ -59,19 +329,78 @@ pub async fn add_deploy_transaction(
             tx.constructor_calldata,
             contract_definition,
             input.token,
+            input.timeout_secs.map(std::time::Duration::from_secs),
+        )
+        .await;
+
+    let record = match &result {
+        Ok(response) => AuditRecord::now(
+            "starknet_addDeployTransaction",
+            Some(response.transaction_hash),
+            Some(response.address),
+            true,
+        ),
+        Err(_) => AuditRecord::now("starknet_addDeployTransaction", None, None, false),
+    };
+    audit::record(context.audit_sink.as_ref(), record).await;
+    if let Ok(response) = &result {
+        submission_store::record(
+            context.submission_store.as_deref(),
+            SubmissionRecord::now(response.transaction_hash, response.address),
         )
-        .await?;
+        .await;
+    }
+
+    let response = result.map_err(|e| match e {
+        // The sequencer reports the hash as part of its message, the computed one is only a
+        // fallback in case the message changes.
+        SequencerError::StarknetError(ref se) if se.code == UndeclaredClass => {
+            match se.undeclared_class().or(class_hash) {
+                Some(class_hash) => AddDeployTransactionError::ClassHashNotFound { class_hash },
+                None => e.into(),
+            }
+        }
+        _ => e.into(),
+    })?;
+
+    if let Some(client_tag) = input.client_tag.as_deref() {
+        tracing::info!(
+            %client_tag,
+            transaction_hash=?response.transaction_hash,
+            contract_address=?response.address,
+            "Deploy transaction submitted"
+        );
+    }
 
     Ok(AddDeployTransactionOutput {
         transaction_hash: response.transaction_hash,
         contract_address: response.address,
+        code: response.code,
+        class_hash: class_hash.filter(|_| input.include_class_hash),
     })
 }
 
+async fn check_not_deployed(
+    sequencer: &impl ClientApi,
+    address: ContractAddress,
+) -> Result<(), AddDeployTransactionError> {
+    use crate::sequencer::error::StarknetErrorCode::UninitializedContract;
+
+    // Only the pending block knows about contracts which were deployed moments ago.
+    match sequencer.class_hash_at(address, BlockId::Pending).await {
+        Ok(_) => Err(AddDeployTransactionError::AlreadyDeployed { address }),
+        Err(SequencerError::StarknetError(e)) if e.code == UninitializedContract => Ok(()),
+        Err(e) => Err(anyhow::Error::new(e)
+            .context("Checking whether the contract is already deployed")
+            .into()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::core::{ContractAddressSalt, TransactionVersion};
     use crate::rpc::v02::types::ContractClass;
+    use crate::sequencer::test_utils::serve_gateway;
     use crate::starkhash;
 
     use super::*;
@@ -89,19 +418,41 @@ mod tests {
         pub static ref CONTRACT_CLASS_JSON: String = {
             serde_json::to_string(&*CONTRACT_CLASS).unwrap()
         };
+
+        /// The gateway's reply to the `DEPLOY` of `deploy_txn()` on testnet.
+        pub static ref TRANSACTION_RECEIVED: serde_json::Value = serde_json::json!({
+            "code": "TRANSACTION_RECEIVED",
+            "transaction_hash": "0x3de4caad951e30581554b92ed5dfc29732dca360740598105d6b7cee7afd94f",
+            "address": "0x159519a16ee4370a05009e584855a29f4f1914326283201356f7650290f7789"
+        });
     }
 
-    mod parsing {
-        use super::*;
+    /// A `DEPLOY` of the fixture with salt `0x1234` and no calldata.
+    fn deploy_txn() -> Transaction {
+        Transaction::Deploy(BroadcastedDeployTransaction {
+            version: TransactionVersion::ZERO,
+            constructor_calldata: vec![],
+            contract_address_salt: ContractAddressSalt(starkhash!("1234")),
+            contract_class: CONTRACT_CLASS.clone(),
+        })
+    }
 
-        fn test_deploy_txn() -> Transaction {
-            Transaction::Deploy(BroadcastedDeployTransaction {
-                version: TransactionVersion::ZERO,
-                constructor_calldata: vec![],
-                contract_address_salt: ContractAddressSalt(starkhash!("1234")),
-                contract_class: CONTRACT_CLASS.clone(),
-            })
+    /// Submits `deploy_transaction` with all optional parameters left out.
+    fn input(deploy_transaction: Transaction) -> AddDeployTransactionInput {
+        AddDeployTransactionInput {
+            deploy_transaction,
+            token: None,
+            client_tag: None,
+            include_class_hash: false,
+            check_not_deployed: false,
+            expected_transaction_hash: None,
+            class_source: None,
+            timeout_secs: None,
         }
+    }
+
+    mod parsing {
+        use super::*;
 
         #[test]
         fn positional_args() {
@@ -124,8 +475,8 @@ mod tests {
 
             let input = positional.parse::<AddDeployTransactionInput>().unwrap();
             let expected = AddDeployTransactionInput {
-                deploy_transaction: test_deploy_txn(),
                 token: Some("token".to_owned()),
+                ..super::input(deploy_txn())
             };
             assert_eq!(input, expected);
         }
@@ -149,12 +500,72 @@ mod tests {
             let named = Params::new(Some(&named));
 
             let input = named.parse::<AddDeployTransactionInput>().unwrap();
-            let expected = AddDeployTransactionInput {
-                deploy_transaction: test_deploy_txn(),
-                token: None,
-            };
+            let expected = super::input(deploy_txn());
             assert_eq!(input, expected);
         }
+
+        mod unknown_fields {
+            use super::*;
+            use jsonrpsee::types::Params;
+
+            /// Named arguments with a misspelled `include_class_hash`.
+            fn named_args() -> String {
+                format!(
+                    r#"{{
+                        "deploy_transaction": {{
+                            "type": "DEPLOY",
+                            "version": "0x0",
+                            "constructor_calldata": [],
+                            "contract_address_salt": "0x1234",
+                            "contract_class": {}
+                        }},
+                        "include_clas_hash": true
+                    }}"#,
+                    CONTRACT_CLASS_JSON.clone()
+                )
+            }
+
+            #[cfg(not(feature = "rpc-strict-inputs"))]
+            #[test]
+            fn are_ignored_by_default() {
+                let named = named_args();
+                let input = Params::new(Some(&named))
+                    .parse::<AddDeployTransactionInput>()
+                    .unwrap();
+                assert!(!input.include_class_hash);
+            }
+
+            #[cfg(feature = "rpc-strict-inputs")]
+            #[test]
+            fn are_rejected_in_strict_mode() {
+                let named = named_args();
+                let error = Params::new(Some(&named))
+                    .parse::<AddDeployTransactionInput>()
+                    .unwrap_err();
+                assert!(error.to_string().contains("include_clas_hash"), "{}", error);
+            }
+
+            #[test]
+            fn are_always_rejected_in_the_transaction() {
+                let named = format!(
+                    r#"{{
+                        "deploy_transaction": {{
+                            "type": "DEPLOY",
+                            "version": "0x0",
+                            "constructor_calldata": [],
+                            "contract_address_salt": "0x1234",
+                            "contract_class": {},
+                            "unknown_field": "0x1"
+                        }}
+                    }}"#,
+                    CONTRACT_CLASS_JSON.clone()
+                );
+                let error = Params::new(Some(&named))
+                    .parse::<AddDeployTransactionInput>()
+                    .unwrap_err();
+                assert!(error.to_string().contains("unknown_field"), "{}", error);
+            }
+        }
     }
 
     #[test_log::test(tokio::test)]
@@ -173,29 +584,97 @@ mod tests {
             contract_class: invalid_contract_class,
         });
 
-        let input = AddDeployTransactionInput {
-            deploy_transaction,
-            token: None,
-        };
+        let input = input(deploy_transaction);
         let error = add_deploy_transaction(context, input).await.unwrap_err();
         assert_matches::assert_matches!(error, AddDeployTransactionError::InvalidContractClass);
     }
 
-    #[test_log::test(tokio::test)]
-    async fn successful_deploy() {
+    #[tokio::test]
+    async fn incompatible_class_version() {
+        // Rejected before reaching the sequencer.
         let context = RpcContext::for_tests();
 
-        let deploy_transaction = Transaction::Deploy(BroadcastedDeployTransaction {
-            version: TransactionVersion::ZERO,
+        let input = input(Transaction::Deploy(BroadcastedDeployTransaction {
+            version: TransactionVersion::ONE,
             constructor_calldata: vec![],
             contract_address_salt: ContractAddressSalt(starkhash!("1234")),
             contract_class: CONTRACT_CLASS.clone(),
+        }));
+        let error = add_deploy_transaction(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeployTransactionError::IncompatibleClassVersion {
+                class_version: ClassKind::Cairo0,
+                tx_version,
+            } if tx_version == TransactionVersion::ONE
+        );
+    }
+
+    #[tokio::test]
+    async fn class_failing_validation_is_rejected() {
+        use crate::rpc::v02::types::ValidationOptions;
+
+        // Rejected before reaching the sequencer.
+        let context = RpcContext::for_tests().with_class_validation(ValidationOptions {
+            max_program_felts: Some(1),
+            strict: true,
+            ..Default::default()
         });
 
-        let input = AddDeployTransactionInput {
-            deploy_transaction,
-            token: None,
-        };
+        let input = input(deploy_txn());
+        let error = add_deploy_transaction(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeployTransactionError::ClassValidation(ContractClassError::ProgramTooLarge {
+                limit: 1,
+                ..
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn sierra_class_is_rejected() {
+        use jsonrpsee::types::Params;
+
+        // Rejected before reaching the sequencer.
+        let context = RpcContext::for_tests();
+
+        let named = r#"{
+            "deploy_transaction": {
+                "type": "DEPLOY",
+                "version": "0x0",
+                "constructor_calldata": [],
+                "contract_address_salt": "0x1234",
+                "contract_class": {
+                    "sierra_program": ["0x1", "0x2"],
+                    "contract_class_version": "0.1.0",
+                    "entry_points_by_type": {
+                        "CONSTRUCTOR": [],
+                        "EXTERNAL": [],
+                        "L1_HANDLER": []
+                    },
+                    "abi": "[]"
+                }
+            }
+        }"#;
+        let input = Params::new(Some(named))
+            .parse::<AddDeployTransactionInput>()
+            .unwrap();
+        assert_eq!(input.deploy_transaction, Transaction::SierraDeploy);
+
+        let error = add_deploy_transaction(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeployTransactionError::SierraNotSupportedForDeploy
+        );
+        assert!(RpcError::from(error).to_string().contains("DEPLOY_ACCOUNT"));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn successful_deploy() {
+        let context = RpcContext::for_tests();
+
+        let input = input(deploy_txn());
         let result = add_deploy_transaction(context, input).await.unwrap();
         assert_eq!(
             result,
@@ -206,7 +685,623 @@ mod tests {
                 contract_address: ContractAddress::new_or_panic(starkhash!(
                     "0159519a16ee4370a05009e584855a29f4f1914326283201356f7650290f7789"
                 )),
+                code: AddTransactionCode::TransactionReceived,
+                class_hash: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn class_hash_is_included_on_request() {
+        use warp::Filter;
+
+        let route = warp::any().map(|| warp::reply::json(&*TRANSACTION_RECEIVED));
+        let (_jh, url) = serve_gateway(route);
+
+        let sequencer = crate::sequencer::Client::with_url(url).unwrap();
+        let context = RpcContext {
+            sequencer,
+            ..RpcContext::for_tests()
+        };
+
+        let input = AddDeployTransactionInput {
+            include_class_hash: true,
+            ..input(deploy_txn())
+        };
+        let result = add_deploy_transaction(context, input).await.unwrap();
+
+        // The hash of the fixture, as computed from its original definition.
+        assert_eq!(
+            result.class_hash,
+            Some(ClassHash(starkhash!(
+                "050b2148c0d782914e0b12a1a32abe5e398930b7e914f82c65cb7afce0a0ab9b"
+            )))
+        );
+    }
+
+    mod check_not_deployed {
+        use super::*;
+        use warp::Filter;
+
+        fn input() -> AddDeployTransactionInput {
+            AddDeployTransactionInput {
+                check_not_deployed: true,
+                ..super::input(deploy_txn())
+            }
+        }
+
+        /// Serves `class_hash_at` and counts the submissions to `add_transaction`.
+        fn serve(
+            class_hash_at: serde_json::Value,
+            status: warp::http::StatusCode,
+        ) -> (
+            tokio::task::JoinHandle<()>,
+            RpcContext,
+            std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        ) {
+            use std::collections::HashMap;
+            use std::sync::atomic::{AtomicUsize, Ordering};
+            use std::sync::Arc;
+
+            let submissions = Arc::new(AtomicUsize::new(0));
+            let counter = submissions.clone();
+
+            // Other blocks are not served, so that checking them fails the tests.
+            let class_hash_at = warp::path!("feeder_gateway" / "get_class_hash_at")
+                .and(warp::query::<HashMap<String, String>>())
+                .and_then(|query: HashMap<String, String>| async move {
+                    match query.get("blockNumber").map(String::as_str) {
+                        Some("pending") => Ok(()),
+                        _ => Err(warp::reject::not_found()),
+                    }
+                })
+                .untuple_one()
+                .map(move || warp::reply::with_status(warp::reply::json(&class_hash_at), status));
+            let add_transaction = warp::path!("gateway" / "add_transaction").map(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                warp::reply::json(&*TRANSACTION_RECEIVED)
+            });
+
+            let (jh, url) = serve_gateway(class_hash_at.or(add_transaction));
+
+            let sequencer = crate::sequencer::Client::with_url(url).unwrap();
+            let context = RpcContext {
+                sequencer,
+                ..RpcContext::for_tests()
+            };
+
+            (jh, context, submissions)
+        }
+
+        #[tokio::test]
+        async fn already_deployed() {
+            let (_jh, context, submissions) = serve(
+                serde_json::json!(
+                    "0x050b2148c0d782914e0b12a1a32abe5e398930b7e914f82c65cb7afce0a0ab9b"
+                ),
+                warp::http::StatusCode::OK,
+            );
+
+            let error = add_deploy_transaction(context, input()).await.unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                AddDeployTransactionError::AlreadyDeployed { address } => {
+                    assert_eq!(address, ContractAddress::new_or_panic(starkhash!(
+                        "0159519a16ee4370a05009e584855a29f4f1914326283201356f7650290f7789"
+                    )));
+                }
+            );
+            assert_eq!(submissions.load(std::sync::atomic::Ordering::Relaxed), 0);
+        }
+
+        #[tokio::test]
+        async fn not_deployed() {
+            let (_jh, context, submissions) = serve(
+                serde_json::json!({
+                    "code": "StarknetErrorCode.UNINITIALIZED_CONTRACT",
+                    "message": "Requested contract address is not deployed."
+                }),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            );
+
+            let result = add_deploy_transaction(context, input()).await.unwrap();
+            assert_eq!(
+                result.contract_address,
+                ContractAddress::new_or_panic(starkhash!(
+                    "0159519a16ee4370a05009e584855a29f4f1914326283201356f7650290f7789"
+                ))
+            );
+            // The class hash was only computed for the check.
+            assert_eq!(result.class_hash, None);
+            assert_eq!(submissions.load(std::sync::atomic::Ordering::Relaxed), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn undeclared_class() {
+        use warp::Filter;
+
+        let add_transaction = warp::path!("gateway" / "add_transaction").map(|| {
+            warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "code": "StarknetErrorCode.UNDECLARED_CLASS",
+                    "message": "Class with hash 0x50b2148c0d782914e0b12a1a32abe5e398930b7e914f82c65cb7afce0a0ab9b is not declared."
+                })),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        });
+        let (_jh, url) = serve_gateway(add_transaction);
+        let context = RpcContext {
+            sequencer: crate::sequencer::Client::with_url(url).unwrap(),
+            ..RpcContext::for_tests()
+        };
+
+        let input = input(deploy_txn());
+
+        let error = add_deploy_transaction(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeployTransactionError::ClassHashNotFound { class_hash } => {
+                assert_eq!(class_hash, ClassHash(starkhash!(
+                    "050b2148c0d782914e0b12a1a32abe5e398930b7e914f82c65cb7afce0a0ab9b"
+                )));
             }
         );
     }
+
+    mod expected_transaction_hash {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use warp::Filter;
+
+        fn input(expected_transaction_hash: StarknetTransactionHash) -> AddDeployTransactionInput {
+            AddDeployTransactionInput {
+                expected_transaction_hash: Some(expected_transaction_hash),
+                ..super::input(deploy_txn())
+            }
+        }
+
+        /// Accepts all submissions and counts them.
+        fn serve() -> (tokio::task::JoinHandle<()>, RpcContext, Arc<AtomicUsize>) {
+            let submissions = Arc::new(AtomicUsize::new(0));
+            let counter = submissions.clone();
+            let add_transaction = warp::path!("gateway" / "add_transaction").map(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                warp::reply::json(&*TRANSACTION_RECEIVED)
+            });
+            let (jh, url) = serve_gateway(add_transaction);
+
+            let context = RpcContext {
+                sequencer: crate::sequencer::Client::with_url(url).unwrap(),
+                ..RpcContext::for_tests()
+            };
+            (jh, context, submissions)
+        }
+
+        #[tokio::test]
+        async fn matching() {
+            let (_jh, context, submissions) = serve();
+            let expected = StarknetTransactionHash(starkhash!(
+                "03de4caad951e30581554b92ed5dfc29732dca360740598105d6b7cee7afd94f"
+            ));
+
+            let result = add_deploy_transaction(context, input(expected))
+                .await
+                .unwrap();
+            assert_eq!(result.transaction_hash, expected);
+            assert_eq!(submissions.load(Ordering::Relaxed), 1);
+        }
+
+        #[tokio::test]
+        async fn mismatched() {
+            let (_jh, context, submissions) = serve();
+            let expected = StarknetTransactionHash(starkhash!("1234"));
+
+            let error = add_deploy_transaction(context, input(expected))
+                .await
+                .unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                AddDeployTransactionError::TransactionHashMismatch { expected: e, computed } => {
+                    assert_eq!(e, expected);
+                    assert_eq!(computed, StarknetTransactionHash(starkhash!(
+                        "03de4caad951e30581554b92ed5dfc29732dca360740598105d6b7cee7afd94f"
+                    )));
+                }
+            );
+            assert_eq!(submissions.load(Ordering::Relaxed), 0);
+        }
+    }
+
+    mod class_source {
+        use super::*;
+        use crate::rpc::v02::class_source::ClassSources;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use warp::Filter;
+
+        /// A `DEPLOY` of the fixture without its `contract_class`.
+        fn input(class_source: String) -> AddDeployTransactionInput {
+            let named = serde_json::json!({
+                "deploy_transaction": {
+                    "type": "DEPLOY",
+                    "version": "0x0",
+                    "constructor_calldata": [],
+                    "contract_address_salt": "0x1234"
+                },
+                "class_source": class_source
+            })
+            .to_string();
+            jsonrpsee::types::Params::new(Some(&named)).parse().unwrap()
+        }
+
+        /// Serves the fixture's class at `/class.json` and accepts all submissions, counting
+        /// how often the class was fetched.
+        fn serve() -> (String, RpcContext, Arc<AtomicUsize>) {
+            let fetches = Arc::new(AtomicUsize::new(0));
+            let counter = fetches.clone();
+            let class = warp::path!("class.json").map(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                CONTRACT_CLASS_JSON.clone()
+            });
+            let add_transaction = warp::path!("gateway" / "add_transaction")
+                .map(|| warp::reply::json(&*TRANSACTION_RECEIVED));
+            let (_, url) = serve_gateway(class.or(add_transaction));
+            let class_url = url.join("class.json").unwrap().to_string();
+
+            let context = RpcContext {
+                sequencer: crate::sequencer::Client::with_url(url).unwrap(),
+                ..RpcContext::for_tests()
+            }
+            .with_class_sources(Arc::new(
+                ClassSources::new(vec!["http".to_owned()], 16 * 1024 * 1024)
+                    .unwrap()
+                    .with_allowed_hosts(vec!["127.0.0.1".to_owned()]),
+            ));
+            (class_url, context, fetches)
+        }
+
+        #[tokio::test]
+        async fn is_fetched() {
+            let (class_url, context, fetches) = serve();
+            let input = AddDeployTransactionInput {
+                include_class_hash: true,
+                ..input(class_url)
+            };
+
+            let result = add_deploy_transaction(context, input).await.unwrap();
+            assert_eq!(fetches.load(Ordering::Relaxed), 1);
+            assert_eq!(
+                result.class_hash,
+                Some(ClassHash(starkhash!(
+                    "050b2148c0d782914e0b12a1a32abe5e398930b7e914f82c65cb7afce0a0ab9b"
+                )))
+            );
+        }
+
+        #[tokio::test]
+        async fn inline_class_takes_precedence() {
+            let (class_url, context, fetches) = serve();
+            let input = AddDeployTransactionInput {
+                deploy_transaction: deploy_txn(),
+                ..input(class_url)
+            };
+
+            add_deploy_transaction(context, input).await.unwrap();
+            assert_eq!(fetches.load(Ordering::Relaxed), 0);
+        }
+
+        #[tokio::test]
+        async fn disabled_by_default() {
+            let (class_url, context, fetches) = serve();
+            let context = RpcContext {
+                class_sources: None,
+                ..context
+            };
+
+            let error = add_deploy_transaction(context, input(class_url))
+                .await
+                .unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                AddDeployTransactionError::ClassSource(ClassSourceError::Disabled)
+            );
+            assert_eq!(fetches.load(Ordering::Relaxed), 0);
+        }
+
+        #[tokio::test]
+        async fn not_fetched_offline() {
+            let (class_url, context, fetches) = serve();
+
+            let error = add_deploy_transaction(context.with_offline(), input(class_url))
+                .await
+                .unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                AddDeployTransactionError::Internal(e) => assert_matches::assert_matches!(
+                    e.downcast_ref::<SequencerError>(),
+                    Some(SequencerError::OfflineMode)
+                )
+            );
+            assert_eq!(fetches.load(Ordering::Relaxed), 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn offline_deploy_is_blocked() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use warp::Filter;
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let route = warp::any().map(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            warp::reply()
+        });
+        let (_jh, url) = serve_gateway(route);
+
+        let context = RpcContext {
+            sequencer: crate::sequencer::Client::with_url(url).unwrap(),
+            ..RpcContext::for_tests()
+        }
+        .with_offline();
+        let input_expecting = |expected_transaction_hash| AddDeployTransactionInput {
+            include_class_hash: true,
+            expected_transaction_hash,
+            ..input(deploy_txn())
+        };
+
+        let error = add_deploy_transaction(context.clone(), input_expecting(None))
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeployTransactionError::Internal(e) => assert_matches::assert_matches!(
+                e.downcast_ref::<SequencerError>(),
+                Some(SequencerError::OfflineMode)
+            )
+        );
+
+        // The class and transaction hashes are still computed, which the check of the expected
+        // transaction hash reports.
+        let expected = StarknetTransactionHash(starkhash!("1234"));
+        let error = add_deploy_transaction(context, input_expecting(Some(expected)))
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeployTransactionError::TransactionHashMismatch { computed, .. } => {
+                assert_eq!(computed, StarknetTransactionHash(starkhash!(
+                    "03de4caad951e30581554b92ed5dfc29732dca360740598105d6b7cee7afd94f"
+                )));
+            }
+        );
+        assert_eq!(requests.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn client_tag_is_logged_but_not_forwarded() {
+        use std::sync::{Arc, Mutex};
+        use warp::Filter;
+
+        #[derive(Clone)]
+        struct LogWriter(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for LogWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        const CLIENT_TAG: &str = "tenant-42";
+
+        let logs = LogWriter(Default::default());
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request_body = Arc::new(Mutex::new(Vec::new()));
+        let captured = request_body.clone();
+        let route = warp::any()
+            .and(warp::body::bytes())
+            .map(move |body: bytes::Bytes| {
+                captured.lock().unwrap().extend_from_slice(&body);
+                warp::reply::json(&*TRANSACTION_RECEIVED)
+            });
+        let (_jh, url) = serve_gateway(route);
+
+        let sequencer = crate::sequencer::Client::with_url(url).unwrap();
+        let context = RpcContext {
+            sequencer,
+            ..RpcContext::for_tests()
+        };
+
+        let input = AddDeployTransactionInput {
+            client_tag: Some(CLIENT_TAG.to_owned()),
+            ..input(deploy_txn())
+        };
+        add_deploy_transaction(context, input).await.unwrap();
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains(CLIENT_TAG), "{logs}");
+
+        let request_body = String::from_utf8(request_body.lock().unwrap().clone()).unwrap();
+        assert!(!request_body.is_empty());
+        assert!(!request_body.contains(CLIENT_TAG));
+        assert!(!request_body.contains("client_tag"));
+    }
+
+    #[tokio::test]
+    async fn successful_deploy_is_audited() {
+        use crate::rpc::v02::audit::FileAuditSink;
+        use std::sync::Arc;
+        use warp::Filter;
+
+        let route = warp::any().map(|| warp::reply::json(&*TRANSACTION_RECEIVED));
+        let (_jh, url) = serve_gateway(route);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        let sink = Arc::new(FileAuditSink::open(&path).unwrap());
+
+        let sequencer = crate::sequencer::Client::with_url(url).unwrap();
+        let context = RpcContext {
+            sequencer,
+            ..RpcContext::for_tests()
+        }
+        .with_audit_sink(sink);
+
+        let input = input(deploy_txn());
+        add_deploy_transaction(context, input).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let records = contents
+            .lines()
+            .map(|line| serde_json::from_str::<AuditRecord>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.method, "starknet_addDeployTransaction");
+        assert_eq!(
+            record.transaction_hash,
+            Some(StarknetTransactionHash(starkhash!(
+                "03de4caad951e30581554b92ed5dfc29732dca360740598105d6b7cee7afd94f"
+            )))
+        );
+        assert_eq!(
+            record.contract_address,
+            Some(ContractAddress::new_or_panic(starkhash!(
+                "0159519a16ee4370a05009e584855a29f4f1914326283201356f7650290f7789"
+            )))
+        );
+        assert!(record.success);
+    }
+
+    #[tokio::test]
+    async fn deploy_is_recorded_as_submitted() {
+        use crate::rpc::v02::submission_store::SubmissionStore;
+        use std::sync::Arc;
+        use warp::Filter;
+
+        let route = warp::any().map(|| warp::reply::json(&*TRANSACTION_RECEIVED));
+        let (_jh, url) = serve_gateway(route);
+
+        let sequencer = crate::sequencer::Client::with_url(url).unwrap();
+        let context = RpcContext {
+            sequencer,
+            ..RpcContext::for_tests()
+        };
+        let store = Arc::new(SubmissionStore::new(context.storage.clone()));
+        let context = context.with_submission_store(store.clone());
+
+        let input = input(deploy_txn());
+        let result = add_deploy_transaction(context, input).await.unwrap();
+
+        let record = store
+            .was_submitted(result.transaction_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.contract_address, result.contract_address);
+    }
+
+    #[tokio::test]
+    async fn deploy_is_recorded_for_replay() {
+        use crate::sequencer::replay::{self, ReplayLog};
+        use std::sync::Arc;
+        use warp::Filter;
+
+        const TOKEN: &str = "secret deploy token";
+
+        let route = warp::any().map(|| warp::reply::json(&*TRANSACTION_RECEIVED));
+        let (_jh, url) = serve_gateway(route);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("replay.log");
+        let log = Arc::new(ReplayLog::open(&path).unwrap());
+
+        let sequencer = crate::sequencer::Client::with_url(url).unwrap();
+        let context = RpcContext {
+            sequencer,
+            ..RpcContext::for_tests()
+        }
+        .with_replay_log(log);
+
+        let input = AddDeployTransactionInput {
+            token: Some(TOKEN.to_owned()),
+            ..input(deploy_txn())
+        };
+        add_deploy_transaction(context, input).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains(TOKEN));
+
+        let records = replay::read(&path).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].token, Some(replay::Redacted::Redacted));
+        assert_eq!(
+            records[0].deploy.contract_address_salt,
+            ContractAddressSalt(starkhash!("1234"))
+        );
+    }
+
+    mod timeout_override {
+        use super::*;
+        use std::time::Duration;
+        use warp::Filter;
+
+        /// A `DEPLOY` of the fixture with the `timeout_secs` parameter.
+        fn input(timeout_secs: Option<u64>) -> AddDeployTransactionInput {
+            let named = serde_json::json!({
+                "deploy_transaction": {
+                    "type": "DEPLOY",
+                    "version": "0x0",
+                    "constructor_calldata": [],
+                    "contract_address_salt": "0x1234",
+                    "contract_class": serde_json::from_str::<serde_json::Value>(&CONTRACT_CLASS_JSON).unwrap()
+                },
+                "timeout_secs": timeout_secs
+            })
+            .to_string();
+            jsonrpsee::types::Params::new(Some(&named)).parse().unwrap()
+        }
+
+        /// Accepts all submissions after 300ms, while the node times out after 100ms.
+        fn serve() -> RpcContext {
+            let add_transaction = warp::path!("gateway" / "add_transaction").and_then(|| async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                Ok::<_, std::convert::Infallible>(warp::reply::json(&*TRANSACTION_RECEIVED))
+            });
+            let (_, url) = serve_gateway(add_transaction);
+
+            let config = crate::sequencer::SequencerConfig::new(url)
+                .with_timeout(Duration::from_millis(100));
+            RpcContext {
+                sequencer: crate::sequencer::Client::with_config(config).unwrap(),
+                ..RpcContext::for_tests()
+            }
+        }
+
+        #[tokio::test]
+        async fn supersedes_the_gateway_timeout() {
+            let context = serve();
+
+            add_deploy_transaction(context.clone(), input(None))
+                .await
+                .unwrap_err();
+            add_deploy_transaction(context, input(Some(5)))
+                .await
+                .unwrap();
+        }
+    }
 }
@@ -1,11 +1,43 @@
 use anyhow::Context;
 
-use crate::core::StarknetTransactionHash;
+use crate::core::{Fee, StarknetTransactionHash};
+use crate::rpc::error::RpcError;
 use crate::rpc::v02::types::request::BroadcastedInvokeTransaction;
 use crate::rpc::v02::RpcContext;
+use crate::sequencer::request::add_transaction::{AddTransaction, InvokeFunction};
 use crate::sequencer::ClientApi;
 
-crate::rpc::error::generate_rpc_error_subset!(AddInvokeTransactionError);
+// Not generated using `generate_rpc_error_subset!` as it does not support variants with fields.
+#[derive(Debug)]
+pub enum AddInvokeTransactionError {
+    Internal(anyhow::Error),
+    FeeExceedsCap { max_fee: Fee, cap: Fee },
+    FeeBudgetExceeded { max_fee: Fee, remaining: Fee },
+    MaxFeeBelowEstimate { max_fee: Fee, required: Fee },
+}
+
+impl From<anyhow::Error> for AddInvokeTransactionError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<AddInvokeTransactionError> for RpcError {
+    fn from(e: AddInvokeTransactionError) -> Self {
+        match e {
+            AddInvokeTransactionError::Internal(internal) => Self::Internal(internal),
+            AddInvokeTransactionError::FeeExceedsCap { max_fee, cap } => {
+                Self::FeeExceedsCap { max_fee, cap }
+            }
+            AddInvokeTransactionError::FeeBudgetExceeded { max_fee, remaining } => {
+                Self::FeeBudgetExceeded { max_fee, remaining }
+            }
+            AddInvokeTransactionError::MaxFeeBelowEstimate { max_fee, required } => {
+                Self::MaxFeeBelowEstimate { max_fee, required }
+            }
+        }
+    }
+}
 
 #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
 #[serde(tag = "type")]
@@ -15,6 +47,7 @@ pub enum Transaction {
 }
 
 #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rpc-strict-inputs", serde(deny_unknown_fields))]
 pub struct AddInvokeTransactionInput {
     invoke_transaction: Transaction,
 }
@@ -29,36 +62,89 @@ pub async fn add_invoke_transaction(
     input: AddInvokeTransactionInput,
 ) -> Result<AddInvokeTransactionOutput, AddInvokeTransactionError> {
     let Transaction::Invoke(tx) = input.invoke_transaction;
-    let response = match tx {
-        BroadcastedInvokeTransaction::V0(v0) => context
-            .sequencer
-            .add_invoke_transaction(
-                v0.version,
-                v0.max_fee,
-                v0.signature,
-                // Nonce is part of the RPC specification for V0 but this
-                // is a bug in the spec. The gateway won't accept it, so
-                // we null it out.
-                None,
-                v0.contract_address,
-                Some(v0.entry_point_selector),
-                v0.calldata,
-            )
+    let (max_fee, signature) = match &tx {
+        BroadcastedInvokeTransaction::V0(v0) => (v0.max_fee, &v0.signature),
+        BroadcastedInvokeTransaction::V1(v1) => (v1.max_fee, &v1.signature),
+        // Saturate on overflow so that such bounds are always over the cap.
+        BroadcastedInvokeTransaction::V3(v3) => (
+            v3.resource_bounds
+                .max_fee()
+                .unwrap_or(Fee(u128::MAX.to_be_bytes().into())),
+            &v3.signature,
+        ),
+    };
+    context
+        .validate_signature(signature)
+        .context("Validating signature")?;
+    if let Some(cap) = context.exceeded_fee_cap(max_fee) {
+        return Err(AddInvokeTransactionError::FeeExceedsCap { max_fee, cap });
+    }
+    context.ensure_online()?;
+    // The fee budget is committed last, so that transactions failing any other check do not
+    // use it up.
+    if let Some(check) = &context.max_fee_check {
+        if let Some(required) = check
+            .below_estimate(&context.sequencer, estimation_request(&tx), max_fee)
             .await
-            .context("Sending V0 invoke transaction to gateway")?,
+        {
+            return Err(AddInvokeTransactionError::MaxFeeBelowEstimate { max_fee, required });
+        }
+    }
+    if let Some(remaining) = context.exceeded_fee_budget(max_fee) {
+        return Err(AddInvokeTransactionError::FeeBudgetExceeded { max_fee, remaining });
+    }
+
+    let response = match tx {
+        BroadcastedInvokeTransaction::V0(v0) => {
+            context
+                .sequencer
+                .add_invoke_transaction(
+                    v0.version,
+                    v0.max_fee,
+                    v0.signature.0,
+                    // Nonce is part of the RPC specification for V0 but this
+                    // is a bug in the spec. The gateway won't accept it, so
+                    // we null it out.
+                    None,
+                    v0.contract_address,
+                    Some(v0.entry_point_selector),
+                    v0.calldata,
+                    None,
+                )
+                .await
+                .context("Sending V0 invoke transaction to gateway")?
+        }
         BroadcastedInvokeTransaction::V1(v1) => context
             .sequencer
             .add_invoke_transaction(
                 v1.version,
                 v1.max_fee,
-                v1.signature,
+                v1.signature.0,
                 Some(v1.nonce),
                 v1.sender_address,
                 None,
                 v1.calldata,
+                None,
             )
             .await
             .context("Sending V1 invoke transaction to gateway")?,
+        BroadcastedInvokeTransaction::V3(v3) => {
+            context
+                .sequencer
+                .add_invoke_transaction(
+                    v3.version,
+                    // V3 transactions are bounded by their resources instead.
+                    Fee(Default::default()),
+                    v3.signature.0,
+                    Some(v3.nonce),
+                    v3.sender_address,
+                    None,
+                    v3.calldata,
+                    Some(v3.resource_bounds.into()),
+                )
+                .await
+                .context("Sending V3 invoke transaction to gateway")?
+        }
     };
 
     Ok(AddInvokeTransactionOutput {
@@ -66,13 +152,57 @@ pub async fn add_invoke_transaction(
     })
 }
 
+/// The sequencer request for estimating the fee of `tx`.
+///
+/// Unlike the estimates of the `starknet_estimateFee` method, the version is not marked as query
+/// only, since the signature covers the version of the transaction as it is submitted.
+fn estimation_request(tx: &BroadcastedInvokeTransaction) -> AddTransaction {
+    let invoke = match tx {
+        BroadcastedInvokeTransaction::V0(v0) => InvokeFunction {
+            version: v0.version,
+            max_fee: v0.max_fee,
+            signature: v0.signature.0.clone(),
+            nonce: None,
+            contract_address: v0.contract_address,
+            entry_point_selector: Some(v0.entry_point_selector),
+            calldata: v0.calldata.clone(),
+            resource_bounds: None,
+        },
+        BroadcastedInvokeTransaction::V1(v1) => InvokeFunction {
+            version: v1.version,
+            max_fee: v1.max_fee,
+            signature: v1.signature.0.clone(),
+            nonce: Some(v1.nonce),
+            contract_address: v1.sender_address,
+            entry_point_selector: None,
+            calldata: v1.calldata.clone(),
+            resource_bounds: None,
+        },
+        BroadcastedInvokeTransaction::V3(v3) => InvokeFunction {
+            version: v3.version,
+            max_fee: Fee(Default::default()),
+            signature: v3.signature.0.clone(),
+            nonce: Some(v3.nonce),
+            contract_address: v3.sender_address,
+            entry_point_selector: None,
+            calldata: v3.calldata.clone(),
+            resource_bounds: Some(v3.resource_bounds.into()),
+        },
+    };
+
+    AddTransaction::Invoke(invoke)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::core::{
         CallParam, ContractAddress, EntryPoint, Fee, TransactionNonce, TransactionSignatureElem,
         TransactionVersion,
     };
-    use crate::rpc::v02::types::request::BroadcastedInvokeTransactionV0;
+    use crate::rpc::v02::types::request::{
+        BroadcastedInvokeTransactionV0, Signature, SignatureError,
+    };
+    use crate::sequencer::test_utils::serve_gateway;
     use crate::starkhash;
 
     use super::*;
@@ -82,14 +212,14 @@ mod tests {
             BroadcastedInvokeTransactionV0 {
                 version: TransactionVersion::ZERO,
                 max_fee: Fee(5444010076217u128.to_be_bytes().into()),
-                signature: vec![
+                signature: Signature(vec![
                     TransactionSignatureElem(starkhash!(
                         "07dd3a55d94a0de6f3d6c104d7e6c88ec719a82f4e2bbc12587c8c187584d3d5"
                     )),
                     TransactionSignatureElem(starkhash!(
                         "071456dded17015d1234779889d78f3e7c763ddcfd2662b19e7843c7542614f8"
                     )),
-                ],
+                ]),
                 nonce: None,
                 contract_address: ContractAddress::new_or_panic(starkhash!(
                     "023371b227eaecd8e8920cd429357edddd2cd0f3fee6abaacca08d3ab82a7cdd"
@@ -191,6 +321,81 @@ mod tests {
             };
             assert_eq!(input, expected);
         }
+
+        const V3_INVOKE: &str = r#"{
+            "invoke_transaction": {
+                "type": "INVOKE",
+                "version": "0x3",
+                "resource_bounds": {
+                    "l1_gas": {"max_amount": "0x186a0", "max_price_per_unit": "0x5af3107a4000"},
+                    "l2_gas": {"max_amount": "0x0", "max_price_per_unit": "0x0"}
+                },
+                "signature": [
+                    "0x07ccc81b438581c9360120e0ba0ef52c7d031bdf20a4c2bc3820391b29a8945f",
+                    "0x02c11c60d11daaa0043eccdc824bb44f87bc7eb2e9c2437e1654876ab8fa7cad"
+                ],
+                "nonce": "0x2",
+                "sender_address": "0x03fdcbeb68e607c8febf01d7ef274cbf68091a0bd1556c0b8f8e80d732f7850f",
+                "calldata": ["0x1"]
+            }
+        }"#;
+
+        #[test]
+        fn v3() {
+            use crate::core::GasPrice;
+            use crate::rpc::v02::types::request::{
+                BroadcastedInvokeTransactionV3, ResourceBound, ResourceBounds,
+            };
+            use jsonrpsee::types::Params;
+
+            let input = Params::new(Some(V3_INVOKE))
+                .parse::<AddInvokeTransactionInput>()
+                .unwrap();
+            let expected = AddInvokeTransactionInput {
+                invoke_transaction: Transaction::Invoke(BroadcastedInvokeTransaction::V3(
+                    BroadcastedInvokeTransactionV3 {
+                        version: TransactionVersion(web3::types::H256::from_low_u64_be(3)),
+                        resource_bounds: ResourceBounds {
+                            l1_gas: ResourceBound {
+                                max_amount: 100_000,
+                                max_price_per_unit: GasPrice(100_000_000_000_000),
+                            },
+                            l2_gas: ResourceBound {
+                                max_amount: 0,
+                                max_price_per_unit: GasPrice(0),
+                            },
+                        },
+                        signature: Signature(vec![
+                            TransactionSignatureElem(starkhash!(
+                                "07ccc81b438581c9360120e0ba0ef52c7d031bdf20a4c2bc3820391b29a8945f"
+                            )),
+                            TransactionSignatureElem(starkhash!(
+                                "02c11c60d11daaa0043eccdc824bb44f87bc7eb2e9c2437e1654876ab8fa7cad"
+                            )),
+                        ]),
+                        nonce: TransactionNonce(starkhash!("02")),
+                        sender_address: ContractAddress::new_or_panic(starkhash!(
+                            "03fdcbeb68e607c8febf01d7ef274cbf68091a0bd1556c0b8f8e80d732f7850f"
+                        )),
+                        calldata: vec![CallParam(starkhash!("01"))],
+                    },
+                )),
+            };
+            assert_eq!(input, expected);
+        }
+
+        #[test]
+        fn max_fee_with_resource_bounds_is_rejected() {
+            use jsonrpsee::types::Params;
+
+            let mut json = serde_json::from_str::<serde_json::Value>(V3_INVOKE).unwrap();
+            json["invoke_transaction"]["max_fee"] = serde_json::json!("0x4f388496839");
+            let json = json.to_string();
+
+            Params::new(Some(&json))
+                .parse::<AddInvokeTransactionInput>()
+                .unwrap_err();
+        }
     }
 
     #[tokio::test]
@@ -217,14 +422,14 @@ mod tests {
         let input = BroadcastedInvokeTransactionV1 {
             version: TransactionVersion::ONE,
             max_fee: Fee(web3::types::H128::from_low_u64_be(0x630a0aff77)),
-            signature: vec![
+            signature: Signature(vec![
                 TransactionSignatureElem(starkhash!(
                     "07ccc81b438581c9360120e0ba0ef52c7d031bdf20a4c2bc3820391b29a8945f"
                 )),
                 TransactionSignatureElem(starkhash!(
                     "02c11c60d11daaa0043eccdc824bb44f87bc7eb2e9c2437e1654876ab8fa7cad"
                 )),
-            ],
+            ]),
             nonce: TransactionNonce(starkhash!("02")),
             sender_address: ContractAddress::new_or_panic(starkhash!(
                 "03fdcbeb68e607c8febf01d7ef274cbf68091a0bd1556c0b8f8e80d732f7850f"
@@ -256,4 +461,269 @@ mod tests {
         let result = add_invoke_transaction(context, input).await.unwrap();
         assert_eq!(result, expected);
     }
+
+    #[tokio::test]
+    async fn submission_is_not_query_only() {
+        use std::sync::{Arc, Mutex};
+        use warp::Filter;
+
+        let submitted = Arc::new(Mutex::new(None));
+        let capture = submitted.clone();
+        let add_transaction = warp::path!("gateway" / "add_transaction")
+            .and(warp::body::json())
+            .map(move |body: serde_json::Value| {
+                *capture.lock().unwrap() = Some(body);
+                warp::reply::json(&serde_json::json!({
+                    "code": "TRANSACTION_RECEIVED",
+                    "transaction_hash": "0x389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"
+                }))
+            });
+        let (_jh, url) = serve_gateway(add_transaction);
+        let context = RpcContext {
+            sequencer: crate::sequencer::Client::with_url(url).unwrap(),
+            ..RpcContext::for_tests()
+        };
+        let input = AddInvokeTransactionInput {
+            invoke_transaction: test_invoke_txn(),
+        };
+
+        add_invoke_transaction(context, input).await.unwrap();
+
+        let submitted = submitted.lock().unwrap().take().unwrap();
+        assert_eq!(submitted["version"], "0x0");
+    }
+
+    mod fee_cap {
+        use super::*;
+
+        #[tokio::test]
+        async fn under_cap() {
+            let context = RpcContext::for_tests()
+                .with_max_fee_cap(Fee(5444010076217u128.to_be_bytes().into()));
+            let input = AddInvokeTransactionInput {
+                invoke_transaction: test_invoke_txn(),
+            };
+            let expected = AddInvokeTransactionOutput {
+                transaction_hash: StarknetTransactionHash(starkhash!(
+                    "0389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"
+                )),
+            };
+
+            let result = add_invoke_transaction(context, input).await.unwrap();
+            assert_eq!(result, expected);
+        }
+
+        #[tokio::test]
+        async fn over_cap() {
+            let cap = Fee(5444010076216u128.to_be_bytes().into());
+            // The sequencer is unreachable, so the transaction must be rejected locally.
+            let context = RpcContext {
+                sequencer: crate::sequencer::Client::with_url(
+                    "http://sequencer.invalid/".parse().unwrap(),
+                )
+                .unwrap(),
+                ..RpcContext::for_tests()
+            }
+            .with_max_fee_cap(cap);
+            let input = AddInvokeTransactionInput {
+                invoke_transaction: test_invoke_txn(),
+            };
+
+            let error = add_invoke_transaction(context, input).await.unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                AddInvokeTransactionError::FeeExceedsCap { max_fee, cap: error_cap } => {
+                    assert_eq!(max_fee, Fee(5444010076217u128.to_be_bytes().into()));
+                    assert_eq!(error_cap, cap);
+                }
+            );
+        }
+    }
+
+    mod signature_check {
+        use super::*;
+
+        fn odd_signature_txn() -> Transaction {
+            let Transaction::Invoke(mut tx) = test_invoke_txn();
+            match &mut tx {
+                BroadcastedInvokeTransaction::V0(v0) => v0.signature.0.truncate(1),
+                _ => unreachable!(),
+            }
+            Transaction::Invoke(tx)
+        }
+
+        #[tokio::test]
+        async fn malformed_signature_is_rejected() {
+            // The sequencer is unreachable, so the transaction must be rejected locally.
+            let context = RpcContext {
+                sequencer: crate::sequencer::Client::with_url(
+                    "http://sequencer.invalid/".parse().unwrap(),
+                )
+                .unwrap(),
+                ..RpcContext::for_tests()
+            }
+            .with_signature_checks();
+            let input = AddInvokeTransactionInput {
+                invoke_transaction: odd_signature_txn(),
+            };
+
+            let error = add_invoke_transaction(context, input).await.unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                AddInvokeTransactionError::Internal(e) => {
+                    assert_eq!(
+                        e.root_cause().downcast_ref::<SignatureError>(),
+                        Some(&SignatureError::OddLength(1))
+                    );
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn unchecked_by_default() {
+            use warp::Filter;
+
+            let add_transaction = warp::path!("gateway" / "add_transaction").map(|| {
+                warp::reply::json(&serde_json::json!({
+                    "code": "TRANSACTION_RECEIVED",
+                    "transaction_hash": "0x389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"
+                }))
+            });
+            let (_jh, url) = serve_gateway(add_transaction);
+            let context = RpcContext {
+                sequencer: crate::sequencer::Client::with_url(url).unwrap(),
+                ..RpcContext::for_tests()
+            };
+            let input = AddInvokeTransactionInput {
+                invoke_transaction: odd_signature_txn(),
+            };
+
+            add_invoke_transaction(context, input).await.unwrap();
+        }
+    }
+
+    mod max_fee_check {
+        use super::*;
+        use crate::rpc::v02::max_fee_check::MaxFeeCheck;
+        use warp::Filter;
+
+        /// Serves a fee estimate of `estimate` and accepts all submissions.
+        fn serve(estimate: u128) -> (tokio::task::JoinHandle<()>, RpcContext) {
+            let estimate_fee = warp::path!("feeder_gateway" / "estimate_fee_bulk").map(move || {
+                warp::reply::json(&serde_json::json!([{
+                    "overall_fee": estimate,
+                    "gas_price": 1,
+                    "gas_usage": estimate as u64
+                }]))
+            });
+            let add_transaction = warp::path!("gateway" / "add_transaction").map(|| {
+                warp::reply::json(&serde_json::json!({
+                    "code": "TRANSACTION_RECEIVED",
+                    "transaction_hash": "0x389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"
+                }))
+            });
+            let (jh, url) = serve_gateway(estimate_fee.or(add_transaction));
+
+            let context = RpcContext {
+                sequencer: crate::sequencer::Client::with_url(url).unwrap(),
+                ..RpcContext::for_tests()
+            };
+            (jh, context)
+        }
+
+        #[tokio::test]
+        async fn below_estimate_is_rejected() {
+            // The max fee covers the estimate, but not its safety margin.
+            let (_jh, context) = serve(5000000000000);
+            let context = context.with_max_fee_check(MaxFeeCheck {
+                safety_factor_bps: 15_000,
+                reject: true,
+            });
+            let input = AddInvokeTransactionInput {
+                invoke_transaction: test_invoke_txn(),
+            };
+
+            let error = add_invoke_transaction(context, input).await.unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                AddInvokeTransactionError::MaxFeeBelowEstimate { max_fee, required } => {
+                    assert_eq!(max_fee, Fee(5444010076217u128.to_be_bytes().into()));
+                    assert_eq!(required, Fee(7500000000000u128.to_be_bytes().into()));
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn below_estimate_is_submitted_unless_rejecting() {
+            let (_jh, context) = serve(5000000000000);
+            let context = context.with_max_fee_check(MaxFeeCheck {
+                safety_factor_bps: 15_000,
+                reject: false,
+            });
+            let input = AddInvokeTransactionInput {
+                invoke_transaction: test_invoke_txn(),
+            };
+
+            add_invoke_transaction(context, input).await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn above_estimate_is_submitted() {
+            let (_jh, context) = serve(3000000000000);
+            let context = context.with_max_fee_check(MaxFeeCheck {
+                safety_factor_bps: 15_000,
+                reject: true,
+            });
+            let input = AddInvokeTransactionInput {
+                invoke_transaction: test_invoke_txn(),
+            };
+
+            let result = add_invoke_transaction(context, input).await.unwrap();
+            assert_eq!(
+                result.transaction_hash,
+                StarknetTransactionHash(starkhash!(
+                    "0389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"
+                ))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn fee_budget_exceeded() {
+        use crate::rpc::v02::fee_budget::FeeBudget;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let max_fee = Fee(5444010076217u128.to_be_bytes().into());
+        let budget = Arc::new(FeeBudget::new(
+            Fee((5444010076217u128 * 2 - 1).to_be_bytes().into()),
+            Duration::from_secs(3600),
+        ));
+        // The sequencer is unreachable, but the first submission is committed regardless.
+        let context = RpcContext {
+            sequencer: crate::sequencer::Client::with_url(
+                "http://sequencer.invalid/".parse().unwrap(),
+            )
+            .unwrap(),
+            ..RpcContext::for_tests()
+        }
+        .with_fee_budget(budget);
+
+        let input = || AddInvokeTransactionInput {
+            invoke_transaction: test_invoke_txn(),
+        };
+        let error = add_invoke_transaction(context.clone(), input())
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(error, AddInvokeTransactionError::Internal(_));
+
+        let error = add_invoke_transaction(context, input()).await.unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddInvokeTransactionError::FeeBudgetExceeded { max_fee: m, remaining } => {
+                assert_eq!(m, max_fee);
+                assert_eq!(remaining, Fee(5444010076216u128.to_be_bytes().into()));
+            }
+        );
+    }
 }
@@ -72,6 +72,8 @@ pub async fn estimate_fee(
     let (when, pending_update) =
         base_block_and_pending_for_call(input.block_id, &context.pending_data).await?;
 
+    // The version is used as is, since the signature covers it: callers which do not want the
+    // estimated transaction to be valid for inclusion sign a query only version themselves.
     let result = handle
         .estimate_fee(input.request, when, gas_price, pending_update)
         .await?;
@@ -135,6 +137,52 @@ pub struct FeeEstimate {
     /// The estimated fee for the transaction (in gwei), product of gas_consumed and gas_price
     #[serde_as(as = "crate::rpc::serde::H256AsHexStr")]
     pub overall_fee: web3::types::H256,
+    /// The Ethereum gas cost of the transaction, broken down like v3 estimates of the sequencer.
+    ///
+    /// Not part of the specification. All gas of the supported transactions is L1 gas, so this
+    /// is `gas_consumed`.
+    #[serde_as(as = "crate::rpc::serde::H256AsHexStr")]
+    pub l1_gas_consumed: web3::types::H256,
+    /// Not part of the specification, the price of [l1_gas_consumed](Self::l1_gas_consumed).
+    #[serde_as(as = "crate::rpc::serde::H256AsHexStr")]
+    pub l1_gas_price: web3::types::H256,
+    /// Not part of the specification, only present for estimates consuming L2 gas.
+    #[serde_as(as = "Option<crate::rpc::serde::H256AsHexStr>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_gas_consumed: Option<web3::types::H256>,
+    /// Not part of the specification, only present for estimates consuming L2 gas.
+    #[serde_as(as = "Option<crate::rpc::serde::H256AsHexStr>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_gas_price: Option<web3::types::H256>,
+    /// The unit of `overall_fee` and the gas prices, which is only used for formatting them.
+    #[serde(skip)]
+    pub unit: crate::sequencer::reply::FeeUnit,
+}
+
+impl FeeEstimate {
+    /// Formats `overall_fee` in ETH or STRK depending on its [unit](Self::unit), e.g.
+    /// `0.000021 ETH`, for display by CLIs.
+    ///
+    /// Both wei and fri are 10^-18 of their token.
+    pub fn fee_human(&self) -> String {
+        use crate::sequencer::reply::FeeUnit;
+
+        let token = match self.unit {
+            FeeUnit::Wei => "ETH",
+            FeeUnit::Fri => "STRK",
+        };
+        let amount = web3::types::U256::from_big_endian(self.overall_fee.as_bytes());
+        let (whole, remainder) = amount.div_mod(web3::types::U256::exp10(18));
+
+        // The remainder is below 10^18 and fits into a u64.
+        let fraction = format!("{:018}", remainder.low_u64());
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            format!("{whole} {token}")
+        } else {
+            format!("{whole}.{fraction} {token}")
+        }
+    }
 }
 
 impl From<crate::rpc::v01::types::reply::FeeEstimate> for FeeEstimate {
@@ -143,6 +191,12 @@ impl From<crate::rpc::v01::types::reply::FeeEstimate> for FeeEstimate {
             gas_consumed: v01.consumed,
             gas_price: v01.gas_price,
             overall_fee: v01.fee,
+            l1_gas_consumed: v01.consumed,
+            l1_gas_price: v01.gas_price,
+            l2_gas_consumed: None,
+            l2_gas_price: None,
+            // The Python estimates only cover transactions which pay in wei.
+            unit: crate::sequencer::reply::FeeUnit::Wei,
         }
     }
 }
@@ -157,12 +211,86 @@ mod tests {
             CallParam, Chain, ContractAddress, EntryPoint, Fee, StarknetBlockHash,
             TransactionNonce, TransactionSignatureElem, TransactionVersion,
         },
-        rpc::v02::types::request::BroadcastedInvokeTransaction,
+        rpc::v02::types::request::{BroadcastedInvokeTransaction, Signature},
+        sequencer::reply::FeeUnit,
         storage::JournalMode,
     };
 
     use super::*;
 
+    mod fee_human {
+        use super::*;
+
+        fn estimate(overall_fee: u128) -> FeeEstimate {
+            let mut bytes = [0u8; 32];
+            bytes[16..].copy_from_slice(&overall_fee.to_be_bytes());
+            FeeEstimate {
+                gas_consumed: Default::default(),
+                gas_price: Default::default(),
+                overall_fee: web3::types::H256(bytes),
+                l1_gas_consumed: Default::default(),
+                l1_gas_price: Default::default(),
+                l2_gas_consumed: None,
+                l2_gas_price: None,
+                unit: FeeUnit::Wei,
+            }
+        }
+
+        #[test]
+        fn whole_ether() {
+            assert_eq!(estimate(0).fee_human(), "0 ETH");
+            assert_eq!(estimate(2_000_000_000_000_000_000).fee_human(), "2 ETH");
+        }
+
+        #[test]
+        fn fractions() {
+            assert_eq!(estimate(1_500_000_000_000_000_000).fee_human(), "1.5 ETH");
+            assert_eq!(estimate(21_000_000_000_000).fee_human(), "0.000021 ETH");
+        }
+
+        #[test]
+        fn sub_gwei() {
+            assert_eq!(estimate(1).fee_human(), "0.000000000000000001 ETH");
+            assert_eq!(
+                estimate(999_999_999).fee_human(),
+                "0.000000000999999999 ETH"
+            );
+            assert_eq!(estimate(1_000_000_000).fee_human(), "0.000000001 ETH");
+        }
+
+        #[test]
+        fn strk() {
+            let strk = |overall_fee| FeeEstimate {
+                unit: FeeUnit::Fri,
+                ..estimate(overall_fee)
+            };
+            assert_eq!(strk(3_000_000_000_000_000_000).fee_human(), "3 STRK");
+            assert_eq!(strk(1_250_000_000_000_000).fee_human(), "0.00125 STRK");
+            assert_eq!(strk(7).fee_human(), "0.000000000000000007 STRK");
+        }
+    }
+
+    #[test]
+    fn legacy_gas_is_broken_down_as_l1_gas() {
+        let v01 = crate::rpc::v01::types::reply::FeeEstimate {
+            consumed: web3::types::H256::from_low_u64_be(100),
+            gas_price: web3::types::H256::from_low_u64_be(10),
+            fee: web3::types::H256::from_low_u64_be(1000),
+        };
+        let json = serde_json::to_value(FeeEstimate::from(v01)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "gas_consumed": "0x64",
+                "gas_price": "0xa",
+                "overall_fee": "0x3e8",
+                "l1_gas_consumed": "0x64",
+                "l1_gas_price": "0xa",
+            })
+        );
+    }
+
     mod parsing {
         use super::*;
 
@@ -171,7 +299,7 @@ mod tests {
                 crate::rpc::v02::types::request::BroadcastedInvokeTransactionV0 {
                     version: TransactionVersion::ZERO_WITH_QUERY_VERSION,
                     max_fee: Fee(web3::types::H128::from_low_u64_be(0x6)),
-                    signature: vec![TransactionSignatureElem(starkhash!("07"))],
+                    signature: Signature(vec![TransactionSignatureElem(starkhash!("07"))]),
                     nonce: Some(TransactionNonce(starkhash!("08"))),
                     contract_address: ContractAddress::new_or_panic(starkhash!("0aaa")),
                     entry_point_selector: EntryPoint(starkhash!("0e")),
@@ -265,7 +393,7 @@ mod tests {
             BroadcastedInvokeTransactionV0 {
                 version: TransactionVersion::ZERO_WITH_QUERY_VERSION,
                 max_fee: Fee(Default::default()),
-                signature: vec![],
+                signature: Default::default(),
                 nonce: Some(TransactionNonce(Default::default())),
                 contract_address: ContractAddress::new_or_panic(starkhash!(
                     "020cfa74ee3564b4cd5435cdace0f9c4d43b939620e4a0bb5076105df0a626c6"
@@ -374,7 +502,12 @@ mod tests {
                 FeeEstimate {
                     gas_consumed: Default::default(),
                     gas_price: Default::default(),
-                    overall_fee: Default::default()
+                    overall_fee: Default::default(),
+                    l1_gas_consumed: Default::default(),
+                    l1_gas_price: Default::default(),
+                    l2_gas_consumed: None,
+                    l2_gas_price: None,
+                    unit: FeeUnit::Wei,
                 }
             );
         }
@@ -395,12 +528,13 @@ mod tests {
                 BroadcastedTransaction::Declare(BroadcastedDeclareTransaction {
                     version: TransactionVersion::ZERO_WITH_QUERY_VERSION,
                     max_fee: Fee(Default::default()),
-                    signature: vec![],
+                    signature: Default::default(),
                     nonce: TransactionNonce(Default::default()),
                     contract_class: CONTRACT_CLASS.clone(),
                     sender_address: ContractAddress::new_or_panic(starkhash!(
                         "020cfa74ee3564b4cd5435cdace0f9c4d43b939620e4a0bb5076105df0a626c6"
                     )),
+                    compiled_class_hash: None,
                 });
 
             let input = EstimateFeeInput {
@@ -413,7 +547,12 @@ mod tests {
                 FeeEstimate {
                     gas_consumed: Default::default(),
                     gas_price: Default::default(),
-                    overall_fee: Default::default()
+                    overall_fee: Default::default(),
+                    l1_gas_consumed: Default::default(),
+                    l1_gas_price: Default::default(),
+                    l2_gas_consumed: None,
+                    l2_gas_price: None,
+                    unit: FeeUnit::Wei,
                 }
             );
         }
@@ -441,7 +580,12 @@ mod tests {
                 FeeEstimate {
                     gas_consumed: Default::default(),
                     gas_price: Default::default(),
-                    overall_fee: Default::default()
+                    overall_fee: Default::default(),
+                    l1_gas_consumed: Default::default(),
+                    l1_gas_price: Default::default(),
+                    l2_gas_consumed: None,
+                    l2_gas_price: None,
+                    unit: FeeUnit::Wei,
                 }
             );
         }
@@ -0,0 +1,48 @@
+use crate::rpc::v02::RpcContext;
+use crate::sequencer::ConfigSummary;
+
+crate::rpc::error::generate_rpc_error_subset!(SequencerConfigError);
+
+/// The effective configuration of the sequencer client, for diagnosing misconfigured nodes.
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+pub struct SequencerConfigOutput {
+    network: String,
+    #[serde(flatten)]
+    config: ConfigSummary,
+}
+
+pub async fn sequencer_config(
+    context: RpcContext,
+) -> Result<SequencerConfigOutput, SequencerConfigError> {
+    Ok(SequencerConfigOutput {
+        network: context.chain.to_string(),
+        config: context.sequencer.config_summary().clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::{Client, SequencerConfig};
+
+    #[tokio::test]
+    async fn token_is_redacted() {
+        let url = "https://alpha4.starknet.io/?token=very-secret"
+            .parse()
+            .unwrap();
+        let context = RpcContext {
+            sequencer: Client::with_config(SequencerConfig::new(url)).unwrap(),
+            ..RpcContext::for_tests()
+        };
+
+        let output = sequencer_config(context).await.unwrap();
+        let output = serde_json::to_value(&output).unwrap();
+
+        assert_eq!(output["network"], "Görli");
+        assert_eq!(
+            output["url"],
+            "https://alpha4.starknet.io/?token=%3Credacted%3E"
+        );
+        assert!(!output.to_string().contains("very-secret"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Per method rate limiting, e.g. to protect a public node from floods of expensive writes
+//! without throttling reads.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::rpc::error::RpcError;
+
+/// The token bucket of a rate limited method.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MethodLimit {
+    /// How many calls are served in a burst.
+    pub capacity: u32,
+    /// How many calls are added back to the burst per second.
+    pub per_second: f64,
+}
+
+/// Limits calls to the configured methods using a token bucket per method, which all clients
+/// share since the HTTP server does not expose the peer to methods.
+///
+/// Methods without a limit are never throttled.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    limits: HashMap<String, MethodLimit>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits calls to `method`, replacing an earlier limit of it.
+    pub fn with_limit(mut self, method: impl Into<String>, limit: MethodLimit) -> Self {
+        self.limits.insert(method.into(), limit);
+        self
+    }
+
+    /// Takes a token from the bucket of `method`, or returns how long it takes until the next one
+    /// is available.
+    pub(crate) fn check(&self, method: &str) -> Result<(), Duration> {
+        let limit = match self.limits.get(method) {
+            Some(limit) => *limit,
+            None => return Ok(()),
+        };
+        let capacity = f64::from(limit.capacity);
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(method.to_owned()).or_insert(Bucket {
+            tokens: capacity,
+            refilled: now,
+        });
+
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.per_second).min(capacity);
+        bucket.refilled = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        let missing = 1.0 - bucket.tokens;
+        Err(if limit.per_second > 0.0 {
+            Duration::from_secs_f64((missing / limit.per_second).min(f64::from(u32::MAX)))
+        } else {
+            Duration::MAX
+        })
+    }
+
+    /// Takes a token for a call to `method`, or returns the [RpcError::RateLimited] error.
+    pub(crate) fn check_call(&self, method: &'static str) -> Result<(), jsonrpsee::core::Error> {
+        self.check(method).map_err(|retry_after| {
+            RpcError::RateLimited {
+                method,
+                retry_after,
+            }
+            .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::v01::api::RpcApi;
+    use crate::rpc::RpcServer;
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn bucket_refills() {
+        let limit = MethodLimit {
+            capacity: 2,
+            per_second: 0.5,
+        };
+        let limiter = RateLimiter::new()
+            .with_limit("method", limit)
+            .with_limit("other method", limit);
+
+        limiter.check("method").unwrap();
+        limiter.check("method").unwrap();
+        assert_eq!(limiter.check("method"), Err(Duration::from_secs(2)));
+        // Methods are throttled independently.
+        limiter.check("other method").unwrap();
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+        limiter.check("method").unwrap();
+        assert!(limiter.check("method").is_err());
+    }
+
+    #[tokio::test]
+    async fn deploy_is_throttled_while_reads_continue() {
+        let context = crate::rpc::v02::RpcContext::for_tests();
+        let api = RpcApi::new(
+            context.storage,
+            context.sequencer,
+            context.chain,
+            context.sync_status,
+        );
+        let limiter = RateLimiter::new().with_limit(
+            "starknet_addDeployTransaction",
+            MethodLimit {
+                capacity: 3,
+                per_second: 0.0,
+            },
+        );
+        let (_handle, addr) = RpcServer::new(*crate::rpc::tests::LOCALHOST, api)
+            .with_rate_limiter(Arc::new(limiter))
+            .run()
+            .await
+            .unwrap();
+
+        let client = reqwest::Client::new();
+        let call = |method: &'static str| {
+            let request = client
+                .post(format!("http://{addr}/rpc/v0.2"))
+                .json(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 0,
+                    "method": method,
+                    "params": {},
+                }));
+            async move {
+                let response: serde_json::Value =
+                    request.send().await.unwrap().json().await.unwrap();
+                response
+            }
+        };
+
+        // The deploys are invalid, but they count against the limit all the same.
+        for _ in 0..3 {
+            let response = call("starknet_addDeployTransaction").await;
+            assert_eq!(response["error"]["code"], -32602, "{response}");
+        }
+        let response = call("starknet_addDeployTransaction").await;
+        assert_eq!(response["error"]["code"], -32012, "{response}");
+
+        for _ in 0..5 {
+            let response = call("starknet_chainId").await;
+            assert!(response["result"].is_string(), "{response}");
+        }
+    }
+
+    #[tokio::test]
+    async fn v01_deploy_is_throttled() {
+        let context = crate::rpc::v02::RpcContext::for_tests();
+        let api = RpcApi::new(
+            context.storage,
+            context.sequencer,
+            context.chain,
+            context.sync_status,
+        );
+        let limiter = RateLimiter::new().with_limit(
+            "starknet_addDeployTransaction",
+            MethodLimit {
+                capacity: 1,
+                per_second: 0.0,
+            },
+        );
+        let (_handle, addr) = RpcServer::new(*crate::rpc::tests::LOCALHOST, api)
+            .with_rate_limiter(Arc::new(limiter))
+            .run()
+            .await
+            .unwrap();
+
+        let client = reqwest::Client::new();
+        let mut codes = Vec::new();
+        // Both API versions share the bucket of a method.
+        for path in ["/rpc/v0.2", "/rpc/v0.1", "/"] {
+            let response: serde_json::Value = client
+                .post(format!("http://{addr}{path}"))
+                .json(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 0,
+                    "method": "starknet_addDeployTransaction",
+                    "params": {},
+                }))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            codes.push(response["error"]["code"].clone());
+        }
+        assert_eq!(codes, [-32602, -32012, -32012]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Configurable JSON representation of field elements in method outputs.
+use serde::Serialize;
+use serde_json::Value;
+use stark_hash::StarkHash;
+
+use crate::rpc::serde::starkhash_to_dec_str;
+
+/// How field elements such as [StarknetTransactionHash](crate::core::StarknetTransactionHash)
+/// or [ContractAddress](crate::core::ContractAddress) are represented in method outputs.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FeltRepresentation {
+    /// `0x` prefixed hex strings, as required by the specification.
+    #[default]
+    Hex,
+    /// Decimal strings, for clients which cannot handle hex encoded field elements.
+    Decimal,
+}
+
+/// Output of a method, serialized using the requested [FeltRepresentation].
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(super) enum Encoded<T> {
+    /// Serialized as is, using the default [FeltRepresentation::Hex].
+    Original(T),
+    Converted(Value),
+}
+
+impl FeltRepresentation {
+    /// Prepares `output` for serialization using this representation.
+    ///
+    /// Outputs are only re-encoded for [FeltRepresentation::Decimal], in which case every
+    /// `0x` prefixed string which is a valid field element is converted.
+    pub(super) fn encode<T: Serialize>(self, output: T) -> serde_json::Result<Encoded<T>> {
+        match self {
+            FeltRepresentation::Hex => Ok(Encoded::Original(output)),
+            FeltRepresentation::Decimal => {
+                let mut value = serde_json::to_value(&output)?;
+                hex_to_decimal(&mut value);
+                Ok(Encoded::Converted(value))
+            }
+        }
+    }
+}
+
+fn hex_to_decimal(value: &mut Value) {
+    match value {
+        Value::String(s) if s.starts_with("0x") => {
+            if let Ok(felt) = StarkHash::from_hex_str(s) {
+                *s = starkhash_to_dec_str(&felt);
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(hex_to_decimal),
+        Value::Object(values) => values.values_mut().for_each(hex_to_decimal),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ContractAddress;
+    use crate::starkhash;
+
+    fn address() -> ContractAddress {
+        ContractAddress::new_or_panic(starkhash!("123abc"))
+    }
+
+    #[test]
+    fn hex() {
+        let encoded = FeltRepresentation::Hex.encode(address()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&encoded).unwrap(),
+            serde_json::json!("0x123abc")
+        );
+    }
+
+    #[test]
+    fn decimal() {
+        let output = serde_json::json!({
+            "contract_address": address(),
+            "nested": [address()],
+            "name": "0xnot a felt",
+            "count": 3,
+        });
+
+        let encoded = FeltRepresentation::Decimal.encode(output).unwrap();
+        assert_eq!(
+            serde_json::to_value(&encoded).unwrap(),
+            serde_json::json!({
+                "contract_address": "1194684",
+                "nested": ["1194684"],
+                "name": "0xnot a felt",
+                "count": 3,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn server_uses_configured_representation() {
+        use crate::rpc::v01::api::RpcApi;
+        use crate::rpc::v02::{ContextOptions, RpcContext};
+        use crate::rpc::RpcServer;
+
+        let context = RpcContext::for_tests();
+        let api = RpcApi::new(
+            context.storage,
+            context.sequencer,
+            context.chain,
+            context.sync_status,
+        );
+        let (_handle, addr) = RpcServer::new(*crate::rpc::tests::LOCALHOST, api)
+            .with_context_options(ContextOptions {
+                felt_representation: FeltRepresentation::Decimal,
+                ..Default::default()
+            })
+            .run()
+            .await
+            .unwrap();
+
+        let response: serde_json::Value = reqwest::Client::new()
+            .post(format!("http://{addr}/rpc/v0.2"))
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": "starknet_chainId",
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        // SN_GOERLI
+        assert_eq!(response["result"], "1536727068981429685321", "{response}");
+    }
+}
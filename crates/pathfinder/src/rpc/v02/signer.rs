@@ -0,0 +1,349 @@
+//! Delegation of transaction signing to external signers.
+use anyhow::Context;
+use num_bigint::BigUint;
+use stark_hash::StarkHash;
+
+use crate::core::{
+    CallParam, Chain, ClassHash, ContractAddress, ContractAddressSalt, Fee,
+    StarknetTransactionHash, TransactionNonce, TransactionSignatureElem, TransactionVersion,
+};
+use crate::rpc::v02::types::request::{BroadcastedDeployAccountTransaction, Signature};
+use crate::sequencer::request::add_transaction::DeployAccount;
+use crate::state::transaction_hash::compute_deploy_account_transaction_hash;
+
+/// Produces signatures for transactions assembled by pathfinder.
+///
+/// This allows custody setups where the private key never leaves the external signer:
+/// pathfinder assembles the transaction and computes its hash, and only the signing of
+/// that hash is delegated.
+#[async_trait::async_trait]
+pub trait Signer: Send + Sync {
+    /// Signs the given transaction hash.
+    async fn sign(&self, message_hash: StarkHash) -> anyhow::Result<Signature>;
+}
+
+lazy_static::lazy_static!(
+    /// The order of the Stark curve's generator.
+    static ref CURVE_ORDER: BigUint = BigUint::parse_bytes(
+        b"0800000000000010ffffffffffffffffb781126dcae7b2321e66a241adc64d2f",
+        16
+    )
+    .unwrap();
+    /// Signed hashes and the signature's `r` and `w = 1/s` must be below 2**251.
+    static ref ECDSA_UPPER_BOUND: BigUint = BigUint::from(1u8) << 251usize;
+);
+
+/// A [Signer] holding the private key itself, e.g. for development networks where custody is
+/// not a concern.
+///
+/// Signatures are the `(r, s)` ECDSA signatures over the Stark curve which accounts verify,
+/// mirroring `sign` of cairo-lang except for using a random instead of a deterministic `k`.
+pub struct LocalSigner {
+    private_key: BigUint,
+}
+
+impl LocalSigner {
+    /// Errors if `private_key` is zero or not below the curve order.
+    pub fn new(private_key: StarkHash) -> anyhow::Result<Self> {
+        let private_key = BigUint::from_bytes_be(private_key.as_be_bytes());
+        anyhow::ensure!(
+            private_key != BigUint::default() && private_key < *CURVE_ORDER,
+            "Private key must be non-zero and below the curve order"
+        );
+
+        Ok(Self { private_key })
+    }
+
+    /// The public key, which accounts verify the signatures against.
+    pub fn public_key(&self) -> StarkHash {
+        multiply_generator(&self.private_key).x.into()
+    }
+
+    fn sign_hash(&self, message_hash: StarkHash) -> anyhow::Result<Signature> {
+        use rand::Rng;
+
+        anyhow::ensure!(
+            !message_hash.has_more_than_251_bits(),
+            "Message hash must fit into 251 bits"
+        );
+        let message_hash = BigUint::from_bytes_be(message_hash.as_be_bytes());
+
+        let mut rng = rand::thread_rng();
+        loop {
+            let k = BigUint::from_bytes_be(&rng.gen::<[u8; 32]>()) % &*CURVE_ORDER;
+            // Unsuitable values of `k` are as unlikely as guessing the private key.
+            if let Some(signature) = self.sign_with_k(&message_hash, &k) {
+                return Ok(signature);
+            }
+        }
+    }
+
+    /// Signs `message_hash` using `k`, or returns [None] if `k` is unsuitable.
+    fn sign_with_k(&self, message_hash: &BigUint, k: &BigUint) -> Option<Signature> {
+        let order = &*CURVE_ORDER;
+        if *k == BigUint::default() {
+            return None;
+        }
+
+        let r = StarkHash::from(multiply_generator(k).x);
+        let r = BigUint::from_bytes_be(r.as_be_bytes());
+        if r == BigUint::default() || r >= *ECDSA_UPPER_BOUND {
+            return None;
+        }
+
+        let numerator = (message_hash + &r * &self.private_key) % order;
+        if numerator == BigUint::default() {
+            return None;
+        }
+        let s = numerator * invert(k) % order;
+        if invert(&s) >= *ECDSA_UPPER_BOUND {
+            return None;
+        }
+
+        let to_elem = |value: BigUint| {
+            StarkHash::from_be_slice(&value.to_bytes_be())
+                .map(TransactionSignatureElem)
+                .expect("value is below the curve order")
+        };
+        Some(Signature(vec![to_elem(r), to_elem(s)]))
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for LocalSigner {
+    async fn sign(&self, message_hash: StarkHash) -> anyhow::Result<Signature> {
+        self.sign_hash(message_hash)
+            .context("Signing with the local key")
+    }
+}
+
+/// Multiplies the generator of the Stark curve by `scalar`.
+fn multiply_generator(scalar: &BigUint) -> stark_curve::AffinePoint {
+    use bitvec::{order::Lsb0, view::BitView};
+
+    let mut limbs = [0u64; 4];
+    for (limb, digit) in limbs.iter_mut().zip(scalar.to_u64_digits()) {
+        *limb = digit;
+    }
+
+    let product = stark_curve::CURVE_G.multiply(limbs[..].view_bits::<Lsb0>());
+    stark_curve::AffinePoint::from(&product)
+}
+
+/// The multiplicative inverse of `value` modulo the curve order, which is prime.
+fn invert(value: &BigUint) -> BigUint {
+    let order = &*CURVE_ORDER;
+    value.modpow(&(order - BigUint::from(2u8)), order)
+}
+
+/// An assembled but unsigned `DEPLOY_ACCOUNT` transaction, see [build_deploy_account_payload].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeployAccountPayload {
+    /// The transaction, with an empty signature.
+    pub transaction: BroadcastedDeployAccountTransaction,
+    /// The address of the account once deployed.
+    pub contract_address: ContractAddress,
+    /// The hash which the signature must sign.
+    pub transaction_hash: StarknetTransactionHash,
+}
+
+impl DeployAccountPayload {
+    /// The transaction signed with `signature`, ready for submission.
+    pub fn with_signature(self, signature: Signature) -> BroadcastedDeployAccountTransaction {
+        BroadcastedDeployAccountTransaction {
+            signature,
+            ..self.transaction
+        }
+    }
+}
+
+/// Assembles a `DEPLOY_ACCOUNT` transaction for `chain` and computes the hash to be signed,
+/// so that signing can happen elsewhere, e.g. by a [Signer].
+pub fn build_deploy_account_payload(
+    version: TransactionVersion,
+    max_fee: Fee,
+    nonce: TransactionNonce,
+    contract_address_salt: ContractAddressSalt,
+    class_hash: ClassHash,
+    constructor_calldata: Vec<CallParam>,
+    chain: Chain,
+) -> DeployAccountPayload {
+    let transaction = BroadcastedDeployAccountTransaction {
+        version,
+        max_fee,
+        signature: Signature::default(),
+        nonce,
+        contract_address_salt,
+        constructor_calldata,
+        class_hash,
+    };
+    let request = DeployAccount {
+        version,
+        max_fee,
+        signature: vec![],
+        nonce,
+        class_hash,
+        contract_address_salt,
+        constructor_calldata: transaction.constructor_calldata.clone(),
+    };
+
+    let (contract_address, transaction_hash) =
+        compute_deploy_account_transaction_hash(&request, chain.execution_chain_id());
+
+    DeployAccountPayload {
+        transaction,
+        contract_address,
+        transaction_hash,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::starkhash;
+
+    /// Values from a deploy account transaction submitted to the integration network.
+    fn payload() -> DeployAccountPayload {
+        build_deploy_account_payload(
+            TransactionVersion::ONE,
+            Fee(web3::types::H128::from_low_u64_be(0xbf391377813)),
+            TransactionNonce::ZERO,
+            ContractAddressSalt(starkhash!(
+                "06d44a6aecb4339e23a9619355f101cf3cb9baec289fcd9fd51486655c1bb8a8"
+            )),
+            ClassHash(starkhash!(
+                "01fac3074c9d5282f0acc5c69a4781a1c711efea5e73c550c5d9fb253cf7fd3d"
+            )),
+            vec![CallParam(starkhash!(
+                "0677bb1cdc050e8d63855e8743ab6e09179138def390676cc03c484daf112ba1"
+            ))],
+            Chain::Integration,
+        )
+    }
+
+    #[test]
+    fn deploy_account_payload() {
+        let payload = payload();
+
+        assert_eq!(
+            payload.transaction_hash,
+            StarknetTransactionHash(starkhash!(
+                "0273fb3c38b20037839d6bad8811cd0afd82f2bc3c95c061eb8f30ce5cedc377"
+            ))
+        );
+        assert_eq!(
+            payload.contract_address,
+            ContractAddress::new_or_panic(starkhash!(
+                "042ae26ab2b8236242bb384c23e74c69af7204bb2fc711a99da63e0dd6adf33f"
+            ))
+        );
+        assert_eq!(payload.transaction.signature, Signature::default());
+    }
+
+    #[test]
+    fn signature_is_attached() {
+        let payload = payload();
+        let signature = Signature(vec![
+            TransactionSignatureElem(starkhash!("0a")),
+            TransactionSignatureElem(starkhash!("0b")),
+        ]);
+
+        let transaction = payload.clone().with_signature(signature.clone());
+        assert_eq!(
+            transaction,
+            BroadcastedDeployAccountTransaction {
+                signature,
+                ..payload.transaction
+            }
+        );
+    }
+
+    fn local_signer() -> LocalSigner {
+        LocalSigner::new(starkhash!(
+            "03c1e9550e66958296d11b60f8e8e7a7ad990d07fa65d5f7652c4a6c87d4e3cc"
+        ))
+        .unwrap()
+    }
+
+    const MESSAGE_HASH: StarkHash =
+        starkhash!("010b559a3b4dc1b7137d90521cb413b397ff07963214d128a92d65aec7182f68");
+
+    /// Whether `signature` of `message_hash` verifies against the key of `signer`.
+    fn verifies(signer: &LocalSigner, message_hash: StarkHash, signature: &Signature) -> bool {
+        use bitvec::{order::Lsb0, view::BitView};
+
+        let to_uint = |value: StarkHash| BigUint::from_bytes_be(value.as_be_bytes());
+        let to_limbs = |value: &BigUint| {
+            let mut limbs = [0u64; 4];
+            for (limb, digit) in limbs.iter_mut().zip(value.to_u64_digits()) {
+                *limb = digit;
+            }
+            limbs
+        };
+
+        let order = &*CURVE_ORDER;
+        let (r, s) = (to_uint(signature.0[0].0), to_uint(signature.0[1].0));
+        let w = invert(&s);
+        let u1 = to_uint(message_hash) * &w % order;
+        let u2 = &r * &w % order;
+
+        let public_key =
+            stark_curve::ProjectivePoint::from(&multiply_generator(&signer.private_key));
+        let mut point = stark_curve::CURVE_G.multiply(to_limbs(&u1)[..].view_bits::<Lsb0>());
+        point.add(&public_key.multiply(to_limbs(&u2)[..].view_bits::<Lsb0>()));
+        let x = StarkHash::from(stark_curve::AffinePoint::from(&point).x);
+
+        to_uint(x) % order == r
+    }
+
+    #[test]
+    fn local_public_key() {
+        assert_eq!(
+            local_signer().public_key(),
+            starkhash!("077a3b314db07c45076d11f62b6f9e748a39790441823307743cf00d6597ea43")
+        );
+    }
+
+    #[test]
+    fn local_signature_with_known_k() {
+        let k = BigUint::parse_bytes(
+            b"054d7beec5ec728223671c627557efc5c9a6508425dc6c900b7741bf60afec06",
+            16,
+        )
+        .unwrap();
+        let message_hash = BigUint::from_bytes_be(MESSAGE_HASH.as_be_bytes());
+
+        let signature = local_signer().sign_with_k(&message_hash, &k).unwrap();
+        assert_eq!(
+            signature,
+            Signature(vec![
+                TransactionSignatureElem(starkhash!(
+                    "063e4a3879017f6d023bdd4057492713bf2735b566e5963f5d7d42ca3c25a285"
+                )),
+                TransactionSignatureElem(starkhash!(
+                    "04edb81aa986983c986f4a8c77f954ac541349532860faaf90f99845905b0189"
+                )),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn local_signatures_verify() {
+        let signer = local_signer();
+
+        let signature = signer.sign(MESSAGE_HASH).await.unwrap();
+        assert!(verifies(&signer, MESSAGE_HASH, &signature));
+        assert!(!verifies(&signer, starkhash!("01"), &signature));
+        // Signatures use a fresh `k` every time.
+        assert_ne!(signer.sign(MESSAGE_HASH).await.unwrap(), signature);
+    }
+
+    #[test]
+    fn invalid_local_keys_are_rejected() {
+        LocalSigner::new(StarkHash::ZERO).unwrap_err();
+        LocalSigner::new(starkhash!(
+            "0800000000000010ffffffffffffffffb781126dcae7b2321e66a241adc64d2f"
+        ))
+        .unwrap_err();
+    }
+}
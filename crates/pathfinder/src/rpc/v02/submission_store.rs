@@ -0,0 +1,147 @@
+//! Durable record of the transactions submitted through this node.
+//!
+//! Unlike the sequencer's handling of already received transactions, this survives restarts
+//! and answers whether this node submitted a transaction without asking the gateway.
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+
+use crate::core::{ContractAddress, StarknetTransactionHash};
+use crate::storage::Storage;
+
+/// A transaction submitted through this node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SubmissionRecord {
+    pub transaction_hash: StarknetTransactionHash,
+    /// The address of the contract the transaction deploys.
+    pub contract_address: ContractAddress,
+    /// Seconds since the UNIX epoch.
+    pub timestamp: u64,
+}
+
+impl SubmissionRecord {
+    /// Creates a record timestamped with the current system time.
+    pub fn now(
+        transaction_hash: StarknetTransactionHash,
+        contract_address: ContractAddress,
+    ) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            transaction_hash,
+            contract_address,
+            timestamp,
+        }
+    }
+}
+
+/// Stores [SubmissionRecord]s in the `submissions` table of the node's database.
+#[derive(Clone)]
+pub struct SubmissionStore {
+    storage: Storage,
+}
+
+impl SubmissionStore {
+    pub fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+
+    /// Stores `record`, replacing an earlier record of the same transaction.
+    pub async fn record(&self, record: SubmissionRecord) -> anyhow::Result<()> {
+        let timestamp = i64::try_from(record.timestamp).context("Timestamp out of range")?;
+        let storage = self.storage.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let connection = storage.connection()?;
+            connection
+                .execute(
+                    "INSERT OR REPLACE INTO submissions (transaction_hash, contract_address, timestamp) VALUES (?, ?, ?)",
+                    params![record.transaction_hash, record.contract_address, timestamp],
+                )
+                .context("Inserting submission record")?;
+            Ok(())
+        })
+        .await
+        .context("Database write panic or shutting down")?
+    }
+
+    /// Returns the record of `transaction_hash` if it was submitted through this node.
+    pub async fn was_submitted(
+        &self,
+        transaction_hash: StarknetTransactionHash,
+    ) -> anyhow::Result<Option<SubmissionRecord>> {
+        let storage = self.storage.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let connection = storage.connection()?;
+            connection
+                .query_row(
+                    "SELECT contract_address, timestamp FROM submissions WHERE transaction_hash = ?",
+                    [transaction_hash],
+                    |row| {
+                        let timestamp: i64 = row.get(1)?;
+                        Ok(SubmissionRecord {
+                            transaction_hash,
+                            contract_address: row.get(0)?,
+                            timestamp: timestamp as u64,
+                        })
+                    },
+                )
+                .optional()
+                .context("Querying submission record")
+        })
+        .await
+        .context("Database read panic or shutting down")?
+    }
+}
+
+/// Writes `record` to the `store`, if there is one.
+///
+/// Failures are only logged since the transaction has already been submitted at this point.
+pub(crate) async fn record(store: Option<&SubmissionStore>, record: SubmissionRecord) {
+    if let Some(store) = store {
+        if let Err(e) = store.record(record).await {
+            tracing::error!(reason=?e, "Failed to write submission record");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::starkhash;
+
+    #[tokio::test]
+    async fn records_survive_a_restart() {
+        use crate::storage::JournalMode;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pathfinder.sqlite");
+
+        let record = SubmissionRecord::now(
+            StarknetTransactionHash(starkhash!("01")),
+            ContractAddress::new_or_panic(starkhash!("02")),
+        );
+
+        let storage = Storage::migrate(path.clone(), JournalMode::Rollback).unwrap();
+        let store = SubmissionStore::new(storage);
+        store.record(record).await.unwrap();
+        drop(store);
+
+        let storage = Storage::migrate(path, JournalMode::Rollback).unwrap();
+        let store = SubmissionStore::new(storage);
+        assert_eq!(
+            store.was_submitted(record.transaction_hash).await.unwrap(),
+            Some(record)
+        );
+        assert_eq!(
+            store
+                .was_submitted(StarknetTransactionHash(starkhash!("03")))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
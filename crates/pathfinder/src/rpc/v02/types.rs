@@ -7,13 +7,15 @@ pub use class::*;
 pub mod request {
     use crate::{
         core::{
-            CallParam, ClassHash, ConstructorParam, ContractAddress, ContractAddressSalt,
-            EntryPoint, Fee, TransactionNonce, TransactionSignatureElem, TransactionVersion,
+            CallParam, CasmHash, ClassHash, ConstructorParam, ContractAddress, ContractAddressSalt,
+            EntryPoint, Fee, GasPrice, TransactionNonce, TransactionSignatureElem,
+            TransactionVersion,
         },
-        rpc::serde::{FeeAsHexStr, TransactionVersionAsHexStr},
+        rpc::serde::{FeeAsHexStr, GasPriceAsHexStr, TransactionVersionAsHexStr, U64AsHexStr},
     };
     use serde::Deserialize;
     use serde_with::serde_as;
+    use stark_hash::StarkHash;
 
     /// "Broadcasted" L2 transaction in requests the RPC API.
     ///
@@ -34,6 +36,102 @@ pub mod request {
         DeployAccount(BroadcastedDeployAccountTransaction),
     }
 
+    impl BroadcastedTransaction {
+        /// Marks the transaction as query only, so that it cannot be submitted for inclusion
+        /// once it has been sent for a fee estimate.
+        ///
+        /// This changes the transaction hash, so it must be done before signing.
+        pub fn into_query_only(mut self) -> Self {
+            let version = match &mut self {
+                Self::Declare(tx) => &mut tx.version,
+                Self::Invoke(BroadcastedInvokeTransaction::V0(tx)) => &mut tx.version,
+                Self::Invoke(BroadcastedInvokeTransaction::V1(tx)) => &mut tx.version,
+                Self::Invoke(BroadcastedInvokeTransaction::V3(tx)) => &mut tx.version,
+                Self::Deploy(tx) => &mut tx.version,
+                Self::DeployAccount(tx) => &mut tx.version,
+            };
+            *version = version.with_query_only();
+            self
+        }
+    }
+
+    /// The signature of a broadcasted transaction.
+    ///
+    /// Each element is guaranteed to be a field element by deserialization. Whether the
+    /// signature is plausible as a whole is checked by [Signature::validate].
+    #[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+    #[cfg_attr(any(test, feature = "rpc-full-serde"), derive(serde::Serialize))]
+    #[serde(transparent)]
+    pub struct Signature(pub Vec<TransactionSignatureElem>);
+
+    impl Signature {
+        /// Checks that the signature consists of one or more `(r, s)` pairs.
+        ///
+        /// This is what the standard account contracts expect, and catches a malformed
+        /// signature before it wastes a submission. Other account contracts may accept
+        /// signatures of any layout, which is why this is only checked if
+        /// [RpcContext::check_signatures](crate::rpc::v02::RpcContext::check_signatures) is set.
+        pub fn validate(&self) -> Result<(), SignatureError> {
+            match self.0.len() {
+                0 => Err(SignatureError::Empty),
+                len if len % 2 != 0 => Err(SignatureError::OddLength(len)),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq, thiserror::Error)]
+    pub enum SignatureError {
+        #[error("Signature is empty")]
+        Empty,
+        #[error("Signature has an odd number of elements ({0}), expected (r, s) pairs")]
+        OddLength(usize),
+    }
+
+    /// A call parameter which is not a valid field element.
+    #[derive(Debug, PartialEq, Eq, thiserror::Error)]
+    #[error("Call parameter at index {index} is not a valid field element")]
+    pub struct InvalidCallParam {
+        pub index: usize,
+    }
+
+    /// Parses each of `params` as a field element, reporting the index of the first one which
+    /// isn't.
+    ///
+    /// Deserializing calldata as a whole only reports that *some* element is invalid, which
+    /// is hard to act on for long calldata.
+    pub fn validate_call_params<S: AsRef<str>>(
+        params: &[S],
+    ) -> Result<Vec<StarkHash>, InvalidCallParam> {
+        params
+            .iter()
+            .enumerate()
+            .map(|(index, param)| {
+                StarkHash::from_hex_str(param.as_ref()).map_err(|_| InvalidCallParam { index })
+            })
+            .collect()
+    }
+
+    fn call_params<'de, D>(deserializer: D) -> Result<Vec<CallParam>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = Vec::<String>::deserialize(deserializer)?;
+        validate_call_params(&params)
+            .map(|params| params.into_iter().map(CallParam).collect())
+            .map_err(serde::de::Error::custom)
+    }
+
+    fn constructor_params<'de, D>(deserializer: D) -> Result<Vec<ConstructorParam>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let params = Vec::<String>::deserialize(deserializer)?;
+        validate_call_params(&params)
+            .map(|params| params.into_iter().map(ConstructorParam).collect())
+            .map_err(serde::de::Error::custom)
+    }
+
     #[serde_as]
     #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
     #[cfg_attr(any(test, feature = "rpc-full-serde"), derive(serde::Serialize))]
@@ -46,11 +144,14 @@ pub mod request {
         pub max_fee: Fee,
         #[serde_as(as = "TransactionVersionAsHexStr")]
         pub version: TransactionVersion,
-        pub signature: Vec<TransactionSignatureElem>,
+        pub signature: Signature,
         pub nonce: TransactionNonce,
 
         pub contract_class: super::ContractClass,
         pub sender_address: ContractAddress,
+        /// Required when declaring a Sierra class.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub compiled_class_hash: Option<CasmHash>,
     }
 
     #[serde_as]
@@ -61,6 +162,7 @@ pub mod request {
         #[serde_as(as = "TransactionVersionAsHexStr")]
         pub version: TransactionVersion,
         pub contract_address_salt: ContractAddressSalt,
+        #[serde(deserialize_with = "constructor_params")]
         pub constructor_calldata: Vec<ConstructorParam>,
 
         /// The class of the contract that will be deployed.
@@ -77,11 +179,12 @@ pub mod request {
         pub version: TransactionVersion,
         #[serde_as(as = "FeeAsHexStr")]
         pub max_fee: Fee,
-        pub signature: Vec<TransactionSignatureElem>,
+        pub signature: Signature,
         pub nonce: TransactionNonce,
 
         // Fields from DEPLOY_ACCOUNT_TXN_PROPERTIES
         pub contract_address_salt: ContractAddressSalt,
+        #[serde(deserialize_with = "call_params")]
         pub constructor_calldata: Vec<CallParam>,
         pub class_hash: ClassHash,
     }
@@ -95,6 +198,7 @@ pub mod request {
     pub enum BroadcastedInvokeTransaction {
         V0(BroadcastedInvokeTransactionV0),
         V1(BroadcastedInvokeTransactionV1),
+        V3(BroadcastedInvokeTransactionV3),
     }
 
     impl<'de> Deserialize<'de> for BroadcastedInvokeTransaction {
@@ -113,6 +217,12 @@ pub mod request {
             }
 
             let v = serde_json::Value::deserialize(deserializer)?;
+            if v.get("max_fee").is_some() && v.get("resource_bounds").is_some() {
+                return Err(de::Error::custom(
+                    "max_fee and resource_bounds are mutually exclusive",
+                ));
+            }
+
             let version = Version::deserialize(&v).map_err(de::Error::custom)?;
             match version.version.without_query_version() {
                 0 => Ok(Self::V0(
@@ -121,7 +231,10 @@ pub mod request {
                 1 => Ok(Self::V1(
                     BroadcastedInvokeTransactionV1::deserialize(&v).map_err(de::Error::custom)?,
                 )),
-                _ => Err(de::Error::custom("version must be 0 or 1")),
+                3 => Ok(Self::V3(
+                    BroadcastedInvokeTransactionV3::deserialize(&v).map_err(de::Error::custom)?,
+                )),
+                _ => Err(de::Error::custom("version must be 0, 1 or 3")),
             }
         }
     }
@@ -143,7 +256,7 @@ pub mod request {
         // `deny_unknown_fields`: https://serde.rs/attr-flatten.html#struct-flattening
         #[serde_as(as = "FeeAsHexStr")]
         pub max_fee: Fee,
-        pub signature: Vec<TransactionSignatureElem>,
+        pub signature: Signature,
         // This is a mistake in RPC specification v0.2. This field should not exist,
         // but since it is part of the spec we make it optional and then don't pass it
         // on to the gateway in the write API.
@@ -151,6 +264,7 @@ pub mod request {
 
         pub contract_address: ContractAddress,
         pub entry_point_selector: EntryPoint,
+        #[serde(deserialize_with = "call_params")]
         pub calldata: Vec<CallParam>,
     }
 
@@ -168,13 +282,83 @@ pub mod request {
         // `deny_unknown_fields`: https://serde.rs/attr-flatten.html#struct-flattening
         #[serde_as(as = "FeeAsHexStr")]
         pub max_fee: Fee,
-        pub signature: Vec<TransactionSignatureElem>,
+        pub signature: Signature,
+        pub nonce: TransactionNonce,
+
+        pub sender_address: ContractAddress,
+        #[serde(deserialize_with = "call_params")]
+        pub calldata: Vec<CallParam>,
+    }
+
+    /// A v3 invoke, which is bounded by [ResourceBounds] instead of `max_fee`.
+    #[serde_as]
+    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[cfg_attr(any(test, feature = "rpc-full-serde"), derive(serde::Serialize))]
+    #[serde(deny_unknown_fields)]
+    pub struct BroadcastedInvokeTransactionV3 {
+        #[serde_as(as = "TransactionVersionAsHexStr")]
+        pub version: TransactionVersion,
+
+        pub resource_bounds: ResourceBounds,
+        pub signature: Signature,
         pub nonce: TransactionNonce,
 
         pub sender_address: ContractAddress,
+        #[serde(deserialize_with = "call_params")]
         pub calldata: Vec<CallParam>,
     }
 
+    /// The maximum amount and price of each resource a v3 transaction may consume.
+    #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[cfg_attr(any(test, feature = "rpc-full-serde"), derive(serde::Serialize))]
+    #[serde(deny_unknown_fields)]
+    pub struct ResourceBounds {
+        pub l1_gas: ResourceBound,
+        pub l2_gas: ResourceBound,
+    }
+
+    #[serde_as]
+    #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[cfg_attr(any(test, feature = "rpc-full-serde"), derive(serde::Serialize))]
+    #[serde(deny_unknown_fields)]
+    pub struct ResourceBound {
+        #[serde_as(as = "U64AsHexStr")]
+        pub max_amount: u64,
+        #[serde_as(as = "GasPriceAsHexStr")]
+        pub max_price_per_unit: GasPrice,
+    }
+
+    impl ResourceBounds {
+        /// The most the transaction can be charged, i.e. the sum of
+        /// `max_amount * max_price_per_unit` over all resources.
+        ///
+        /// Returns `None` on overflow.
+        pub fn max_fee(&self) -> Option<Fee> {
+            let l1 =
+                u128::from(self.l1_gas.max_amount).checked_mul(self.l1_gas.max_price_per_unit.0)?;
+            let l2 =
+                u128::from(self.l2_gas.max_amount).checked_mul(self.l2_gas.max_price_per_unit.0)?;
+            let total = l1.checked_add(l2)?;
+            Some(Fee(total.to_be_bytes().into()))
+        }
+    }
+
+    impl From<ResourceBounds> for crate::sequencer::request::add_transaction::ResourceBounds {
+        fn from(bounds: ResourceBounds) -> Self {
+            use crate::sequencer::request::add_transaction::ResourceBound as SequencerBound;
+
+            let bound = |b: ResourceBound| SequencerBound {
+                max_amount: b.max_amount,
+                max_price_per_unit: b.max_price_per_unit,
+            };
+
+            Self {
+                l1_gas: bound(bounds.l1_gas),
+                l2_gas: bound(bounds.l2_gas),
+            }
+        }
+    }
+
     #[cfg(test)]
     mod tests {
         macro_rules! fixture {
@@ -184,6 +368,43 @@ pub mod request {
             };
         }
 
+        mod query_only {
+            use super::super::*;
+
+            fn version(tx: &BroadcastedTransaction) -> TransactionVersion {
+                match tx {
+                    BroadcastedTransaction::Declare(tx) => tx.version,
+                    BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V0(tx)) => {
+                        tx.version
+                    }
+                    BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V1(tx)) => {
+                        tx.version
+                    }
+                    BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V3(tx)) => {
+                        tx.version
+                    }
+                    BroadcastedTransaction::Deploy(tx) => tx.version,
+                    BroadcastedTransaction::DeployAccount(tx) => tx.version,
+                }
+            }
+
+            #[test]
+            fn is_set_for_estimates() {
+                let json_fixture = fixture!("broadcasted_transactions.json");
+                let txs =
+                    serde_json::from_str::<Vec<BroadcastedTransaction>>(&json_fixture).unwrap();
+                // The fixture also holds a transaction which is query only already.
+                assert!(txs.iter().any(|tx| !version(tx).is_query_only()));
+
+                for tx in txs {
+                    let expected = version(&tx).without_query_version();
+                    let version = version(&tx.into_query_only());
+                    assert!(version.is_query_only());
+                    assert_eq!(version.without_query_version(), expected);
+                }
+            }
+        }
+
         /// The aim of these tests is to check if deserialization works correctly
         /// **without resorting to serialization to prepare the test data**,
         /// which in itself could contain an "opposite phase" bug that cancels out.
@@ -216,10 +437,11 @@ pub mod request {
                     BroadcastedTransaction::Declare(BroadcastedDeclareTransaction {
                         max_fee: Fee(web3::types::H128::from_low_u64_be(0x5)),
                         version: TransactionVersion(web3::types::H256::from_low_u64_be(0x0)),
-                        signature: vec![TransactionSignatureElem(starkhash!("07"))],
+                        signature: Signature(vec![TransactionSignatureElem(starkhash!("07"))]),
                         nonce: TransactionNonce(starkhash!("08")),
                         contract_class: contract_class.clone(),
                         sender_address: ContractAddress::new_or_panic(starkhash!("0a")),
+                        compiled_class_hash: None,
                     }),
                     BroadcastedTransaction::Deploy(BroadcastedDeployTransaction {
                         version: TransactionVersion(web3::types::H256::from_low_u64_be(0x0)),
@@ -231,7 +453,7 @@ pub mod request {
                         BroadcastedInvokeTransactionV0 {
                             version: TransactionVersion(web3::types::H256::zero()),
                             max_fee: Fee(web3::types::H128::from_low_u64_be(0x6)),
-                            signature: vec![TransactionSignatureElem(starkhash!("07"))],
+                            signature: Signature(vec![TransactionSignatureElem(starkhash!("07"))]),
                             nonce: Some(TransactionNonce(starkhash!("08"))),
                             contract_address: ContractAddress::new_or_panic(starkhash!("0aaa")),
                             entry_point_selector: EntryPoint(starkhash!("0e")),
@@ -242,7 +464,7 @@ pub mod request {
                         BroadcastedInvokeTransactionV1 {
                             version: TransactionVersion(web3::types::H256::from_low_u64_be(1)),
                             max_fee: Fee(web3::types::H128::from_low_u64_be(0x6)),
-                            signature: vec![TransactionSignatureElem(starkhash!("07"))],
+                            signature: Signature(vec![TransactionSignatureElem(starkhash!("07"))]),
                             nonce: TransactionNonce(starkhash!("08")),
                             sender_address: ContractAddress::new_or_panic(starkhash!("0aaa")),
                             calldata: vec![CallParam(starkhash!("ff"))],
@@ -254,7 +476,7 @@ pub mod request {
                                 "0000000000000000000000000000000100000000000000000000000000000001"
                             ))),
                             max_fee: Fee(web3::types::H128::from_low_u64_be(0x6)),
-                            signature: vec![TransactionSignatureElem(starkhash!("07"))],
+                            signature: Signature(vec![TransactionSignatureElem(starkhash!("07"))]),
                             nonce: TransactionNonce(starkhash!("08")),
                             sender_address: ContractAddress::new_or_panic(starkhash!("0aaa")),
                             calldata: vec![CallParam(starkhash!("ff"))],
@@ -270,6 +492,210 @@ pub mod request {
                     txs
                 );
             }
+
+            mod v3 {
+                use super::*;
+
+                const V3_INVOKE: &str = r#"{
+                    "type": "INVOKE",
+                    "version": "0x3",
+                    "resource_bounds": {
+                        "l1_gas": {"max_amount": "0x10", "max_price_per_unit": "0x2"},
+                        "l2_gas": {"max_amount": "0x0", "max_price_per_unit": "0x0"}
+                    },
+                    "signature": ["0x7"],
+                    "nonce": "0x8",
+                    "sender_address": "0xaaa",
+                    "calldata": ["0xff"]
+                }"#;
+
+                #[test]
+                fn invoke() {
+                    let tx = serde_json::from_str::<BroadcastedTransaction>(V3_INVOKE).unwrap();
+
+                    let expected = BroadcastedTransaction::Invoke(
+                        BroadcastedInvokeTransaction::V3(BroadcastedInvokeTransactionV3 {
+                            version: TransactionVersion(web3::types::H256::from_low_u64_be(3)),
+                            resource_bounds: ResourceBounds {
+                                l1_gas: ResourceBound {
+                                    max_amount: 0x10,
+                                    max_price_per_unit: GasPrice(2),
+                                },
+                                l2_gas: ResourceBound {
+                                    max_amount: 0,
+                                    max_price_per_unit: GasPrice(0),
+                                },
+                            },
+                            signature: Signature(vec![TransactionSignatureElem(starkhash!("07"))]),
+                            nonce: TransactionNonce(starkhash!("08")),
+                            sender_address: ContractAddress::new_or_panic(starkhash!("0aaa")),
+                            calldata: vec![CallParam(starkhash!("ff"))],
+                        }),
+                    );
+                    assert_eq!(tx, expected);
+                }
+
+                #[test]
+                fn max_fee_of_resource_bounds() {
+                    let tx =
+                        serde_json::from_str::<BroadcastedInvokeTransaction>(V3_INVOKE).unwrap();
+                    let tx = match tx {
+                        BroadcastedInvokeTransaction::V3(tx) => tx,
+                        other => panic!("Expected V3, got {:?}", other),
+                    };
+                    assert_eq!(
+                        tx.resource_bounds.max_fee(),
+                        Some(Fee(web3::types::H128::from_low_u64_be(0x20)))
+                    );
+                }
+
+                #[test]
+                fn both_max_fee_and_resource_bounds_is_rejected() {
+                    let mut json = serde_json::from_str::<serde_json::Value>(V3_INVOKE).unwrap();
+                    json["max_fee"] = serde_json::json!("0x6");
+
+                    let error =
+                        serde_json::from_value::<BroadcastedInvokeTransaction>(json).unwrap_err();
+                    assert!(
+                        error.to_string().contains("mutually exclusive"),
+                        "{}",
+                        error
+                    );
+                }
+
+                #[test]
+                fn v1_with_resource_bounds_is_rejected() {
+                    let mut json = serde_json::from_str::<serde_json::Value>(V3_INVOKE).unwrap();
+                    json["version"] = serde_json::json!("0x1");
+
+                    serde_json::from_value::<BroadcastedInvokeTransaction>(json).unwrap_err();
+                }
+            }
+
+            mod v2_declare {
+                use super::*;
+
+                fn declare_json(compiled_class_hash: &str) -> serde_json::Value {
+                    serde_json::json!({
+                        "type": "DECLARE",
+                        "max_fee": "0x5",
+                        "version": "0x2",
+                        "signature": ["0x7"],
+                        "nonce": "0x8",
+                        "contract_class": {
+                            "program": "program",
+                            "entry_points_by_type": {
+                                "CONSTRUCTOR": [],
+                                "EXTERNAL": [],
+                                "L1_HANDLER": []
+                            }
+                        },
+                        "sender_address": "0xa",
+                        "compiled_class_hash": compiled_class_hash
+                    })
+                }
+
+                #[test]
+                fn with_compiled_class_hash() {
+                    let tx = serde_json::from_value::<BroadcastedTransaction>(declare_json("0xcc"))
+                        .unwrap();
+                    let tx = match tx {
+                        BroadcastedTransaction::Declare(tx) => tx,
+                        other => panic!("Expected a declare, got {:?}", other),
+                    };
+
+                    assert_eq!(
+                        tx.version,
+                        TransactionVersion(web3::types::H256::from_low_u64_be(2))
+                    );
+                    assert_eq!(tx.compiled_class_hash, Some(CasmHash(starkhash!("cc"))));
+                }
+
+                #[test]
+                fn compiled_class_hash_is_not_a_field_element() {
+                    let overflowing = format!("0x{}", "f".repeat(64));
+                    serde_json::from_value::<BroadcastedTransaction>(declare_json(&overflowing))
+                        .unwrap_err();
+                }
+            }
+        }
+
+        mod call_params {
+            use super::super::*;
+            use crate::starkhash;
+
+            #[test]
+            fn valid() {
+                assert_eq!(
+                    validate_call_params(&["0x1", "0x02", "0x0"]),
+                    Ok(vec![starkhash!("01"), starkhash!("02"), StarkHash::ZERO])
+                );
+            }
+
+            #[test]
+            fn invalid_element_is_reported_by_index() {
+                let modulus = "0x800000000000011000000000000000000000000000000000000000000000001";
+                assert_eq!(
+                    validate_call_params(&["0x1", "0x2", modulus, "not hex"]),
+                    Err(InvalidCallParam { index: 2 })
+                );
+            }
+
+            #[test]
+            fn deserialization_reports_the_index() {
+                let json = r#"{
+                    "version": "0x0",
+                    "contract_address_salt": "0x1",
+                    "constructor_calldata": ["0x1", "0xzz"],
+                    "contract_class": {
+                        "program": "",
+                        "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []}
+                    }
+                }"#;
+                let error = serde_json::from_str::<BroadcastedDeployTransaction>(json).unwrap_err();
+                assert!(error.to_string().contains("index 1"), "{}", error);
+            }
+        }
+
+        mod signature {
+            use super::super::*;
+            use crate::starkhash;
+
+            #[test]
+            fn valid() {
+                let signature = serde_json::from_str::<Signature>(r#"["0x1", "0x2"]"#).unwrap();
+                assert_eq!(
+                    signature,
+                    Signature(vec![
+                        TransactionSignatureElem(starkhash!("01")),
+                        TransactionSignatureElem(starkhash!("02")),
+                    ])
+                );
+                signature.validate().unwrap();
+            }
+
+            #[test]
+            fn empty() {
+                let signature = serde_json::from_str::<Signature>("[]").unwrap();
+                assert_eq!(signature, Signature::default());
+                assert_eq!(signature.validate(), Err(SignatureError::Empty));
+            }
+
+            #[test]
+            fn odd_length() {
+                let signature = serde_json::from_str::<Signature>(r#"["0x1"]"#).unwrap();
+                assert_eq!(
+                    signature,
+                    Signature(vec![TransactionSignatureElem(starkhash!("01"))])
+                );
+                assert_eq!(signature.validate(), Err(SignatureError::OddLength(1)));
+            }
+
+            #[test]
+            fn element_is_not_a_field_element() {
+                let json = format!(r#"["0x1", "0x{}"]"#, "f".repeat(64));
+                serde_json::from_str::<Signature>(&json).unwrap_err();
+            }
         }
     }
 }
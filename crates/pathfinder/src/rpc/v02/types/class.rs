@@ -1,4 +1,6 @@
+use crate::core::{ClassHash, EntryPoint, EventKey, TransactionVersion};
 use crate::rpc::serde::U64AsHexStr;
+use crate::sequencer::request::add_transaction::ContractDefinition;
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
@@ -46,6 +48,436 @@ impl ContractClass {
             abi,
         })
     }
+
+    /// Like [from_definition_bytes](Self::from_definition_bytes), but fails with
+    /// [ContractClassError::InvalidAbi] instead of discarding a malformed ABI.
+    ///
+    /// Meant for classes which are yet to be declared. Declared classes may well have
+    /// nonsense ABIs, which must not prevent serving them.
+    pub fn from_definition_bytes_checked(data: &[u8]) -> Result<ContractClass, ContractClassError> {
+        #[derive(Deserialize)]
+        struct Definition {
+            #[serde(default)]
+            abi: Option<serde_json::Value>,
+        }
+
+        let definition =
+            serde_json::from_slice::<Definition>(data).context("Parsing class definition")?;
+        check_abi(definition.abi.as_ref())?;
+
+        Ok(Self::from_definition_bytes(data)?)
+    }
+
+    /// Performs local sanity checks on the class before it gets submitted to the sequencer.
+    ///
+    /// Exceeding a limit is only logged as a warning unless [ValidationOptions::strict] is set.
+    pub fn validate(&self, options: &ValidationOptions) -> Result<(), ContractClassError> {
+        if let Some(limit) = options.max_program_felts {
+            let felts = self
+                .program_felt_count()
+                .context("Counting program felts")?;
+            if felts > limit {
+                if options.strict {
+                    return Err(ContractClassError::ProgramTooLarge { felts, limit });
+                }
+                tracing::warn!(%felts, %limit, "Program exceeds the felt-count limit");
+            }
+        }
+
+        if options.check_abi_entry_points {
+            self.check_abi_entry_points()?;
+        }
+
+        if options.check_program_schema {
+            self.check_program_schema()?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the decompressed program against [CAIRO0_PROGRAM_SCHEMA].
+    fn check_program_schema(&self) -> Result<(), ContractClassError> {
+        let program = self
+            .decompressed_program::<serde_json::Value>()
+            .context("Decompressing program")?;
+        let schema = serde_json::from_str::<serde_json::Value>(CAIRO0_PROGRAM_SCHEMA)
+            .expect("Embedded schema is valid JSON");
+
+        match schema_violation(&program, &schema, "") {
+            Some((path, detail)) => Err(ContractClassError::InvalidProgram { path, detail }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that every external entry point has a matching ABI function and vice versa.
+    ///
+    /// Classes without an ABI are not checked, as there is nothing to compare against.
+    fn check_abi_entry_points(&self) -> Result<(), ContractClassError> {
+        let abi = match &self.abi {
+            Some(abi) => abi,
+            None => return Ok(()),
+        };
+
+        let functions = abi
+            .iter()
+            .filter_map(|entry| match entry {
+                ContractAbiEntry::Function(function)
+                    if function.r#type == FunctionAbiType::Function =>
+                {
+                    Some(function.name.as_str())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let selectors = functions
+            .iter()
+            .map(|name| EntryPoint::hashed(name.as_bytes()).0)
+            .collect::<Vec<_>>();
+
+        let missing_from_abi = self
+            .entry_points_by_type
+            .external
+            .iter()
+            .map(|entry_point| entry_point.selector)
+            // The `__default__` entry point has no ABI function.
+            .filter(|selector| *selector != StarkHash::ZERO && !selectors.contains(selector))
+            .collect::<Vec<_>>();
+        let missing_from_entry_points = functions
+            .iter()
+            .zip(&selectors)
+            .filter(|(_, selector)| {
+                !self
+                    .entry_points_by_type
+                    .external
+                    .iter()
+                    .any(|entry_point| entry_point.selector == **selector)
+            })
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+
+        if missing_from_abi.is_empty() && missing_from_entry_points.is_empty() {
+            Ok(())
+        } else {
+            Err(ContractClassError::AbiEntryPointMismatch {
+                missing_from_abi,
+                missing_from_entry_points,
+            })
+        }
+    }
+
+    /// The parameters of the constructor according to the ABI, e.g. for building a deploy form.
+    ///
+    /// [None] if the ABI has no constructor, which includes classes without an ABI. A
+    /// constructor without inputs has no parameters.
+    pub fn constructor_signature(&self) -> Option<&[TypedParameter]> {
+        self.abi.as_ref()?.iter().find_map(|entry| match entry {
+            ContractAbiEntry::Function(function)
+                if function.r#type == FunctionAbiType::Constructor =>
+            {
+                Some(function.inputs.as_deref().unwrap_or_default())
+            }
+            _ => None,
+        })
+    }
+
+    /// The keys identifying the events of the ABI in emitted events, i.e. the starknet-keccak
+    /// of each event's name, in the order of the ABI. E.g. for registering event filters before
+    /// the contract is deployed.
+    ///
+    /// Empty for classes without an ABI.
+    pub fn event_keys(&self) -> Vec<EventKey> {
+        self.abi
+            .iter()
+            .flatten()
+            .filter_map(|entry| match entry {
+                ContractAbiEntry::Event(event) => {
+                    Some(EventKey(EntryPoint::hashed(event.name.as_bytes()).0))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Computes the hash of the class, as the sequencer does once it has been submitted.
+    pub fn class_hash(&self) -> anyhow::Result<ClassHash> {
+        let definition = ContractDefinition::try_from(self.clone())
+            .context("Converting to contract definition")?;
+
+        definition.class_hash()
+    }
+
+    /// Only Cairo 0 classes can be represented by [ContractClass].
+    pub fn kind(&self) -> ClassKind {
+        ClassKind::Cairo0
+    }
+
+    /// Compares the classes while ignoring differences in their encoding, such as the
+    /// compression of the program and the order of entry points and ABI entries.
+    ///
+    /// A missing ABI is equal to an empty one.
+    pub fn semantically_eq(&self, other: &ContractClass) -> bool {
+        let programs_eq = self.program == other.program
+            || match (
+                self.decompressed_program::<serde_json::Value>(),
+                other.decompressed_program::<serde_json::Value>(),
+            ) {
+                (Ok(program), Ok(other)) => program == other,
+                _ => false,
+            };
+
+        programs_eq
+            && self.entry_points_by_type.sorted() == other.entry_points_by_type.sorted()
+            && normalized_abi(&self.abi) == normalized_abi(&other.abi)
+    }
+
+    /// Counts the entries of the program's bytecode.
+    fn program_felt_count(&self) -> anyhow::Result<usize> {
+        #[derive(Deserialize)]
+        struct Program {
+            data: Vec<serde::de::IgnoredAny>,
+        }
+
+        let program = self.decompressed_program::<Program>()?;
+
+        Ok(program.data.len())
+    }
+
+    fn decompressed_program<T: serde::de::DeserializeOwned>(&self) -> anyhow::Result<T> {
+        let compressed_program = base64::decode(&self.program).context("Decoding program")?;
+        let gzip_decoder = flate2::read::GzDecoder::new(compressed_program.as_slice());
+        serde_json::from_reader::<_, T>(gzip_decoder).context("Parsing program")
+    }
+}
+
+/// The entries of `abi` in a canonical order.
+fn normalized_abi(abi: &Option<Vec<ContractAbiEntry>>) -> Vec<String> {
+    let mut entries = abi
+        .iter()
+        .flatten()
+        .map(|entry| serde_json::to_string(entry).expect("ABI entries serialize to JSON"))
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+/// Checks that `abi`, the ABI of a class as JSON, is an array of valid ABI entries. A missing
+/// ABI is valid.
+pub(crate) fn check_abi(abi: Option<&serde_json::Value>) -> Result<(), ContractClassError> {
+    match abi {
+        None => Ok(()),
+        Some(serde_json::Value::Array(entries)) => {
+            for (index, entry) in entries.iter().enumerate() {
+                if let Err(e) = ContractAbiEntry::deserialize(entry) {
+                    return Err(ContractClassError::InvalidAbi {
+                        detail: format!("entry {index} is not a valid ABI entry: {e}"),
+                    });
+                }
+            }
+            Ok(())
+        }
+        Some(_) => Err(ContractClassError::InvalidAbi {
+            detail: "expected an array of entries".to_owned(),
+        }),
+    }
+}
+
+/// JSON schema of the Cairo 0 programs, which [ContractClass::validate] checks against if
+/// [ValidationOptions::check_program_schema] is set.
+const CAIRO0_PROGRAM_SCHEMA: &str = include_str!("cairo0_program.schema.json");
+
+/// Checks `value` at the JSON pointer `path` against `schema`, returning the path to and a
+/// description of the first violation.
+///
+/// Only the parts of JSON schema used by [CAIRO0_PROGRAM_SCHEMA] are supported, i.e. `type`,
+/// `required`, `properties`, `additionalProperties` and `items`.
+fn schema_violation(
+    value: &serde_json::Value,
+    schema: &serde_json::Value,
+    path: &str,
+) -> Option<(String, String)> {
+    use serde_json::Value;
+
+    let actual = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_u64() || n.is_i64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    let expected = match schema.get("type") {
+        Some(Value::String(expected)) => vec![expected.as_str()],
+        Some(Value::Array(expected)) => expected.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    let matches =
+        |expected: &&str| *expected == actual || (*expected == "number" && actual == "integer");
+    if !expected.is_empty() && !expected.iter().any(matches) {
+        return Some((
+            path.to_owned(),
+            format!("expected {}, got {}", expected.join(" or "), actual),
+        ));
+    }
+
+    match value {
+        Value::Object(object) => {
+            let required = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str);
+            for key in required {
+                if !object.contains_key(key) {
+                    return Some((
+                        pointer(path, key),
+                        "required property is missing".to_owned(),
+                    ));
+                }
+            }
+
+            for (key, value) in object {
+                let property_schema = schema
+                    .get("properties")
+                    .and_then(|properties| properties.get(key))
+                    .or_else(|| schema.get("additionalProperties"));
+                if let Some(property_schema) = property_schema {
+                    let violation = schema_violation(value, property_schema, &pointer(path, key));
+                    if violation.is_some() {
+                        return violation;
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    let violation =
+                        schema_violation(item, item_schema, &pointer(path, &index.to_string()));
+                    if violation.is_some() {
+                        return violation;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    None
+}
+
+/// Appends `token` to the JSON pointer `path`.
+fn pointer(path: &str, token: &str) -> String {
+    format!("{}/{}", path, token.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Options for [ContractClass::validate].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// Maximum number of felts in the program's bytecode, unlimited if not set.
+    pub max_program_felts: Option<usize>,
+    /// Fail validation instead of warning when a limit is exceeded.
+    pub strict: bool,
+    /// Fail validation if the ABI functions and the external entry points do not match.
+    pub check_abi_entry_points: bool,
+    /// Fail validation if the program does not match the embedded Cairo 0 program schema.
+    /// Costly for large programs, since the whole program is decompressed and parsed.
+    pub check_program_schema: bool,
+}
+
+/// The Cairo version a class was compiled with, which determines the transaction versions
+/// it can be submitted with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClassKind {
+    Cairo0,
+    /// Cairo 1 classes, which are declared as Sierra.
+    Sierra,
+}
+
+impl std::fmt::Display for ClassKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassKind::Cairo0 => f.write_str("Cairo 0"),
+            ClassKind::Sierra => f.write_str("Sierra"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "{class_version} classes cannot be submitted with transaction version {:#x}",
+    .tx_version.without_query_version()
+)]
+pub struct IncompatibleClassVersion {
+    pub class_version: ClassKind,
+    pub tx_version: TransactionVersion,
+}
+
+impl ClassKind {
+    /// Checks that a `DECLARE` of this kind of class may use `version`.
+    pub fn check_declare_version(
+        self,
+        version: TransactionVersion,
+    ) -> Result<(), IncompatibleClassVersion> {
+        let compatible: &[u128] = match self {
+            ClassKind::Cairo0 => &[0, 1],
+            ClassKind::Sierra => &[2],
+        };
+        self.check(compatible, version)
+    }
+
+    /// Checks that a `DEPLOY` of this kind of class may use `version`.
+    ///
+    /// Sierra classes cannot be deployed using `DEPLOY` transactions at all.
+    pub fn check_deploy_version(
+        self,
+        version: TransactionVersion,
+    ) -> Result<(), IncompatibleClassVersion> {
+        let compatible: &[u128] = match self {
+            ClassKind::Cairo0 => &[0],
+            ClassKind::Sierra => &[],
+        };
+        self.check(compatible, version)
+    }
+
+    fn check(
+        self,
+        compatible: &[u128],
+        version: TransactionVersion,
+    ) -> Result<(), IncompatibleClassVersion> {
+        if compatible.contains(&version.without_query_version()) {
+            Ok(())
+        } else {
+            Err(IncompatibleClassVersion {
+                class_version: self,
+                tx_version: version,
+            })
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ContractClassError {
+    #[error("Program has {felts} felts which exceeds the limit of {limit}")]
+    ProgramTooLarge { felts: usize, limit: usize },
+    #[error("ABI functions do not match the external entry points")]
+    AbiEntryPointMismatch {
+        /// Selectors of external entry points without an ABI function.
+        missing_from_abi: Vec<StarkHash>,
+        /// Names of ABI functions without an external entry point.
+        missing_from_entry_points: Vec<String>,
+    },
+    #[error("Invalid ABI: {detail}")]
+    InvalidAbi { detail: String },
+    #[error("Program does not match the Cairo 0 program schema at {path:?}: {detail}")]
+    InvalidProgram {
+        /// JSON pointer to the violating part of the program.
+        path: String,
+        detail: String,
+    },
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -64,6 +496,23 @@ pub struct ContractEntryPoints {
     pub l1_handler: Vec<ContractEntryPoint>,
 }
 
+impl ContractEntryPoints {
+    /// The entry points of each type sorted by selector and offset.
+    fn sorted(&self) -> ContractEntryPoints {
+        let sorted = |entry_points: &[ContractEntryPoint]| {
+            let mut entry_points = entry_points.to_vec();
+            entry_points.sort_by_key(|entry_point| (entry_point.selector, entry_point.offset));
+            entry_points
+        };
+
+        ContractEntryPoints {
+            constructor: sorted(&self.constructor),
+            external: sorted(&self.external),
+            l1_handler: sorted(&self.l1_handler),
+        }
+    }
+}
+
 #[serde_with::serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
@@ -179,3 +628,594 @@ pub struct TypedParameter {
     name: String,
     r#type: String,
 }
+
+impl TypedParameter {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The Cairo type of the parameter, e.g. `felt` or `Uint256`.
+    pub fn r#type(&self) -> &str {
+        &self.r#type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod validate {
+        use super::*;
+
+        /// A class whose program consists of three felts.
+        fn class() -> ContractClass {
+            let definition = br#"{
+                "program": {"data": ["0x1", "0x2", "0x3"]},
+                "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []}
+            }"#;
+            ContractClass::from_definition_bytes(definition).unwrap()
+        }
+
+        #[test]
+        fn unlimited() {
+            class().validate(&ValidationOptions::default()).unwrap();
+        }
+
+        #[test]
+        fn at_limit() {
+            let options = ValidationOptions {
+                max_program_felts: Some(3),
+                strict: true,
+                check_abi_entry_points: false,
+                check_program_schema: false,
+            };
+            class().validate(&options).unwrap();
+        }
+
+        #[test]
+        fn over_limit_warns() {
+            let options = ValidationOptions {
+                max_program_felts: Some(2),
+                strict: false,
+                check_abi_entry_points: false,
+                check_program_schema: false,
+            };
+            class().validate(&options).unwrap();
+        }
+
+        #[test]
+        fn over_limit_strict() {
+            let options = ValidationOptions {
+                max_program_felts: Some(2),
+                strict: true,
+                check_abi_entry_points: false,
+                check_program_schema: false,
+            };
+            let error = class().validate(&options).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "Program has 3 felts which exceeds the limit of 2"
+            );
+        }
+
+        mod abi_entry_points {
+            use super::*;
+
+            /// A class whose ABI declares `functions` and which has external entry points
+            /// for `entry_points`.
+            fn class(functions: &[&str], entry_points: &[&str]) -> ContractClass {
+                let abi = functions
+                    .iter()
+                    .map(|name| {
+                        serde_json::json!({
+                            "type": "function",
+                            "name": name,
+                            "inputs": [],
+                            "outputs": [],
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let external = entry_points
+                    .iter()
+                    .map(|name| {
+                        serde_json::json!({
+                            "selector": EntryPoint::hashed(name.as_bytes()).0,
+                            "offset": "0x0",
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let definition = serde_json::json!({
+                    "abi": abi,
+                    "program": {"data": []},
+                    "entry_points_by_type": {
+                        "CONSTRUCTOR": [],
+                        "EXTERNAL": external,
+                        "L1_HANDLER": [],
+                    },
+                });
+                ContractClass::from_definition_bytes(&serde_json::to_vec(&definition).unwrap())
+                    .unwrap()
+            }
+
+            fn options() -> ValidationOptions {
+                ValidationOptions {
+                    check_abi_entry_points: true,
+                    ..Default::default()
+                }
+            }
+
+            #[test]
+            fn matching() {
+                class(
+                    &["get_balance", "increase_balance"],
+                    &["increase_balance", "get_balance"],
+                )
+                .validate(&options())
+                .unwrap();
+            }
+
+            #[test]
+            fn mismatched() {
+                let error = class(
+                    &["get_balance", "increase_balance"],
+                    &["get_balance", "withdraw"],
+                )
+                .validate(&options())
+                .unwrap_err();
+
+                assert_matches::assert_matches!(
+                    error,
+                    ContractClassError::AbiEntryPointMismatch { missing_from_abi, missing_from_entry_points } => {
+                        assert_eq!(missing_from_abi, vec![EntryPoint::hashed(b"withdraw").0]);
+                        assert_eq!(missing_from_entry_points, vec!["increase_balance".to_owned()]);
+                    }
+                );
+            }
+
+            #[test]
+            fn not_checked_by_default() {
+                class(&["get_balance"], &["withdraw"])
+                    .validate(&ValidationOptions::default())
+                    .unwrap();
+            }
+        }
+    }
+
+    mod constructor_signature {
+        use super::*;
+
+        fn class(abi: serde_json::Value) -> ContractClass {
+            let definition = serde_json::json!({
+                "abi": abi,
+                "program": {"data": []},
+                "entry_points_by_type": {
+                    "CONSTRUCTOR": [],
+                    "EXTERNAL": [],
+                    "L1_HANDLER": [],
+                },
+            });
+            ContractClass::from_definition_bytes(&serde_json::to_vec(&definition).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn with_constructor() {
+            let class = class(serde_json::json!([
+                {
+                    "type": "function",
+                    "name": "get_balance",
+                    "inputs": [],
+                    "outputs": [{"name": "res", "type": "felt"}],
+                },
+                {
+                    "type": "constructor",
+                    "name": "constructor",
+                    "inputs": [
+                        {"name": "owner", "type": "felt"},
+                        {"name": "initial_supply", "type": "Uint256"},
+                    ],
+                    "outputs": [],
+                },
+            ]));
+
+            let parameters = class
+                .constructor_signature()
+                .unwrap()
+                .iter()
+                .map(|parameter| (parameter.name(), parameter.r#type()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                parameters,
+                vec![("owner", "felt"), ("initial_supply", "Uint256")]
+            );
+        }
+
+        #[test]
+        fn without_constructor() {
+            let class = class(serde_json::json!([{
+                "type": "function",
+                "name": "get_balance",
+                "inputs": [],
+                "outputs": [{"name": "res", "type": "felt"}],
+            }]));
+
+            assert_eq!(class.constructor_signature(), None);
+        }
+
+        #[test]
+        fn without_abi() {
+            let class = ContractClass {
+                abi: None,
+                ..class(serde_json::json!([]))
+            };
+
+            assert_eq!(class.constructor_signature(), None);
+        }
+    }
+
+    mod event_keys {
+        use super::*;
+        use crate::starkhash;
+
+        fn class(abi: serde_json::Value) -> ContractClass {
+            let definition = serde_json::json!({
+                "abi": abi,
+                "program": {"data": []},
+                "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []},
+            });
+            ContractClass::from_definition_bytes(&serde_json::to_vec(&definition).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn are_the_selectors_of_the_event_names() {
+            let class = class(serde_json::json!([
+                {
+                    "type": "event",
+                    "name": "Transfer",
+                    "keys": [],
+                    "data": [
+                        {"name": "from_", "type": "felt"},
+                        {"name": "to", "type": "felt"},
+                        {"name": "value", "type": "Uint256"},
+                    ],
+                },
+                {
+                    "type": "function",
+                    "name": "transfer",
+                    "inputs": [
+                        {"name": "recipient", "type": "felt"},
+                        {"name": "amount", "type": "Uint256"},
+                    ],
+                    "outputs": [],
+                },
+                {
+                    "type": "event",
+                    "name": "Approval",
+                    "keys": [],
+                    "data": [
+                        {"name": "owner", "type": "felt"},
+                        {"name": "spender", "type": "felt"},
+                        {"name": "value", "type": "Uint256"},
+                    ],
+                },
+            ]));
+
+            assert_eq!(
+                class.event_keys(),
+                vec![
+                    EventKey(starkhash!(
+                        "0099cd8bde557814842a3121e8ddfd433a539b8c9f14bf31ebf108d12e6196e9"
+                    )),
+                    EventKey(starkhash!(
+                        "0134692b230b9e1ffa39098904722134159652b09c5bc41d88d6698779d228ff"
+                    )),
+                ]
+            );
+        }
+
+        #[test]
+        fn without_abi() {
+            let class = ContractClass {
+                abi: None,
+                ..class(serde_json::json!([]))
+            };
+
+            assert_eq!(class.event_keys(), vec![]);
+        }
+    }
+
+    mod program_schema {
+        use super::*;
+
+        fn options() -> ValidationOptions {
+            ValidationOptions {
+                check_program_schema: true,
+                ..Default::default()
+            }
+        }
+
+        fn class(program: serde_json::Value) -> ContractClass {
+            let definition = serde_json::json!({
+                "program": program,
+                "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []},
+            });
+            ContractClass::from_definition_bytes(&serde_json::to_vec(&definition).unwrap()).unwrap()
+        }
+
+        fn program() -> serde_json::Value {
+            serde_json::json!({
+                "builtins": ["pedersen"],
+                "data": ["0x1", "0x2"],
+                "debug_info": null,
+                "hints": {"0": []},
+                "identifiers": {},
+                "main_scope": "__main__",
+                "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+                "reference_manager": {"references": []},
+            })
+        }
+
+        #[test]
+        fn valid() {
+            class(program()).validate(&options()).unwrap();
+        }
+
+        #[test]
+        fn fixture_is_valid() {
+            let definition = zstd::decode_all(std::io::Cursor::new(include_bytes!(
+                "../../../../fixtures/contract_definition.json.zst"
+            )))
+            .unwrap();
+            ContractClass::from_definition_bytes(&definition)
+                .unwrap()
+                .validate(&options())
+                .unwrap();
+        }
+
+        #[test]
+        fn missing_required_field() {
+            let mut program = program();
+            program["reference_manager"]
+                .as_object_mut()
+                .unwrap()
+                .remove("references");
+
+            let error = class(program).validate(&options()).unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                ContractClassError::InvalidProgram { path, detail } => {
+                    assert_eq!(path, "/reference_manager/references");
+                    assert_eq!(detail, "required property is missing");
+                }
+            );
+        }
+
+        #[test]
+        fn wrong_type() {
+            let mut program = program();
+            program["data"][1] = serde_json::json!(2);
+
+            let error = class(program).validate(&options()).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                r#"Program does not match the Cairo 0 program schema at "/data/1": expected string, got integer"#
+            );
+        }
+
+        #[test]
+        fn not_checked_by_default() {
+            class(serde_json::json!({"data": []}))
+                .validate(&ValidationOptions::default())
+                .unwrap();
+        }
+    }
+
+    mod from_definition_bytes_checked {
+        use super::*;
+
+        fn definition(abi: serde_json::Value) -> Vec<u8> {
+            serde_json::to_vec(&serde_json::json!({
+                "abi": abi,
+                "program": {"data": []},
+                "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []},
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn valid_abi() {
+            let abi = serde_json::json!([{
+                "type": "function",
+                "name": "get_balance",
+                "inputs": [],
+                "outputs": [],
+            }]);
+            let class = ContractClass::from_definition_bytes_checked(&definition(abi)).unwrap();
+            assert_eq!(class.abi.unwrap().len(), 1);
+        }
+
+        #[test]
+        fn malformed_entry() {
+            let abi = serde_json::json!([{"type": "function", "name": "get_balance"}]);
+            let data = definition(abi);
+
+            let error = ContractClass::from_definition_bytes_checked(&data).unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                ContractClassError::InvalidAbi { detail } => assert!(detail.starts_with("entry 0 "), "{}", detail)
+            );
+            // The unchecked variant discards the ABI instead.
+            let class = ContractClass::from_definition_bytes(&data).unwrap();
+            assert_eq!(class.abi, None);
+        }
+
+        #[test]
+        fn not_an_array() {
+            let error =
+                ContractClass::from_definition_bytes_checked(&definition(serde_json::json!({})))
+                    .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "Invalid ABI: expected an array of entries"
+            );
+        }
+    }
+
+    mod semantically_eq {
+        use super::*;
+
+        fn class(definition: serde_json::Value) -> ContractClass {
+            ContractClass::from_definition_bytes(&serde_json::to_vec(&definition).unwrap()).unwrap()
+        }
+
+        fn class_with_external(selectors: &[&str]) -> ContractClass {
+            let external = selectors
+                .iter()
+                .enumerate()
+                .map(|(offset, selector)| {
+                    serde_json::json!({"offset": format!("{offset:#x}"), "selector": selector})
+                })
+                .collect::<Vec<_>>();
+            class(serde_json::json!({
+                "program": {"data": ["0x1"]},
+                "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": external, "L1_HANDLER": []},
+            }))
+        }
+
+        #[test]
+        fn different_encodings() {
+            let abi = serde_json::json!([
+                {"type": "function", "name": "get_balance", "inputs": [], "outputs": []},
+                {"type": "event", "name": "Transfer", "keys": [], "data": []},
+            ]);
+            let entry_points = serde_json::json!({
+                "CONSTRUCTOR": [],
+                "EXTERNAL": [
+                    {"offset": "0x1", "selector": "0x1"},
+                    {"offset": "0x2", "selector": "0x2"},
+                ],
+                "L1_HANDLER": [],
+            });
+            let original = class(serde_json::json!({
+                "abi": abi,
+                "program": {"builtins": ["pedersen"], "data": ["0x1", "0x2"]},
+                "entry_points_by_type": entry_points,
+            }));
+
+            // The same program compressed differently.
+            let mut gzip_encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+            serde_json::to_writer(
+                &mut gzip_encoder,
+                &serde_json::json!({"data": ["0x1", "0x2"], "builtins": ["pedersen"]}),
+            )
+            .unwrap();
+            let program = base64::encode(gzip_encoder.finish().unwrap());
+
+            let mut abi = original.abi.clone().unwrap();
+            abi.reverse();
+            let mut entry_points_by_type = original.entry_points_by_type.clone();
+            entry_points_by_type.external.reverse();
+            let reencoded = ContractClass {
+                program,
+                entry_points_by_type,
+                abi: Some(abi),
+            };
+
+            assert_ne!(reencoded, original);
+            assert!(reencoded.semantically_eq(&original));
+            assert!(original.semantically_eq(&reencoded));
+        }
+
+        #[test]
+        fn different_programs() {
+            let one = class(serde_json::json!({
+                "program": {"data": ["0x1"]},
+                "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []},
+            }));
+            let two = class(serde_json::json!({
+                "program": {"data": ["0x2"]},
+                "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []},
+            }));
+            assert!(!one.semantically_eq(&two));
+
+            let undecodable = ContractClass {
+                program: "not base64".to_owned(),
+                ..one.clone()
+            };
+            assert!(!one.semantically_eq(&undecodable));
+        }
+
+        #[test]
+        fn different_entry_points() {
+            let one = class_with_external(&["0x1", "0x2"]);
+            // The offsets are swapped, which is not just a reordering.
+            let other = class_with_external(&["0x2", "0x1"]);
+
+            assert!(!one.semantically_eq(&other));
+        }
+
+        #[test]
+        fn missing_abi_equals_empty_abi() {
+            let without_abi = class_with_external(&[]);
+            let with_empty_abi = ContractClass {
+                abi: Some(vec![]),
+                ..without_abi.clone()
+            };
+
+            assert!(without_abi.semantically_eq(&with_empty_abi));
+        }
+    }
+
+    mod class_kind {
+        use super::*;
+        use web3::types::H256;
+
+        fn version(version: u64) -> TransactionVersion {
+            TransactionVersion(H256::from_low_u64_be(version))
+        }
+
+        #[test]
+        fn cairo0_declare() {
+            for compatible in [0, 1] {
+                ClassKind::Cairo0
+                    .check_declare_version(version(compatible))
+                    .unwrap();
+            }
+            ClassKind::Cairo0
+                .check_declare_version(TransactionVersion::ONE_WITH_QUERY_VERSION)
+                .unwrap();
+            assert_eq!(
+                ClassKind::Cairo0.check_declare_version(version(2)),
+                Err(IncompatibleClassVersion {
+                    class_version: ClassKind::Cairo0,
+                    tx_version: version(2),
+                })
+            );
+        }
+
+        #[test]
+        fn sierra_declare() {
+            ClassKind::Sierra.check_declare_version(version(2)).unwrap();
+            for incompatible in [0, 1, 3] {
+                assert_eq!(
+                    ClassKind::Sierra.check_declare_version(version(incompatible)),
+                    Err(IncompatibleClassVersion {
+                        class_version: ClassKind::Sierra,
+                        tx_version: version(incompatible),
+                    })
+                );
+            }
+        }
+
+        #[test]
+        fn cairo0_deploy() {
+            ClassKind::Cairo0.check_deploy_version(version(0)).unwrap();
+            assert!(ClassKind::Cairo0.check_deploy_version(version(1)).is_err());
+        }
+
+        #[test]
+        fn sierra_deploy() {
+            assert!(ClassKind::Sierra.check_deploy_version(version(0)).is_err());
+        }
+    }
+}
@@ -1,23 +1,51 @@
 //! StarkNet L2 sequencer client.
+mod affinity;
 mod builder;
+mod cache;
+pub mod calibration;
+mod chain_check;
+mod config;
+pub mod deploy;
 pub mod error;
 mod metrics;
+mod middleware;
+pub mod nonce;
+pub mod replay;
 pub mod reply;
 pub mod request;
+mod submission_queue;
+
+pub use self::affinity::SessionAffinity;
+pub use self::config::{
+    ConfigSummary, Http2Config, ProxyConfig, RetryPolicies, RetryPolicy, SequencerConfig,
+};
+pub use self::middleware::RequestMiddleware;
+pub use self::submission_queue::SubmissionPriority;
 
 use self::request::add_transaction::ContractDefinition;
+use crate::state::transaction_hash::{compute_contract_address, ZERO_DEPLOYER_ADDRESS};
 use crate::{
     consts::INTEGRATION_GENESIS_HASH,
     core::{
-        BlockId, CallParam, Chain, ClassHash, ConstructorParam, ContractAddress,
-        ContractAddressSalt, EntryPoint, Fee, StarknetTransactionHash, StorageAddress,
-        StorageValue, TransactionNonce, TransactionSignatureElem, TransactionVersion,
+        BlockId, CallParam, CasmHash, Chain, ClassHash, ConstructorParam, ContractAddress,
+        ContractAddressSalt, ContractNonce, EntryPoint, Fee, StarknetBlockHash,
+        StarknetBlockNumber, StarknetTransactionHash, StorageAddress, StorageValue,
+        TransactionNonce, TransactionSignatureElem, TransactionVersion,
     },
     rpc::v01::types::BlockHashOrTag,
     sequencer::error::SequencerError,
 };
 use reqwest::Url;
-use std::{fmt::Debug, result::Result, time::Duration};
+use std::{fmt::Debug, result::Result, sync::Arc};
+
+/// Maximum number of concurrent queries of [ClientApi::transaction_statuses].
+pub const TRANSACTION_STATUS_CONCURRENCY: usize = 16;
+
+/// Delay between the block status queries of [ClientApi::await_block_finality].
+#[cfg(not(test))]
+const BLOCK_FINALITY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+#[cfg(test)]
+const BLOCK_FINALITY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
@@ -31,9 +59,20 @@ pub trait ClientApi {
 
     async fn class_by_hash(&self, class_hash: ClassHash) -> Result<bytes::Bytes, SequencerError>;
 
+    /// Like [class_by_hash](ClientApi::class_by_hash), but parses the class while it is being
+    /// received and compresses its program on the fly, so that neither the whole response nor
+    /// the uncompressed program are held in memory. The class is not cached.
+    async fn class_definition(
+        &self,
+        class_hash: ClassHash,
+    ) -> Result<reply::ClassDefinition, SequencerError>;
+
+    /// Gets the class hash of `contract_address` at `block`. Querying [BlockId::Pending]
+    /// accounts for contracts which are not part of a block yet.
     async fn class_hash_at(
         &self,
         contract_address: ContractAddress,
+        block: BlockId,
     ) -> Result<ClassHash, SequencerError>;
 
     async fn storage(
@@ -43,16 +82,81 @@ pub trait ClientApi {
         block_hash: BlockHashOrTag,
     ) -> Result<StorageValue, SequencerError>;
 
+    /// Gets the nonce of `contract_address` at `block`. Querying [BlockId::Pending] accounts
+    /// for transactions which are not part of a block yet.
+    async fn nonce(
+        &self,
+        contract_address: ContractAddress,
+        block: BlockId,
+    ) -> Result<ContractNonce, SequencerError>;
+
+    /// Gets a transaction, along with the [block](reply::Transaction::block) containing it if
+    /// `include_block` is set, which saves fetching the block separately.
+    ///
+    /// Fails with [SequencerError::TransactionNotYetIndexed] like
+    /// [transaction_status](ClientApi::transaction_status).
     async fn transaction(
         &self,
         transaction_hash: StarknetTransactionHash,
+        include_block: bool,
     ) -> Result<reply::Transaction, SequencerError>;
 
+    /// Fails with [SequencerError::TransactionNotYetIndexed] if the gateway does not know the
+    /// transaction, as it does briefly after it was submitted.
     async fn transaction_status(
         &self,
         transaction_hash: StarknetTransactionHash,
     ) -> Result<reply::TransactionStatus, SequencerError>;
 
+    /// Queries the status of each of `transaction_hashes` concurrently, returning the
+    /// results in the same order.
+    async fn transaction_statuses(
+        &self,
+        transaction_hashes: &[StarknetTransactionHash],
+    ) -> Vec<Result<reply::TransactionStatus, SequencerError>>;
+
+    async fn transaction_receipt(
+        &self,
+        transaction_hash: StarknetTransactionHash,
+    ) -> Result<reply::TransactionReceipt, SequencerError>;
+
+    /// Polls the status of `block` until it reaches the `target` finality, the block is aborted
+    /// or `timeout` runs out, returning the finality the block reached by then.
+    ///
+    /// A block which has not been produced yet is waited for, its finality is
+    /// [Received](deploy::Finality::Received) until then.
+    async fn await_block_finality(
+        &self,
+        block: StarknetBlockNumber,
+        target: deploy::Finality,
+        timeout: std::time::Duration,
+    ) -> Result<deploy::Finality, SequencerError>;
+
+    /// Gets the execution trace of a transaction, e.g. to find out what the constructor of a
+    /// deployed contract did.
+    async fn transaction_trace(
+        &self,
+        transaction_hash: StarknetTransactionHash,
+    ) -> Result<reply::simulation::TransactionTrace, SequencerError>;
+
+    /// Executes `transaction` on top of `block` without submitting it.
+    async fn simulate_transaction(
+        &self,
+        transaction: request::add_transaction::AddTransaction,
+        block: BlockId,
+        block_context: request::BlockContext,
+    ) -> Result<reply::SimulatedTransaction, SequencerError>;
+
+    /// Estimates the fees of `transactions`, which are executed on top of `block` in order,
+    /// each on the state left by the previous ones.
+    ///
+    /// The estimates are in the order of `transactions`.
+    async fn estimate_fee_bulk(
+        &self,
+        transactions: Vec<request::add_transaction::AddTransaction>,
+        block: BlockId,
+    ) -> Result<Vec<reply::FeeEstimate>, SequencerError>;
+
     async fn state_update(&self, block: BlockId) -> Result<reply::StateUpdate, SequencerError>;
 
     async fn eth_contract_addresses(&self) -> Result<reply::EthContractAddresses, SequencerError>;
@@ -67,6 +171,7 @@ pub trait ClientApi {
         contract_address: ContractAddress,
         entry_point_selector: Option<EntryPoint>,
         calldata: Vec<CallParam>,
+        resource_bounds: Option<request::add_transaction::ResourceBounds>,
     ) -> Result<reply::add_transaction::InvokeResponse, SequencerError>;
 
     #[allow(clippy::too_many_arguments)]
@@ -78,6 +183,7 @@ pub trait ClientApi {
         nonce: TransactionNonce,
         contract_definition: ContractDefinition,
         sender_address: ContractAddress,
+        compiled_class_hash: Option<CasmHash>,
         token: Option<String>,
     ) -> Result<reply::add_transaction::DeclareResponse, SequencerError>;
 
@@ -88,6 +194,7 @@ pub trait ClientApi {
         constructor_calldata: Vec<ConstructorParam>,
         contract_definition: ContractDefinition,
         token: Option<String>,
+        timeout: Option<std::time::Duration>,
     ) -> Result<reply::add_transaction::DeployResponse, SequencerError>;
 
     #[allow(clippy::too_many_arguments)]
@@ -103,12 +210,87 @@ pub trait ClientApi {
     ) -> Result<reply::add_transaction::DeployAccountResponse, SequencerError>;
 }
 
+/// Header tagging each request with the logical operation it is part of, so that e.g. a
+/// mock sequencer can tell the requests of concurrent operations apart.
+///
+/// Only sent if [SequencerConfig::correlation_ids] is set. Requests sent from within
+/// [correlate] share the id of its operation, e.g. those of [deploy::deploy_and_await], any
+/// other request is its own operation with a unique id. See [Client::with_correlation_id] to
+/// choose the id.
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+tokio::task_local! {
+    /// The correlation id of the [correlate] operation the current task is performing.
+    static OPERATION_ID: reqwest::header::HeaderValue;
+}
+
+/// Runs `operation` as a single logical operation named `name`, so that all requests it sends
+/// from the current task share one unique [correlation id](CORRELATION_ID_HEADER).
+///
+/// Operations nested in another one are part of the outer operation.
+pub async fn correlate<F: std::future::Future>(name: &str, operation: F) -> F::Output {
+    if OPERATION_ID.try_with(|_| ()).is_ok() {
+        return operation.await;
+    }
+    OPERATION_ID
+        .scope(new_correlation_id(name), operation)
+        .await
+}
+
+/// The correlation id of the [correlate] operation the current task is performing, if any.
+fn operation_correlation_id() -> Option<reqwest::header::HeaderValue> {
+    OPERATION_ID.try_with(Clone::clone).ok()
+}
+
+/// An id unique to this process, e.g. `deploy-3`.
+fn new_correlation_id(name: &str) -> reqwest::header::HeaderValue {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let id = format!("{}-{}", name, NEXT.fetch_add(1, Ordering::Relaxed));
+    reqwest::header::HeaderValue::try_from(id).expect("Operation names are valid header values")
+}
+
+/// The gateway which served a request, see [served_by].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Gateway {
+    /// The gateway of [SequencerConfig::url].
+    Primary,
+    /// One of the [fallback gateways](SequencerConfig::with_fallback_url), by its URL.
+    Fallback(reqwest::Url),
+}
+
+tokio::task_local! {
+    /// The gateway which served the latest request of the current [served_by] scope.
+    static SERVING_GATEWAY: std::sync::Mutex<Option<Gateway>>;
+}
+
+/// Runs `operation` and returns its output along with the [Gateway] which served the latest
+/// successful request it sent from the current task, or [None] if no request succeeded.
+pub async fn served_by<F: std::future::Future>(operation: F) -> (F::Output, Option<Gateway>) {
+    SERVING_GATEWAY
+        .scope(std::sync::Mutex::new(None), async {
+            let output = operation.await;
+            let gateway = SERVING_GATEWAY.with(|gateway| gateway.lock().unwrap().take());
+            (output, gateway)
+        })
+        .await
+}
+
+/// Reports `gateway` to the enclosing [served_by] scope, if any.
+fn record_serving_gateway(gateway: Gateway) {
+    let _ = SERVING_GATEWAY.try_with(|serving| *serving.lock().unwrap() = Some(gateway));
+}
+
 /// StarkNet sequencer client using REST API.
 ///
-/// Retry is performed on __all__ types of errors __except for__
-/// [StarkNet specific errors](crate::sequencer::error::StarknetError).
+/// Failed requests are retried according to the [RetryPolicy] configured for their StarkNet
+/// error code or HTTP status code in [SequencerConfig::retry_policies]. Errors without a policy
+/// are retried if they are transient, e.g. transport errors, timeouts and maintenance, but not
+/// if they are [StarkNet specific errors](crate::sequencer::error::StarknetError).
+/// Submitting transactions is never retried.
 ///
-/// Initial backoff time is 30 seconds and saturates at 1 hour:
+/// [RetryPolicy::Exponential] backs off starting at 30 seconds and saturates at 1 hour:
 ///
 /// `backoff [secs] = min((2 ^ N) * 15, 3600) [secs]`
 ///
@@ -119,6 +301,22 @@ pub struct Client {
     inner: reqwest::Client,
     /// StarkNet sequencer URL.
     sequencer_url: Url,
+    /// Settings shared by all requests.
+    settings: Arc<builder::Settings>,
+    /// The non-sensitive parts of the configuration this client was created from.
+    summary: Arc<ConfigSummary>,
+    /// Classes fetched by [ClientApi::class_by_hash].
+    classes: Arc<cache::LruCache<ClassHash, bytes::Bytes>>,
+    /// Upper bound of the timeout overrides of [ClientApi::add_deploy_transaction].
+    max_timeout_override: std::time::Duration,
+    /// Sent with every request, superseding the default headers of the [SequencerConfig].
+    headers: reqwest::header::HeaderMap,
+    /// Verifies the gateway's network before writes, if [SequencerConfig::chain] is set.
+    chain_check: Option<Arc<chain_check::ChainCheck>>,
+    /// Limits concurrent submissions, if [SequencerConfig::max_concurrent_submissions] is set.
+    submissions: Option<Arc<submission_queue::SubmissionQueue>>,
+    /// The priority of this client's submissions while waiting for [Client::submissions].
+    priority: SubmissionPriority,
 }
 
 impl Client {
@@ -129,51 +327,307 @@ impl Client {
 
     /// Creates a new Sequencer client for the given chain.
     pub fn new(chain: Chain) -> reqwest::Result<Self> {
-        let url = match chain {
-            Chain::Mainnet => Url::parse("https://alpha-mainnet.starknet.io/").unwrap(),
-            Chain::Testnet => Url::parse("https://alpha4.starknet.io/").unwrap(),
-            Chain::Integration => Url::parse("https://external.integration.starknet.io").unwrap(),
-        };
-
-        Self::with_url(url)
+        Self::with_config(SequencerConfig::for_chain(chain))
     }
 
     /// Create a Sequencer client for the given [Url].
     pub fn with_url(url: Url) -> reqwest::Result<Self> {
+        Self::with_config(SequencerConfig::new(url))
+    }
+
+    /// Create a Sequencer client from the given [SequencerConfig].
+    pub fn with_config(config: SequencerConfig) -> reqwest::Result<Self> {
         metrics::register();
+        let summary = Arc::new(config.summary());
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(config.timeout)
+            // Sets `Accept-Encoding` and decompresses responses per their `Content-Encoding`,
+            // which saves a lot of bandwidth on classes.
+            .gzip(true)
+            .brotli(true)
+            // Gateways do not redirect, following one typically ends on an HTML page which
+            // fails to parse. See SequencerError::UnexpectedRedirect.
+            .redirect(reqwest::redirect::Policy::none())
+            // Times the DNS lookups of slow requests.
+            .dns_resolver(Arc::new(builder::TimedResolver))
+            .user_agent(crate::consts::USER_AGENT);
+        if let Some(proxy) = config.proxy {
+            builder = builder.proxy(reqwest::Proxy::custom(move |url| proxy.proxy_for(url)));
+        }
+        match config.http2 {
+            Some(http2) => {
+                if http2.prior_knowledge {
+                    builder = builder.http2_prior_knowledge();
+                }
+                if let Some(interval) = http2.keep_alive_interval {
+                    builder = builder
+                        .http2_keep_alive_interval(interval)
+                        .http2_keep_alive_while_idle(true);
+                }
+            }
+            None => builder = builder.http1_only(),
+        }
+        if let Some(version) = config.min_tls_version {
+            builder = builder.min_tls_version(version);
+        }
+
+        let fallbacks = config
+            .fallback_urls
+            .iter()
+            .map(|fallback| builder::Fallback::new(&config.url, fallback.clone()))
+            .collect();
 
         Ok(Self {
-            inner: reqwest::Client::builder()
-                .timeout(Duration::from_secs(120))
-                .user_agent(crate::consts::USER_AGENT)
-                .build()?,
-            sequencer_url: url,
+            inner: builder.build()?,
+            sequencer_url: config.url,
+            settings: Arc::new(builder::Settings {
+                fallbacks,
+                compression_threshold: config.request_compression_threshold,
+                alerts: config.error_alerts,
+                middleware: config.middleware,
+                affinity: config.session_affinity.map(affinity::Pin::new),
+                retry_policies: config.retry_policies,
+                default_headers: config.default_headers,
+                max_response_size: Some(config.max_response_size),
+                slow_request_threshold: config.slow_request_threshold,
+                offline: config.offline,
+                correlation_ids: config.correlation_ids,
+            }),
+            summary,
+            classes: Arc::new(cache::LruCache::new(config.class_cache_bytes)),
+            max_timeout_override: config.max_timeout_override,
+            headers: reqwest::header::HeaderMap::new(),
+            chain_check: config
+                .chain
+                .map(|chain| Arc::new(chain_check::ChainCheck::new(chain))),
+            submissions: config
+                .max_concurrent_submissions
+                .map(|capacity| Arc::new(submission_queue::SubmissionQueue::new(capacity))),
+            priority: SubmissionPriority::default(),
         })
     }
 
+    /// A client which sends `headers` with every request, superseding the
+    /// [default headers](SequencerConfig::default_headers) of the same name.
+    ///
+    /// Intended for single calls, e.g. `client.with_headers(auth).add_deploy_transaction(..)`.
+    /// Credentials like `Authorization` are redacted in logs.
+    pub fn with_headers(&self, mut headers: reqwest::header::HeaderMap) -> Self {
+        config::mark_credentials_sensitive(&mut headers);
+        Self {
+            headers,
+            ..self.clone()
+        }
+    }
+
+    /// A client which tags all its requests with the [correlation id](CORRELATION_ID_HEADER)
+    /// `id` instead of a generated one, if [SequencerConfig::correlation_ids] is set.
+    ///
+    /// Intended for tests which know the ids of their operations up front.
+    pub fn with_correlation_id(&self, id: reqwest::header::HeaderValue) -> Self {
+        let mut headers = self.headers.clone();
+        headers.insert(CORRELATION_ID_HEADER, id);
+        Self {
+            headers,
+            ..self.clone()
+        }
+    }
+
+    /// A client whose submissions wait with `priority` while the
+    /// [concurrent submissions](SequencerConfig::max_concurrent_submissions) are exhausted,
+    /// so that more urgent submissions are sent first.
+    pub fn with_priority(&self, priority: SubmissionPriority) -> Self {
+        Self {
+            priority,
+            ..self.clone()
+        }
+    }
+
+    /// The non-sensitive parts of the configuration this client was created from.
+    pub fn config_summary(&self) -> &ConfigSummary {
+        &self.summary
+    }
+
     fn request(&self) -> builder::Request<'_, builder::stage::Gateway> {
-        builder::Request::builder(&self.inner, self.sequencer_url.clone())
+        builder::Request::builder(
+            &self.inner,
+            self.sequencer_url.clone(),
+            &self.settings,
+            &self.headers,
+        )
     }
 
     /// Returns the [network chain](Chain) this client is operating on.
     pub async fn chain(&self) -> anyhow::Result<Chain> {
-        use crate::consts::{MAINNET_GENESIS_HASH, TESTNET_GENESIS_HASH};
+        self.chain_with_retry(Self::RETRY).await
+    }
+
+    /// Probes the gateway exactly once, failing fast if it is unreachable or if it
+    /// serves a different [network chain](Chain) than `expected`.
+    ///
+    /// Intended to be used on startup to catch misconfigured gateway URLs.
+    pub async fn probe(&self, expected: Chain) -> anyhow::Result<()> {
+        use anyhow::Context;
+
+        let chain = self
+            .chain_with_retry(builder::Retry::Disabled)
+            .await
+            .context("Probing the feeder gateway")?;
+
+        anyhow::ensure!(
+            chain == expected,
+            "Feeder gateway network mismatch. Gateway is on {chain} but {expected} was expected"
+        );
+
+        Ok(())
+    }
+
+    async fn chain_with_retry(&self, retry: builder::Retry) -> anyhow::Result<Chain> {
+        let genesis_hash = self.genesis_hash(retry).await?;
+
+        chain_of_genesis(genesis_hash)
+            .ok_or_else(|| anyhow::anyhow!("Unknown genesis block hash: {}", genesis_hash.0))
+    }
+
+    async fn genesis_hash(
+        &self,
+        retry: builder::Retry,
+    ) -> Result<StarknetBlockHash, SequencerError> {
         use crate::core::StarknetBlockNumber;
 
         // unwrap is safe as `block_hash` is always present for non-pending blocks.
         let genesis_hash = self
-            .block(StarknetBlockNumber::GENESIS.into())
+            .request()
+            .feeder_gateway()
+            .get_block()
+            .with_block(StarknetBlockNumber::GENESIS)
+            .with_retry(retry)
+            .get::<reply::MaybePendingBlock>()
             .await?
             .as_block()
             .expect("Genesis block should not be pending")
             .block_hash;
 
-        match genesis_hash {
-            testnet if testnet == TESTNET_GENESIS_HASH => Ok(Chain::Testnet),
-            mainnet if mainnet == MAINNET_GENESIS_HASH => Ok(Chain::Mainnet),
-            integration if integration == INTEGRATION_GENESIS_HASH => Ok(Chain::Integration),
-            other => Err(anyhow::anyhow!("Unknown genesis block hash: {}", other.0)),
+        Ok(genesis_hash)
+    }
+
+    /// Waits until this client may submit a transaction, if concurrent submissions are limited.
+    async fn submission_permit(&self) -> Option<submission_queue::SubmissionPermit> {
+        match &self.submissions {
+            Some(queue) => Some(queue.acquire(self.priority).await),
+            None => None,
+        }
+    }
+
+    /// Fails with [SequencerError::ChainIdMismatch] if the gateway does not serve the
+    /// [configured network](SequencerConfig::chain), so that transactions hashed for the
+    /// configured network are never submitted to another one.
+    ///
+    /// The gateway's network is fetched without retrying, since every other write waits for
+    /// the check. The write fails instead if the network cannot be fetched.
+    async fn verify_chain(&self) -> Result<(), SequencerError> {
+        match &self.chain_check {
+            Some(check) => {
+                check
+                    .verify(|| async {
+                        let genesis_hash = self.genesis_hash(builder::Retry::Disabled).await?;
+                        Ok(chain_of_genesis(genesis_hash))
+                    })
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the class of `class_hash` to `writer` as it is received, instead of holding the
+    /// whole class in memory like [ClientApi::class_by_hash]. Returns the number of bytes
+    /// written.
+    ///
+    /// Meant for archiving many classes, which is why streamed classes are not cached. A
+    /// transport error cuts the class short, leaving a partial class in `writer`. So does a
+    /// class exceeding the [max_response_size](SequencerConfig::max_response_size), which fails
+    /// with [SequencerError::ResponseTooLarge].
+    pub async fn get_class_to_writer(
+        &self,
+        class_hash: ClassHash,
+        writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+    ) -> anyhow::Result<u64> {
+        use anyhow::Context;
+        use tokio::io::AsyncWriteExt;
+
+        if let Some(class) = self.classes.get(&class_hash) {
+            writer.write_all(&class).await.context("Writing class")?;
+            return Ok(class.len() as u64);
+        }
+
+        let mut response = self
+            .request()
+            .feeder_gateway()
+            .get_class_by_hash()
+            .with_class_hash(class_hash)
+            .with_retry(Self::RETRY)
+            .get_response()
+            .await?;
+
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await.context("Receiving class")? {
+            written += chunk.len() as u64;
+            if let Some(limit) = self.settings.max_response_size {
+                if written > limit as u64 {
+                    return Err(SequencerError::ResponseTooLarge { limit }.into());
+                }
+            }
+            writer.write_all(&chunk).await.context("Writing class")?;
         }
+        writer.flush().await.context("Writing class")?;
+
+        Ok(written)
+    }
+
+    /// Same as [ClientApi::add_deploy_transaction] but also returns the raw response body
+    /// of the gateway.
+    ///
+    /// Only intended for debugging, which is why it requires the `debug-responses` feature.
+    /// The body is returned even if it does not match the typed response. Unlike
+    /// [ClientApi::add_deploy_transaction], a `DUPLICATED_TRANSACTION` error is returned as
+    /// is, since there is no response body to return in that case.
+    #[cfg(any(test, feature = "debug-responses"))]
+    #[tracing::instrument(skip(self, contract_definition))]
+    pub async fn add_deploy_transaction_debug(
+        &self,
+        version: TransactionVersion,
+        contract_address_salt: ContractAddressSalt,
+        constructor_calldata: Vec<ConstructorParam>,
+        contract_definition: ContractDefinition,
+        token: Option<String>,
+    ) -> Result<reply::WithRawBody<reply::add_transaction::DeployResponse>, SequencerError> {
+        use serde::Deserialize;
+
+        self.verify_chain().await?;
+        let _permit = self.submission_permit().await;
+
+        let req =
+            request::add_transaction::AddTransaction::Deploy(request::add_transaction::Deploy {
+                version,
+                contract_address_salt,
+                contract_definition,
+                constructor_calldata,
+            });
+
+        let body: serde_json::Value = self
+            .request()
+            .gateway()
+            .add_transaction()
+            .with_optional_token(token.as_deref())
+            .with_retry(builder::Retry::Disabled)
+            .post_with_json(&req)
+            .await?;
+
+        Ok(reply::WithRawBody {
+            output: reply::add_transaction::DeployResponse::deserialize(&body),
+            body,
+        })
     }
 }
 
@@ -208,12 +662,34 @@ impl ClientApi for Client {
     /// Gets class for a particular class hash.
     #[tracing::instrument(skip(self))]
     async fn class_by_hash(&self, class_hash: ClassHash) -> Result<bytes::Bytes, SequencerError> {
-        self.request()
+        if let Some(class) = self.classes.get(&class_hash) {
+            return Ok(class);
+        }
+
+        let class = self
+            .request()
             .feeder_gateway()
             .get_class_by_hash()
             .with_class_hash(class_hash)
             .with_retry(Self::RETRY)
             .get_as_bytes()
+            .await?;
+        self.classes.insert(class_hash, class.clone());
+
+        Ok(class)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn class_definition(
+        &self,
+        class_hash: ClassHash,
+    ) -> Result<reply::ClassDefinition, SequencerError> {
+        self.request()
+            .feeder_gateway()
+            .get_class_by_hash()
+            .with_class_hash(class_hash)
+            .with_retry(Self::RETRY)
+            .get_streamed()
             .await
     }
 
@@ -222,11 +698,13 @@ impl ClientApi for Client {
     async fn class_hash_at(
         &self,
         contract_address: ContractAddress,
+        block: BlockId,
     ) -> Result<ClassHash, SequencerError> {
         self.request()
             .feeder_gateway()
             .get_class_hash_at()
             .with_contract_address(contract_address)
+            .with_block(block)
             .with_retry(Self::RETRY)
             .get()
             .await
@@ -251,19 +729,44 @@ impl ClientApi for Client {
             .await
     }
 
+    /// Gets the nonce of a contract, i.e. the nonce its next transaction must use.
+    #[tracing::instrument(skip(self))]
+    async fn nonce(
+        &self,
+        contract_address: ContractAddress,
+        block: BlockId,
+    ) -> Result<ContractNonce, SequencerError> {
+        self.request()
+            .feeder_gateway()
+            .get_nonce()
+            .with_contract_address(contract_address)
+            .with_block(block)
+            .with_retry(Self::RETRY)
+            .get()
+            .await
+    }
+
     /// Gets transaction by hash.
     #[tracing::instrument(skip(self))]
     async fn transaction(
         &self,
         transaction_hash: StarknetTransactionHash,
+        include_block: bool,
     ) -> Result<reply::Transaction, SequencerError> {
-        self.request()
+        let request = self
+            .request()
             .feeder_gateway()
             .get_transaction()
-            .with_transaction_hash(transaction_hash)
+            .with_transaction_hash(transaction_hash);
+        let request = match include_block {
+            true => request.add_param("includeBlock", "true"),
+            false => request,
+        };
+        request
             .with_retry(Self::RETRY)
             .get()
             .await
+            .map_err(|e| e.not_yet_indexed(transaction_hash))
     }
 
     /// Gets transaction status by transaction hash.
@@ -279,6 +782,121 @@ impl ClientApi for Client {
             .with_retry(Self::RETRY)
             .get()
             .await
+            .map_err(|e| e.not_yet_indexed(transaction_hash))
+    }
+
+    /// Gets the statuses of many transactions, with at most
+    /// [TRANSACTION_STATUS_CONCURRENCY] queries in flight at a time.
+    #[tracing::instrument(skip(self, transaction_hashes), fields(count = transaction_hashes.len()))]
+    async fn transaction_statuses(
+        &self,
+        transaction_hashes: &[StarknetTransactionHash],
+    ) -> Vec<Result<reply::TransactionStatus, SequencerError>> {
+        use futures::StreamExt;
+
+        futures::stream::iter(transaction_hashes)
+            .map(|hash| self.transaction_status(*hash))
+            .buffered(TRANSACTION_STATUS_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Gets transaction receipt by transaction hash.
+    #[tracing::instrument(skip(self))]
+    async fn transaction_receipt(
+        &self,
+        transaction_hash: StarknetTransactionHash,
+    ) -> Result<reply::TransactionReceipt, SequencerError> {
+        self.request()
+            .feeder_gateway()
+            .get_transaction_receipt()
+            .with_transaction_hash(transaction_hash)
+            .with_retry(Self::RETRY)
+            .get()
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn await_block_finality(
+        &self,
+        block: StarknetBlockNumber,
+        target: deploy::Finality,
+        timeout: std::time::Duration,
+    ) -> Result<deploy::Finality, SequencerError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut finality = deploy::Finality::Received;
+        loop {
+            // Requests are retried until they succeed, which must not outlast the deadline.
+            let reply =
+                match tokio::time::timeout_at(deadline, self.block(BlockId::Number(block))).await {
+                    Ok(reply) => reply,
+                    Err(_) => return Ok(finality),
+                };
+            let status = match reply {
+                Ok(reply::MaybePendingBlock::Block(block)) => block.status,
+                Ok(reply::MaybePendingBlock::Pending(block)) => block.status,
+                Err(SequencerError::StarknetError(e))
+                    if e.code == error::StarknetErrorCode::BlockNotFound =>
+                {
+                    reply::Status::NotReceived
+                }
+                Err(e) => return Err(e),
+            };
+
+            finality = deploy::Finality::of(status);
+            let now = tokio::time::Instant::now();
+            if finality >= target || status == reply::Status::Aborted || now >= deadline {
+                return Ok(finality);
+            }
+            tokio::time::sleep(BLOCK_FINALITY_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// Gets the execution trace of a transaction by transaction hash.
+    #[tracing::instrument(skip(self))]
+    async fn transaction_trace(
+        &self,
+        transaction_hash: StarknetTransactionHash,
+    ) -> Result<reply::simulation::TransactionTrace, SequencerError> {
+        self.request()
+            .feeder_gateway()
+            .get_transaction_trace()
+            .with_transaction_hash(transaction_hash)
+            .with_retry(Self::RETRY)
+            .get()
+            .await
+    }
+
+    #[tracing::instrument(skip(self, transaction))]
+    async fn simulate_transaction(
+        &self,
+        transaction: request::add_transaction::AddTransaction,
+        block: BlockId,
+        block_context: request::BlockContext,
+    ) -> Result<reply::SimulatedTransaction, SequencerError> {
+        self.request()
+            .feeder_gateway()
+            .simulate_transaction()
+            .with_block(block)
+            .with_block_context(block_context)
+            .with_retry(Self::RETRY)
+            .post_with_json(&transaction)
+            .await
+    }
+
+    #[tracing::instrument(skip(self, transactions))]
+    async fn estimate_fee_bulk(
+        &self,
+        transactions: Vec<request::add_transaction::AddTransaction>,
+        block: BlockId,
+    ) -> Result<Vec<reply::FeeEstimate>, SequencerError> {
+        self.request()
+            .feeder_gateway()
+            .estimate_fee_bulk()
+            .with_block(block)
+            .with_retry(Self::RETRY)
+            .post_with_json(&transactions)
+            .await
     }
 
     #[tracing::instrument(skip(self))]
@@ -314,7 +932,11 @@ impl ClientApi for Client {
         contract_address: ContractAddress,
         entry_point_selector: Option<EntryPoint>,
         calldata: Vec<CallParam>,
+        resource_bounds: Option<request::add_transaction::ResourceBounds>,
     ) -> Result<reply::add_transaction::InvokeResponse, SequencerError> {
+        self.verify_chain().await?;
+        let _permit = self.submission_permit().await;
+
         let req = request::add_transaction::AddTransaction::Invoke(
             request::add_transaction::InvokeFunction {
                 contract_address,
@@ -324,6 +946,7 @@ impl ClientApi for Client {
                 version,
                 signature,
                 nonce,
+                resource_bounds,
             },
         );
 
@@ -331,12 +954,20 @@ impl ClientApi for Client {
         // This method is used to proxy an add transaction operation from the JSON-RPC
         // API to the sequencer. Retries should be implemented in the JSON-RPC
         // client instead.
-        self.request()
+        let result = self
+            .request()
             .gateway()
             .add_transaction()
             .with_retry(builder::Retry::Disabled)
             .post_with_json(&req)
-            .await
+            .await;
+
+        recover_already_received(result, |transaction_hash| {
+            Some(reply::add_transaction::InvokeResponse {
+                code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                transaction_hash,
+            })
+        })
     }
 
     /// Adds a transaction declaring a class.
@@ -349,8 +980,12 @@ impl ClientApi for Client {
         nonce: TransactionNonce,
         contract_definition: ContractDefinition,
         sender_address: ContractAddress,
+        compiled_class_hash: Option<CasmHash>,
         token: Option<String>,
     ) -> Result<reply::add_transaction::DeclareResponse, SequencerError> {
+        self.verify_chain().await?;
+        let _permit = self.submission_permit().await;
+
         let req =
             request::add_transaction::AddTransaction::Declare(request::add_transaction::Declare {
                 contract_class: contract_definition,
@@ -359,23 +994,42 @@ impl ClientApi for Client {
                 signature,
                 nonce,
                 version,
+                compiled_class_hash,
             });
 
         // Note that we don't do retries here.
         // This method is used to proxy an add transaction operation from the JSON-RPC
         // API to the sequencer. Retries should be implemented in the JSON-RPC
         // client instead.
-        self.request()
+        let result = self
+            .request()
             .gateway()
             .add_transaction()
             // mainnet requires a token (but testnet does not so its optional).
             .with_optional_token(token.as_deref())
             .with_retry(builder::Retry::Disabled)
             .post_with_json(&req)
-            .await
+            .await;
+
+        recover_already_received(result, |transaction_hash| {
+            let class_hash = match &req {
+                request::add_transaction::AddTransaction::Declare(declare) => {
+                    declare.contract_class.class_hash().ok()?
+                }
+                _ => unreachable!("Request is a declare"),
+            };
+            Some(reply::add_transaction::DeclareResponse {
+                code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                transaction_hash,
+                class_hash,
+            })
+        })
     }
 
     /// Deploys a contract.
+    ///
+    /// `timeout` supersedes the configured timeout for this request only, but is clamped to
+    /// [SequencerConfig::max_timeout_override].
     #[tracing::instrument(skip(self, contract_definition))]
     async fn add_deploy_transaction(
         &self,
@@ -384,7 +1038,11 @@ impl ClientApi for Client {
         constructor_calldata: Vec<ConstructorParam>,
         contract_definition: ContractDefinition,
         token: Option<String>,
+        timeout: Option<std::time::Duration>,
     ) -> Result<reply::add_transaction::DeployResponse, SequencerError> {
+        self.verify_chain().await?;
+        let _permit = self.submission_permit().await;
+
         let req =
             request::add_transaction::AddTransaction::Deploy(request::add_transaction::Deploy {
                 version,
@@ -398,14 +1056,35 @@ impl ClientApi for Client {
         // API to the sequencer. Retries should be implemented in the JSON-RPC
         // client instead.
 
-        self.request()
+        let result = self
+            .request()
             .gateway()
             .add_transaction()
             // mainnet requires a token (but testnet does not so its optional).
             .with_optional_token(token.as_deref())
+            .with_optional_timeout(timeout.map(|timeout| timeout.min(self.max_timeout_override)))
             .with_retry(builder::Retry::Disabled)
             .post_with_json(&req)
-            .await
+            .await;
+
+        recover_already_received(result, |transaction_hash| {
+            let deploy = match &req {
+                request::add_transaction::AddTransaction::Deploy(deploy) => deploy,
+                _ => unreachable!("Request is a deploy"),
+            };
+            let class_hash = deploy.contract_definition.class_hash().ok()?;
+            Some(reply::add_transaction::DeployResponse {
+                code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                transaction_hash,
+                address: compute_contract_address(
+                    deploy.contract_address_salt,
+                    class_hash,
+                    deploy.constructor_calldata.iter().map(|param| param.0),
+                    ZERO_DEPLOYER_ADDRESS,
+                ),
+                other: Default::default(),
+            })
+        })
     }
 
     #[tracing::instrument(skip(self))]
@@ -419,6 +1098,9 @@ impl ClientApi for Client {
         class_hash: ClassHash,
         calldata: Vec<CallParam>,
     ) -> Result<reply::add_transaction::DeployAccountResponse, SequencerError> {
+        self.verify_chain().await?;
+        let _permit = self.submission_permit().await;
+
         let req = request::add_transaction::AddTransaction::DeployAccount(
             request::add_transaction::DeployAccount {
                 version,
@@ -436,15 +1118,74 @@ impl ClientApi for Client {
         // API to the sequencer. Retries should be implemented in the JSON-RPC
         // client instead.
 
-        self.request()
+        let result = self
+            .request()
             .gateway()
             .add_transaction()
             .with_retry(builder::Retry::Disabled)
             .post_with_json(&req)
-            .await
+            .await;
+
+        recover_already_received(result, |transaction_hash| {
+            let deploy_account = match &req {
+                request::add_transaction::AddTransaction::DeployAccount(deploy_account) => {
+                    deploy_account
+                }
+                _ => unreachable!("Request is a deploy account"),
+            };
+            Some(reply::add_transaction::DeployAccountResponse {
+                code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                transaction_hash,
+                address: compute_contract_address(
+                    deploy_account.contract_address_salt,
+                    deploy_account.class_hash,
+                    deploy_account
+                        .constructor_calldata
+                        .iter()
+                        .map(|param| param.0),
+                    ZERO_DEPLOYER_ADDRESS,
+                ),
+            })
+        })
     }
 }
 
+/// The network whose genesis block is `genesis_hash`, [None] if it is not a known network.
+fn chain_of_genesis(genesis_hash: StarknetBlockHash) -> Option<Chain> {
+    use crate::consts::{MAINNET_GENESIS_HASH, TESTNET_GENESIS_HASH};
+
+    match genesis_hash {
+        testnet if testnet == TESTNET_GENESIS_HASH => Some(Chain::Testnet),
+        mainnet if mainnet == MAINNET_GENESIS_HASH => Some(Chain::Mainnet),
+        integration if integration == INTEGRATION_GENESIS_HASH => Some(Chain::Integration),
+        _ => None,
+    }
+}
+
+/// Treats the sequencer reporting a transaction as already received as a success, which
+/// makes it safe to resubmit a transaction whose response got lost, e.g. to a timeout.
+///
+/// `response` recreates the response for the original transaction hash. The error is kept
+/// if it fails to do so.
+fn recover_already_received<T>(
+    result: Result<T, SequencerError>,
+    response: impl FnOnce(StarknetTransactionHash) -> Option<T>,
+) -> Result<T, SequencerError> {
+    let transaction_hash = match &result {
+        Err(SequencerError::StarknetError(e)) => e.already_received_transaction(),
+        _ => None,
+    };
+
+    if let Some(transaction_hash) = transaction_hash {
+        if let Some(response) = response(transaction_hash) {
+            tracing::debug!(transaction_hash=%transaction_hash.0, "Transaction was already received");
+            return Ok(response);
+        }
+    }
+
+    result
+}
+
 #[cfg(test)]
 pub mod test_utils {
     use crate::rpc::v01::types::{BlockHashOrTag, BlockNumberOrTag};
@@ -504,6 +1245,23 @@ pub mod test_utils {
     pub const INVALID_CLASS_HASH: ClassHash = ClassHash(starkhash!(
         "031a7f43387573b68666669a0ed764252ce5367708e696e31967764a90b429c2"
     ));
+
+    /// Serves `filter` as a mock gateway on an ephemeral local port. Returns the handle of the
+    /// server task and the URL the gateway is reachable at.
+    pub fn serve_gateway<F, R>(filter: F) -> (tokio::task::JoinHandle<()>, reqwest::Url)
+    where
+        F: warp::Filter<Extract = (R,)> + Clone + Send + Sync + 'static,
+        F::Error: Into<warp::Rejection>,
+        R: warp::Reply + Send + 'static,
+    {
+        use warp::Filter;
+
+        // Boxing turns the error into a `Rejection`, which `warp::serve` accepts.
+        let (addr, serve_fut) = warp::serve(filter.boxed()).bind_ephemeral(([127, 0, 0, 1], 0));
+        let server_handle = tokio::spawn(serve_fut);
+        let url = reqwest::Url::parse(&format!("http://{}", addr)).unwrap();
+        (server_handle, url)
+    }
 }
 
 #[cfg(test)]
@@ -580,6 +1338,7 @@ mod tests {
             let e = StarknetError {
                 code: self,
                 message: "".to_string(),
+                problems: None,
             };
             (serde_json::to_string(&e).unwrap(), 500)
         }
@@ -647,11 +1406,8 @@ mod tests {
                 },
             );
 
-            let (addr, serve_fut) = warp::serve(path).bind_ephemeral(([127, 0, 0, 1], 0));
-            let server_handle = tokio::spawn(serve_fut);
-            let client =
-                Client::with_url(reqwest::Url::parse(&format!("http://{}", addr)).unwrap())
-                    .unwrap();
+            let (server_handle, url) = serve_gateway(path);
+            let client = Client::with_url(url).unwrap();
             (Some(server_handle), client)
         }
     }
@@ -718,10 +1474,8 @@ mod tests {
             },
         );
 
-        let (addr, serve_fut) = warp::serve(path).bind_ephemeral(([127, 0, 0, 1], 0));
-        let server_handle = tokio::spawn(serve_fut);
-        let client =
-            Client::with_url(reqwest::Url::parse(&format!("http://{}", addr)).unwrap()).unwrap();
+        let (server_handle, url) = serve_gateway(path);
+        let client = Client::with_url(url).unwrap();
         (Some(server_handle), client)
     }
 
@@ -1012,6 +1766,214 @@ mod tests {
             let bytes = client.class_by_hash(VALID_CLASS_HASH).await.unwrap();
             serde_json::from_slice::<serde_json::value::Value>(&bytes).unwrap();
         }
+
+        #[tokio::test]
+        async fn streamed_to_writer() {
+            // A multi-megabyte class, so that the body is received in many chunks.
+            let class = serde_json::json!({
+                "program": {"data": vec!["0x40780017fff7fff"; 200_000]},
+                "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []},
+            })
+            .to_string();
+            let (_jh, client) = setup([(
+                format!(
+                    "/feeder_gateway/get_class_by_hash?classHash={}",
+                    VALID_CLASS_HASH
+                ),
+                (class.clone(), 200),
+            )]);
+
+            let mut buffer = Vec::new();
+            let written = client
+                .get_class_to_writer(VALID_CLASS_HASH, &mut buffer)
+                .await
+                .unwrap();
+
+            assert_eq!(written, class.len() as u64);
+            assert_eq!(String::from_utf8(buffer).unwrap(), class);
+        }
+
+        #[tokio::test]
+        async fn streamed_to_writer_is_limited_in_size() {
+            use warp::Filter;
+
+            let class = format!(r#"{{"program": "{}"}}"#, "a".repeat(1_000_000));
+            let route =
+                warp::path!("feeder_gateway" / "get_class_by_hash").map(move || class.clone());
+            let (_jh, url) = serve_gateway(route);
+            let client =
+                Client::with_config(SequencerConfig::new(url).with_max_response_size(1_000))
+                    .unwrap();
+
+            let mut buffer = Vec::new();
+            let error = client
+                .get_class_to_writer(VALID_CLASS_HASH, &mut buffer)
+                .await
+                .unwrap_err();
+
+            assert_matches!(
+                error.downcast_ref::<SequencerError>(),
+                Some(SequencerError::ResponseTooLarge { limit: 1_000 })
+            );
+            assert!(buffer.len() <= 1_000);
+        }
+
+        mod class_definition {
+            use super::*;
+            use crate::sequencer::request::contract::EntryPointType;
+
+            #[tokio::test]
+            async fn large_class() {
+                // A multi-megabyte program, so that the body is received in many chunks.
+                let data = vec!["0x40780017fff7fff"; 200_000];
+                // Exceeds 64 bits, which only round trips as an arbitrary precision number.
+                let value = serde_json::from_str::<serde_json::Value>(
+                    "-106710729501573572985208420194530329073740042555888586719489",
+                )
+                .unwrap();
+                let program = serde_json::json!({
+                    "data": data,
+                    "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+                    "debug_info": null,
+                    "hints": {},
+                    "identifiers": {
+                        "__main__.x": {"type": "const", "value": value}
+                    },
+                    "attributes": [],
+                    "reference_manager": {"references": [{"pc": 0, "value": "[cast(fp, felt*)]"}]}
+                });
+                let class = serde_json::json!({
+                    "program": program,
+                    "entry_points_by_type": {
+                        "CONSTRUCTOR": [{"selector": "0x1", "offset": "0x2"}],
+                        "EXTERNAL": [],
+                        "L1_HANDLER": []
+                    },
+                    "abi": []
+                })
+                .to_string();
+                let (_jh, client) = setup([(
+                    format!(
+                        "/feeder_gateway/get_class_by_hash?classHash={}",
+                        VALID_CLASS_HASH
+                    ),
+                    (class.clone(), 200),
+                )]);
+
+                let definition = client.class_definition(VALID_CLASS_HASH).await.unwrap();
+
+                // Only the compressed program is kept.
+                assert!(definition.program.len() < class.len() / 100);
+                let compressed = base64::decode(&definition.program).unwrap();
+                let decompressed = serde_json::from_reader::<_, serde_json::Value>(
+                    flate2::read::GzDecoder::new(&*compressed),
+                )
+                .unwrap();
+                assert_eq!(decompressed, program);
+                assert_eq!(
+                    definition.entry_points_by_type[&EntryPointType::Constructor].len(),
+                    1
+                );
+                assert_eq!(definition.abi, Some(serde_json::json!([])));
+            }
+
+            #[tokio::test]
+            async fn invalid_body() {
+                let (_jh, client) = setup([(
+                    format!(
+                        "/feeder_gateway/get_class_by_hash?classHash={}",
+                        VALID_CLASS_HASH
+                    ),
+                    (r#"{"program": {}"#, 200),
+                )]);
+
+                let error = client.class_definition(VALID_CLASS_HASH).await.unwrap_err();
+                assert_matches!(error, SequencerError::InvalidResponseBody(_));
+            }
+        }
+
+        mod cache {
+            use super::*;
+            use std::sync::atomic::{AtomicUsize, Ordering};
+            use warp::Filter;
+
+            /// A gateway serving every class, and the number of classes it served.
+            fn gateway() -> (Url, Arc<AtomicUsize>) {
+                let served = Arc::new(AtomicUsize::new(0));
+                let served_clone = served.clone();
+                let route = warp::path!("feeder_gateway" / "get_class_by_hash").map(move || {
+                    served_clone.fetch_add(1, Ordering::Relaxed);
+                    r#"{"hello":"world"}"#
+                });
+                let (_jh, url) = serve_gateway(route);
+
+                (url, served)
+            }
+
+            #[tokio::test]
+            async fn second_fetch_is_a_cache_hit() {
+                let (url, served) = gateway();
+                let client =
+                    Client::with_config(SequencerConfig::new(url).with_class_cache_bytes(1 << 20))
+                        .unwrap();
+
+                let first = client.class_by_hash(VALID_CLASS_HASH).await.unwrap();
+                let second = client.class_by_hash(VALID_CLASS_HASH).await.unwrap();
+
+                assert_eq!(first, second);
+                assert_eq!(served.load(Ordering::Relaxed), 1);
+            }
+
+            #[tokio::test]
+            async fn disabled_by_default() {
+                let (url, served) = gateway();
+                let client = Client::with_url(url).unwrap();
+
+                client.class_by_hash(VALID_CLASS_HASH).await.unwrap();
+                client.class_by_hash(VALID_CLASS_HASH).await.unwrap();
+
+                assert_eq!(served.load(Ordering::Relaxed), 2);
+            }
+        }
+
+        mod max_response_size {
+            use super::*;
+            use warp::Filter;
+
+            /// A client limited to `limit` bytes of a gateway serving a class of `size` bytes.
+            fn client(limit: usize, size: usize) -> Client {
+                let class = format!(r#"{{"program": "{}"}}"#, "a".repeat(size - 15));
+                let route =
+                    warp::path!("feeder_gateway" / "get_class_by_hash").map(move || class.clone());
+                let (_, url) = serve_gateway(route);
+                Client::with_config(SequencerConfig::new(url).with_max_response_size(limit))
+                    .unwrap()
+            }
+
+            #[tokio::test]
+            async fn oversized_body_is_aborted() {
+                let client = client(1024, 1024 * 1024);
+
+                let error = client.class_by_hash(VALID_CLASS_HASH).await.unwrap_err();
+                assert_matches!(error, SequencerError::ResponseTooLarge { limit: 1024 });
+            }
+
+            #[tokio::test]
+            async fn oversized_streamed_body_is_aborted() {
+                let client = client(1024, 1024 * 1024);
+
+                let error = client.class_definition(VALID_CLASS_HASH).await.unwrap_err();
+                assert_matches!(error, SequencerError::ResponseTooLarge { limit: 1024 });
+            }
+
+            #[tokio::test]
+            async fn body_at_the_limit_is_received() {
+                let client = client(1024, 1024);
+
+                let class = client.class_by_hash(VALID_CLASS_HASH).await.unwrap();
+                assert_eq!(class.len(), 1024);
+            }
+        }
     }
 
     mod class_hash {
@@ -1022,13 +1984,13 @@ mod tests {
         async fn invalid_contract_address() {
             let (_jh, client) = setup([(
                 format!(
-                    "/feeder_gateway/get_class_hash_at?contractAddress={}",
+                    "/feeder_gateway/get_class_hash_at?contractAddress={}&blockNumber=pending",
                     INVALID_CONTRACT_ADDR
                 ),
                 StarknetErrorCode::UninitializedContract.into_response(),
             )]);
             let error = client
-                .class_hash_at(INVALID_CONTRACT_ADDR)
+                .class_hash_at(INVALID_CONTRACT_ADDR, BlockId::Pending)
                 .await
                 .unwrap_err();
             assert_matches!(
@@ -1041,12 +2003,59 @@ mod tests {
         async fn success() {
             let (_jh, client) = setup([(
                 format!(
-                    "/feeder_gateway/get_class_hash_at?contractAddress={}",
+                    "/feeder_gateway/get_class_hash_at?contractAddress={}&blockNumber=5",
                     VALID_CONTRACT_ADDR
                 ),
                 (r#""0x01""#, 200),
             )]);
-            client.class_hash_at(VALID_CONTRACT_ADDR).await.unwrap();
+            client
+                .class_hash_at(
+                    VALID_CONTRACT_ADDR,
+                    BlockId::Number(StarknetBlockNumber::new_or_panic(5)),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    mod nonce {
+        use super::*;
+        use crate::starkhash;
+        use pretty_assertions::assert_eq;
+
+        #[tokio::test]
+        async fn success() {
+            let (_jh, client) = setup([(
+                format!(
+                    "/feeder_gateway/get_nonce?contractAddress={}&blockNumber=pending",
+                    VALID_CONTRACT_ADDR
+                ),
+                (r#""0x5""#, 200),
+            )]);
+            let nonce = client
+                .nonce(VALID_CONTRACT_ADDR, BlockId::Pending)
+                .await
+                .unwrap();
+            assert_eq!(nonce, ContractNonce(starkhash!("05")));
+        }
+
+        #[tokio::test]
+        async fn at_block_number() {
+            let (_jh, client) = setup([(
+                format!(
+                    "/feeder_gateway/get_nonce?contractAddress={}&blockNumber=5",
+                    VALID_CONTRACT_ADDR
+                ),
+                (r#""0x2""#, 200),
+            )]);
+            let nonce = client
+                .nonce(
+                    VALID_CONTRACT_ADDR,
+                    BlockId::Number(StarknetBlockNumber::new_or_panic(5)),
+                )
+                .await
+                .unwrap();
+            assert_eq!(nonce, ContractNonce(starkhash!("02")));
         }
     }
 
@@ -1184,9 +2193,12 @@ mod tests {
             )]);
             assert_eq!(
                 client
-                    .transaction(StarknetTransactionHash(starkhash!(
-                        "0587d93f2339b7f2beda040187dbfcb9e076ce4a21eb8d15ae64819718817fbe"
-                    )))
+                    .transaction(
+                        StarknetTransactionHash(starkhash!(
+                            "0587d93f2339b7f2beda040187dbfcb9e076ce4a21eb8d15ae64819718817fbe"
+                        )),
+                        false
+                    )
                     .await
                     .unwrap()
                     .status,
@@ -1202,9 +2214,12 @@ mod tests {
             )]);
             assert_eq!(
                 client
-                    .transaction(StarknetTransactionHash(starkhash!(
-                        "03d7623443283d9a0cec946492db78b06d57642a551745ddfac8d3f1f4fcc2a8"
-                    )))
+                    .transaction(
+                        StarknetTransactionHash(starkhash!(
+                            "03d7623443283d9a0cec946492db78b06d57642a551745ddfac8d3f1f4fcc2a8"
+                        )),
+                        false
+                    )
                     .await
                     .unwrap()
                     .status,
@@ -1220,9 +2235,12 @@ mod tests {
             )]);
             assert_eq!(
                 client
-                    .transaction(StarknetTransactionHash(starkhash!(
-                        "0587d93f2339b7f2beda040187dbfcb9e076ce4a21eb8d15ae64819718817fbe"
-                    )))
+                    .transaction(
+                        StarknetTransactionHash(starkhash!(
+                            "0587d93f2339b7f2beda040187dbfcb9e076ce4a21eb8d15ae64819718817fbe"
+                        )),
+                        false
+                    )
                     .await
                     .unwrap()
                     .status,
@@ -1240,10 +2258,47 @@ mod tests {
                 (r#"{"status": "NOT_RECEIVED"}"#, 200),
             )]);
             assert_eq!(
-                client.transaction(INVALID_TX_HASH).await.unwrap().status,
+                client
+                    .transaction(INVALID_TX_HASH, false)
+                    .await
+                    .unwrap()
+                    .status,
                 Status::NotReceived,
             );
         }
+
+        #[tokio::test]
+        async fn with_block() {
+            let mut reply: serde_json::Value =
+                serde_json::from_str(include_str!("../fixtures/sequencer/0.9.0/txn/invoke.json"))
+                    .unwrap();
+            reply["block"] = serde_json::from_str(include_str!(
+                "../fixtures/sequencer/0.9.0/block/231579.json"
+            ))
+            .unwrap();
+            let (_jh, client) = setup([(
+                "/feeder_gateway/get_transaction?transactionHash=0x587d93f2339b7f2beda040187dbfcb9e076ce4a21eb8d15ae64819718817fbe&includeBlock=true",
+                (reply.to_string(), 200),
+            )]);
+
+            let transaction = client
+                .transaction(
+                    StarknetTransactionHash(starkhash!(
+                        "0587d93f2339b7f2beda040187dbfcb9e076ce4a21eb8d15ae64819718817fbe"
+                    )),
+                    true,
+                )
+                .await
+                .unwrap();
+
+            let block = transaction.block.unwrap();
+            assert_eq!(Some(block.block_hash), transaction.block_hash);
+            assert_eq!(Some(block.block_number), transaction.block_number);
+            assert_eq!(
+                Some(&block.transactions[transaction.transaction_index.unwrap() as usize]),
+                transaction.transaction.as_ref()
+            );
+        }
     }
 
     mod transaction_status {
@@ -1286,87 +2341,384 @@ mod tests {
                 Status::NotReceived
             );
         }
-    }
-
-    mod state_update_matches_by_hash_on {
-        use super::{
-            reply::{
-                state_update::{DeployedContract, StorageDiff},
-                StateUpdate,
-            },
-            *,
-        };
-        use crate::monitoring::metrics::test::RecorderGuard;
-        use crate::{
-            core::{ContractAddress, GlobalRoot},
-            starkhash,
-        };
-        use pretty_assertions::assert_eq;
-        use std::collections::{BTreeSet, HashMap};
-
-        #[derive(Clone, Debug, PartialEq, Eq)]
-        pub struct OrderedStateDiff {
-            pub storage_diffs: HashMap<ContractAddress, BTreeSet<StorageDiff>>,
-            pub deployed_contracts: BTreeSet<DeployedContract>,
-        }
 
-        #[derive(Clone, Debug, PartialEq, Eq)]
-        pub struct OrderedStateUpdate {
-            pub new_root: GlobalRoot,
-            pub old_root: GlobalRoot,
-            pub state_diff: OrderedStateDiff,
+        #[tokio::test]
+        async fn rejected_with_reason() {
+            let (_jh, client) = setup([(
+                format!(
+                    "/feeder_gateway/get_transaction_status?transactionHash={}",
+                    INVALID_TX_HASH
+                ),
+                (
+                    r#"{"tx_status": "REJECTED", "tx_failure_reason": {"code": "TRANSACTION_FAILED", "error_message": "Insufficient max fee", "tx_id": 7}}"#,
+                    200,
+                ),
+            )]);
+            let status = client.transaction_status(INVALID_TX_HASH).await.unwrap();
+            assert_eq!(status.tx_status, Status::Rejected);
+            assert_eq!(status.rejection_reason(), Some("Insufficient max fee"));
         }
 
-        impl From<StateUpdate> for OrderedStateUpdate {
-            fn from(s: StateUpdate) -> Self {
-                Self {
-                    new_root: s.new_root,
-                    old_root: s.old_root,
-                    state_diff: OrderedStateDiff {
-                        storage_diffs: s
-                            .state_diff
-                            .storage_diffs
-                            .into_iter()
-                            .map(|(addr, diffs)| (addr, diffs.into_iter().collect()))
-                            .collect(),
-                        deployed_contracts: s.state_diff.deployed_contracts.into_iter().collect(),
-                    },
-                }
-            }
+        #[tokio::test]
+        async fn not_yet_indexed() {
+            let (_jh, client) = setup([(
+                format!(
+                    "/feeder_gateway/get_transaction_status?transactionHash={}",
+                    INVALID_TX_HASH
+                ),
+                StarknetErrorCode::TransactionNotFound.into_response(),
+            )]);
+            assert_matches!(
+                client.transaction_status(INVALID_TX_HASH).await.unwrap_err(),
+                SequencerError::TransactionNotYetIndexed { transaction_hash } => assert_eq!(transaction_hash, INVALID_TX_HASH)
+            );
         }
 
         #[tokio::test]
-        async fn genesis() {
-            let _guard = RecorderGuard::lock_as_noop();
+        async fn many() {
             let (_jh, client) = setup([
                 (
-                    "/feeder_gateway/get_state_update?blockNumber=0".to_string(),
-                    response!("0.9.1/state_update/genesis.json"),
+                    "/feeder_gateway/get_transaction_status?transactionHash=0x79cc07feed4f4046276aea23ddcea8b2f956d14f2bfe97382fa333a11169205",
+                    response_owned!("0.9.0/txn/status.json"),
                 ),
                 (
-                    format!(
-                        "/feeder_gateway/get_state_update?blockHash={}",
-                        GENESIS_BLOCK_HASH
-                    ),
-                    response!("0.9.1/state_update/genesis.json"),
+                    "/feeder_gateway/get_transaction_status?transactionHash=0x393d8fab73af67e972788e603aee18130facd3c7685f16084ecd98b07153e24",
+                    (r#"{"tx_status": "NOT_RECEIVED"}"#.to_owned(), 200),
+                ),
+                (
+                    "/feeder_gateway/get_transaction_status?transactionHash=0x1",
+                    StarknetErrorCode::OutOfRangeTransactionHash.into_response(),
                 ),
             ]);
-            let by_number: OrderedStateUpdate = client
-                .state_update(BlockId::from(GENESIS_BLOCK_NUMBER))
-                .await
-                .unwrap()
-                .into();
-            let by_hash: OrderedStateUpdate = client
-                .state_update(BlockId::from(GENESIS_BLOCK_HASH))
-                .await
-                .unwrap()
-                .into();
-
-            assert_eq!(by_number, by_hash);
-        }
+            let accepted = StarknetTransactionHash(starkhash!(
+                "079cc07feed4f4046276aea23ddcea8b2f956d14f2bfe97382fa333a11169205"
+            ));
+            let statuses = client
+                .transaction_statuses(&[
+                    INVALID_TX_HASH,
+                    accepted,
+                    StarknetTransactionHash(starkhash!("01")),
+                    accepted,
+                ])
+                .await;
 
-        #[tokio::test]
-        async fn specific_block() {
+            assert_eq!(statuses.len(), 4);
+            assert_eq!(statuses[0].as_ref().unwrap().tx_status, Status::NotReceived);
+            assert_eq!(
+                statuses[1].as_ref().unwrap().tx_status,
+                Status::AcceptedOnL1
+            );
+            assert_matches!(
+                &statuses[2],
+                Err(SequencerError::StarknetError(e)) => assert_eq!(e.code, StarknetErrorCode::OutOfRangeTransactionHash)
+            );
+            assert_eq!(
+                statuses[3].as_ref().unwrap().tx_status,
+                Status::AcceptedOnL1
+            );
+        }
+    }
+
+    mod transaction_receipt {
+        use super::{reply::Status, *};
+        use crate::starkhash;
+
+        #[tokio::test]
+        async fn accepted() {
+            let (_jh, client) = setup([(
+                "/feeder_gateway/get_transaction_receipt?transactionHash=0x79cc07feed4f4046276aea23ddcea8b2f956d14f2bfe97382fa333a11169205",
+                (
+                    r#"{
+                        "status": "ACCEPTED_ON_L1",
+                        "block_hash": "0x1",
+                        "block_number": 2,
+                        "transaction_index": 3,
+                        "transaction_hash": "0x79cc07feed4f4046276aea23ddcea8b2f956d14f2bfe97382fa333a11169205",
+                        "l2_to_l1_messages": [],
+                        "events": []
+                    }"#,
+                    200,
+                ),
+            )]);
+            let receipt = client
+                .transaction_receipt(StarknetTransactionHash(starkhash!(
+                    "079cc07feed4f4046276aea23ddcea8b2f956d14f2bfe97382fa333a11169205"
+                )))
+                .await
+                .unwrap();
+            assert_eq!(receipt.status, Status::AcceptedOnL1);
+            assert_eq!(
+                receipt.transaction_hash,
+                Some(StarknetTransactionHash(starkhash!(
+                    "079cc07feed4f4046276aea23ddcea8b2f956d14f2bfe97382fa333a11169205"
+                )))
+            );
+        }
+
+        #[tokio::test]
+        async fn invalid_hash() {
+            let (_jh, client) = setup([(
+                format!(
+                    "/feeder_gateway/get_transaction_receipt?transactionHash={}",
+                    INVALID_TX_HASH
+                ),
+                (
+                    r#"{"status": "NOT_RECEIVED", "l2_to_l1_messages": [], "events": []}"#,
+                    200,
+                ),
+            )]);
+            let receipt = client.transaction_receipt(INVALID_TX_HASH).await.unwrap();
+            assert_eq!(receipt.status, Status::NotReceived);
+            assert_eq!(receipt.block_hash, None);
+        }
+    }
+
+    mod await_block_finality {
+        use super::*;
+        use crate::sequencer::deploy::Finality;
+        use std::time::Duration;
+
+        const PATH: &str = "/feeder_gateway/get_block?blockNumber=231579";
+
+        fn block(status: &str) -> (String, u16) {
+            let mut block: serde_json::Value = serde_json::from_str(include_str!(
+                "../fixtures/sequencer/0.9.0/block/231579.json"
+            ))
+            .unwrap();
+            block["status"] = serde_json::json!(status);
+            (block.to_string(), 200)
+        }
+
+        #[tokio::test]
+        async fn reaches_target() {
+            let (_jh, client) = setup_with_varied_responses([(
+                PATH.to_owned(),
+                [
+                    StarknetErrorCode::BlockNotFound.into_response(),
+                    block("PENDING"),
+                    block("ACCEPTED_ON_L2"),
+                    block("ACCEPTED_ON_L2"),
+                    block("ACCEPTED_ON_L1"),
+                ],
+            )]);
+
+            let finality = client
+                .await_block_finality(
+                    StarknetBlockNumber::new_or_panic(231579),
+                    Finality::AcceptedOnL1,
+                    Duration::from_secs(60),
+                )
+                .await
+                .unwrap();
+            assert_eq!(finality, Finality::AcceptedOnL1);
+        }
+
+        #[tokio::test]
+        async fn lower_target_returns_early() {
+            let (_jh, client) =
+                setup_with_varied_responses([(PATH.to_owned(), [block("ACCEPTED_ON_L1")])]);
+
+            let finality = client
+                .await_block_finality(
+                    StarknetBlockNumber::new_or_panic(231579),
+                    Finality::AcceptedOnL2,
+                    Duration::from_secs(60),
+                )
+                .await
+                .unwrap();
+            assert_eq!(finality, Finality::AcceptedOnL1);
+        }
+
+        #[tokio::test]
+        async fn aborted_block_is_not_waited_for() {
+            let (_jh, client) = setup_with_varied_responses([(
+                PATH.to_owned(),
+                [block("ACCEPTED_ON_L2"), block("ABORTED")],
+            )]);
+
+            let finality = client
+                .await_block_finality(
+                    StarknetBlockNumber::new_or_panic(231579),
+                    Finality::AcceptedOnL1,
+                    Duration::from_secs(60),
+                )
+                .await
+                .unwrap();
+            assert_eq!(finality, Finality::Received);
+        }
+
+        #[tokio::test]
+        async fn times_out() {
+            let (_jh, client) = setup_with_varied_responses([(
+                PATH.to_owned(),
+                [block("ACCEPTED_ON_L2"), block("ACCEPTED_ON_L2")],
+            )]);
+
+            let finality = client
+                .await_block_finality(
+                    StarknetBlockNumber::new_or_panic(231579),
+                    Finality::AcceptedOnL1,
+                    BLOCK_FINALITY_POLL_INTERVAL,
+                )
+                .await
+                .unwrap();
+            assert_eq!(finality, Finality::AcceptedOnL2);
+        }
+
+        #[tokio::test]
+        async fn slow_gateway_does_not_outlast_timeout() {
+            use warp::Filter;
+
+            let route = warp::any().and_then(|| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok::<_, std::convert::Infallible>("{}")
+            });
+            let (_jh, url) = serve_gateway(route);
+            let client = Client::with_url(url).unwrap();
+
+            let started = tokio::time::Instant::now();
+            let finality = client
+                .await_block_finality(
+                    StarknetBlockNumber::new_or_panic(231579),
+                    Finality::AcceptedOnL1,
+                    Duration::from_millis(100),
+                )
+                .await
+                .unwrap();
+            assert_eq!(finality, Finality::Received);
+            assert!(started.elapsed() < Duration::from_secs(30));
+        }
+    }
+
+    mod transaction_trace {
+        use super::*;
+        use crate::core::{CallParam, ContractAddress, EventData};
+        use crate::starkhash;
+
+        #[tokio::test]
+        async fn constructor_invocation() {
+            let (_jh, client) = setup([(
+                format!(
+                    "/feeder_gateway/get_transaction_trace?transactionHash={}",
+                    INVALID_TX_HASH
+                ),
+                (
+                    r#"{
+                        "function_invocation": {
+                            "caller_address": "0x0",
+                            "contract_address": "0x123",
+                            "calldata": ["0x1", "0x2"],
+                            "call_type": "CALL",
+                            "selector": "0x28ffe4ff0f226a9107253e17a904099aa4f63a02a5621de0576e5aa71bc5194",
+                            "entry_point_type": "CONSTRUCTOR",
+                            "result": [],
+                            "events": [{"order": 0, "data": ["0x3"], "keys": []}],
+                            "internal_calls": []
+                        },
+                        "signature": []
+                    }"#,
+                    200,
+                ),
+            )]);
+            let trace = client.transaction_trace(INVALID_TX_HASH).await.unwrap();
+
+            let constructor = trace.constructor_invocation().unwrap();
+            assert_eq!(
+                constructor.contract_address,
+                Some(ContractAddress::new_or_panic(starkhash!("0123")))
+            );
+            assert_eq!(
+                constructor.calldata,
+                vec![CallParam(starkhash!("01")), CallParam(starkhash!("02"))]
+            );
+            assert_eq!(
+                constructor.events[0].data,
+                vec![EventData(starkhash!("03"))]
+            );
+            assert_eq!(trace.validate_invocation, None);
+            assert_eq!(trace.fee_transfer_invocation, None);
+        }
+    }
+
+    mod state_update_matches_by_hash_on {
+        use super::{
+            reply::{
+                state_update::{DeployedContract, StorageDiff},
+                StateUpdate,
+            },
+            *,
+        };
+        use crate::monitoring::metrics::test::RecorderGuard;
+        use crate::{
+            core::{ContractAddress, GlobalRoot},
+            starkhash,
+        };
+        use pretty_assertions::assert_eq;
+        use std::collections::{BTreeSet, HashMap};
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct OrderedStateDiff {
+            pub storage_diffs: HashMap<ContractAddress, BTreeSet<StorageDiff>>,
+            pub deployed_contracts: BTreeSet<DeployedContract>,
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct OrderedStateUpdate {
+            pub new_root: GlobalRoot,
+            pub old_root: GlobalRoot,
+            pub state_diff: OrderedStateDiff,
+        }
+
+        impl From<StateUpdate> for OrderedStateUpdate {
+            fn from(s: StateUpdate) -> Self {
+                Self {
+                    new_root: s.new_root,
+                    old_root: s.old_root,
+                    state_diff: OrderedStateDiff {
+                        storage_diffs: s
+                            .state_diff
+                            .storage_diffs
+                            .into_iter()
+                            .map(|(addr, diffs)| (addr, diffs.into_iter().collect()))
+                            .collect(),
+                        deployed_contracts: s.state_diff.deployed_contracts.into_iter().collect(),
+                    },
+                }
+            }
+        }
+
+        #[tokio::test]
+        async fn genesis() {
+            let _guard = RecorderGuard::lock_as_noop();
+            let (_jh, client) = setup([
+                (
+                    "/feeder_gateway/get_state_update?blockNumber=0".to_string(),
+                    response!("0.9.1/state_update/genesis.json"),
+                ),
+                (
+                    format!(
+                        "/feeder_gateway/get_state_update?blockHash={}",
+                        GENESIS_BLOCK_HASH
+                    ),
+                    response!("0.9.1/state_update/genesis.json"),
+                ),
+            ]);
+            let by_number: OrderedStateUpdate = client
+                .state_update(BlockId::from(GENESIS_BLOCK_NUMBER))
+                .await
+                .unwrap()
+                .into();
+            let by_hash: OrderedStateUpdate = client
+                .state_update(BlockId::from(GENESIS_BLOCK_HASH))
+                .await
+                .unwrap()
+                .into();
+
+            assert_eq!(by_number, by_hash);
+        }
+
+        #[tokio::test]
+        async fn specific_block() {
             let _guard = RecorderGuard::lock_as_noop();
             let (_jh, client) = setup([
                 (
@@ -1524,6 +2876,7 @@ mod tests {
                         CallParam(starkhash!("2b")),
                         CallParam(StarkHash::ZERO),
                     ],
+                    None,
                 )
                 .await
                 .unwrap_err();
@@ -1576,6 +2929,7 @@ mod tests {
                         CallParam(starkhash!("2b")),
                         CallParam(StarkHash::ZERO),
                     ],
+                    None,
                 )
                 .await
                 .unwrap();
@@ -1622,6 +2976,7 @@ mod tests {
                     // actual address dumped from a `starknet declare` call
                     ContractAddress::new_or_panic(starkhash!("01")),
                     None,
+                    None,
                 )
                 .await
                 .unwrap();
@@ -1650,9 +3005,57 @@ mod tests {
                     vec![ConstructorParam(starkhash!("01"))],
                     contract_definition,
                     None,
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        #[tokio::test]
+        async fn deploy_contract_debug() {
+            let (_jh, client) = setup([(
+                "/gateway/add_transaction",
+                (
+                    r#"{"code":"TRANSACTION_RECEIVED","transaction_hash":"0x057ED4B4C76A1CA0BA044A654DD3EE2D0D3E550343D739350A22AACDD524110D",
+                    "address":"0x03926AEA98213EC34FE9783D803237D221C54C52344422E1F4942A5B340FA6AD","unexpected":[1,2]}"#,
+                    200,
+                ),
+            )]);
+            let response = client
+                .add_deploy_transaction_debug(
+                    TransactionVersion::ZERO,
+                    ContractAddressSalt(starkhash!(
+                        "05864b5e296c05028ac2bbc4a4c1378f56a3489d13e581f21d566bb94580f76d"
+                    )),
+                    vec![ConstructorParam(starkhash!("01"))],
+                    get_contract_class_from_fixture(),
+                    None,
                 )
                 .await
                 .unwrap();
+
+            assert_eq!(
+                response.body,
+                serde_json::json!({
+                    "code": "TRANSACTION_RECEIVED",
+                    "transaction_hash": "0x057ED4B4C76A1CA0BA044A654DD3EE2D0D3E550343D739350A22AACDD524110D",
+                    "address": "0x03926AEA98213EC34FE9783D803237D221C54C52344422E1F4942A5B340FA6AD",
+                    "unexpected": [1, 2],
+                })
+            );
+            let output = response.output.unwrap();
+            assert_eq!(
+                output.transaction_hash,
+                StarknetTransactionHash(starkhash!(
+                    "057ED4B4C76A1CA0BA044A654DD3EE2D0D3E550343D739350A22AACDD524110D"
+                ))
+            );
+            assert_eq!(
+                output.address,
+                ContractAddress::new_or_panic(starkhash!(
+                    "03926AEA98213EC34FE9783D803237D221C54C52344422E1F4942A5B340FA6AD"
+                ))
+            );
         }
 
         #[tokio::test]
@@ -1689,7 +3092,7 @@ mod tests {
                 .expect("DEPLOY_ACCOUNT response");
 
             let expected = reply::add_transaction::DeployAccountResponse {
-                code: "TRANSACTION_RECEIVED".to_string(),
+                code: reply::add_transaction::AddTransactionCode::TransactionReceived,
                 transaction_hash: StarknetTransactionHash(crate::starkhash!(
                     "06dac1655b34e52a449cfe961188f7cc2b1496bcd36706cedf4935567be29d5b"
                 )),
@@ -1744,7 +3147,7 @@ mod tests {
             const EXPECTED_TOKEN: &str = "magic token value";
             const EXPECTED_ERROR_MESSAGE: &str = "error message";
 
-            fn test_server() -> (tokio::task::JoinHandle<()>, std::net::SocketAddr) {
+            fn test_server() -> (tokio::task::JoinHandle<()>, reqwest::Url) {
                 fn token_check(params: HashMap<String, String>) -> impl warp::Reply {
                     match params.get("token") {
                         Some(token) if token == EXPECTED_TOKEN => Response::builder().status(StatusCode::OK).body(serde_json::to_vec(&serde_json::json!({
@@ -1762,16 +3165,12 @@ mod tests {
                 let route = warp::any()
                     .and(warp::query::<HashMap<String, String>>())
                     .map(token_check);
-                let (addr, run_srv) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
-                let server_handle = tokio::spawn(run_srv);
-                (server_handle, addr)
+                serve_gateway(route)
             }
 
             #[test_log::test(tokio::test)]
             async fn test_token_is_passed_to_sequencer_api() {
-                let (_jh, addr) = test_server();
-                let mut url = reqwest::Url::parse("http://localhost/").unwrap();
-                url.set_port(Some(addr.port())).unwrap();
+                let (_jh, url) = test_server();
                 let client = Client::with_url(url).unwrap();
 
                 client
@@ -1785,6 +3184,7 @@ mod tests {
                             abi: None,
                         },
                         Some(EXPECTED_TOKEN.to_owned()),
+                        None,
                     )
                     .await
                     .unwrap();
@@ -1792,9 +3192,7 @@ mod tests {
 
             #[test_log::test(tokio::test)]
             async fn test_deploy_fails_with_no_token() {
-                let (_jh, addr) = test_server();
-                let mut url = reqwest::Url::parse("http://localhost/").unwrap();
-                url.set_port(Some(addr.port())).unwrap();
+                let (_jh, url) = test_server();
                 let client = Client::with_url(url).unwrap();
 
                 let err = client
@@ -1808,6 +3206,7 @@ mod tests {
                             abi: None,
                         },
                         None,
+                        None,
                     )
                     .await
                     .unwrap_err();
@@ -1818,47 +3217,271 @@ mod tests {
                 });
             }
         }
-    }
-
-    mod chain {
-        use crate::core::Chain;
-        use crate::sequencer;
-
-        #[derive(Copy, Clone, PartialEq, Eq)]
-        /// Used by [setup_server] to determine which block to return.
-        enum TargetChain {
-            Testnet,
-            Mainnet,
-            Invalid,
-        }
 
-        /// Creates a [sequencer::Client] whose Sequencer gateway is either the real Sequencer,
-        /// or a local warp server. A local server is created if:
-        /// - SEQUENCER_TESTS_LIVE_API is not set, __or__
-        /// - `target == TargetChain::Invalid`
-        ///
-        /// The local server only supports the `feeder_gateway/get_block?blockNumber=0` queries.
-        fn setup_server(
-            target: TargetChain,
-        ) -> (Option<tokio::task::JoinHandle<()>>, sequencer::Client) {
-            use warp::http::{Response, StatusCode};
+        mod chain_id_mismatch {
+            use super::*;
+            use std::sync::atomic::{AtomicUsize, Ordering};
             use warp::Filter;
 
-            // `TargetChain::Invalid` always uses the local server setup as the Sequencer
-            // won't return an invalid genesis block.
-            if std::env::var_os("SEQUENCER_TESTS_LIVE_API").is_some()
-                && target != TargetChain::Invalid
-            {
-                match target {
-                    TargetChain::Mainnet => (None, sequencer::Client::new(Chain::Mainnet).unwrap()),
-                    TargetChain::Testnet => (None, sequencer::Client::new(Chain::Testnet).unwrap()),
-                    // Escaped above already
-                    TargetChain::Invalid => unreachable!(),
-                }
-            } else {
-                #[derive(serde::Deserialize, serde::Serialize)]
-                #[serde(deny_unknown_fields)]
-                struct Params {
+            /// A Görli gateway configured as `chain`, and the number of transactions the gateway
+            /// received.
+            fn client(chain: Chain) -> (Client, Arc<AtomicUsize>) {
+                let received = Arc::new(AtomicUsize::new(0));
+                let received_clone = received.clone();
+                let genesis = warp::path!("feeder_gateway" / "get_block")
+                    .map(|| include_str!("../fixtures/sequencer/0.9.0/block/genesis.json"));
+                let add_transaction = warp::path!("gateway" / "add_transaction").map(move || {
+                    received_clone.fetch_add(1, Ordering::Relaxed);
+                    r#"{"code":"TRANSACTION_RECEIVED","transaction_hash":"0x01","address":"0x02"}"#
+                });
+                let (_, url) = serve_gateway(genesis.or(add_transaction));
+                let client =
+                    Client::with_config(SequencerConfig::new(url).with_chain(chain)).unwrap();
+                (client, received)
+            }
+
+            async fn deploy(client: &Client) -> Result<(), SequencerError> {
+                client
+                    .add_deploy_transaction(
+                        TransactionVersion::ZERO,
+                        ContractAddressSalt(StarkHash::ZERO),
+                        vec![],
+                        ContractDefinition {
+                            program: "".to_owned(),
+                            entry_points_by_type: HashMap::new(),
+                            abi: None,
+                        },
+                        None,
+                        None,
+                    )
+                    .await
+                    .map(|_| ())
+            }
+
+            #[tokio::test]
+            async fn deploy_is_refused() {
+                let (client, received) = client(Chain::Mainnet);
+
+                let error = deploy(&client).await.unwrap_err();
+                assert_matches!(
+                    &error,
+                    SequencerError::ChainIdMismatch {
+                        configured: Chain::Mainnet,
+                        gateway: Some(Chain::Testnet)
+                    }
+                );
+                assert_eq!(
+                    error.to_string(),
+                    "gateway serves Görli but Mainnet is configured"
+                );
+                assert_eq!(received.load(Ordering::Relaxed), 0);
+            }
+
+            #[tokio::test]
+            async fn matching_chain_is_submitted() {
+                let (client, received) = client(Chain::Testnet);
+
+                deploy(&client).await.unwrap();
+                deploy(&client).await.unwrap();
+                assert_eq!(received.load(Ordering::Relaxed), 2);
+            }
+
+            #[tokio::test]
+            async fn unavailable_chain_fails_the_write() {
+                let fetches = Arc::new(AtomicUsize::new(0));
+                let fetches_clone = fetches.clone();
+                let genesis = warp::path!("feeder_gateway" / "get_block").map(move || {
+                    let status = match fetches_clone.fetch_add(1, Ordering::Relaxed) {
+                        0 => http::StatusCode::SERVICE_UNAVAILABLE,
+                        _ => http::StatusCode::OK,
+                    };
+                    warp::reply::with_status(
+                        include_str!("../fixtures/sequencer/0.9.0/block/genesis.json"),
+                        status,
+                    )
+                });
+                let add_transaction = warp::path!("gateway" / "add_transaction").map(|| {
+                    r#"{"code":"TRANSACTION_RECEIVED","transaction_hash":"0x01","address":"0x02"}"#
+                });
+                let (_, url) = serve_gateway(genesis.or(add_transaction));
+                let client =
+                    Client::with_config(SequencerConfig::new(url).with_chain(Chain::Testnet))
+                        .unwrap();
+
+                let error = deploy(&client).await.unwrap_err();
+                assert!(
+                    !matches!(error, SequencerError::ChainIdMismatch { .. }),
+                    "{error:?}"
+                );
+                // The failure is not cached.
+                deploy(&client).await.unwrap();
+                assert_eq!(fetches.load(Ordering::Relaxed), 2);
+            }
+        }
+
+        mod already_received {
+            use super::*;
+            use warp::Filter;
+
+            /// A gateway which reports every transaction as already received.
+            fn client(transaction_hash: &'static str) -> (tokio::task::JoinHandle<()>, Client) {
+                let route = warp::any().map(move || {
+                    warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({
+                            "code": "StarknetErrorCode.DUPLICATED_TRANSACTION",
+                            "message": format!("Transaction with hash {transaction_hash} has already been received."),
+                        })),
+                        http::StatusCode::INTERNAL_SERVER_ERROR,
+                    )
+                });
+                let (jh, url) = serve_gateway(route);
+                (jh, Client::with_url(url).unwrap())
+            }
+
+            #[tokio::test]
+            async fn invoke() {
+                let (_jh, client) =
+                    client("0x389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b");
+
+                let response = client
+                    .add_invoke_transaction(
+                        TransactionVersion::ZERO,
+                        Fee(5444010076217u128.to_be_bytes().into()),
+                        vec![],
+                        None,
+                        ContractAddress::new_or_panic(starkhash!(
+                            "023371b227eaecd8e8920cd429357edddd2cd0f3fee6abaacca08d3ab82a7cdd"
+                        )),
+                        Some(EntryPoint(starkhash!(
+                            "015d40a3d6ca2ac30f4031e42be28da9b056fef9bb7357ac5e85627ee876e5ad"
+                        ))),
+                        vec![CallParam(starkhash!("01"))],
+                        None,
+                    )
+                    .await
+                    .unwrap();
+
+                assert_eq!(
+                    response,
+                    reply::add_transaction::InvokeResponse {
+                        code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                        transaction_hash: StarknetTransactionHash(starkhash!(
+                            "0389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"
+                        )),
+                    }
+                );
+            }
+
+            #[tokio::test]
+            async fn deploy_account_address_is_recomputed() {
+                let (_jh, client) =
+                    client("0x6dac1655b34e52a449cfe961188f7cc2b1496bcd36706cedf4935567be29d5b");
+
+                let json = include_str!(
+                    "../fixtures/sequencer/0.10.1/add_transaction/deploy_account_request.json"
+                );
+                let req = match serde_json::from_str(json).unwrap() {
+                    request::add_transaction::AddTransaction::DeployAccount(req) => req,
+                    _ => panic!("Request is not a DEPLOY_ACCOUNT"),
+                };
+
+                let response = client
+                    .add_deploy_account(
+                        req.version,
+                        req.max_fee,
+                        req.signature,
+                        req.nonce,
+                        req.contract_address_salt,
+                        req.class_hash,
+                        req.constructor_calldata,
+                    )
+                    .await
+                    .unwrap();
+
+                // Same as the address reported by the gateway in `test_deploy_account`.
+                assert_eq!(
+                    response.address,
+                    ContractAddress::new_or_panic(starkhash!(
+                        "04e574ea2abd76d3105b3d29de28af0c5a28b889aa465903080167f6b48b1acc"
+                    ))
+                );
+            }
+
+            #[tokio::test]
+            async fn other_errors_are_kept() {
+                let route = warp::any().map(|| {
+                    warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({
+                            "code": "StarknetErrorCode.TRANSACTION_FAILED",
+                            "message": "Transaction with hash 0x1 failed.",
+                        })),
+                        http::StatusCode::INTERNAL_SERVER_ERROR,
+                    )
+                });
+                let (_jh, url) = serve_gateway(route);
+                let client = Client::with_url(url).unwrap();
+
+                let error = client
+                    .add_invoke_transaction(
+                        TransactionVersion::ZERO,
+                        Fee(Default::default()),
+                        vec![],
+                        None,
+                        ContractAddress::new_or_panic(starkhash!("01")),
+                        None,
+                        vec![],
+                        None,
+                    )
+                    .await
+                    .unwrap_err();
+
+                assert_matches!(error, SequencerError::StarknetError(se) => {
+                    assert_eq!(se.code, StarknetErrorCode::TransactionFailed);
+                });
+            }
+        }
+    }
+
+    mod chain {
+        use crate::core::Chain;
+        use crate::sequencer;
+        use crate::sequencer::test_utils::serve_gateway;
+
+        #[derive(Copy, Clone, PartialEq, Eq)]
+        /// Used by [setup_server] to determine which block to return.
+        enum TargetChain {
+            Testnet,
+            Mainnet,
+            Invalid,
+        }
+
+        /// Creates a [sequencer::Client] whose Sequencer gateway is either the real Sequencer,
+        /// or a local warp server. A local server is created if:
+        /// - SEQUENCER_TESTS_LIVE_API is not set, __or__
+        /// - `target == TargetChain::Invalid`
+        ///
+        /// The local server only supports the `feeder_gateway/get_block?blockNumber=0` queries.
+        fn setup_server(
+            target: TargetChain,
+        ) -> (Option<tokio::task::JoinHandle<()>>, sequencer::Client) {
+            use warp::http::{Response, StatusCode};
+            use warp::Filter;
+
+            // `TargetChain::Invalid` always uses the local server setup as the Sequencer
+            // won't return an invalid genesis block.
+            if std::env::var_os("SEQUENCER_TESTS_LIVE_API").is_some()
+                && target != TargetChain::Invalid
+            {
+                match target {
+                    TargetChain::Mainnet => (None, sequencer::Client::new(Chain::Mainnet).unwrap()),
+                    TargetChain::Testnet => (None, sequencer::Client::new(Chain::Testnet).unwrap()),
+                    // Escaped above already
+                    TargetChain::Invalid => unreachable!(),
+                }
+            } else {
+                #[derive(serde::Deserialize, serde::Serialize)]
+                #[serde(deny_unknown_fields)]
+                struct Params {
                     #[serde(rename = "blockNumber")]
                     block_number: u64,
                 }
@@ -1893,12 +3516,8 @@ mod tests {
                             .unwrap(),
                     });
 
-                let (addr, serve_fut) = warp::serve(filter).bind_ephemeral(([127, 0, 0, 1], 0));
-                let server_handle = tokio::spawn(serve_fut);
-                let client = sequencer::Client::with_url(
-                    reqwest::Url::parse(&format!("http://{}", addr)).unwrap(),
-                )
-                .unwrap();
+                let (server_handle, url) = serve_gateway(filter);
+                let client = sequencer::Client::with_url(url).unwrap();
 
                 (Some(server_handle), client)
             }
@@ -1923,6 +3542,1106 @@ mod tests {
             let (_server_handle, sequencer) = setup_server(TargetChain::Invalid);
             sequencer.chain().await.unwrap_err();
         }
+
+        mod probe {
+            use super::*;
+
+            #[tokio::test]
+            async fn matching_chain() {
+                let (_server_handle, sequencer) = setup_server(TargetChain::Testnet);
+                sequencer.probe(Chain::Testnet).await.unwrap();
+            }
+
+            #[tokio::test]
+            async fn mismatched_chain() {
+                let (_server_handle, sequencer) = setup_server(TargetChain::Mainnet);
+                let error = sequencer.probe(Chain::Testnet).await.unwrap_err();
+                assert!(
+                    error.to_string().contains("network mismatch"),
+                    "unexpected error: {error}"
+                );
+            }
+
+            #[tokio::test]
+            async fn unreachable() {
+                // Nothing should be listening on this port.
+                let sequencer =
+                    sequencer::Client::with_url(reqwest::Url::parse("http://127.0.0.1:1").unwrap())
+                        .unwrap();
+                sequencer.probe(Chain::Testnet).await.unwrap_err();
+            }
+        }
+    }
+
+    mod proxy {
+        use super::*;
+        use warp::Filter;
+
+        /// A sequencer URL which cannot be resolved, so that requests only succeed if they
+        /// are routed through the proxy.
+        const UNRESOLVABLE_URL: &str = "http://sequencer.invalid/";
+
+        /// Serves as an HTTP proxy which replies to every request with a transaction status.
+        fn proxy_server() -> (tokio::task::JoinHandle<()>, Url) {
+            let route = warp::any().map(|| r#"{"tx_status": "RECEIVED"}"#);
+            serve_gateway(route)
+        }
+
+        #[tokio::test]
+        async fn requests_are_proxied() {
+            let (_jh, proxy_url) = proxy_server();
+            let config = SequencerConfig::new(Url::parse(UNRESOLVABLE_URL).unwrap())
+                .with_proxy(ProxyConfig::all(proxy_url));
+            let client = Client::with_config(config).unwrap();
+
+            let status = client.transaction_status(INVALID_TX_HASH).await.unwrap();
+            assert_eq!(status.tx_status, reply::Status::Received);
+        }
+
+        #[tokio::test]
+        async fn no_proxy_is_respected() {
+            let (_jh, proxy_url) = proxy_server();
+            let config = SequencerConfig::new(Url::parse(UNRESOLVABLE_URL).unwrap()).with_proxy(
+                ProxyConfig {
+                    no_proxy: vec!["sequencer.invalid".to_owned()],
+                    ..ProxyConfig::all(proxy_url)
+                },
+            );
+            let client = Client::with_config(config).unwrap();
+
+            client
+                .transaction_status(INVALID_TX_HASH)
+                .await
+                .unwrap_err();
+        }
+    }
+
+    mod simulate_transaction {
+        use super::*;
+        use crate::core::StarknetBlockTimestamp;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+        use warp::Filter;
+
+        #[tokio::test]
+        async fn block_timestamp_is_forwarded() {
+            let query = Arc::new(Mutex::new(HashMap::new()));
+            let captured = query.clone();
+            let route = warp::query::<HashMap<String, String>>().map(
+                move |params: HashMap<String, String>| {
+                    *captured.lock().unwrap() = params;
+                    warp::reply::json(&serde_json::json!({
+                        "trace": {"function_invocation": null, "signature": []},
+                        "fee_estimation": {"overall_fee": 2460, "gas_price": 41, "gas_usage": 60}
+                    }))
+                },
+            );
+            let (_jh, url) = serve_gateway(route);
+            let client = Client::with_url(url).unwrap();
+
+            let deploy = request::add_transaction::AddTransaction::Deploy(
+                request::add_transaction::Deploy {
+                    version: TransactionVersion::ZERO,
+                    contract_address_salt: ContractAddressSalt(StarkHash::ZERO),
+                    contract_definition: ContractDefinition {
+                        program: String::new(),
+                        entry_points_by_type: HashMap::new(),
+                        abi: None,
+                    },
+                    constructor_calldata: vec![],
+                },
+            );
+            let block_context = request::BlockContext {
+                timestamp: Some(StarknetBlockTimestamp::new_or_panic(1666000000)),
+            };
+            let simulated = client
+                .simulate_transaction(deploy, BlockId::Pending, block_context)
+                .await
+                .unwrap();
+
+            assert_eq!(simulated.fee_estimation.overall_fee, 2460);
+            let query = query.lock().unwrap();
+            assert_eq!(
+                query.get("blockTimestamp").map(String::as_str),
+                Some("1666000000")
+            );
+            assert_eq!(
+                query.get("blockNumber").map(String::as_str),
+                Some("pending")
+            );
+        }
+    }
+
+    mod timeout {
+        use super::*;
+        use std::time::Duration;
+        use warp::Filter;
+
+        #[tokio::test]
+        async fn is_reported_as_timeout() {
+            let route = warp::any().and_then(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, std::convert::Infallible>("{}")
+            });
+            let (_jh, url) = serve_gateway(route);
+            let config = SequencerConfig::new(url).with_timeout(Duration::from_millis(100));
+            let client = Client::with_config(config).unwrap();
+
+            let error = client.eth_contract_addresses().await.unwrap_err();
+            assert_matches!(
+                &error,
+                SequencerError::Timeout { elapsed, endpoint } => {
+                    assert!(*elapsed >= Duration::from_millis(100), "{:?}", elapsed);
+                    assert_eq!(*endpoint, "get_contract_addresses");
+                }
+            );
+
+            use crate::rpc::error::ServerErrorCode;
+            use jsonrpsee::{core::error::Error, types::error::CallError};
+            assert_matches!(
+                Error::from(error),
+                Error::Call(CallError::Custom(e)) => assert_eq!(e.code(), ServerErrorCode::GatewayTimeout.code())
+            );
+        }
+
+        /// A gateway which accepts deploys after `delay`.
+        fn slow_gateway(delay: Duration) -> Url {
+            let route = warp::any().and_then(move || async move {
+                tokio::time::sleep(delay).await;
+                Ok::<_, std::convert::Infallible>(
+                    r#"{"code":"TRANSACTION_RECEIVED","transaction_hash":"0x01","address":"0x02"}"#,
+                )
+            });
+            serve_gateway(route).1
+        }
+
+        async fn deploy(
+            client: &Client,
+            timeout: Option<Duration>,
+        ) -> Result<reply::add_transaction::DeployResponse, SequencerError> {
+            client
+                .add_deploy_transaction(
+                    TransactionVersion::ZERO,
+                    ContractAddressSalt(StarkHash::ZERO),
+                    vec![],
+                    ContractDefinition {
+                        program: "".to_owned(),
+                        entry_points_by_type: std::collections::HashMap::new(),
+                        abi: None,
+                    },
+                    None,
+                    timeout,
+                )
+                .await
+        }
+
+        #[tokio::test]
+        async fn override_supersedes_configured_timeout() {
+            let url = slow_gateway(Duration::from_millis(300));
+            let config = SequencerConfig::new(url).with_timeout(Duration::from_millis(100));
+            let client = Client::with_config(config).unwrap();
+
+            assert_matches!(
+                deploy(&client, None).await,
+                Err(SequencerError::Timeout { .. })
+            );
+            deploy(&client, Some(Duration::from_secs(5))).await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn override_is_clamped() {
+            let url = slow_gateway(Duration::from_secs(5));
+            let config = SequencerConfig::new(url)
+                .with_timeout(Duration::from_millis(100))
+                .with_max_timeout_override(Duration::from_millis(200));
+            let client = Client::with_config(config).unwrap();
+
+            let error = deploy(&client, Some(Duration::from_secs(60)))
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::Timeout { elapsed, endpoint } => {
+                    assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+                    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+                    assert_eq!(endpoint, "add_transaction");
+                }
+            );
+        }
+    }
+
+    mod estimate_fee_bulk {
+        use super::*;
+        use std::collections::HashMap;
+        use warp::Filter;
+
+        const FACTORY: ContractAddress = ContractAddress::new_or_panic(starkhash!("0fac"));
+
+        fn deploy_factory() -> request::add_transaction::AddTransaction {
+            request::add_transaction::AddTransaction::Deploy(request::add_transaction::Deploy {
+                version: TransactionVersion::ZERO,
+                contract_address_salt: ContractAddressSalt(StarkHash::ZERO),
+                contract_definition: ContractDefinition {
+                    program: String::new(),
+                    entry_points_by_type: HashMap::new(),
+                    abi: None,
+                },
+                constructor_calldata: vec![],
+            })
+        }
+
+        fn call_factory() -> request::add_transaction::AddTransaction {
+            request::add_transaction::AddTransaction::Invoke(
+                request::add_transaction::InvokeFunction {
+                    version: TransactionVersion::ZERO,
+                    max_fee: Fee(Default::default()),
+                    signature: vec![],
+                    nonce: None,
+                    contract_address: FACTORY,
+                    entry_point_selector: Some(EntryPoint(starkhash!("01"))),
+                    calldata: vec![],
+                    resource_bounds: None,
+                },
+            )
+        }
+
+        /// A gateway which executes the transactions in order, so that an invoke of
+        /// [FACTORY] only succeeds after it was deployed.
+        fn gateway() -> Url {
+            let route = warp::path!("feeder_gateway" / "estimate_fee_bulk")
+                .and(warp::body::json())
+                .map(|transactions: Vec<serde_json::Value>| {
+                    let mut deployed = false;
+                    let mut estimates = Vec::new();
+                    for tx in transactions {
+                        match tx["type"].as_str() {
+                            Some("DEPLOY") => {
+                                deployed = true;
+                                estimates.push(serde_json::json!({
+                                    "overall_fee": 1000, "gas_price": 10, "gas_usage": 100, "unit": "wei"
+                                }));
+                            }
+                            Some("INVOKE_FUNCTION") if deployed => {
+                                estimates.push(serde_json::json!({
+                                    "overall_fee": 200, "gas_price": 10, "gas_usage": 20, "unit": "wei"
+                                }));
+                            }
+                            _ => {
+                                let error = StarknetErrorCode::UninitializedContract.into_response();
+                                return http::response::Builder::new()
+                                    .status(error.1)
+                                    .body(error.0);
+                            }
+                        }
+                    }
+                    http::response::Builder::new()
+                        .status(200)
+                        .body(serde_json::to_string(&estimates).unwrap())
+                });
+            serve_gateway(route).1
+        }
+
+        #[tokio::test]
+        async fn dependent_transactions() {
+            let client = Client::with_url(gateway()).unwrap();
+
+            let estimates = client
+                .estimate_fee_bulk(vec![deploy_factory(), call_factory()], BlockId::Pending)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                estimates,
+                vec![
+                    reply::FeeEstimate {
+                        overall_fee: 1000,
+                        l1_gas_consumed: 100,
+                        l1_gas_price: 10,
+                        l2_gas_consumed: None,
+                        l2_gas_price: None,
+                        unit: reply::FeeUnit::Wei,
+                    },
+                    reply::FeeEstimate {
+                        overall_fee: 200,
+                        l1_gas_consumed: 20,
+                        l1_gas_price: 10,
+                        l2_gas_consumed: None,
+                        l2_gas_price: None,
+                        unit: reply::FeeUnit::Wei,
+                    },
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn dependency_out_of_order() {
+            let client = Client::with_url(gateway()).unwrap();
+
+            let error = client
+                .estimate_fee_bulk(vec![call_factory(), deploy_factory()], BlockId::Pending)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::UninitializedContract)
+            );
+        }
+    }
+
+    mod http2 {
+        use super::*;
+        use warp::Filter;
+
+        /// Returns the HTTP version of a response from a mock gateway, which serves both
+        /// HTTP/1.1 and HTTP/2.
+        async fn negotiated_version(config: impl FnOnce(Url) -> SequencerConfig) -> http::Version {
+            let route = warp::any().map(warp::reply);
+            let (_jh, url) = serve_gateway(route);
+            let client = Client::with_config(config(url.clone())).unwrap();
+            client.inner.get(url).send().await.unwrap().version()
+        }
+
+        #[tokio::test]
+        async fn disabled_by_default() {
+            let version = negotiated_version(SequencerConfig::new).await;
+            assert_eq!(version, http::Version::HTTP_11);
+        }
+
+        #[tokio::test]
+        async fn enabled() {
+            let version = negotiated_version(|url| {
+                SequencerConfig::new(url).with_http2(Http2Config {
+                    prior_knowledge: true,
+                    keep_alive_interval: Some(std::time::Duration::from_secs(10)),
+                })
+            })
+            .await;
+            assert_eq!(version, http::Version::HTTP_2);
+        }
+    }
+
+    mod min_tls_version {
+        use super::*;
+        use reqwest::tls::Version;
+
+        #[test]
+        fn is_applied() {
+            let config = SequencerConfig::new(Url::parse("https://localhost/").unwrap())
+                .with_min_tls_version(Version::TLS_1_2);
+            let client = Client::with_config(config).unwrap();
+
+            assert_eq!(
+                client.config_summary().min_tls_version.as_deref(),
+                Some("TLS 1.2")
+            );
+        }
+
+        #[test]
+        fn unsupported_by_backend_is_an_error() {
+            // `native-tls` cannot enforce TLS 1.3, which must not silently allow older versions.
+            let config = SequencerConfig::new(Url::parse("https://localhost/").unwrap())
+                .with_min_tls_version(Version::TLS_1_3);
+            Client::with_config(config).unwrap_err();
+        }
+    }
+
+    mod session_affinity {
+        use super::*;
+        use std::sync::Mutex;
+        use warp::Filter;
+
+        /// The `x-backend` and `cookie` headers of each received request.
+        type Received = Arc<Mutex<Vec<(Option<String>, Option<String>)>>>;
+
+        /// A gateway which assigns every request to the `b1` instance.
+        fn gateway() -> (Url, Received) {
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let received_clone = received.clone();
+            let route = warp::header::optional::<String>("x-backend")
+                .and(warp::header::optional::<String>("cookie"))
+                .map(move |backend, cookie| {
+                    received_clone.lock().unwrap().push((backend, cookie));
+                    let reply = warp::reply::json(&serde_json::json!({
+                        "Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e",
+                        "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                    }));
+                    let reply = warp::reply::with_header(reply, "x-backend", "b1");
+                    warp::reply::with_header(reply, "set-cookie", "backend=b1; Path=/; HttpOnly")
+                });
+            let (_jh, url) = serve_gateway(route);
+
+            (url, received)
+        }
+
+        async fn send_twice(config: SequencerConfig) {
+            let client = Client::with_config(config).unwrap();
+            client.eth_contract_addresses().await.unwrap();
+            client.clone().eth_contract_addresses().await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn header_is_echoed() {
+            let (url, received) = gateway();
+            let affinity =
+                SessionAffinity::Header(reqwest::header::HeaderName::from_static("x-backend"));
+            send_twice(SequencerConfig::new(url).with_session_affinity(affinity)).await;
+
+            let received = received.lock().unwrap();
+            assert_eq!(received[0], (None, None));
+            assert_eq!(received[1], (Some("b1".to_owned()), None));
+        }
+
+        #[tokio::test]
+        async fn cookie_is_replayed() {
+            let (url, received) = gateway();
+            let affinity = SessionAffinity::Cookie("backend".to_owned());
+            send_twice(SequencerConfig::new(url).with_session_affinity(affinity)).await;
+
+            let received = received.lock().unwrap();
+            assert_eq!(received[0], (None, None));
+            assert_eq!(received[1], (None, Some("backend=b1".to_owned())));
+        }
+
+        #[tokio::test]
+        async fn disabled_by_default() {
+            let (url, received) = gateway();
+            send_twice(SequencerConfig::new(url)).await;
+
+            let received = received.lock().unwrap();
+            assert_eq!(received[1], (None, None));
+        }
+    }
+
+    mod fallback {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use warp::Filter;
+
+        /// A gateway at `prefix` which responds to `get_contract_addresses` with `response`, and
+        /// the number of requests it received.
+        fn gateway(prefix: &'static str, response: (String, u16)) -> (Url, Arc<AtomicUsize>) {
+            let hits = Arc::new(AtomicUsize::new(0));
+            let hits_clone = hits.clone();
+            let route = warp::path(prefix)
+                .and(warp::path!("feeder_gateway" / "get_contract_addresses"))
+                .map(move || {
+                    hits_clone.fetch_add(1, Ordering::Relaxed);
+                    let (body, status) = response.clone();
+                    http::response::Builder::new().status(status).body(body)
+                });
+            let (_jh, url) = serve_gateway(route);
+
+            (url.join(prefix).unwrap(), hits)
+        }
+
+        fn addresses() -> (String, u16) {
+            (
+                r#"{"Starknet":"0xde29d060d45901fb19ed6c6e959eb22d8626708e","GpsStatementVerifier":"0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"}"#.to_owned(),
+                200,
+            )
+        }
+
+        fn client(primary: Url, secondary: Url) -> Client {
+            Client::with_config(SequencerConfig::new(primary).with_fallback_url(secondary)).unwrap()
+        }
+
+        #[tokio::test]
+        async fn secondary_serves_when_primary_is_unavailable() {
+            let (primary, primary_hits) = gateway("primary", (String::new(), 503));
+            let (secondary, secondary_hits) = gateway("secondary", addresses());
+
+            let (result, gateway) =
+                served_by(client(primary, secondary.clone()).eth_contract_addresses()).await;
+            result.unwrap();
+
+            assert_eq!(gateway, Some(Gateway::Fallback(secondary)));
+            assert_eq!(primary_hits.load(Ordering::Relaxed), 1);
+            assert_eq!(secondary_hits.load(Ordering::Relaxed), 1);
+        }
+
+        #[tokio::test]
+        async fn secondary_is_unused_when_primary_succeeds() {
+            let (primary, primary_hits) = gateway("primary", addresses());
+            let (secondary, secondary_hits) = gateway("secondary", addresses());
+
+            let (result, gateway) =
+                served_by(client(primary, secondary).eth_contract_addresses()).await;
+            result.unwrap();
+
+            assert_eq!(gateway, Some(Gateway::Primary));
+            assert_eq!(primary_hits.load(Ordering::Relaxed), 1);
+            assert_eq!(secondary_hits.load(Ordering::Relaxed), 0);
+        }
+
+        #[tokio::test]
+        async fn non_retryable_error_is_not_sent_to_secondary() {
+            let (primary, primary_hits) =
+                gateway("primary", StarknetErrorCode::BlockNotFound.into_response());
+            let (secondary, secondary_hits) = gateway("secondary", addresses());
+
+            let error = client(primary, secondary)
+                .eth_contract_addresses()
+                .await
+                .unwrap_err();
+            assert_matches!(error, SequencerError::StarknetError(_));
+
+            assert_eq!(primary_hits.load(Ordering::Relaxed), 1);
+            assert_eq!(secondary_hits.load(Ordering::Relaxed), 0);
+        }
+
+        mod submission {
+            use super::*;
+            use crate::starkhash;
+            use std::time::Duration;
+
+            /// A gateway at `prefix` which responds to `add_transaction` with `response` after
+            /// `delay`, and the number of submissions it received.
+            fn gateway(
+                prefix: &'static str,
+                delay: Duration,
+                response: (String, u16),
+            ) -> (Url, Arc<AtomicUsize>) {
+                let hits = Arc::new(AtomicUsize::new(0));
+                let hits_clone = hits.clone();
+                let route = warp::path(prefix)
+                    .and(warp::path!("gateway" / "add_transaction"))
+                    .and_then(move || {
+                        hits_clone.fetch_add(1, Ordering::Relaxed);
+                        let (body, status) = response.clone();
+                        async move {
+                            tokio::time::sleep(delay).await;
+                            Ok::<_, std::convert::Infallible>(
+                                http::response::Builder::new().status(status).body(body),
+                            )
+                        }
+                    });
+                let (_jh, url) = serve_gateway(route);
+
+                (url.join(prefix).unwrap(), hits)
+            }
+
+            fn received() -> (String, u16) {
+                (
+                    r#"{"code":"TRANSACTION_RECEIVED","transaction_hash":"0x01"}"#.to_owned(),
+                    200,
+                )
+            }
+
+            async fn submit(client: Client) -> Result<(), SequencerError> {
+                client
+                    .add_invoke_transaction(
+                        TransactionVersion::ZERO,
+                        Fee(Default::default()),
+                        vec![],
+                        None,
+                        ContractAddress::new_or_panic(starkhash!("01")),
+                        Some(EntryPoint(starkhash!("01"))),
+                        vec![],
+                        None,
+                    )
+                    .await
+                    .map(|_| ())
+            }
+
+            #[tokio::test]
+            async fn falls_back_when_primary_is_unreachable() {
+                let (primary, primary_hits) =
+                    gateway("primary", Duration::ZERO, (String::new(), 503));
+                let (secondary, secondary_hits) = gateway("secondary", Duration::ZERO, received());
+
+                let (result, gateway) = served_by(submit(client(primary, secondary.clone()))).await;
+                result.unwrap();
+
+                assert_eq!(gateway, Some(Gateway::Fallback(secondary)));
+                assert_eq!(primary_hits.load(Ordering::Relaxed), 1);
+                assert_eq!(secondary_hits.load(Ordering::Relaxed), 1);
+            }
+
+            #[tokio::test]
+            async fn is_not_resent_after_a_timeout() {
+                let (primary, primary_hits) =
+                    gateway("primary", Duration::from_millis(300), received());
+                let (secondary, secondary_hits) = gateway("secondary", Duration::ZERO, received());
+                let client = Client::with_config(
+                    SequencerConfig::new(primary)
+                        .with_fallback_url(secondary)
+                        .with_timeout(Duration::from_millis(100)),
+                )
+                .unwrap();
+
+                let error = submit(client).await.unwrap_err();
+                assert_matches!(error, SequencerError::Timeout { .. });
+
+                // The primary may still accept the submission.
+                assert_eq!(primary_hits.load(Ordering::Relaxed), 1);
+                assert_eq!(secondary_hits.load(Ordering::Relaxed), 0);
+            }
+
+            #[tokio::test]
+            async fn is_not_resent_after_an_error_page() {
+                let (primary, primary_hits) = gateway(
+                    "primary",
+                    Duration::ZERO,
+                    ("upstream failed mid-request".to_owned(), 502),
+                );
+                let (secondary, secondary_hits) = gateway("secondary", Duration::ZERO, received());
+
+                submit(client(primary, secondary)).await.unwrap_err();
+
+                assert_eq!(primary_hits.load(Ordering::Relaxed), 1);
+                assert_eq!(secondary_hits.load(Ordering::Relaxed), 0);
+            }
+        }
+    }
+
+    mod middleware {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use warp::Filter;
+
+        #[derive(Debug)]
+        struct InjectHeader;
+
+        impl RequestMiddleware for InjectHeader {
+            fn on_request(&self, request: &mut reqwest::Request) -> anyhow::Result<()> {
+                request.headers_mut().insert(
+                    reqwest::header::AUTHORIZATION,
+                    reqwest::header::HeaderValue::from_static("Bearer secret"),
+                );
+                Ok(())
+            }
+        }
+
+        #[derive(Debug)]
+        struct Reject;
+
+        impl RequestMiddleware for Reject {
+            fn on_request(&self, _: &mut reqwest::Request) -> anyhow::Result<()> {
+                anyhow::bail!("Not allowed")
+            }
+        }
+
+        /// Serves contract addresses to requests which are authorized by [InjectHeader]
+        /// and counts them.
+        fn gateway() -> (Url, Arc<AtomicUsize>) {
+            let received = Arc::new(AtomicUsize::new(0));
+            let received_clone = received.clone();
+            let route = warp::header::exact("authorization", "Bearer secret").map(move || {
+                received_clone.fetch_add(1, Ordering::Relaxed);
+                warp::reply::json(&serde_json::json!({
+                    "Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e",
+                    "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                }))
+            });
+            let (_jh, url) = serve_gateway(route);
+
+            (url, received)
+        }
+
+        #[tokio::test]
+        async fn injects_header() {
+            let (url, received) = gateway();
+            let client =
+                Client::with_config(SequencerConfig::new(url).with_middleware(InjectHeader))
+                    .unwrap();
+
+            client.eth_contract_addresses().await.unwrap();
+            assert_eq!(received.load(Ordering::Relaxed), 1);
+        }
+
+        #[tokio::test]
+        async fn rejects() {
+            let (url, received) = gateway();
+            let client = Client::with_config(
+                SequencerConfig::new(url)
+                    .with_middleware(InjectHeader)
+                    .with_middleware(Reject),
+            )
+            .unwrap();
+
+            let error = client.eth_contract_addresses().await.unwrap_err();
+            assert_matches!(error, SequencerError::RequestRejected(e) => assert_eq!(e.to_string(), "Not allowed"));
+            assert_eq!(received.load(Ordering::Relaxed), 0);
+        }
+    }
+
+    mod headers {
+        use super::*;
+        use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+        use warp::Filter;
+
+        fn tenant() -> HeaderName {
+            HeaderName::from_static("x-tenant")
+        }
+
+        /// Accepts deploys which are authorized and sent on behalf of the `override` tenant.
+        fn gateway() -> Url {
+            let route = warp::header::exact("authorization", "Bearer secret")
+                .and(warp::header::exact("x-tenant", "override"))
+                .map(|| {
+                    r#"{"code":"TRANSACTION_RECEIVED","transaction_hash":"0x01","address":"0x02"}"#
+                });
+            serve_gateway(route).1
+        }
+
+        fn client() -> Client {
+            let config = SequencerConfig::new(gateway())
+                .with_header(AUTHORIZATION, HeaderValue::from_static("Bearer secret"))
+                .with_header(tenant(), HeaderValue::from_static("default"));
+            Client::with_config(config).unwrap()
+        }
+
+        fn tenant_override() -> HeaderMap {
+            HeaderMap::from_iter([(tenant(), HeaderValue::from_static("override"))])
+        }
+
+        async fn deploy(
+            client: &Client,
+        ) -> Result<reply::add_transaction::DeployResponse, SequencerError> {
+            client
+                .add_deploy_transaction(
+                    TransactionVersion::ZERO,
+                    ContractAddressSalt(StarkHash::ZERO),
+                    vec![],
+                    ContractDefinition {
+                        program: "".to_owned(),
+                        entry_points_by_type: std::collections::HashMap::new(),
+                        abi: None,
+                    },
+                    None,
+                    None,
+                )
+                .await
+        }
+
+        #[tokio::test]
+        async fn are_sent_with_deploys() {
+            let client = client();
+
+            // Only the default tenant is sent.
+            deploy(&client).await.unwrap_err();
+            deploy(&client.with_headers(tenant_override()))
+                .await
+                .unwrap();
+        }
+
+        #[tokio::test]
+        async fn are_redacted_in_logs() {
+            #[derive(Clone)]
+            struct LogWriter(Arc<std::sync::Mutex<Vec<u8>>>);
+
+            impl std::io::Write for LogWriter {
+                fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                    self.0.lock().unwrap().extend_from_slice(buf);
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> std::io::Result<()> {
+                    Ok(())
+                }
+            }
+
+            let logs = LogWriter(Default::default());
+            let writer = logs.clone();
+            let subscriber = tracing_subscriber::fmt()
+                .with_writer(move || writer.clone())
+                .with_ansi(false)
+                .with_max_level(tracing::Level::DEBUG)
+                .finish();
+            let _guard = tracing::subscriber::set_default(subscriber);
+
+            let mut headers = tenant_override();
+            headers.insert(
+                reqwest::header::PROXY_AUTHORIZATION,
+                HeaderValue::from_static("Basic hunter2"),
+            );
+            deploy(&client().with_headers(headers)).await.unwrap();
+
+            let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+            let sent = logs
+                .lines()
+                .find(|line| line.contains("Sending request to gateway"))
+                .unwrap();
+            assert!(sent.contains(r#""authorization": "<redacted>""#), "{sent}");
+            assert!(
+                sent.contains(r#""proxy-authorization": "<redacted>""#),
+                "{sent}"
+            );
+            assert!(sent.contains(r#""x-tenant": "override""#), "{sent}");
+            assert!(!logs.contains("secret"), "{logs}");
+            assert!(!logs.contains("hunter2"), "{logs}");
+        }
+    }
+
+    mod correlation_id {
+        use super::*;
+        use crate::starkhash;
+        use reqwest::header::HeaderValue;
+        use warp::Filter;
+
+        /// Accepts a deploy and reports it as accepted, recording the method and correlation
+        /// id of each request.
+        fn gateway() -> (Url, Arc<std::sync::Mutex<Vec<(String, Option<String>)>>>) {
+            let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let recorded = requests.clone();
+            let route = warp::path!(String / String)
+                .and(warp::header::optional::<String>(CORRELATION_ID_HEADER))
+                .map(move |_gateway, method: String, id| {
+                    let reply = match method.as_str() {
+                        "add_transaction" => {
+                            r#"{"code":"TRANSACTION_RECEIVED","transaction_hash":"0x01","address":"0x02"}"#
+                        }
+                        _ => r#"{"tx_status":"ACCEPTED_ON_L2"}"#,
+                    };
+                    recorded.lock().unwrap().push((method, id));
+                    reply
+                });
+            let (_, url) = serve_gateway(route);
+
+            (url, requests)
+        }
+
+        fn client(url: Url) -> Client {
+            Client::with_config(SequencerConfig::new(url).with_correlation_ids()).unwrap()
+        }
+
+        fn deploy() -> request::add_transaction::Deploy {
+            request::add_transaction::Deploy {
+                version: TransactionVersion::ZERO,
+                contract_address_salt: ContractAddressSalt(StarkHash::ZERO),
+                constructor_calldata: vec![],
+                contract_definition: ContractDefinition {
+                    program: "".to_owned(),
+                    entry_points_by_type: std::collections::HashMap::new(),
+                    abi: None,
+                },
+            }
+        }
+
+        #[tokio::test]
+        async fn not_sent_by_default() {
+            let (url, requests) = gateway();
+            let client = Client::with_url(url).unwrap();
+
+            client
+                .with_correlation_id(HeaderValue::from_static("status-1"))
+                .transaction_status(StarknetTransactionHash(starkhash!("01")))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                *requests.lock().unwrap(),
+                vec![("get_transaction_status".to_owned(), None)]
+            );
+        }
+
+        #[tokio::test]
+        async fn unique_per_request() {
+            let (url, requests) = gateway();
+            let client = client(url);
+
+            for _ in 0..2 {
+                client
+                    .transaction_status(StarknetTransactionHash(starkhash!("01")))
+                    .await
+                    .unwrap();
+            }
+
+            let requests = requests.lock().unwrap();
+            let ids = requests
+                .iter()
+                .map(|(_, id)| id.clone().unwrap())
+                .collect::<Vec<_>>();
+            assert!(ids[0].starts_with("get_transaction_status-"), "{ids:?}");
+            assert_ne!(ids[0], ids[1]);
+        }
+
+        #[tokio::test]
+        async fn correlates_deploy_submission_and_status_polls() {
+            let (url, requests) = gateway();
+            let client = client(url);
+
+            for _ in 0..2 {
+                deploy::deploy_and_await(&client, deploy(), None, &deploy::AwaitOptions::default())
+                    .await
+                    .unwrap();
+            }
+            client
+                .transaction_status(StarknetTransactionHash(starkhash!("01")))
+                .await
+                .unwrap();
+
+            let requests = requests.lock().unwrap();
+            let methods = requests
+                .iter()
+                .map(|(method, _)| method.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                methods,
+                vec![
+                    "add_transaction",
+                    "get_transaction_status",
+                    "add_transaction",
+                    "get_transaction_status",
+                    "get_transaction_status"
+                ]
+            );
+            let ids = requests
+                .iter()
+                .map(|(_, id)| id.clone().unwrap())
+                .collect::<Vec<_>>();
+            // Each deploy is its own operation, the unrelated poll is not part of either.
+            assert!(ids[0].starts_with("deploy-"), "{ids:?}");
+            assert_eq!(ids[0], ids[1]);
+            assert_eq!(ids[2], ids[3]);
+            assert_ne!(ids[1], ids[2]);
+            assert_ne!(ids[3], ids[4]);
+            assert_ne!(ids[0], ids[4]);
+        }
+
+        #[tokio::test]
+        async fn explicit_id_supersedes_the_operation() {
+            let (url, requests) = gateway();
+            let client = client(url);
+
+            deploy::deploy_and_await(
+                &client.with_correlation_id(HeaderValue::from_static("deploy-a")),
+                deploy(),
+                None,
+                &deploy::AwaitOptions::default(),
+            )
+            .await
+            .unwrap();
+
+            let requests = requests.lock().unwrap();
+            assert!(
+                requests
+                    .iter()
+                    .all(|(_, id)| id.as_deref() == Some("deploy-a")),
+                "{requests:?}"
+            );
+        }
+    }
+
+    mod response_decompression {
+        use super::*;
+        use warp::Filter;
+
+        #[tokio::test]
+        async fn gzip_response_is_decompressed() {
+            use std::io::Write;
+
+            let body = r#"{"Starknet":"0xde29d060d45901fb19ed6c6e959eb22d8626708e","GpsStatementVerifier":"0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"}"#;
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body.as_bytes()).unwrap();
+            let compressed = encoder.finish().unwrap();
+
+            let route = warp::any()
+                .and(warp::header::<String>("accept-encoding"))
+                .map(move |accept_encoding: String| {
+                    assert!(accept_encoding.contains("gzip"), "{}", accept_encoding);
+                    assert!(accept_encoding.contains("br"), "{}", accept_encoding);
+                    warp::http::Response::builder()
+                        .header("content-encoding", "gzip")
+                        .body(compressed.clone())
+                        .unwrap()
+                });
+            let (_jh, url) = serve_gateway(route);
+            let client = Client::with_url(url).unwrap();
+            let addresses = client.eth_contract_addresses().await.unwrap();
+            assert_eq!(
+                addresses.starknet.0,
+                web3::types::H160(hex_literal::hex!(
+                    "de29d060d45901fb19ed6c6e959eb22d8626708e"
+                ))
+            );
+        }
+    }
+
+    mod request_compression {
+        use super::*;
+        use crate::{
+            core::{CallParam, EntryPoint},
+            starkhash,
+        };
+        use std::sync::{Arc, Mutex};
+        use warp::Filter;
+
+        /// Returns the `Content-Encoding` header and the decoded JSON body of the request
+        /// submitted by an invoke transaction.
+        async fn submitted_request(
+            config: impl FnOnce(Url) -> SequencerConfig,
+        ) -> (Option<String>, serde_json::Value) {
+            let received = Arc::new(Mutex::new(None));
+            let received_clone = received.clone();
+            let route = warp::any()
+                .and(warp::header::optional::<String>("content-encoding"))
+                .and(warp::body::bytes())
+                .map(move |encoding: Option<String>, body: bytes::Bytes| {
+                    *received_clone.lock().unwrap() = Some((encoding, body));
+                    warp::reply::json(&serde_json::json!({
+                        "code": "TRANSACTION_RECEIVED",
+                        "transaction_hash": "0x389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"
+                    }))
+                });
+            let (_jh, url) = serve_gateway(route);
+            let client = Client::with_config(config(url)).unwrap();
+            client
+                .add_invoke_transaction(
+                    TransactionVersion::ZERO,
+                    Fee(5444010076217u128.to_be_bytes().into()),
+                    vec![],
+                    None,
+                    ContractAddress::new_or_panic(starkhash!(
+                        "023371b227eaecd8e8920cd429357edddd2cd0f3fee6abaacca08d3ab82a7cdd"
+                    )),
+                    Some(EntryPoint(starkhash!(
+                        "015d40a3d6ca2ac30f4031e42be28da9b056fef9bb7357ac5e85627ee876e5ad"
+                    ))),
+                    vec![CallParam(starkhash!("01"))],
+                    None,
+                )
+                .await
+                .unwrap();
+
+            let (encoding, body) = received.lock().unwrap().take().unwrap();
+            let body = match encoding.as_deref() {
+                Some("gzip") => {
+                    use std::io::Read;
+
+                    let mut decoded = Vec::new();
+                    flate2::read::GzDecoder::new(body.as_ref())
+                        .read_to_end(&mut decoded)
+                        .unwrap();
+                    decoded
+                }
+                _ => body.to_vec(),
+            };
+            (encoding, serde_json::from_slice(&body).unwrap())
+        }
+
+        #[tokio::test]
+        async fn large_body_is_compressed() {
+            let (encoding, compressed_body) =
+                submitted_request(|url| SequencerConfig::new(url).with_request_compression(0))
+                    .await;
+            assert_eq!(encoding.as_deref(), Some("gzip"));
+
+            let (encoding, body) = submitted_request(SequencerConfig::new).await;
+            assert_eq!(encoding, None);
+            assert_eq!(compressed_body, body);
+            assert_eq!(body["type"], "INVOKE_FUNCTION");
+        }
+
+        #[tokio::test]
+        async fn small_body_is_not_compressed() {
+            let (encoding, _) = submitted_request(|url| {
+                SequencerConfig::new(url).with_request_compression(1024 * 1024)
+            })
+            .await;
+            assert_eq!(encoding, None);
+        }
     }
 
     mod metrics {
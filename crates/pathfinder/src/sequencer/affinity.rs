@@ -0,0 +1,66 @@
+//! Pinning the [Client](super::Client) to a single gateway instance behind a load balancer.
+use std::sync::Mutex;
+
+use reqwest::header::{HeaderName, HeaderValue, COOKIE, SET_COOKIE};
+
+/// How a load balancer assigns requests to a gateway instance.
+///
+/// Once a response carries an assignment, all subsequent requests of the [Client](super::Client)
+/// and of its clones are sent with it. This keeps e.g. polling the status of a just submitted
+/// transaction consistent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SessionAffinity {
+    /// The header is echoed as is.
+    Header(HeaderName),
+    /// The cookie with this name is sent back in the `Cookie` header.
+    Cookie(String),
+}
+
+/// The captured [SessionAffinity] assignment.
+#[derive(Debug)]
+pub(super) struct Pin {
+    affinity: SessionAffinity,
+    value: Mutex<Option<HeaderValue>>,
+}
+
+impl Pin {
+    pub fn new(affinity: SessionAffinity) -> Self {
+        Self {
+            affinity,
+            value: Mutex::new(None),
+        }
+    }
+
+    /// Adds the captured assignment to `request`, if any.
+    pub fn apply(&self, request: &mut reqwest::Request) {
+        let value = match self.value.lock().unwrap().clone() {
+            Some(value) => value,
+            None => return,
+        };
+
+        let name = match &self.affinity {
+            SessionAffinity::Header(name) => name.clone(),
+            SessionAffinity::Cookie(_) => COOKIE,
+        };
+        request.headers_mut().insert(name, value);
+    }
+
+    /// Captures the assignment carried by `response`, replacing the previous one.
+    pub fn capture(&self, response: &reqwest::Response) {
+        let value = match &self.affinity {
+            SessionAffinity::Header(name) => response.headers().get(name).cloned(),
+            SessionAffinity::Cookie(name) => response
+                .headers()
+                .get_all(SET_COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .filter_map(|value| value.split(';').next())
+                .find(|pair| matches!(pair.split_once('='), Some((key, _)) if key.trim() == name))
+                .and_then(|pair| HeaderValue::from_str(pair.trim()).ok()),
+        };
+
+        if let Some(value) = value {
+            *self.value.lock().unwrap() = Some(value);
+        }
+    }
+}
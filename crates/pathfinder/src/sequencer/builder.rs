@@ -13,16 +13,87 @@
 use crate::{
     core::{ClassHash, ContractAddress, StarknetTransactionHash, StorageAddress},
     sequencer::{
-        error::SequencerError,
+        affinity,
+        config::redacted_headers,
+        error::{ErrorAlerts, SequencerError},
         metrics::{with_metrics, BlockTag, RequestMetadata},
+        RequestMiddleware, RetryPolicies, RetryPolicy, CORRELATION_ID_HEADER,
     },
 };
+use std::sync::Arc;
 
 /// A Sequencer Request builder.
 pub struct Request<'a, S: RequestState> {
     state: S,
     url: reqwest::Url,
     client: &'a reqwest::Client,
+    settings: &'a Settings,
+    /// Sent in addition to the [default headers](Settings::default_headers), superseding them.
+    headers: &'a reqwest::header::HeaderMap,
+}
+
+/// Client wide settings which apply to every [Request].
+#[derive(Debug, Default)]
+pub struct Settings {
+    /// `POST` bodies larger than this many bytes are gzip compressed.
+    pub compression_threshold: Option<usize>,
+    /// StarkNet errors which operators are alerted about.
+    pub alerts: ErrorAlerts,
+    /// Run on every request before it is sent.
+    pub middleware: Vec<Arc<dyn RequestMiddleware>>,
+    /// The gateway instance requests are pinned to.
+    pub affinity: Option<affinity::Pin>,
+    /// Overrides of [retry_condition] for specific errors.
+    pub retry_policies: RetryPolicies,
+    /// Backup gateways, in the order they are tried.
+    pub fallbacks: Vec<Fallback>,
+    /// Sent with every request, unless the request sets a header of the same name.
+    pub default_headers: reqwest::header::HeaderMap,
+    /// Receiving a response body larger than this many bytes fails with
+    /// [SequencerError::ResponseTooLarge]. Unlimited if not set.
+    pub max_response_size: Option<usize>,
+    /// Attempts taking at least this long are traced with their [RequestTimings].
+    pub slow_request_threshold: Option<std::time::Duration>,
+    /// Fail every request with [SequencerError::OfflineMode] instead of sending it.
+    pub offline: bool,
+    /// Tag every request with a [correlation id](CORRELATION_ID_HEADER), which is stripped
+    /// otherwise.
+    pub correlation_ids: bool,
+}
+
+/// A backup gateway, which a request is sent to if it failed against the primary gateway.
+#[derive(Clone, Debug)]
+pub struct Fallback {
+    url: reqwest::Url,
+    /// The number of path segments of the primary gateway's URL.
+    primary_segments: usize,
+}
+
+impl Fallback {
+    pub fn new(primary: &reqwest::Url, url: reqwest::Url) -> Self {
+        Self {
+            url,
+            primary_segments: non_empty_segments(primary).count(),
+        }
+    }
+
+    /// Redirects `request_url`, which was built for the primary gateway, to this gateway.
+    fn rebase(&self, request_url: &reqwest::Url) -> reqwest::Url {
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .expect("Base URL is valid")
+            .pop_if_empty()
+            .extend(non_empty_segments(request_url).skip(self.primary_segments));
+        url.set_query(request_url.query());
+        url
+    }
+}
+
+fn non_empty_segments(url: &reqwest::Url) -> impl Iterator<Item = &str> {
+    url.path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|segment| !segment.is_empty())
 }
 
 /// Describes the retry behavior of a [Request] and is specified using
@@ -50,22 +121,30 @@ pub mod stage {
     /// - [get_storage_at](super::Request::get_storage_at)
     /// - [get_transaction](super::Request::get_transaction)
     /// - [get_transaction_status](super::Request::get_transaction_status)
+    /// - [get_transaction_receipt](super::Request::get_transaction_receipt)
+    /// - [get_transaction_trace](super::Request::get_transaction_trace)
     /// - [get_state_update](super::Request::get_state_update)
     /// - [get_contract_addresses](super::Request::get_contract_addresses)
+    /// - [get_nonce](super::Request::get_nonce)
+    /// - [simulate_transaction](super::Request::simulate_transaction)
+    /// - [estimate_fee_bulk](super::Request::estimate_fee_bulk)
     pub struct Method;
 
     /// Specify the request parameters:
     /// - [at_block](super::Request::with_block)
+    /// - [with_block_context](super::Request::with_block_context)
     /// - [with_contract_address](super::Request::with_contract_address)
     /// - [with_class_hash](super::Request::with_class_hash)
     /// - [with_optional_token](super::Request::with_optional_token)
     /// - [with_storage_address](super::Request::with_storage_address)
     /// - [with_transaction_hash](super::Request::with_transaction_hash)
+    /// - [with_optional_timeout](super::Request::with_optional_timeout)
     /// - [add_param](super::Request::add_param) (allows adding custom (name, value) parameter)
     ///
     /// and then specify the [retry behavior](super::Request::with_retry).
     pub struct Params {
         pub meta: RequestMetadata,
+        pub timeout: Option<std::time::Duration>,
     }
 
     /// Specify the REST operation send the request:
@@ -75,6 +154,7 @@ pub mod stage {
     pub struct Final {
         pub meta: RequestMetadata,
         pub retry: super::Retry,
+        pub timeout: Option<std::time::Duration>,
     }
 
     impl super::RequestState for Init {}
@@ -86,10 +166,17 @@ pub mod stage {
 
 impl<'a> Request<'a, stage::Init> {
     /// Initialize a [Request] builder.
-    pub fn builder(client: &'a reqwest::Client, url: reqwest::Url) -> Request<'a, stage::Gateway> {
+    pub fn builder(
+        client: &'a reqwest::Client,
+        url: reqwest::Url,
+        settings: &'a Settings,
+        headers: &'a reqwest::header::HeaderMap,
+    ) -> Request<'a, stage::Gateway> {
         Request {
             url,
             client,
+            settings,
+            headers,
             state: stage::Gateway,
         }
     }
@@ -114,6 +201,8 @@ impl<'a> Request<'a, stage::Gateway> {
         Request {
             url: self.url,
             client: self.client,
+            settings: self.settings,
+            headers: self.headers,
             state: stage::Method,
         }
     }
@@ -176,8 +265,13 @@ impl<'a> Request<'a, stage::Method> {
         get_storage_at,
         get_transaction,
         get_transaction_status,
+        get_transaction_receipt,
+        get_transaction_trace,
         get_state_update,
-        get_contract_addresses
+        get_contract_addresses,
+        get_nonce,
+        simulate_transaction,
+        estimate_fee_bulk
     );
 
     /// Appends the given method to the request url.
@@ -190,8 +284,11 @@ impl<'a> Request<'a, stage::Method> {
         Request {
             url: self.url,
             client: self.client,
+            settings: self.settings,
+            headers: self.headers,
             state: stage::Params {
                 meta: RequestMetadata::new(method),
+                timeout: None,
             },
         }
     }
@@ -218,6 +315,16 @@ impl<'a> Request<'a, stage::Params> {
         self.update_tag(tag).add_param(name, &value)
     }
 
+    pub fn with_block_context(
+        self,
+        block_context: crate::sequencer::request::BlockContext,
+    ) -> Self {
+        match block_context.timestamp {
+            Some(timestamp) => self.add_param("blockTimestamp", &timestamp.get().to_string()),
+            None => self,
+        }
+    }
+
     pub fn with_contract_address(self, address: ContractAddress) -> Self {
         self.add_param("contractAddress", &address.get().to_hex_str())
     }
@@ -242,6 +349,12 @@ impl<'a> Request<'a, stage::Params> {
         self.add_param("transactionHash", &hash.0.to_hex_str())
     }
 
+    /// Overrides the client's timeout for this request only.
+    pub fn with_optional_timeout(mut self, timeout: Option<std::time::Duration>) -> Self {
+        self.state.timeout = timeout;
+        self
+    }
+
     pub fn add_param(mut self, name: &str, value: &str) -> Self {
         self.url.query_pairs_mut().append_pair(name, value);
         self
@@ -257,9 +370,12 @@ impl<'a> Request<'a, stage::Params> {
         Request {
             url: self.url,
             client: self.client,
+            settings: self.settings,
+            headers: self.headers,
             state: stage::Final {
                 meta: self.state.meta,
                 retry,
+                timeout: self.state.timeout,
             },
         }
     }
@@ -271,62 +387,55 @@ impl<'a> Request<'a, stage::Final> {
     where
         T: serde::de::DeserializeOwned,
     {
-        async fn send_request<T: serde::de::DeserializeOwned>(
-            url: reqwest::Url,
-            client: &reqwest::Client,
-            meta: RequestMetadata,
-        ) -> Result<T, SequencerError> {
-            with_metrics(meta, async move {
-                let response = client.get(url).send().await?;
-                parse::<T>(response).await
-            })
-            .await
-        }
+        let limit = self.settings.max_response_size;
+        self.send(
+            |client, url| client.get(url),
+            |response| parse::<T>(response, limit),
+        )
+        .await
+    }
 
-        match self.state.retry {
-            Retry::Disabled => send_request(self.url, self.client, self.state.meta).await,
-            Retry::Enabled => {
-                retry0(
-                    || async {
-                        let clone_url = self.url.clone();
-                        send_request(clone_url, self.client, self.state.meta).await
-                    },
-                    retry_condition,
-                )
-                .await
-            }
-        }
+    /// Like [get](Self::get), but parses the response while it is being received instead of
+    /// buffering it fully first. This only reduces the peak memory of very large responses if
+    /// `T` does not hold most of the response either.
+    pub async fn get_streamed<T>(self) -> Result<T, SequencerError>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let limit = self.settings.max_response_size;
+        self.send(
+            |client, url| client.get(url),
+            |response| parse_streamed::<T>(response, limit),
+        )
+        .await
+    }
+
+    /// Sends the Sequencer request as a REST `GET` operation and returns the successful response
+    /// without receiving its body, so that the body can be streamed elsewhere.
+    ///
+    /// Retries and fallbacks only cover the request up to the response headers, the
+    /// [max_response_size](super::super::SequencerConfig::max_response_size) only limits error
+    /// bodies.
+    pub async fn get_response(self) -> Result<reqwest::Response, SequencerError> {
+        let limit = self.settings.max_response_size;
+        self.send(
+            |client, url| client.get(url),
+            |response| parse_raw(response, limit),
+        )
+        .await
     }
 
     /// Sends the Sequencer request as a REST `GET` operation and returns the response's bytes.
     pub async fn get_as_bytes(self) -> Result<bytes::Bytes, SequencerError> {
-        async fn get_as_bytes_inner(
-            url: reqwest::Url,
-            client: &reqwest::Client,
-            meta: RequestMetadata,
-        ) -> Result<bytes::Bytes, SequencerError> {
-            with_metrics(meta, async {
-                let response = client.get(url).send().await?;
-                let response = parse_raw(response).await?;
-                let bytes = response.bytes().await?;
-                Ok(bytes)
-            })
-            .await
-        }
-
-        match self.state.retry {
-            Retry::Disabled => get_as_bytes_inner(self.url, self.client, self.state.meta).await,
-            Retry::Enabled => {
-                retry0(
-                    || async {
-                        let clone_url = self.url.clone();
-                        get_as_bytes_inner(clone_url, self.client, self.state.meta).await
-                    },
-                    retry_condition,
-                )
-                .await
-            }
-        }
+        let limit = self.settings.max_response_size;
+        self.send(
+            |client, url| client.get(url),
+            |response| async move {
+                let response = parse_raw(response, limit).await?;
+                read_body(response, limit).await
+            },
+        )
+        .await
     }
 
     /// Sends the Sequencer request as a REST `POST` operation, in addition to the specified
@@ -336,94 +445,607 @@ impl<'a> Request<'a, stage::Final> {
         T: serde::de::DeserializeOwned,
         J: serde::Serialize + ?Sized,
     {
-        async fn post_with_json_inner<T, J>(
-            url: reqwest::Url,
-            client: &reqwest::Client,
-            meta: RequestMetadata,
-            json: &J,
-        ) -> Result<T, SequencerError>
-        where
-            T: serde::de::DeserializeOwned,
-            J: serde::Serialize + ?Sized,
-        {
-            with_metrics(meta, async {
-                let response = client.post(url).json(json).send().await?;
-                parse::<T>(response).await
-            })
-            .await
-        }
+        let limit = self.settings.max_response_size;
+        self.send(
+            |client, url| client.post(url).json(json),
+            |response| parse::<T>(response, limit),
+        )
+        .await
+    }
 
-        match self.state.retry {
+    /// Sends the request built by `build` with the configured retries and fallbacks, and
+    /// handles each response with `handle`.
+    async fn send<T, Fut>(
+        self,
+        build: impl Fn(&reqwest::Client, reqwest::Url) -> reqwest::RequestBuilder,
+        handle: impl Fn(reqwest::Response) -> Fut,
+    ) -> Result<T, SequencerError>
+    where
+        Fut: futures::Future<Output = Result<T, SequencerError>>,
+    {
+        // Set if the latest attempt was answered with an empty 502 or 503, which proxies send
+        // when they could not reach the gateway behind them.
+        let unreachable = std::sync::atomic::AtomicBool::new(false);
+        let send = |url| {
+            attempt(
+                self.client,
+                self.settings,
+                self.headers,
+                self.state.meta,
+                self.state.timeout,
+                build(self.client, url),
+                |response: reqwest::Response| {
+                    use reqwest::StatusCode;
+
+                    let empty_unavailable = matches!(
+                        response.status(),
+                        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE
+                    ) && response.content_length() == Some(0);
+                    unreachable.store(empty_unavailable, std::sync::atomic::Ordering::Relaxed);
+                    handle(response)
+                },
+            )
+        };
+        let result = match self.state.retry {
+            // Requests which are not retried, like submissions, may have been received even
+            // though they failed. They only fall back if they certainly did not reach the
+            // gateway, so that nothing is submitted twice.
             Retry::Disabled => {
-                post_with_json_inner(self.url, self.client, self.state.meta, json).await
+                let undelivered = |e: &SequencerError| match e {
+                    SequencerError::ReqwestError(e) if e.is_connect() => true,
+                    SequencerError::ReqwestError(e) if e.is_status() => {
+                        unreachable.load(std::sync::atomic::Ordering::Relaxed)
+                    }
+                    _ => false,
+                };
+                with_fallbacks(&self.url, self.settings, send, undelivered).await
             }
             Retry::Enabled => {
                 retry0(
-                    || async {
-                        let clone_url = self.url.clone();
-                        post_with_json_inner(clone_url, self.client, self.state.meta, json).await
+                    || {
+                        with_fallbacks(&self.url, self.settings, send, |e| {
+                            is_retryable(e, &self.settings.retry_policies)
+                        })
                     },
-                    retry_condition,
+                    &self.settings.retry_policies,
                 )
                 .await
             }
         }
+        .map(|(response, gateway)| {
+            super::record_serving_gateway(gateway);
+            response
+        });
+
+        if let Err(e) = &result {
+            self.settings.alerts.check(e);
+        }
+        result
+    }
+}
+
+/// Sends a single attempt of `request` to a gateway and handles its response with `handle`,
+/// recording its metrics and [RequestTimings].
+async fn attempt<T, Fut>(
+    client: &reqwest::Client,
+    settings: &Settings,
+    headers: &reqwest::header::HeaderMap,
+    meta: RequestMetadata,
+    timeout: Option<std::time::Duration>,
+    request: reqwest::RequestBuilder,
+    handle: impl FnOnce(reqwest::Response) -> Fut,
+) -> Result<T, SequencerError>
+where
+    Fut: futures::Future<Output = Result<T, SequencerError>>,
+{
+    let timings = &RequestTimings::start();
+    let attempt = with_metrics(
+        meta,
+        trace_if_slow(
+            meta,
+            settings,
+            timings,
+            with_timeout_error(meta, async {
+                let mut request = request.build()?;
+                *request.timeout_mut() = timeout;
+                let response = execute(client, settings, headers, meta, request, timings).await?;
+                handle(response).await
+            }),
+        ),
+    );
+    DNS_LOOKUP.scope(timings.dns_lookup.clone(), attempt).await
+}
+
+/// Sends the request to `url` of the primary gateway and then, for as long as it fails with an
+/// error which `may_fall_back` accepts, to each of the [Fallback]s of `settings` in order.
+///
+/// Returns the response along with the gateway which served it.
+async fn with_fallbacks<T, Fut>(
+    url: &reqwest::Url,
+    settings: &Settings,
+    send: impl Fn(reqwest::Url) -> Fut,
+    may_fall_back: impl Fn(&SequencerError) -> bool,
+) -> Result<(T, super::Gateway), SequencerError>
+where
+    Fut: futures::Future<Output = Result<T, SequencerError>>,
+{
+    let mut result = send(url.clone())
+        .await
+        .map(|response| (response, super::Gateway::Primary));
+
+    for fallback in &settings.fallbacks {
+        // Only the origin is logged, the rest of the URL may contain secrets.
+        let gateway = fallback.url.origin().ascii_serialization();
+        match &result {
+            Err(e) if may_fall_back(e) => {
+                tracing::warn!(reason=%e, %gateway, "Request failed, falling back to the next gateway");
+            }
+            _ => break,
+        }
+
+        result = send(fallback.rebase(url))
+            .await
+            .map(|response| (response, super::Gateway::Fallback(fallback.url.clone())));
+        if result.is_ok() {
+            tracing::info!(%gateway, "Request served by fallback gateway");
+        }
+    }
+
+    result
+}
+
+/// The timing breakdown of a single request attempt.
+///
+/// reqwest does not expose the connect and TLS handshake phases, these are part of the time to
+/// the response headers after the DNS lookup. A slow first byte from a close address hence
+/// points at the gateway, while a slow DNS lookup or body points at the network.
+#[derive(Debug)]
+struct RequestTimings {
+    started: std::time::Instant,
+    /// The duration of the DNS lookup, see [TimedResolver]. [None] if the attempt reused a
+    /// connection, or the gateway's host is an IP address.
+    dns_lookup: Arc<std::sync::Mutex<Option<std::time::Duration>>>,
+    /// The time from the start to the response headers and the address they came from.
+    headers: std::sync::Mutex<Option<(std::time::Duration, Option<std::net::SocketAddr>)>>,
+}
+
+impl RequestTimings {
+    fn start() -> Self {
+        Self {
+            started: std::time::Instant::now(),
+            dns_lookup: Default::default(),
+            headers: std::sync::Mutex::new(None),
+        }
+    }
+
+    fn headers_received(&self, response: &reqwest::Response) {
+        *self.headers.lock().unwrap() = Some((self.started.elapsed(), response.remote_addr()));
+    }
+}
+
+tokio::task_local! {
+    /// The [RequestTimings::dns_lookup] of the attempt the current task is sending.
+    static DNS_LOOKUP: Arc<std::sync::Mutex<Option<std::time::Duration>>>;
+}
+
+/// Resolves gateway hosts with the system resolver like reqwest does by default, and records
+/// the duration of each lookup in the [RequestTimings] of the attempt which needed it.
+pub(super) struct TimedResolver;
+
+impl reqwest::dns::Resolve for TimedResolver {
+    fn resolve(&self, name: warp::hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        let lookup = DNS_LOOKUP.try_with(Arc::clone).ok();
+        Box::pin(async move {
+            let started = std::time::Instant::now();
+            // The connector replaces the port with the one of the URL.
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await?;
+            if let Some(lookup) = lookup {
+                *lookup.lock().unwrap() = Some(started.elapsed());
+            }
+            Ok(Box::new(addrs) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Emits a `Slow gateway request` event with the [RequestTimings] of `attempt` if it took at
+/// least the [slow request threshold](Settings::slow_request_threshold).
+async fn trace_if_slow<T>(
+    meta: RequestMetadata,
+    settings: &Settings,
+    timings: &RequestTimings,
+    attempt: impl futures::Future<Output = Result<T, SequencerError>>,
+) -> Result<T, SequencerError> {
+    let result = attempt.await;
+
+    let total = timings.started.elapsed();
+    match settings.slow_request_threshold {
+        Some(threshold) if total >= threshold => {
+            let headers = *timings.headers.lock().unwrap();
+            let first_byte = headers.map(|(first_byte, _)| first_byte);
+            let dns_lookup = *timings.dns_lookup.lock().unwrap();
+            tracing::warn!(
+                method = meta.method,
+                total_ms = total.as_millis() as u64,
+                dns_ms = ?dns_lookup.map(|d| d.as_millis() as u64),
+                first_byte_ms = ?first_byte.map(|d| d.as_millis() as u64),
+                body_ms = ?first_byte.map(|d| total.saturating_sub(d).as_millis() as u64),
+                remote_addr = ?headers.and_then(|(_, addr)| addr),
+                succeeded = result.is_ok(),
+                "Slow gateway request"
+            );
+        }
+        _ => {}
     }
+
+    result
+}
+
+/// Reports a timed out request of `attempt` as [SequencerError::Timeout].
+async fn with_timeout_error<T>(
+    meta: RequestMetadata,
+    attempt: impl futures::Future<Output = Result<T, SequencerError>>,
+) -> Result<T, SequencerError> {
+    let start = std::time::Instant::now();
+    attempt.await.map_err(|e| match e {
+        SequencerError::ReqwestError(e) if e.is_timeout() => SequencerError::Timeout {
+            elapsed: start.elapsed(),
+            endpoint: meta.method,
+        },
+        e => e,
+    })
+}
+
+/// Adds `headers` and the default headers of `settings` to `request`, tags it with a
+/// [correlation id](CORRELATION_ID_HEADER) if configured to, pins it to a gateway instance, runs the
+/// [RequestMiddleware] of `settings` on it, compresses its body if configured to and sends it.
+async fn execute(
+    client: &reqwest::Client,
+    settings: &Settings,
+    headers: &reqwest::header::HeaderMap,
+    meta: RequestMetadata,
+    mut request: reqwest::Request,
+    timings: &RequestTimings,
+) -> Result<reqwest::Response, SequencerError> {
+    if settings.offline {
+        return Err(SequencerError::OfflineMode);
+    }
+
+    for (name, value) in headers {
+        request.headers_mut().insert(name, value.clone());
+    }
+    for (name, value) in &settings.default_headers {
+        if !request.headers().contains_key(name) {
+            request.headers_mut().insert(name, value.clone());
+        }
+    }
+    if !settings.correlation_ids {
+        request.headers_mut().remove(CORRELATION_ID_HEADER);
+    } else if !request.headers().contains_key(CORRELATION_ID_HEADER) {
+        // Requests which are not part of a larger operation are their own operation.
+        let id = super::operation_correlation_id()
+            .unwrap_or_else(|| super::new_correlation_id(meta.method));
+        request.headers_mut().insert(CORRELATION_ID_HEADER, id);
+    }
+
+    if let Some(affinity) = &settings.affinity {
+        affinity.apply(&mut request);
+    }
+
+    for middleware in &settings.middleware {
+        middleware
+            .on_request(&mut request)
+            .map_err(SequencerError::RequestRejected)?;
+    }
+
+    if let Some(threshold) = settings.compression_threshold {
+        compress_body(&mut request, threshold);
+    }
+
+    tracing::debug!(
+        method = %request.method(),
+        headers = ?redacted_headers(request.headers()),
+        "Sending request to gateway"
+    );
+    let response = client.execute(request).await?;
+    timings.headers_received(&response);
+
+    if let Some(affinity) = &settings.affinity {
+        affinity.capture(&response);
+    }
+
+    Ok(response)
+}
+
+/// Gzip compresses the body of `request` if it is larger than `threshold` bytes.
+fn compress_body(request: &mut reqwest::Request, threshold: usize) {
+    use std::io::Write;
+
+    let compressed = match request.body().and_then(reqwest::Body::as_bytes) {
+        Some(body) if body.len() > threshold => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(body)
+                .expect("Writing to a Vec never fails");
+            encoder.finish().expect("Writing to a Vec never fails")
+        }
+        _ => return,
+    };
+
+    *request.body_mut() = Some(compressed.into());
+    request.headers_mut().insert(
+        reqwest::header::CONTENT_ENCODING,
+        reqwest::header::HeaderValue::from_static("gzip"),
+    );
 }
 
-async fn parse<T>(response: reqwest::Response) -> Result<T, SequencerError>
+async fn parse<T>(response: reqwest::Response, limit: Option<usize>) -> Result<T, SequencerError>
 where
     T: ::serde::de::DeserializeOwned,
 {
-    let response = parse_raw(response).await?;
+    let response = parse_raw(response, limit).await?;
+    let body = read_body(response, limit).await?;
     // Attempt to deserialize the actual data we are looking for
-    let response = response.json::<T>().await?;
-    Ok(response)
+    serde_json::from_slice::<T>(&body).map_err(SequencerError::InvalidResponseBody)
+}
+
+/// Receives the whole body of `response`, aborting with [SequencerError::ResponseTooLarge] as
+/// soon as it exceeds `limit` bytes.
+async fn read_body(
+    mut response: reqwest::Response,
+    limit: Option<usize>,
+) -> Result<bytes::Bytes, SequencerError> {
+    let limit = match limit {
+        Some(limit) => limit,
+        None => return Ok(response.bytes().await?),
+    };
+
+    if matches!(response.content_length(), Some(length) if length > limit as u64) {
+        return Err(SequencerError::ResponseTooLarge { limit });
+    }
+
+    let mut body = bytes::BytesMut::new();
+    while let Some(chunk) = response.chunk().await? {
+        // The announced length is not trusted, nor known for compressed bodies.
+        if body.len() + chunk.len() > limit {
+            return Err(SequencerError::ResponseTooLarge { limit });
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body.freeze())
+}
+
+/// Number of received response body chunks which [parse_streamed] buffers at most, before
+/// waiting for the deserializer to catch up.
+const STREAMED_CHUNKS_IN_FLIGHT: usize = 16;
+
+/// Like [parse], but deserializes the body in a blocking task as its chunks are received, so
+/// that the body itself is never buffered in whole.
+async fn parse_streamed<T>(
+    response: reqwest::Response,
+    limit: Option<usize>,
+) -> Result<T, SequencerError>
+where
+    T: ::serde::de::DeserializeOwned + Send + 'static,
+{
+    let mut response = parse_raw(response, limit).await?;
+
+    let (sender, receiver) = tokio::sync::mpsc::channel(STREAMED_CHUNKS_IN_FLIGHT);
+    let parse = tokio::task::spawn_blocking(move || {
+        serde_json::from_reader::<_, T>(std::io::BufReader::new(ChunkReader::new(receiver)))
+    });
+
+    let received = async {
+        let mut received_bytes = 0;
+        while let Some(chunk) = response.chunk().await? {
+            received_bytes += chunk.len();
+            if let Some(limit) = limit.filter(|limit| received_bytes > *limit) {
+                return Err(SequencerError::ResponseTooLarge { limit });
+            }
+            if sender.send(chunk).await.is_err() {
+                // The deserializer finished early, e.g. due to a syntax error.
+                break;
+            }
+        }
+        Ok::<_, SequencerError>(())
+    }
+    .await;
+    // Signals the end of the body to the deserializer.
+    drop(sender);
+
+    let parsed = parse.await.expect("Deserializer should not panic");
+    // A body cut short by a transport error or the size limit fails to deserialize as well,
+    // but the cut is the cause.
+    received?;
+    parsed.map_err(SequencerError::InvalidResponseBody)
+}
+
+/// Reads the chunks of a response body in order, blocking until the next one is received.
+struct ChunkReader {
+    receiver: tokio::sync::mpsc::Receiver<bytes::Bytes>,
+    /// The unread part of the current chunk.
+    chunk: bytes::Bytes,
+}
+
+impl ChunkReader {
+    fn new(receiver: tokio::sync::mpsc::Receiver<bytes::Bytes>) -> Self {
+        Self {
+            receiver,
+            chunk: bytes::Bytes::new(),
+        }
+    }
+}
+
+impl std::io::Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.chunk.is_empty() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.chunk = chunk,
+                // The whole body was read.
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len());
+        buf[..len].copy_from_slice(&self.chunk.split_to(len));
+        Ok(len)
+    }
 }
 
 /// Helper function which allows skipping deserialization when required.
-async fn parse_raw(response: reqwest::Response) -> Result<reqwest::Response, SequencerError> {
+///
+/// Error bodies larger than `limit` bytes fail with [SequencerError::ResponseTooLarge].
+async fn parse_raw(
+    response: reqwest::Response,
+    limit: Option<usize>,
+) -> Result<reqwest::Response, SequencerError> {
     use crate::sequencer::error::StarknetError;
 
     // Starknet specific errors end with a 500 status code
     // but the body contains a JSON object with the error description
     if response.status() == reqwest::StatusCode::INTERNAL_SERVER_ERROR {
-        let error = match response.json::<StarknetError>().await {
+        let body = read_body(response, limit).await?;
+        let error = match serde_json::from_slice::<StarknetError>(&body) {
             Ok(e) => SequencerError::StarknetError(e),
-            Err(e) if e.is_decode() => SequencerError::InvalidStarknetErrorVariant,
-            Err(e) => SequencerError::ReqwestError(e),
+            Err(_) => SequencerError::InvalidStarknetErrorVariant,
         };
         return Err(error);
     }
+    // Maintenance is announced using a 503 with a banner, which we distinguish from
+    // other 503s (e.g. those of an overloaded load balancer).
+    if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+        let status_error = response
+            .error_for_status_ref()
+            .expect_err("status is an error");
+        let body = read_body(response, limit).await?;
+        let body = String::from_utf8_lossy(&body);
+        return Err(match maintenance_message(&body) {
+            Some(message) => SequencerError::GatewayMaintenance { message },
+            None => SequencerError::ReqwestError(status_error),
+        });
+    }
+    // Redirects are not followed, see Client::with_config.
+    if response.status().is_redirection() {
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .map(|location| String::from_utf8_lossy(location.as_bytes()).into_owned());
+        return Err(SequencerError::UnexpectedRedirect { location });
+    }
     // Status codes 400..499 and 501..599 are mapped to SequencerError::TransportError
     response.error_for_status_ref().map(|_| ())?;
     Ok(response)
 }
 
+/// Returns the banner of a maintenance response body, or [None] if `body` does not
+/// announce maintenance.
+///
+/// The banner is either the `message` of a JSON body, or the text content of an HTML body.
+fn maintenance_message(body: &str) -> Option<String> {
+    if !body.to_ascii_lowercase().contains("maintenance") {
+        return None;
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Banner {
+        message: String,
+    }
+
+    if let Ok(banner) = serde_json::from_str::<Banner>(body) {
+        return Some(banner.message);
+    }
+
+    // Strip the markup, keeping only the text content.
+    let mut text = String::with_capacity(body.len());
+    let mut in_tag = false;
+    for c in body.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
 pub trait RequestState {}
 
 /// Wrapper function to allow retrying sequencer queries in an exponential manner.
-async fn retry0<T, Fut, FutureFactory, Ret>(
+///
+/// Errors are retried according to their [RetryPolicy] in `policies`, falling back to
+/// exponential backoff if [retry_condition] holds.
+///
+/// Each attempt runs in its own `attempt` span, see
+/// [Retry::with_backoff](crate::retry::Retry::with_backoff).
+async fn retry0<T, Fut, FutureFactory>(
     future_factory: FutureFactory,
-    retry_condition: Ret,
+    policies: &RetryPolicies,
 ) -> Result<T, SequencerError>
 where
     Fut: futures::Future<Output = Result<T, SequencerError>>,
     FutureFactory: FnMut() -> Fut,
-    Ret: FnMut(&SequencerError) -> bool,
 {
-    use crate::retry::Retry;
+    use crate::retry::{Backoff, Retry};
     use std::num::NonZeroU64;
 
     Retry::exponential(future_factory, NonZeroU64::new(2).unwrap())
         .factor(NonZeroU64::new(15).unwrap())
         .max_delay(std::time::Duration::from_secs(60 * 60))
-        .when(retry_condition)
+        .with_backoff(|e| match policies.policy_for(e) {
+            Some(RetryPolicy::Never) => Backoff::Stop,
+            Some(RetryPolicy::Fixed(delay)) => {
+                tracing::debug!(reason=%e, ?delay, "Request failed, retrying");
+                Backoff::Fixed(delay)
+            }
+            Some(RetryPolicy::Exponential) => {
+                tracing::debug!(reason=%e, "Request failed, retrying");
+                Backoff::Exponential
+            }
+            None if retry_condition(e) => {
+                log_retried(e);
+                Backoff::Exponential
+            }
+            None => Backoff::Stop,
+        })
         .await
 }
 
+/// Whether `e` is retried, according to `policies` or [retry_condition] otherwise.
+fn is_retryable(e: &SequencerError, policies: &RetryPolicies) -> bool {
+    match policies.policy_for(e) {
+        Some(RetryPolicy::Never) => false,
+        Some(RetryPolicy::Fixed(_) | RetryPolicy::Exponential) => true,
+        None => retry_condition(e),
+    }
+}
+
 /// Determines if an error is retryable or not.
 fn retry_condition(e: &SequencerError) -> bool {
+    match e {
+        SequencerError::ReqwestError(_)
+        | SequencerError::InvalidStarknetErrorVariant
+        | SequencerError::InvalidResponseBody(_)
+        | SequencerError::GatewayMaintenance { .. }
+        | SequencerError::Timeout { .. } => true,
+        SequencerError::StarknetError(_)
+        | SequencerError::RequestRejected(_)
+        | SequencerError::ResponseTooLarge { .. }
+        | SequencerError::ChainIdMismatch { .. }
+        | SequencerError::TransactionNotYetIndexed { .. }
+        | SequencerError::UnexpectedRedirect { .. }
+        | SequencerError::OfflineMode => false,
+    }
+}
+
+/// Logs that `e`, which meets [retry_condition], is retried, at a level matching how unusual
+/// the error is.
+fn log_retried(e: &SequencerError) {
     use reqwest::StatusCode;
     use tracing::{debug, error, info, warn};
 
@@ -453,33 +1075,70 @@ fn retry_condition(e: &SequencerError) -> bool {
             } else {
                 warn!(reason=%e, "Request failed, retrying");
             }
-
-            true
         }
-        SequencerError::StarknetError(_) => false,
-        SequencerError::InvalidStarknetErrorVariant => {
+        SequencerError::InvalidStarknetErrorVariant | SequencerError::InvalidResponseBody(_) => {
             error!(reason=%e, "Request failed, retrying");
-            true
         }
+        SequencerError::GatewayMaintenance { .. } => {
+            warn!(reason=%e, "Request failed, retrying");
+        }
+        SequencerError::Timeout { .. } => {
+            info!(reason=%e, "Request failed, retrying");
+        }
+        _ => {}
     }
 }
 
 #[cfg(test)]
 mod tests {
+    mod chunk_reader {
+        use super::super::ChunkReader;
+
+        #[test]
+        fn values_are_split_across_chunks() {
+            let (sender, receiver) = tokio::sync::mpsc::channel(4);
+            let chunks = [r#"{"a": [1, 2"#, r#"3, 4], "b""#, r#": "value"}"#];
+            for chunk in chunks {
+                sender
+                    .try_send(bytes::Bytes::from_static(chunk.as_bytes()))
+                    .unwrap();
+            }
+            drop(sender);
+
+            let value = serde_json::from_reader::<_, serde_json::Value>(ChunkReader::new(receiver))
+                .unwrap();
+            assert_eq!(value, serde_json::json!({"a": [1, 23, 4], "b": "value"}));
+        }
+
+        #[test]
+        fn truncated_body_is_an_error() {
+            let (sender, receiver) = tokio::sync::mpsc::channel(1);
+            sender
+                .try_send(bytes::Bytes::from_static(br#"{"a": "#))
+                .unwrap();
+            drop(sender);
+
+            serde_json::from_reader::<_, serde_json::Value>(ChunkReader::new(receiver))
+                .unwrap_err();
+        }
+    }
+
     mod retry {
         use assert_matches::assert_matches;
         use http::{response::Builder, StatusCode};
         use pretty_assertions::assert_eq;
-        use std::{collections::VecDeque, net::SocketAddr, sync::Arc, time::Duration};
+        use std::{collections::VecDeque, sync::Arc, time::Duration};
         use tokio::{sync::Mutex, task::JoinHandle};
         use warp::Filter;
 
-        use crate::sequencer::builder::{retry0, retry_condition};
+        use crate::sequencer::builder::retry0;
+        use crate::sequencer::test_utils::serve_gateway;
+        use crate::sequencer::{RetryPolicies, RetryPolicy};
 
         // A test helper
         fn status_queue_server(
             statuses: VecDeque<(StatusCode, &'static str)>,
-        ) -> (JoinHandle<()>, SocketAddr) {
+        ) -> (JoinHandle<()>, reqwest::Url) {
             use std::cell::RefCell;
 
             let statuses = Arc::new(Mutex::new(RefCell::new(statuses)));
@@ -492,20 +1151,16 @@ mod tests {
                 }
             });
 
-            let (addr, run_srv) = warp::serve(any).bind_ephemeral(([127, 0, 0, 1], 0));
-            let server_handle = tokio::spawn(run_srv);
-            (server_handle, addr)
+            serve_gateway(any)
         }
 
         // A test helper
-        fn slow_server() -> (tokio::task::JoinHandle<()>, std::net::SocketAddr) {
+        fn slow_server() -> (tokio::task::JoinHandle<()>, reqwest::Url) {
             let any = warp::any().then(|| async {
                 tokio::time::sleep(Duration::from_secs(1)).await;
                 Ok(Builder::new().status(200).body(""))
             });
-            let (addr, run_srv) = warp::serve(any).bind_ephemeral(([127, 0, 0, 1], 0));
-            let server_handle = tokio::spawn(run_srv);
-            (server_handle, addr)
+            serve_gateway(any)
         }
 
         #[test_log::test(tokio::test)]
@@ -523,21 +1178,101 @@ mod tests {
                 (StatusCode::SERVICE_UNAVAILABLE, ""),
             ]);
 
-            let (_jh, addr) = status_queue_server(statuses);
+            let (_jh, url) = status_queue_server(statuses);
             let result = retry0(
                 || async {
-                    let mut url = reqwest::Url::parse("http://localhost/").unwrap();
-                    url.set_port(Some(addr.port())).unwrap();
-                    let response = reqwest::get(url).await?;
-                    builder::parse::<String>(response).await
+                    let response = reqwest::get(url.clone()).await?;
+                    builder::parse::<String>(response, None).await
                 },
-                retry_condition,
+                &RetryPolicies::default(),
             )
             .await
             .unwrap();
             assert_eq!(result, "Finally!");
         }
 
+        #[tokio::test]
+        async fn attempts_are_traced_in_child_spans() {
+            use crate::sequencer::builder;
+            use tracing::Instrument;
+
+            #[derive(Clone)]
+            struct LogWriter(Arc<std::sync::Mutex<Vec<u8>>>);
+
+            impl std::io::Write for LogWriter {
+                fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                    self.0.lock().unwrap().extend_from_slice(buf);
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> std::io::Result<()> {
+                    Ok(())
+                }
+            }
+
+            let logs = LogWriter(Default::default());
+            let writer = logs.clone();
+            let subscriber = tracing_subscriber::fmt()
+                .with_writer(move || writer.clone())
+                .with_ansi(false)
+                .with_max_level(tracing::Level::DEBUG)
+                .finish();
+            let _guard = tracing::subscriber::set_default(subscriber);
+
+            let statuses = VecDeque::from([
+                (StatusCode::SERVICE_UNAVAILABLE, ""),
+                (StatusCode::SERVICE_UNAVAILABLE, ""),
+                (StatusCode::OK, r#""Finally!""#),
+            ]);
+            let (_jh, url) = status_queue_server(statuses);
+
+            retry0(
+                || async {
+                    tracing::info!("Sending request");
+                    let response = reqwest::get(url.clone()).await?;
+                    builder::parse::<String>(response, None).await
+                },
+                &RetryPolicies::default(),
+            )
+            .instrument(tracing::info_span!("deploy"))
+            .await
+            .unwrap();
+
+            let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+            let attempts = logs
+                .lines()
+                .filter(|line| line.contains("Sending request"))
+                .collect::<Vec<_>>();
+            assert_eq!(attempts.len(), 3, "{logs}");
+            assert!(
+                attempts[0].contains("deploy:attempt{attempt=1 delay_ms=0}"),
+                "{}",
+                attempts[0]
+            );
+            for (i, line) in attempts.iter().enumerate().skip(1) {
+                let prefix = format!("deploy:attempt{{attempt={} delay_ms=", i + 1);
+                assert!(line.contains(&prefix), "{line}");
+                assert!(!line.contains("delay_ms=0}"), "{line}");
+            }
+
+            // The reasons for retrying are logged within the failed attempt.
+            let retried = logs
+                .lines()
+                .filter(|line| line.contains("Request failed, retrying"))
+                .collect::<Vec<_>>();
+            assert_eq!(retried.len(), 2, "{logs}");
+            assert!(
+                retried[0].contains("deploy:attempt{attempt=1 "),
+                "{}",
+                retried[0]
+            );
+            assert!(
+                retried[1].contains("deploy:attempt{attempt=2 "),
+                "{}",
+                retried[1]
+            );
+        }
+
         #[test_log::test(tokio::test)]
         async fn stop_on_fatal() {
             use crate::sequencer::builder;
@@ -557,15 +1292,13 @@ mod tests {
                 (StatusCode::SERVICE_UNAVAILABLE, ""),
             ]);
 
-            let (_jh, addr) = status_queue_server(statuses);
+            let (_jh, url) = status_queue_server(statuses);
             let error = retry0(
                 || async {
-                    let mut url = reqwest::Url::parse("http://localhost/").unwrap();
-                    url.set_port(Some(addr.port())).unwrap();
-                    let response = reqwest::get(url).await?;
-                    builder::parse::<String>(response).await
+                    let response = reqwest::get(url.clone()).await?;
+                    builder::parse::<String>(response, None).await
                 },
-                retry_condition,
+                &RetryPolicies::default(),
             )
             .await
             .unwrap_err();
@@ -581,27 +1314,24 @@ mod tests {
 
             use std::sync::atomic::{AtomicUsize, Ordering};
 
-            let (_jh, addr) = slow_server();
+            let (_jh, url) = slow_server();
             static CNT: AtomicUsize = AtomicUsize::new(0);
 
             let fut = retry0(
                 || async {
-                    let mut url = reqwest::Url::parse("http://localhost/").unwrap();
-                    url.set_port(Some(addr.port())).unwrap();
-
                     let client = reqwest::Client::builder().build().unwrap();
 
                     CNT.fetch_add(1, Ordering::Relaxed);
 
                     // This is the same as using Client::builder().timeout()
                     let response = client
-                        .get(url)
+                        .get(url.clone())
                         .timeout(Duration::from_millis(1))
                         .send()
                         .await?;
-                    builder::parse::<String>(response).await
+                    builder::parse::<String>(response, None).await
                 },
-                retry_condition,
+                &RetryPolicies::default(),
             );
 
             // The retry loops forever, so wrap it in a timeout and check the counter.
@@ -613,23 +1343,170 @@ mod tests {
         }
     }
 
+    mod retry_policy {
+        use assert_matches::assert_matches;
+        use http::{response::Builder, StatusCode};
+        use std::collections::VecDeque;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+        use warp::Filter;
+
+        use crate::sequencer::builder::{parse, retry0};
+        use crate::sequencer::error::{SequencerError, StarknetErrorCode};
+        use crate::sequencer::test_utils::serve_gateway;
+        use crate::sequencer::{RetryPolicies, RetryPolicy};
+
+        fn starknet_error(code: &str) -> (StatusCode, String) {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!(r#"{{"code":"StarknetErrorCode.{code}","message":""}}"#),
+            )
+        }
+
+        /// Replies with `responses` in order and counts the requests.
+        async fn run(
+            responses: Vec<(StatusCode, String)>,
+            policies: RetryPolicies,
+        ) -> (Result<String, SequencerError>, usize) {
+            let responses = Arc::new(Mutex::new(VecDeque::from(responses)));
+            let any = warp::any().map(move || {
+                let (status, body) = responses.lock().unwrap().pop_front().unwrap();
+                Builder::new().status(status).body(body)
+            });
+            let (_jh, url) = serve_gateway(any);
+
+            let count = AtomicUsize::new(0);
+            let result = retry0(
+                || async {
+                    count.fetch_add(1, Ordering::Relaxed);
+                    let response = reqwest::get(url.clone()).await?;
+                    parse::<String>(response, None).await
+                },
+                &policies,
+            )
+            .await;
+
+            (result, count.into_inner())
+        }
+
+        #[tokio::test]
+        async fn exponential_for_code() {
+            let policies = RetryPolicies::default().with_code(
+                StarknetErrorCode::TransactionLimitExceeded,
+                RetryPolicy::Exponential,
+            );
+            let (result, count) = run(
+                vec![
+                    starknet_error("TRANSACTION_LIMIT_EXCEEDED"),
+                    starknet_error("TRANSACTION_LIMIT_EXCEEDED"),
+                    (StatusCode::OK, r#""Finally!""#.to_owned()),
+                ],
+                policies,
+            )
+            .await;
+
+            assert_eq!(result.unwrap(), "Finally!");
+            assert_eq!(count, 3);
+        }
+
+        #[tokio::test]
+        async fn never_for_code() {
+            let policies = RetryPolicies::default()
+                .with_code(StarknetErrorCode::InvalidProgram, RetryPolicy::Never);
+            let (result, count) = run(
+                vec![
+                    starknet_error("INVALID_PROGRAM"),
+                    (StatusCode::OK, r#""Finally!""#.to_owned()),
+                ],
+                policies,
+            )
+            .await;
+
+            assert_matches!(
+                result.unwrap_err(),
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::InvalidProgram)
+            );
+            assert_eq!(count, 1);
+        }
+
+        #[tokio::test]
+        async fn never_for_status() {
+            // Would be retried by default.
+            let policies = RetryPolicies::default()
+                .with_status(StatusCode::TOO_MANY_REQUESTS, RetryPolicy::Never);
+            let (result, count) = run(
+                vec![
+                    (StatusCode::TOO_MANY_REQUESTS, String::new()),
+                    (StatusCode::OK, r#""Finally!""#.to_owned()),
+                ],
+                policies,
+            )
+            .await;
+
+            assert_matches!(
+                result.unwrap_err(),
+                SequencerError::ReqwestError(e) => assert_eq!(e.status(), Some(StatusCode::TOO_MANY_REQUESTS))
+            );
+            assert_eq!(count, 1);
+        }
+
+        #[tokio::test(flavor = "current_thread", start_paused = true)]
+        async fn fixed_for_status() {
+            let policies = RetryPolicies::default().with_status(
+                StatusCode::BAD_GATEWAY,
+                RetryPolicy::Fixed(Duration::from_secs(10)),
+            );
+            let start = tokio::time::Instant::now();
+            let (result, count) = run(
+                vec![
+                    (StatusCode::BAD_GATEWAY, String::new()),
+                    (StatusCode::BAD_GATEWAY, String::new()),
+                    (StatusCode::OK, r#""Finally!""#.to_owned()),
+                ],
+                policies,
+            )
+            .await;
+
+            assert_eq!(result.unwrap(), "Finally!");
+            assert_eq!(count, 3);
+            assert!(start.elapsed() >= Duration::from_secs(20));
+        }
+
+        #[tokio::test]
+        async fn default_for_other_codes() {
+            let policies = RetryPolicies::default().with_code(
+                StarknetErrorCode::TransactionLimitExceeded,
+                RetryPolicy::Exponential,
+            );
+            let (result, count) = run(
+                vec![
+                    starknet_error("BLOCK_NOT_FOUND"),
+                    (StatusCode::OK, r#""Finally!""#.to_owned()),
+                ],
+                policies,
+            )
+            .await;
+
+            assert_matches!(result.unwrap_err(), SequencerError::StarknetError(_));
+            assert_eq!(count, 1);
+        }
+    }
+
     mod invalid_starknet_error_variant {
+        use crate::sequencer::test_utils::serve_gateway;
         use crate::sequencer::Client;
         use http::response::Builder;
         use warp::Filter;
 
-        fn server() -> (tokio::task::JoinHandle<()>, std::net::SocketAddr) {
+        fn server() -> (tokio::task::JoinHandle<()>, reqwest::Url) {
             let any = warp::any().then(|| async { Builder::new().status(500).body("whatever") });
-            let (addr, run_srv) = warp::serve(any).bind_ephemeral(([127, 0, 0, 1], 0));
-            let server_handle = tokio::spawn(run_srv);
-            (server_handle, addr)
+            serve_gateway(any)
         }
 
         #[tokio::test]
         async fn causes_short_reply() {
-            let (_jh, addr) = server();
-            let mut url = reqwest::Url::parse("http://localhost/").unwrap();
-            url.set_port(Some(addr.port())).unwrap();
+            let (_jh, url) = server();
             let client = Client::with_url(url).unwrap();
             let error = client.chain().await.unwrap_err();
             assert_eq!(
@@ -638,4 +1515,192 @@ mod tests {
             );
         }
     }
+
+    mod slow_request {
+        use crate::sequencer::test_utils::serve_gateway;
+        use crate::sequencer::{Client, ClientApi, SequencerConfig};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+        use warp::Filter;
+
+        #[derive(Clone)]
+        struct LogWriter(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for LogWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        /// The `Slow gateway request` events logged while fetching the contract addresses from
+        /// a gateway at `host` responding after `delay`.
+        async fn slow_request_events(
+            host: &str,
+            delay: Duration,
+            threshold: Duration,
+        ) -> Vec<String> {
+            let logs = LogWriter(Default::default());
+            let writer = logs.clone();
+            let subscriber = tracing_subscriber::fmt()
+                .with_writer(move || writer.clone())
+                .with_ansi(false)
+                .finish();
+            let _guard = tracing::subscriber::set_default(subscriber);
+
+            let route =
+                warp::path!("feeder_gateway" / "get_contract_addresses").then(move || async move {
+                    tokio::time::sleep(delay).await;
+                    warp::reply::json(&serde_json::json!({
+                        "Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e",
+                        "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                    }))
+                });
+            let (_, mut url) = serve_gateway(route);
+            url.set_host(Some(host)).unwrap();
+            let client = Client::with_config(
+                SequencerConfig::new(url).with_slow_request_threshold(threshold),
+            )
+            .unwrap();
+            client.eth_contract_addresses().await.unwrap();
+
+            let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+            logs.lines()
+                .filter(|line| line.contains("Slow gateway request"))
+                .map(ToOwned::to_owned)
+                .collect()
+        }
+
+        #[tokio::test]
+        async fn is_traced_past_the_threshold() {
+            let events = slow_request_events(
+                "127.0.0.1",
+                Duration::from_millis(200),
+                Duration::from_millis(100),
+            )
+            .await;
+
+            assert_eq!(events.len(), 1, "{events:?}");
+            let event = &events[0];
+            for field in [
+                "method=\"get_contract_addresses\"",
+                "total_ms=",
+                // Nothing to look up.
+                "dns_ms=None",
+                "first_byte_ms=Some(",
+                "body_ms=Some(",
+                "remote_addr=Some(127.0.0.1:",
+                "succeeded=true",
+            ] {
+                assert!(event.contains(field), "{field} missing in {event}");
+            }
+        }
+
+        #[tokio::test]
+        async fn is_not_traced_below_the_threshold() {
+            let events =
+                slow_request_events("127.0.0.1", Duration::ZERO, Duration::from_secs(10)).await;
+
+            assert_eq!(events, Vec::<String>::new());
+        }
+
+        #[tokio::test]
+        async fn dns_lookup_is_timed() {
+            let events = slow_request_events("localhost", Duration::ZERO, Duration::ZERO).await;
+
+            assert_eq!(events.len(), 1, "{events:?}");
+            assert!(events[0].contains("dns_ms=Some("), "{}", events[0]);
+        }
+    }
+
+    mod unexpected_redirect {
+        use crate::sequencer::error::SequencerError;
+        use crate::sequencer::test_utils::serve_gateway;
+        use crate::sequencer::{Client, ClientApi};
+        use assert_matches::assert_matches;
+        use warp::Filter;
+
+        #[tokio::test]
+        async fn is_not_followed() {
+            let login = warp::path!("login").map(|| "<html>Please log in</html>");
+            let redirect = warp::any().map(|| {
+                warp::http::Response::builder()
+                    .status(302)
+                    .header("location", "/login")
+                    .body("")
+            });
+            let (_, url) = serve_gateway(login.or(redirect));
+
+            let client = Client::with_url(url).unwrap();
+            let error = client.eth_contract_addresses().await.unwrap_err();
+            assert_matches!(
+                &error,
+                SequencerError::UnexpectedRedirect { location } => assert_eq!(location.as_deref(), Some("/login"))
+            );
+            assert!(error
+                .to_string()
+                .contains("check the configured gateway URL"));
+        }
+    }
+
+    mod gateway_maintenance {
+        use crate::rpc::error::ServerErrorCode;
+        use crate::sequencer::builder::parse_raw;
+        use crate::sequencer::error::SequencerError;
+        use crate::sequencer::test_utils::serve_gateway;
+        use assert_matches::assert_matches;
+        use http::response::Builder;
+        use warp::Filter;
+
+        const MAINTENANCE_BODY: &str = r#"<html>
+<head><title>503 Service Temporarily Unavailable</title></head>
+<body>
+<h1>Service Temporarily Unavailable</h1>
+<p>The StarkNet gateway is undergoing scheduled maintenance. Please try again later.</p>
+</body>
+</html>"#;
+
+        async fn response(body: &'static str) -> reqwest::Response {
+            let any = warp::any().map(move || Builder::new().status(503).body(body));
+            let (_, url) = serve_gateway(any);
+
+            reqwest::get(url).await.unwrap()
+        }
+
+        #[tokio::test]
+        async fn is_recognized() {
+            let error = parse_raw(response(MAINTENANCE_BODY).await, None)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                &error,
+                SequencerError::GatewayMaintenance { message } => assert_eq!(
+                    message,
+                    "503 Service Temporarily Unavailable Service Temporarily Unavailable \
+                    The StarkNet gateway is undergoing scheduled maintenance. Please try again later."
+                )
+            );
+
+            use jsonrpsee::{core::error::Error, types::error::CallError};
+            assert_matches!(
+                Error::from(error),
+                Error::Call(CallError::Custom(e)) => assert_eq!(e.code(), ServerErrorCode::GatewayMaintenance.code())
+            );
+        }
+
+        #[tokio::test]
+        async fn generic_503_is_a_status_error() {
+            let error = parse_raw(response("Service Unavailable").await, None)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::ReqwestError(e) => assert_eq!(e.status(), Some(reqwest::StatusCode::SERVICE_UNAVAILABLE))
+            );
+        }
+    }
 }
@@ -0,0 +1,153 @@
+//! In-memory caching of immutable gateway responses.
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::Mutex;
+
+/// A cache holding values of at most `capacity` bytes in total, evicting the least recently
+/// used ones once full. Values larger than `capacity` are not cached at all.
+///
+/// Only suitable for responses which never change, e.g. classes keyed by their hash.
+pub(super) struct LruCache<K, V> {
+    capacity: usize,
+    inner: Mutex<Inner<K, V>>,
+}
+
+struct Inner<K, V> {
+    entries: HashMap<K, V>,
+    /// Keys of `entries`, the least recently used first.
+    order: VecDeque<K>,
+    /// The total size of `entries`, in bytes.
+    size: usize,
+}
+
+impl<K: Copy + Eq + Hash, V: Clone + AsRef<[u8]>> LruCache<K, V> {
+    /// A `capacity` of zero disables the cache.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Inner {
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+                size: 0,
+            }),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut inner = self.inner.lock().unwrap();
+        let value = inner.entries.get(key)?.clone();
+        inner.touch(key);
+        Some(value)
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let size = value.as_ref().len();
+        if size > self.capacity {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.size += size;
+        match inner.entries.insert(key, value) {
+            Some(replaced) => {
+                inner.size -= replaced.as_ref().len();
+                inner.touch(&key);
+            }
+            None => inner.order.push_back(key),
+        }
+
+        while inner.size > self.capacity {
+            let evicted = match inner.order.pop_front() {
+                Some(evicted) => evicted,
+                None => break,
+            };
+            if let Some(evicted) = inner.entries.remove(&evicted) {
+                inner.size -= evicted.as_ref().len();
+            }
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash, V> Inner<K, V> {
+    /// Marks `key` as the most recently used.
+    fn touch(&mut self, key: &K) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(*key);
+    }
+}
+
+impl<K, V> std::fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The values may be large, e.g. entire classes.
+        let (len, size) = match self.inner.lock() {
+            Ok(inner) => (Some(inner.entries.len()), Some(inner.size)),
+            Err(_) => (None, None),
+        };
+        f.debug_struct("LruCache")
+            .field("capacity", &self.capacity)
+            .field("len", &len)
+            .field("size", &size)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_is_evicted() {
+        let cache = LruCache::new(6);
+        cache.insert(1, "one");
+        cache.insert(2, "two");
+        // Makes 2 the least recently used.
+        assert_eq!(cache.get(&1), Some("one"));
+
+        cache.insert(3, "six");
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some("one"));
+        assert_eq!(cache.get(&3), Some("six"));
+    }
+
+    #[test]
+    fn evicts_until_the_value_fits() {
+        let cache = LruCache::new(6);
+        cache.insert(1, "one");
+        cache.insert(2, "two");
+
+        cache.insert(3, "three");
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&3), Some("three"));
+    }
+
+    #[test]
+    fn value_larger_than_the_capacity_is_not_cached() {
+        let cache = LruCache::new(4);
+        cache.insert(1, "one");
+        cache.insert(2, "three");
+
+        assert_eq!(cache.get(&1), Some("one"));
+        assert_eq!(cache.get(&2), None);
+    }
+
+    #[test]
+    fn reinserting_does_not_evict() {
+        let cache = LruCache::new(6);
+        cache.insert(1, "one");
+        cache.insert(2, "two");
+        cache.insert(1, "uno");
+
+        assert_eq!(cache.get(&1), Some("uno"));
+        assert_eq!(cache.get(&2), Some("two"));
+    }
+
+    #[test]
+    fn zero_capacity_is_disabled() {
+        let cache = LruCache::new(0);
+        cache.insert(1, "one");
+        assert_eq!(cache.get(&1), None);
+    }
+}
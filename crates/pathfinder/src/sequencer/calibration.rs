@@ -0,0 +1,254 @@
+//! Estimation of how many concurrent requests the gateway serves without degrading.
+use std::time::Duration;
+
+use super::builder::Retry;
+use super::error::SequencerError;
+use super::{reply, Client};
+
+/// Options for [calibrate_concurrency].
+#[derive(Clone, Debug)]
+pub struct CalibrationOptions {
+    /// The highest concurrency probed. Concurrency is doubled from 1 up to this.
+    pub max_concurrency: usize,
+    /// How many times each concurrency is probed.
+    pub rounds: usize,
+    /// A concurrency is too high once its median latency exceeds the median latency of
+    /// sequential requests by this factor.
+    pub max_latency_factor: u32,
+}
+
+impl Default for CalibrationOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 64,
+            rounds: 3,
+            max_latency_factor: 2,
+        }
+    }
+}
+
+/// The result of [calibrate_concurrency].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConcurrencyRecommendation {
+    /// The highest probed concurrency the gateway kept up with.
+    pub concurrency: usize,
+    /// The median latency of sequential requests, which the other latencies are compared to.
+    pub baseline_latency: Duration,
+    /// Why no higher concurrency is recommended, [None] if the gateway kept up with
+    /// [CalibrationOptions::max_concurrency].
+    pub limited_by: Option<CalibrationLimit>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CalibrationLimit {
+    /// The median latency at `concurrency` exceeded the allowed factor of the baseline.
+    Latency {
+        concurrency: usize,
+        latency: Duration,
+    },
+    /// The gateway responded with `429 Too Many Requests` at `concurrency`.
+    RateLimited { concurrency: usize },
+}
+
+/// Probes the gateway with increasing concurrency until its latency spikes or it starts rate
+/// limiting, and recommends the highest concurrency it kept up with.
+///
+/// Only cheap read requests are sent and nothing is configured, the recommendation is meant to
+/// be applied by the operator, e.g. as
+/// [max_concurrent_submissions](super::SequencerConfig::max_concurrent_submissions). Errors
+/// other than rate limiting abort the calibration.
+///
+/// Probes are never retried, rate limited ones in particular would otherwise be retried until
+/// the gateway serves them.
+pub async fn calibrate_concurrency(
+    client: &Client,
+    options: &CalibrationOptions,
+) -> Result<ConcurrencyRecommendation, SequencerError> {
+    let probe = move || async move {
+        client
+            .request()
+            .feeder_gateway()
+            .get_contract_addresses()
+            .with_retry(Retry::Disabled)
+            .get::<reply::EthContractAddresses>()
+            .await
+            .map(|_| ())
+    };
+
+    calibrate_with(probe, options).await
+}
+
+async fn calibrate_with<F, Fut>(
+    probe: F,
+    options: &CalibrationOptions,
+) -> Result<ConcurrencyRecommendation, SequencerError>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<(), SequencerError>>,
+{
+    let max_concurrency = options.max_concurrency.max(1);
+    let mut recommendation = ConcurrencyRecommendation {
+        concurrency: 1,
+        baseline_latency: Duration::ZERO,
+        limited_by: None,
+    };
+
+    let mut concurrency = 1;
+    loop {
+        let latency = match median_latency(&probe, concurrency, options.rounds).await? {
+            Some(latency) => latency,
+            None => {
+                recommendation.limited_by = Some(CalibrationLimit::RateLimited { concurrency });
+                return Ok(recommendation);
+            }
+        };
+
+        if concurrency == 1 {
+            recommendation.baseline_latency = latency;
+        } else if latency
+            > recommendation
+                .baseline_latency
+                .saturating_mul(options.max_latency_factor)
+        {
+            recommendation.limited_by = Some(CalibrationLimit::Latency {
+                concurrency,
+                latency,
+            });
+            return Ok(recommendation);
+        }
+        recommendation.concurrency = concurrency;
+
+        if concurrency == max_concurrency {
+            return Ok(recommendation);
+        }
+        concurrency = concurrency.saturating_mul(2).min(max_concurrency);
+    }
+}
+
+/// The median latency of `rounds` batches of `concurrency` concurrent probes, [None] if the
+/// gateway rate limited any of them.
+async fn median_latency<F, Fut>(
+    probe: &F,
+    concurrency: usize,
+    rounds: usize,
+) -> Result<Option<Duration>, SequencerError>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<(), SequencerError>>,
+{
+    let mut latencies = Vec::with_capacity(concurrency * rounds);
+    for _ in 0..rounds.max(1) {
+        let batch = (0..concurrency).map(move |_| async move {
+            let started = tokio::time::Instant::now();
+            probe().await.map(|_| started.elapsed())
+        });
+
+        for result in futures::future::join_all(batch).await {
+            match result {
+                Ok(latency) => latencies.push(latency),
+                Err(SequencerError::ReqwestError(e))
+                    if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) =>
+                {
+                    return Ok(None)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    latencies.sort();
+    Ok(Some(latencies[latencies.len() / 2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::test_utils::serve_gateway;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn latency_spike_limits_the_concurrency() {
+        // Requests beyond the fourth concurrent one are queued by the gateway.
+        let in_flight = &AtomicUsize::new(0);
+        let probe = move || async move {
+            let concurrent = in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+            let latency = match concurrent {
+                1..=4 => Duration::from_millis(100),
+                _ => Duration::from_millis(500),
+            };
+            tokio::time::sleep(latency).await;
+            in_flight.fetch_sub(1, Ordering::Relaxed);
+            Ok(())
+        };
+
+        let recommendation = calibrate_with(probe, &CalibrationOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            recommendation,
+            ConcurrencyRecommendation {
+                concurrency: 4,
+                baseline_latency: Duration::from_millis(100),
+                limited_by: Some(CalibrationLimit::Latency {
+                    concurrency: 8,
+                    latency: Duration::from_millis(500),
+                }),
+            }
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_concurrency_is_recommended_if_the_gateway_keeps_up() {
+        let probe = || async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Ok(())
+        };
+        let options = CalibrationOptions {
+            max_concurrency: 6,
+            ..Default::default()
+        };
+
+        let recommendation = calibrate_with(probe, &options).await.unwrap();
+
+        assert_eq!(recommendation.concurrency, 6);
+        assert_eq!(recommendation.limited_by, None);
+    }
+
+    #[tokio::test]
+    async fn rate_limiting_limits_the_concurrency() {
+        use warp::Filter;
+
+        // Serves sequential requests and those of concurrency 2, then rate limits.
+        let served = Arc::new(AtomicUsize::new(0));
+        let route = warp::path!("feeder_gateway" / "get_contract_addresses").map(move || {
+            let status = match served.fetch_add(1, Ordering::Relaxed) {
+                0..=8 => warp::http::StatusCode::OK,
+                _ => warp::http::StatusCode::TOO_MANY_REQUESTS,
+            };
+            warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e",
+                    "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                })),
+                status,
+            )
+        });
+        let (_, url) = serve_gateway(route);
+        let client = super::super::Client::with_url(url).unwrap();
+
+        // Latencies of a local gateway are too noisy to compare.
+        let options = CalibrationOptions {
+            max_latency_factor: u32::MAX,
+            ..Default::default()
+        };
+        let recommendation = calibrate_concurrency(&client, &options).await.unwrap();
+
+        assert_eq!(recommendation.concurrency, 2);
+        assert_eq!(
+            recommendation.limited_by,
+            Some(CalibrationLimit::RateLimited { concurrency: 4 })
+        );
+    }
+}
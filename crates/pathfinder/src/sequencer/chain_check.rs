@@ -0,0 +1,97 @@
+//! Verification that the gateway serves the configured network before submitting to it.
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use super::error::SequencerError;
+use crate::core::Chain;
+
+/// How long the gateway's network is cached, after which it is fetched again so that a
+/// gateway switching networks is noticed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Caches the network served by the gateway and compares it against the `configured` one.
+#[derive(Debug)]
+pub(super) struct ChainCheck {
+    configured: Chain,
+    /// The network served by the gateway when it was last fetched, [None] if unknown.
+    gateway: Mutex<Option<(Instant, Option<Chain>)>>,
+}
+
+impl ChainCheck {
+    pub fn new(configured: Chain) -> Self {
+        Self {
+            configured,
+            gateway: Mutex::new(None),
+        }
+    }
+
+    /// Fails with [SequencerError::ChainIdMismatch] unless the gateway serves the configured
+    /// network, using `fetch` to get the gateway's network if the cached one is stale.
+    pub async fn verify<F, Fut>(&self, fetch: F) -> Result<(), SequencerError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<Option<Chain>, SequencerError>>,
+    {
+        let mut gateway = self.gateway.lock().await;
+        let chain = match *gateway {
+            Some((fetched_at, chain)) if fetched_at.elapsed() < REFRESH_INTERVAL => chain,
+            _ => {
+                let chain = fetch().await?;
+                *gateway = Some((Instant::now(), chain));
+                chain
+            }
+        };
+
+        if chain == Some(self.configured) {
+            Ok(())
+        } else {
+            Err(SequencerError::ChainIdMismatch {
+                configured: self.configured,
+                gateway: chain,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn gateway_chain_is_cached() {
+        let check = ChainCheck::new(Chain::Testnet);
+
+        check
+            .verify(|| async { Ok(Some(Chain::Testnet)) })
+            .await
+            .unwrap();
+        check
+            .verify(|| async { unreachable!("Gateway chain is cached") })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gateway_chain_change_is_noticed() {
+        let check = ChainCheck::new(Chain::Testnet);
+        check
+            .verify(|| async { Ok(Some(Chain::Testnet)) })
+            .await
+            .unwrap();
+
+        tokio::time::advance(REFRESH_INTERVAL).await;
+        let error = check
+            .verify(|| async { Ok(Some(Chain::Mainnet)) })
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            SequencerError::ChainIdMismatch {
+                configured: Chain::Testnet,
+                gateway: Some(Chain::Mainnet)
+            }
+        );
+    }
+}
@@ -0,0 +1,1858 @@
+//! Helpers for submitting a deploy and following it to a terminal state.
+use std::time::Duration;
+
+use anyhow::Context;
+
+use super::error::{SequencerError, StarknetErrorCode};
+use super::reply::{self, simulation, Status};
+use super::request::add_transaction::{AddTransaction, Declare, Deploy, DeployAccount};
+use super::request::BlockContext;
+use super::ClientApi;
+use crate::core::{
+    BlockId, ClassHash, ConstructorParam, ContractAddress, ContractAddressSalt, Fee,
+    StarknetTransactionHash, StorageAddress, StorageValue, TransactionSignatureElem,
+    TransactionVersion,
+};
+use crate::rpc::v01::types::{BlockHashOrTag, Tag};
+
+const METRIC_FEE_ESTIMATE_ERROR: &str = "fee_estimate_error";
+
+/// Options for [deploy_and_await].
+#[derive(Clone, Debug)]
+pub struct AwaitOptions {
+    /// Delay between the first consecutive transaction status queries.
+    pub poll_interval: Duration,
+    /// The delay is multiplied by this after every query, so that freshly submitted
+    /// transactions are checked quickly while long pending ones are polled less often. A
+    /// factor of 1 polls at a fixed interval.
+    pub poll_backoff_factor: u32,
+    /// Upper bound of the growing delay.
+    pub max_poll_interval: Duration,
+    /// Every delay is randomly shortened or lengthened by up to this fraction of itself, so
+    /// that transactions submitted together do not poll the gateway in synchronized bursts.
+    /// Clamped to `0.0..=1.0`, where 0 polls on a fixed schedule.
+    pub poll_jitter: f64,
+    /// Fetch the full receipt once the transaction is accepted, instead of
+    /// only returning its status.
+    pub fetch_receipt_on_accept: bool,
+    /// Keep polling once the transaction is accepted on L2, until it is accepted on L1 or
+    /// for at most this long since its L2 acceptance. Acceptance on L1 takes hours, so this
+    /// is typically much longer than the wait for L2.
+    pub await_l1_acceptance: Option<Duration>,
+    /// Read this storage slot of the deployed contract once the deploy is accepted, e.g. to
+    /// confirm that the constructor initialized it.
+    pub read_storage_on_accept: Option<StorageAddress>,
+    /// How long after submitting the gateway may not know the transaction yet, before giving up
+    /// with a [TransactionNotIndexed] error. Freshly submitted transactions take a moment to be
+    /// indexed, while one still unknown after a while was most likely dropped.
+    pub indexing_grace_period: Duration,
+}
+
+impl Default for AwaitOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            poll_backoff_factor: 2,
+            max_poll_interval: Duration::from_secs(30),
+            poll_jitter: 0.1,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: None,
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        }
+    }
+}
+
+impl AwaitOptions {
+    /// The delays between consecutive transaction status queries.
+    fn poll_intervals(&self) -> impl Iterator<Item = Duration> {
+        let factor = self.poll_backoff_factor;
+        let max = self.max_poll_interval;
+        let jitter = self.poll_jitter.clamp(0.0, 1.0);
+        std::iter::successors(Some(self.poll_interval.min(max)), move |interval| {
+            Some(interval.saturating_mul(factor).min(max))
+        })
+        .map(move |interval| match jitter > 0.0 {
+            true => {
+                use rand::Rng;
+                interval.mul_f64(rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter))
+            }
+            false => interval,
+        })
+    }
+}
+
+/// The terminal state of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Confirmation {
+    Status(Status),
+    Receipt(Box<reply::TransactionReceipt>),
+}
+
+/// How final the state reached by a transaction or block is, from the least to the most final.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finality {
+    /// The transaction is known to the sequencer, but not part of an accepted block.
+    Received,
+    AcceptedOnL2,
+    AcceptedOnL1,
+}
+
+impl Finality {
+    /// A reverted transaction is still part of an accepted block, while an aborted one never
+    /// made it into one.
+    pub fn of(status: Status) -> Self {
+        match status {
+            Status::AcceptedOnL1 => Finality::AcceptedOnL1,
+            Status::AcceptedOnL2 | Status::Reverted => Finality::AcceptedOnL2,
+            Status::NotReceived
+            | Status::Received
+            | Status::Pending
+            | Status::Rejected
+            | Status::Aborted => Finality::Received,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeployOutcome {
+    pub transaction_hash: StarknetTransactionHash,
+    pub contract_address: ContractAddress,
+    /// The finality of the terminal state, regardless of whether a receipt was fetched.
+    pub finality: Finality,
+    /// The fee charged for the deploy, only known if its receipt was fetched.
+    pub actual_fee: Option<reply::ActualFee>,
+    /// The events emitted by the deploy, such as those of the constructor, only known if its
+    /// receipt was fetched.
+    pub events: Vec<reply::transaction::Event>,
+    /// The value of [AwaitOptions::read_storage_on_accept], only read if the deploy was
+    /// accepted.
+    pub storage_value: Option<StorageValue>,
+    pub confirmation: Confirmation,
+}
+
+/// The deploy was received, but then rejected by the sequencer.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Deploy transaction {} was rejected: {}",
+    .transaction_hash.0,
+    .reason.as_deref().unwrap_or("no reason given")
+)]
+pub struct DeployRejected {
+    pub transaction_hash: StarknetTransactionHash,
+    /// The sequencer's reason for the rejection, if it gave one.
+    pub reason: Option<String>,
+}
+
+/// The deploy was accepted on L2, but not on L1 within
+/// [AwaitOptions::await_l1_acceptance].
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Deploy transaction {} was not accepted on L1 within {:?} of its L2 acceptance",
+    .transaction_hash.0,
+    .waited
+)]
+pub struct L1AcceptanceTimeout {
+    pub transaction_hash: StarknetTransactionHash,
+    pub waited: Duration,
+}
+
+/// The gateway still did not know the deploy [AwaitOptions::indexing_grace_period] after it
+/// was submitted.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Deploy transaction {} is still unknown to the gateway {:?} after submitting it",
+    .transaction_hash.0,
+    .waited
+)]
+pub struct TransactionNotIndexed {
+    pub transaction_hash: StarknetTransactionHash,
+    pub waited: Duration,
+}
+
+/// Submits a DEPLOY transaction and polls its status until it reaches a terminal state,
+/// i.e. it is either accepted or it failed.
+///
+/// If [AwaitOptions::await_l1_acceptance] is set, only acceptance on L1 counts as accepted and
+/// running out of time is returned as a [L1AcceptanceTimeout] error.
+///
+/// A deploy the gateway does not know within [AwaitOptions::indexing_grace_period] is returned
+/// as a [TransactionNotIndexed] error.
+///
+/// A rejected deploy is returned as a [DeployRejected] error. The receipt is only fetched for
+/// accepted transactions and only if [AwaitOptions::fetch_receipt_on_accept] is set.
+///
+/// This polls indefinitely, wrap it in [tokio::time::timeout] to limit the wait.
+///
+/// The submission and all polls are [correlated](super::correlate) as one operation.
+pub async fn deploy_and_await(
+    client: &impl ClientApi,
+    deploy: Deploy,
+    token: Option<String>,
+    options: &AwaitOptions,
+) -> anyhow::Result<DeployOutcome> {
+    super::correlate("deploy", await_deploy(client, deploy, token, options)).await
+}
+
+async fn await_deploy(
+    client: &impl ClientApi,
+    deploy: Deploy,
+    token: Option<String>,
+    options: &AwaitOptions,
+) -> anyhow::Result<DeployOutcome> {
+    let response = client
+        .add_deploy_transaction(
+            deploy.version,
+            deploy.contract_address_salt,
+            deploy.constructor_calldata,
+            deploy.contract_definition,
+            token,
+            None,
+        )
+        .await
+        .context("Submitting deploy transaction")?;
+
+    let submitted_at = std::time::Instant::now();
+    let mut poll_intervals = options.poll_intervals();
+    let mut accepted_on_l2_at = None;
+    let status = loop {
+        let status = match client.transaction_status(response.transaction_hash).await {
+            Ok(status) => status,
+            // Handled like an unknown transaction, see below.
+            Err(SequencerError::TransactionNotYetIndexed { .. }) => reply::TransactionStatus {
+                block_hash: None,
+                tx_status: Status::NotReceived,
+                tx_failure_reason: None,
+            },
+            Err(e) => return Err(anyhow::Error::new(e).context("Polling transaction status")),
+        };
+
+        match status.tx_status {
+            Status::Rejected => {
+                return Err(DeployRejected {
+                    transaction_hash: response.transaction_hash,
+                    reason: status.rejection_reason().map(ToOwned::to_owned),
+                }
+                .into());
+            }
+            Status::AcceptedOnL2 => match options.await_l1_acceptance {
+                Some(limit) => {
+                    let since = *accepted_on_l2_at.get_or_insert_with(std::time::Instant::now);
+                    let waited = since.elapsed();
+                    if waited >= limit {
+                        return Err(L1AcceptanceTimeout {
+                            transaction_hash: response.transaction_hash,
+                            waited,
+                        }
+                        .into());
+                    }
+                    let interval = poll_intervals.next().unwrap_or(options.max_poll_interval);
+                    tokio::time::sleep(interval).await;
+                }
+                None => break status.tx_status,
+            },
+            Status::AcceptedOnL1 | Status::Reverted | Status::Aborted => break status.tx_status,
+            // The deploy was just submitted, so the gateway should know it soon.
+            Status::NotReceived => {
+                let waited = submitted_at.elapsed();
+                if waited >= options.indexing_grace_period {
+                    return Err(TransactionNotIndexed {
+                        transaction_hash: response.transaction_hash,
+                        waited,
+                    }
+                    .into());
+                }
+                let interval = poll_intervals.next().unwrap_or(options.max_poll_interval);
+                tokio::time::sleep(interval).await;
+            }
+            Status::Received | Status::Pending => {
+                let interval = poll_intervals.next().unwrap_or(options.max_poll_interval);
+                tokio::time::sleep(interval).await;
+            }
+        }
+    };
+
+    let finality = Finality::of(status);
+    let confirmation = match status {
+        Status::AcceptedOnL2 | Status::AcceptedOnL1 if options.fetch_receipt_on_accept => {
+            let receipt = client
+                .transaction_receipt(response.transaction_hash)
+                .await
+                .context("Fetching transaction receipt")?;
+            Confirmation::Receipt(Box::new(receipt))
+        }
+        status => Confirmation::Status(status),
+    };
+
+    let (actual_fee, events) = match &confirmation {
+        Confirmation::Receipt(receipt) => (receipt.actual_fee(), receipt.events.clone()),
+        Confirmation::Status(_) => (None, vec![]),
+    };
+
+    let storage_value = match options.read_storage_on_accept {
+        Some(key) if matches!(status, Status::AcceptedOnL2 | Status::AcceptedOnL1) => {
+            // The latest block includes the deploy if the receipt did not tell which one does.
+            let block = match &confirmation {
+                Confirmation::Receipt(receipt) => receipt.block_hash.map(BlockHashOrTag::Hash),
+                Confirmation::Status(_) => None,
+            }
+            .unwrap_or(BlockHashOrTag::Tag(Tag::Latest));
+            let value = client
+                .storage(response.address, key, block)
+                .await
+                .context("Reading storage of the deployed contract")?;
+            Some(value)
+        }
+        _ => None,
+    };
+
+    Ok(DeployOutcome {
+        transaction_hash: response.transaction_hash,
+        contract_address: response.address,
+        finality,
+        actual_fee,
+        events,
+        storage_value,
+        confirmation,
+    })
+}
+
+/// Waits for the block including `transaction_hash` to reach the `target` finality, for at most
+/// `timeout`, e.g. for L1 acceptance of a deploy once [deploy_and_await] returned.
+///
+/// Returns the finality the block reached by then.
+pub async fn await_inclusion_finality(
+    client: &impl ClientApi,
+    transaction_hash: StarknetTransactionHash,
+    target: Finality,
+    timeout: Duration,
+) -> anyhow::Result<Finality> {
+    let transaction = client
+        .transaction(transaction_hash, false)
+        .await
+        .context("Fetching transaction")?;
+    let block = transaction
+        .block_number
+        .with_context(|| format!("Transaction {} is not in a block", transaction_hash.0))?;
+
+    client
+        .await_block_finality(block, target, timeout)
+        .await
+        .context("Awaiting block finality")
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeclareError {
+    /// The class is already on chain, which is not a failure when only its existence matters.
+    #[error("Class {} is already declared", .class_hash.0)]
+    ClassAlreadyDeclared { class_hash: ClassHash },
+    #[error(transparent)]
+    Sequencer(SequencerError),
+}
+
+impl From<SequencerError> for DeclareError {
+    fn from(e: SequencerError) -> Self {
+        match e {
+            SequencerError::StarknetError(ref se) => match se.already_declared_class() {
+                Some(class_hash) => Self::ClassAlreadyDeclared { class_hash },
+                None => Self::Sequencer(e),
+            },
+            e => Self::Sequencer(e),
+        }
+    }
+}
+
+/// Submits a DECLARE transaction.
+pub async fn declare(
+    client: &impl ClientApi,
+    declare: Declare,
+    token: Option<String>,
+) -> Result<reply::add_transaction::DeclareResponse, DeclareError> {
+    let response = client
+        .add_declare_transaction(
+            declare.version,
+            declare.max_fee,
+            declare.signature,
+            declare.nonce,
+            declare.contract_class,
+            declare.sender_address,
+            declare.compiled_class_hash,
+            token,
+        )
+        .await?;
+
+    Ok(response)
+}
+
+/// Result of declaring a single class with [declare_classes].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeclareOutput {
+    Declared(reply::add_transaction::DeclareResponse),
+    /// Nothing was submitted, since the class is already on chain.
+    AlreadyDeclared {
+        class_hash: ClassHash,
+    },
+}
+
+/// Declares the classes of `declares` concurrently, skipping those which are already
+/// declared.
+///
+/// The results are in the order of `declares`. Since the DECLAREs are submitted
+/// concurrently, those from the same sender must already have distinct nonces.
+pub async fn declare_classes(
+    client: &impl ClientApi,
+    declares: Vec<Declare>,
+    token: Option<String>,
+) -> Vec<anyhow::Result<DeclareOutput>> {
+    let declares = declares
+        .into_iter()
+        .map(|declare| declare_if_missing(client, declare, token.clone()));
+
+    futures::future::join_all(declares).await
+}
+
+async fn declare_if_missing(
+    client: &impl ClientApi,
+    declare: Declare,
+    token: Option<String>,
+) -> anyhow::Result<DeclareOutput> {
+    let class_hash = declare
+        .contract_class
+        .class_hash()
+        .context("Computing class hash")?;
+
+    match client.class_by_hash(class_hash).await {
+        Ok(_) => return Ok(DeclareOutput::AlreadyDeclared { class_hash }),
+        Err(SequencerError::StarknetError(e)) if e.code == StarknetErrorCode::UndeclaredClass => {}
+        Err(e) => return Err(anyhow::Error::new(e).context("Checking for the class")),
+    }
+
+    match self::declare(client, declare, token).await {
+        Ok(response) => Ok(DeclareOutput::Declared(response)),
+        // It was declared by someone else in the meantime.
+        Err(DeclareError::ClassAlreadyDeclared { class_hash }) => {
+            Ok(DeclareOutput::AlreadyDeclared { class_hash })
+        }
+        Err(e) => Err(anyhow::Error::new(e).context("Declaring class")),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeclareAndDeployOutcome {
+    /// Not present if the class had already been declared.
+    pub declare_transaction_hash: Option<StarknetTransactionHash>,
+    pub deploy: DeployOutcome,
+}
+
+/// Declares the class of `declare` and then deploys it using [deploy_and_await].
+///
+/// The class already being declared is not considered a failure.
+pub async fn declare_and_deploy(
+    client: &impl ClientApi,
+    declare: Declare,
+    contract_address_salt: ContractAddressSalt,
+    constructor_calldata: Vec<ConstructorParam>,
+    token: Option<String>,
+    options: &AwaitOptions,
+) -> anyhow::Result<DeclareAndDeployOutcome> {
+    let contract_definition = declare.contract_class.clone();
+
+    let declare_transaction_hash = match self::declare(client, declare, token.clone()).await {
+        Ok(response) => Some(response.transaction_hash),
+        Err(DeclareError::ClassAlreadyDeclared { class_hash }) => {
+            tracing::debug!(class_hash=%class_hash.0, "Class is already declared");
+            None
+        }
+        Err(e) => return Err(anyhow::Error::new(e).context("Declaring class")),
+    };
+
+    let deploy = Deploy {
+        version: TransactionVersion::ZERO,
+        contract_address_salt,
+        contract_definition,
+        constructor_calldata,
+    };
+    let deploy = deploy_and_await(client, deploy, token, options).await?;
+
+    Ok(DeclareAndDeployOutcome {
+        declare_transaction_hash,
+        deploy,
+    })
+}
+
+/// Result of [preflight_deploy].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreflightResult {
+    /// `false` if the deploy reverted.
+    pub success: bool,
+    /// Events emitted by the deploy, in emission order. Empty if it reverted.
+    pub events: Vec<simulation::Event>,
+    /// The error reported by the sequencer if the deploy reverted.
+    pub revert_reason: Option<String>,
+}
+
+/// Simulates a DEPLOY transaction on top of the pending block, without submitting it.
+///
+/// A reverting deploy is not an error, but is reported via [PreflightResult::success].
+///
+/// This needs the sequencer, see `preflight_deploy_locally` for running the constructor offline.
+pub async fn preflight_deploy(
+    client: &impl ClientApi,
+    deploy: Deploy,
+) -> Result<PreflightResult, SequencerError> {
+    let simulated = client
+        .simulate_transaction(
+            AddTransaction::Deploy(deploy),
+            BlockId::Pending,
+            BlockContext::default(),
+        )
+        .await;
+
+    match simulated {
+        Ok(simulated) => {
+            let mut events = Vec::new();
+            if let Some(invocation) = simulated.trace.function_invocation {
+                collect_events(invocation, &mut events);
+            }
+            events.sort_by_key(|event| event.order);
+
+            Ok(PreflightResult {
+                success: true,
+                events,
+                revert_reason: None,
+            })
+        }
+        Err(SequencerError::StarknetError(e)) if e.code == StarknetErrorCode::TransactionFailed => {
+            Ok(PreflightResult {
+                success: false,
+                events: vec![],
+                revert_reason: Some(e.message),
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Runs the constructor of a DEPLOY transaction on the `cairo-lang` workers of
+/// [ext_py](crate::cairo::ext_py), on top of the latest block this node has synced.
+///
+/// This is heavier than [preflight_deploy] but needs no sequencer. Events are not collected, so
+/// [PreflightResult::events] is always empty.
+#[cfg(feature = "local-preflight")]
+pub async fn preflight_deploy_locally(
+    handle: &crate::cairo::ext_py::Handle,
+    deploy: Deploy,
+) -> Result<PreflightResult, crate::cairo::ext_py::CallFailure> {
+    use crate::cairo::ext_py::{BlockHashNumberOrLatest, CallFailure};
+
+    match handle
+        .simulate_deploy(deploy, BlockHashNumberOrLatest::Latest, None)
+        .await
+    {
+        Ok(_) => Ok(PreflightResult {
+            success: true,
+            events: vec![],
+            revert_reason: None,
+        }),
+        Err(CallFailure::ExecutionFailed(reason)) => Ok(PreflightResult {
+            success: false,
+            events: vec![],
+            revert_reason: Some(reason),
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+/// Estimates the fee of a DEPLOY transaction by simulating it on top of the pending block.
+///
+/// `block_context` allows estimating time dependent constructors as if they were executed
+/// at a different time.
+pub async fn estimate_deploy_fee(
+    client: &impl ClientApi,
+    deploy: Deploy,
+    block_context: BlockContext,
+) -> Result<simulation::FeeEstimation, SequencerError> {
+    let simulated = client
+        .simulate_transaction(
+            AddTransaction::Deploy(deploy),
+            BlockId::Pending,
+            block_context,
+        )
+        .await?;
+
+    Ok(simulated.fee_estimation)
+}
+
+/// Result of [estimate_and_deploy].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EstimatedDeploy {
+    pub estimate: simulation::FeeEstimation,
+    /// The `max_fee` suggested by the [FeeStrategy], the most the deploy is expected to cost.
+    pub max_fee: Fee,
+    pub deploy: DeployOutcome,
+    /// The actual fee minus the estimated one in wei, [None] if the receipt reported no
+    /// actual fee.
+    pub fee_estimate_error: Option<i128>,
+}
+
+/// Estimates the fee of `deploy` on top of the pending block, sets the `max_fee` to the one
+/// `strategy` suggests for the estimate, then deploys it using [deploy_and_await] and compares the
+/// estimate to the actual fee of its receipt.
+///
+/// DEPLOY transactions carry no fee on the wire, so the `max_fee` is not submitted. Instead,
+/// deploys whose actual fee exceeds it are logged, as a sign of the strategy being too tight.
+/// The receipt is always fetched, regardless of [AwaitOptions::fetch_receipt_on_accept]. The
+/// error is recorded in the `fee_estimate_error` histogram, which helps calibrating fee
+/// multipliers. Nothing is submitted if the estimate fails.
+pub async fn estimate_and_deploy(
+    client: &impl ClientApi,
+    deploy: Deploy,
+    strategy: &impl FeeStrategy,
+    token: Option<String>,
+    options: &AwaitOptions,
+) -> anyhow::Result<EstimatedDeploy> {
+    let estimate = estimate_deploy_fee(client, deploy.clone(), BlockContext::default())
+        .await
+        .context("Estimating fee")?;
+    let max_fee = strategy.suggest_max_fee(&estimate);
+
+    let options = AwaitOptions {
+        fetch_receipt_on_accept: true,
+        ..options.clone()
+    };
+    let deploy = deploy_and_await(client, deploy, token, &options).await?;
+
+    let actual_fee = deploy
+        .actual_fee
+        .as_ref()
+        .map(|actual| u128::from_be_bytes(actual.amount.0.to_fixed_bytes()));
+    let fee_estimate_error =
+        actual_fee.map(|actual| (actual as i128).saturating_sub(estimate.overall_fee as i128));
+    if let Some(error) = fee_estimate_error {
+        metrics::histogram!(METRIC_FEE_ESTIMATE_ERROR, error as f64);
+    }
+    if let Some(actual) = actual_fee {
+        let max = u128::from_be_bytes(max_fee.0.to_fixed_bytes());
+        if actual > max {
+            tracing::warn!(actual_fee=%actual, max_fee=%max, transaction_hash=%deploy.transaction_hash.0, "Deploy cost more than its max fee");
+        }
+    }
+
+    Ok(EstimatedDeploy {
+        estimate,
+        max_fee,
+        deploy,
+        fee_estimate_error,
+    })
+}
+
+/// Turns a fee estimate into the `max_fee` a transaction is submitted with, see
+/// [estimate_and_deploy] and [estimate_and_deploy_account].
+///
+/// The `max_fee` should leave room for the fee rising between the estimate and the execution
+/// of the transaction. [FeeMultiplier] is the default, other heuristics such as a percentile of
+/// recent fees or an oracle can be plugged in by implementing this.
+pub trait FeeStrategy: Send + Sync {
+    fn suggest_max_fee(&self, estimate: &simulation::FeeEstimation) -> Fee;
+}
+
+/// Suggests the estimate times a fixed factor of at least one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FeeMultiplier(u32);
+
+impl FeeMultiplier {
+    /// A factor of `basis_points` / 10 000, e.g. `15_000` for 1.5. Factors below one would
+    /// suggest a `max_fee` below the estimate, and are rejected.
+    pub fn new(basis_points: u32) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            basis_points >= 10_000,
+            "Fee multiplier of {} basis points is below one",
+            basis_points
+        );
+        Ok(Self(basis_points))
+    }
+}
+
+impl Default for FeeMultiplier {
+    fn default() -> Self {
+        Self(15_000)
+    }
+}
+
+impl FeeStrategy for FeeMultiplier {
+    fn suggest_max_fee(&self, estimate: &simulation::FeeEstimation) -> Fee {
+        let max_fee = multiply_fee(estimate.overall_fee, self.0);
+        Fee(max_fee.to_be_bytes().into())
+    }
+}
+
+/// Multiplies `fee` by `basis_points` / 10 000 in integers, rounding up and saturating at the
+/// largest fee.
+pub(crate) fn multiply_fee(fee: u128, basis_points: u32) -> u128 {
+    let bps = u128::from(basis_points);
+    let whole = (fee / 10_000).saturating_mul(bps);
+    let fraction = ((fee % 10_000) * bps + 9_999) / 10_000;
+    whole.saturating_add(fraction)
+}
+
+/// Result of [estimate_and_deploy_account].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EstimatedDeployAccount {
+    pub estimate: simulation::FeeEstimation,
+    /// The `max_fee` suggested by the [FeeStrategy], which the transaction was submitted with.
+    pub max_fee: Fee,
+    pub response: reply::add_transaction::DeployAccountResponse,
+}
+
+/// Estimates the fee of `deploy_account` on top of the pending block and then submits it
+/// with the `max_fee` the `strategy` suggests for the estimate.
+///
+/// DEPLOY transactions have no fee, hence this deploys an account. Since the signature
+/// covers the `max_fee`, the submitted transaction is signed by `sign` once the `max_fee` is
+/// known. Nothing is submitted if the estimate fails or if a custom `strategy` suggests a
+/// `max_fee` below the estimate, which the sequencer would likely reject.
+pub async fn estimate_and_deploy_account(
+    client: &impl ClientApi,
+    deploy_account: DeployAccount,
+    strategy: &impl FeeStrategy,
+    sign: impl FnOnce(Fee) -> Vec<TransactionSignatureElem>,
+) -> anyhow::Result<EstimatedDeployAccount> {
+    let estimate = client
+        .simulate_transaction(
+            AddTransaction::DeployAccount(deploy_account.clone()),
+            BlockId::Pending,
+            BlockContext::default(),
+        )
+        .await
+        .context("Estimating fee")?
+        .fee_estimation;
+
+    let max_fee = strategy.suggest_max_fee(&estimate);
+    let suggested = u128::from_be_bytes(max_fee.0.to_fixed_bytes());
+    anyhow::ensure!(
+        suggested >= estimate.overall_fee,
+        "Suggested max fee {} is below the estimated fee {}",
+        suggested,
+        estimate.overall_fee
+    );
+
+    let response = client
+        .add_deploy_account(
+            deploy_account.version,
+            max_fee,
+            sign(max_fee),
+            deploy_account.nonce,
+            deploy_account.contract_address_salt,
+            deploy_account.class_hash,
+            deploy_account.constructor_calldata,
+        )
+        .await
+        .context("Submitting deploy account")?;
+
+    Ok(EstimatedDeployAccount {
+        estimate,
+        max_fee,
+        response,
+    })
+}
+
+fn collect_events(invocation: simulation::FunctionInvocation, events: &mut Vec<simulation::Event>) {
+    events.extend(invocation.events);
+    for call in invocation.internal_calls {
+        collect_events(call, events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{EventData, EventKey};
+    use crate::sequencer::reply::add_transaction::DeployResponse;
+    use crate::sequencer::request::add_transaction::ContractDefinition;
+    use crate::sequencer::MockClientApi;
+    use crate::starkhash;
+
+    const TRANSACTION_HASH: StarknetTransactionHash = StarknetTransactionHash(starkhash!("01"));
+    const CONTRACT_ADDRESS: ContractAddress = ContractAddress::new_or_panic(starkhash!("02"));
+    const REJECTION_REASON: &str = "Insufficient max fee";
+
+    fn deploy() -> Deploy {
+        Deploy {
+            version: TransactionVersion::ZERO,
+            contract_address_salt: ContractAddressSalt(starkhash!("03")),
+            contract_definition: ContractDefinition {
+                program: String::new(),
+                entry_points_by_type: Default::default(),
+                abi: None,
+            },
+            constructor_calldata: vec![],
+        }
+    }
+
+    fn constructor_event() -> reply::transaction::Event {
+        reply::transaction::Event {
+            data: vec![EventData(starkhash!("05"))],
+            from_address: CONTRACT_ADDRESS,
+            keys: vec![EventKey(starkhash!("04"))],
+        }
+    }
+
+    fn receipt() -> reply::TransactionReceipt {
+        reply::TransactionReceipt {
+            status: Status::AcceptedOnL2,
+            block_hash: None,
+            block_number: None,
+            transaction_index: None,
+            transaction_hash: Some(TRANSACTION_HASH),
+            actual_fee: Some(Fee(web3::types::H128::from_low_u64_be(1_000))),
+            events: vec![constructor_event()],
+            execution_resources: None,
+            l1_to_l2_consumed_message: None,
+            l2_to_l1_messages: vec![],
+        }
+    }
+
+    /// A sequencer which accepts the deploy and reports the given status progression.
+    ///
+    /// Rejections are reported with [REJECTION_REASON].
+    fn sequencer(statuses: Vec<Status>) -> MockClientApi {
+        let mut sequencer = MockClientApi::new();
+        sequencer
+            .expect_add_deploy_transaction()
+            .times(1)
+            .returning(|_, _, _, _, _, _| {
+                Ok(DeployResponse {
+                    code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                    transaction_hash: TRANSACTION_HASH,
+                    address: CONTRACT_ADDRESS,
+                    other: Default::default(),
+                })
+            });
+
+        let mut statuses = statuses.into_iter();
+        sequencer
+            .expect_transaction_status()
+            .withf(|hash| *hash == TRANSACTION_HASH)
+            .returning(move |_| {
+                let tx_status = statuses.next().expect("Polled past the terminal status");
+                let tx_failure_reason =
+                    (tx_status == Status::Rejected).then(|| reply::TransactionFailureReason {
+                        code: "TRANSACTION_FAILED".to_owned(),
+                        error_message: REJECTION_REASON.to_owned(),
+                    });
+                Ok(reply::TransactionStatus {
+                    block_hash: None,
+                    tx_status,
+                    tx_failure_reason,
+                })
+            });
+
+        sequencer
+    }
+
+    #[tokio::test]
+    async fn status_only() {
+        let mut sequencer = sequencer(vec![
+            Status::NotReceived,
+            Status::Received,
+            Status::AcceptedOnL2,
+        ]);
+        sequencer.expect_transaction_receipt().never();
+
+        let options = AwaitOptions {
+            poll_interval: Duration::ZERO,
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::ZERO,
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: None,
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        let outcome = deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            outcome,
+            DeployOutcome {
+                transaction_hash: TRANSACTION_HASH,
+                contract_address: CONTRACT_ADDRESS,
+                finality: Finality::AcceptedOnL2,
+                actual_fee: None,
+                events: vec![],
+                storage_value: None,
+                confirmation: Confirmation::Status(Status::AcceptedOnL2),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn receipt_on_accept() {
+        let mut sequencer = sequencer(vec![Status::Received, Status::AcceptedOnL2]);
+        sequencer
+            .expect_transaction_receipt()
+            .times(1)
+            .withf(|hash| *hash == TRANSACTION_HASH)
+            .returning(|_| Ok(receipt()));
+
+        let options = AwaitOptions {
+            poll_interval: Duration::ZERO,
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::ZERO,
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: true,
+            await_l1_acceptance: None,
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        let outcome = deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            outcome.confirmation,
+            Confirmation::Receipt(Box::new(receipt()))
+        );
+        assert_eq!(
+            outcome.actual_fee,
+            Some(reply::ActualFee {
+                amount: Fee(web3::types::H128::from_low_u64_be(1_000)),
+                unit: reply::FeeUnit::Wei,
+            })
+        );
+        assert_eq!(outcome.events, vec![constructor_event()]);
+    }
+
+    #[tokio::test]
+    async fn not_yet_indexed_is_awaited() {
+        let mut sequencer = MockClientApi::new();
+        sequencer
+            .expect_add_deploy_transaction()
+            .times(1)
+            .returning(|_, _, _, _, _, _| {
+                Ok(DeployResponse {
+                    code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                    transaction_hash: TRANSACTION_HASH,
+                    address: CONTRACT_ADDRESS,
+                    other: Default::default(),
+                })
+            });
+        let mut polls = 0;
+        sequencer
+            .expect_transaction_status()
+            .times(3)
+            .returning(move |transaction_hash| {
+                polls += 1;
+                match polls {
+                    1 => Err(SequencerError::TransactionNotYetIndexed { transaction_hash }),
+                    2 => Ok(reply::TransactionStatus {
+                        block_hash: None,
+                        tx_status: Status::Received,
+                        tx_failure_reason: None,
+                    }),
+                    _ => Ok(reply::TransactionStatus {
+                        block_hash: None,
+                        tx_status: Status::AcceptedOnL2,
+                        tx_failure_reason: None,
+                    }),
+                }
+            });
+
+        let options = AwaitOptions {
+            poll_interval: Duration::ZERO,
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::ZERO,
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: None,
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        let outcome = deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.finality, Finality::AcceptedOnL2);
+    }
+
+    #[tokio::test]
+    async fn unknown_deploy_fails_after_grace_period() {
+        let mut sequencer = MockClientApi::new();
+        sequencer
+            .expect_add_deploy_transaction()
+            .times(1)
+            .returning(|_, _, _, _, _, _| {
+                Ok(DeployResponse {
+                    code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                    transaction_hash: TRANSACTION_HASH,
+                    address: CONTRACT_ADDRESS,
+                    other: Default::default(),
+                })
+            });
+        // Both ways of the gateway not knowing the transaction count towards the grace period.
+        let mut polls = 0;
+        sequencer
+            .expect_transaction_status()
+            .returning(move |transaction_hash| {
+                polls += 1;
+                match polls % 2 {
+                    0 => Err(SequencerError::TransactionNotYetIndexed { transaction_hash }),
+                    _ => Ok(reply::TransactionStatus {
+                        block_hash: None,
+                        tx_status: Status::NotReceived,
+                        tx_failure_reason: None,
+                    }),
+                }
+            });
+
+        let options = AwaitOptions {
+            poll_interval: Duration::from_millis(10),
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::from_millis(10),
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: None,
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_millis(50),
+        };
+        let error = deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap_err();
+
+        let error = error.downcast::<TransactionNotIndexed>().unwrap();
+        assert_eq!(error.transaction_hash, TRANSACTION_HASH);
+        assert!(error.waited >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn storage_on_accept() {
+        let mut sequencer = sequencer(vec![Status::Received, Status::AcceptedOnL2]);
+        sequencer
+            .expect_storage()
+            .times(1)
+            .withf(|address, key, block| {
+                *address == CONTRACT_ADDRESS
+                    && *key == StorageAddress::new_or_panic(starkhash!("0a"))
+                    && *block == BlockHashOrTag::Tag(Tag::Latest)
+            })
+            .returning(|_, _, _| Ok(StorageValue(starkhash!("0b"))));
+
+        let options = AwaitOptions {
+            poll_interval: Duration::ZERO,
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::ZERO,
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: None,
+            read_storage_on_accept: Some(StorageAddress::new_or_panic(starkhash!("0a"))),
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        let outcome = deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.storage_value, Some(StorageValue(starkhash!("0b"))));
+    }
+
+    #[tokio::test]
+    async fn rejection_reason_when_rejected() {
+        let mut sequencer = sequencer(vec![Status::Pending, Status::Rejected]);
+        sequencer.expect_transaction_receipt().never();
+
+        let options = AwaitOptions {
+            poll_interval: Duration::ZERO,
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::ZERO,
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: true,
+            await_l1_acceptance: None,
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        let error = deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<DeployRejected>(),
+            Some(&DeployRejected {
+                transaction_hash: TRANSACTION_HASH,
+                reason: Some(REJECTION_REASON.to_owned()),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn awaits_l1_acceptance() {
+        let mut sequencer = sequencer(vec![
+            Status::Received,
+            Status::AcceptedOnL2,
+            Status::AcceptedOnL2,
+            Status::AcceptedOnL1,
+        ]);
+        sequencer.expect_transaction_receipt().never();
+
+        let options = AwaitOptions {
+            poll_interval: Duration::ZERO,
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::ZERO,
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: Some(Duration::from_secs(60)),
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        let outcome = deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            outcome.confirmation,
+            Confirmation::Status(Status::AcceptedOnL1)
+        );
+        assert_eq!(outcome.finality, Finality::AcceptedOnL1);
+    }
+
+    #[tokio::test]
+    async fn finality_of_each_terminal_state() {
+        for (status, finality) in [
+            (Status::AcceptedOnL2, Finality::AcceptedOnL2),
+            (Status::AcceptedOnL1, Finality::AcceptedOnL1),
+            (Status::Reverted, Finality::AcceptedOnL2),
+            (Status::Aborted, Finality::Received),
+        ] {
+            let sequencer = sequencer(vec![Status::Received, status]);
+            let options = AwaitOptions {
+                poll_interval: Duration::ZERO,
+                poll_backoff_factor: 1,
+                max_poll_interval: Duration::ZERO,
+                poll_jitter: 0.0,
+                fetch_receipt_on_accept: false,
+                await_l1_acceptance: None,
+                read_storage_on_accept: None,
+                indexing_grace_period: Duration::from_secs(60),
+            };
+            let outcome = deploy_and_await(&sequencer, deploy(), None, &options)
+                .await
+                .unwrap();
+
+            assert_eq!(outcome.finality, finality, "{}", status);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_interval_grows() {
+        let mut sequencer = MockClientApi::new();
+        sequencer
+            .expect_add_deploy_transaction()
+            .returning(|_, _, _, _, _, _| {
+                Ok(DeployResponse {
+                    code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                    transaction_hash: TRANSACTION_HASH,
+                    address: CONTRACT_ADDRESS,
+                    other: Default::default(),
+                })
+            });
+        let polled_at = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let polls = polled_at.clone();
+        sequencer.expect_transaction_status().returning(move |_| {
+            let mut polls = polls.lock().unwrap();
+            polls.push(tokio::time::Instant::now());
+            let tx_status = match polls.len() {
+                6 => Status::AcceptedOnL2,
+                _ => Status::Received,
+            };
+            Ok(reply::TransactionStatus {
+                block_hash: None,
+                tx_status,
+                tx_failure_reason: None,
+            })
+        });
+
+        let options = AwaitOptions {
+            poll_interval: Duration::from_secs(1),
+            poll_backoff_factor: 2,
+            max_poll_interval: Duration::from_secs(5),
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: None,
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap();
+
+        let polled_at = polled_at.lock().unwrap();
+        let intervals = polled_at
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .collect::<Vec<_>>();
+        assert_eq!(intervals, [1, 2, 4, 5, 5].map(Duration::from_secs).to_vec());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn jitter_spreads_polls() {
+        const DEPLOYS: usize = 10;
+
+        let mut sequencer = MockClientApi::new();
+        sequencer
+            .expect_add_deploy_transaction()
+            .returning(|_, _, _, _, _, _| {
+                Ok(DeployResponse {
+                    code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                    transaction_hash: TRANSACTION_HASH,
+                    address: CONTRACT_ADDRESS,
+                    other: Default::default(),
+                })
+            });
+        // Every deploy polls once right after its submission and once more after the jittered
+        // interval, by which time it is accepted.
+        let polled_at = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let polls = polled_at.clone();
+        sequencer.expect_transaction_status().returning(move |_| {
+            let mut polls = polls.lock().unwrap();
+            polls.push(tokio::time::Instant::now());
+            let tx_status = match polls.len() {
+                1..=DEPLOYS => Status::Received,
+                _ => Status::AcceptedOnL2,
+            };
+            Ok(reply::TransactionStatus {
+                block_hash: None,
+                tx_status,
+                tx_failure_reason: None,
+            })
+        });
+
+        let options = AwaitOptions {
+            poll_interval: Duration::from_secs(10),
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::from_secs(10),
+            poll_jitter: 0.5,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: None,
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        let start = tokio::time::Instant::now();
+        let deploys = (0..DEPLOYS).map(|_| deploy_and_await(&sequencer, deploy(), None, &options));
+        for result in futures::future::join_all(deploys).await {
+            result.unwrap();
+        }
+
+        let polled_at = polled_at.lock().unwrap();
+        let (submitted, jittered) = polled_at.split_at(DEPLOYS);
+        assert!(submitted.iter().all(|at| *at == start));
+        for at in jittered {
+            let interval = *at - start;
+            assert!(interval >= Duration::from_secs(5), "{interval:?}");
+            assert!(interval <= Duration::from_secs(15), "{interval:?}");
+        }
+        let distinct = jittered
+            .iter()
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        assert!(distinct > 1, "Polls are aligned");
+    }
+
+    #[tokio::test]
+    async fn l1_acceptance_times_out() {
+        let sequencer = sequencer(vec![Status::AcceptedOnL2]);
+
+        let options = AwaitOptions {
+            poll_interval: Duration::ZERO,
+            poll_backoff_factor: 1,
+            max_poll_interval: Duration::ZERO,
+            poll_jitter: 0.0,
+            fetch_receipt_on_accept: false,
+            await_l1_acceptance: Some(Duration::ZERO),
+            read_storage_on_accept: None,
+            indexing_grace_period: Duration::from_secs(60),
+        };
+        let error = deploy_and_await(&sequencer, deploy(), None, &options)
+            .await
+            .unwrap_err();
+
+        assert_matches::assert_matches!(
+            error.downcast_ref::<L1AcceptanceTimeout>(),
+            Some(L1AcceptanceTimeout { transaction_hash, .. }) => assert_eq!(*transaction_hash, TRANSACTION_HASH)
+        );
+    }
+
+    #[tokio::test]
+    async fn inclusion_block_finality_is_awaited() {
+        use crate::core::StarknetBlockNumber;
+
+        const BLOCK: StarknetBlockNumber = StarknetBlockNumber::new_or_panic(7);
+
+        let mut sequencer = MockClientApi::new();
+        sequencer
+            .expect_transaction()
+            .withf(|hash, include_block| *hash == TRANSACTION_HASH && !include_block)
+            .returning(|_, _| {
+                Ok(reply::Transaction {
+                    block_hash: None,
+                    block_number: Some(BLOCK),
+                    status: Status::AcceptedOnL2,
+                    transaction: None,
+                    transaction_index: Some(0),
+                    block: None,
+                })
+            });
+        sequencer
+            .expect_await_block_finality()
+            .withf(|block, target, timeout| {
+                *block == BLOCK
+                    && *target == Finality::AcceptedOnL1
+                    && *timeout == Duration::from_secs(60)
+            })
+            .returning(|_, _, _| Ok(Finality::AcceptedOnL1));
+
+        let finality = await_inclusion_finality(
+            &sequencer,
+            TRANSACTION_HASH,
+            Finality::AcceptedOnL1,
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+        assert_eq!(finality, Finality::AcceptedOnL1);
+    }
+
+    mod declare_and_deploy {
+        use super::*;
+        use crate::core::{Fee, TransactionNonce};
+        use crate::sequencer::error::{StarknetError, StarknetErrorCode};
+        use crate::sequencer::reply::add_transaction::DeclareResponse;
+
+        const CLASS_HASH: ClassHash = ClassHash(starkhash!("04"));
+
+        fn declare() -> Declare {
+            Declare {
+                version: TransactionVersion::ONE,
+                max_fee: Fee(Default::default()),
+                signature: vec![],
+                contract_class: deploy().contract_definition,
+                sender_address: ContractAddress::new_or_panic(starkhash!("05")),
+                nonce: TransactionNonce::ZERO,
+                compiled_class_hash: None,
+            }
+        }
+
+        fn already_declared() -> SequencerError {
+            SequencerError::StarknetError(StarknetError {
+                code: StarknetErrorCode::ClassAlreadyDeclared,
+                message: "Class with hash 0x4 is already declared.".to_owned(),
+                problems: None,
+            })
+        }
+
+        #[test]
+        fn already_declared_is_detected() {
+            assert_matches::assert_matches!(
+                DeclareError::from(already_declared()),
+                DeclareError::ClassAlreadyDeclared { class_hash } => assert_eq!(class_hash, CLASS_HASH)
+            );
+        }
+
+        #[tokio::test]
+        async fn declared() {
+            let mut sequencer = sequencer(vec![Status::AcceptedOnL2]);
+            sequencer
+                .expect_add_declare_transaction()
+                .times(1)
+                .returning(|_, _, _, _, _, _, _, _| {
+                    Ok(DeclareResponse {
+                        code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                        transaction_hash: StarknetTransactionHash(starkhash!("06")),
+                        class_hash: CLASS_HASH,
+                    })
+                });
+
+            let outcome = declare_and_deploy(
+                &sequencer,
+                declare(),
+                ContractAddressSalt(starkhash!("03")),
+                vec![],
+                None,
+                &AwaitOptions {
+                    poll_interval: Duration::ZERO,
+                    poll_backoff_factor: 1,
+                    max_poll_interval: Duration::ZERO,
+                    poll_jitter: 0.0,
+                    fetch_receipt_on_accept: false,
+                    await_l1_acceptance: None,
+                    read_storage_on_accept: None,
+                    indexing_grace_period: Duration::from_secs(60),
+                },
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(
+                outcome.declare_transaction_hash,
+                Some(StarknetTransactionHash(starkhash!("06")))
+            );
+            assert_eq!(outcome.deploy.contract_address, CONTRACT_ADDRESS);
+        }
+
+        #[tokio::test]
+        async fn already_declared_is_not_fatal() {
+            let mut sequencer = sequencer(vec![Status::AcceptedOnL2]);
+            sequencer
+                .expect_add_declare_transaction()
+                .times(1)
+                .returning(|_, _, _, _, _, _, _, _| Err(already_declared()));
+
+            let outcome = declare_and_deploy(
+                &sequencer,
+                declare(),
+                ContractAddressSalt(starkhash!("03")),
+                vec![],
+                None,
+                &AwaitOptions {
+                    poll_interval: Duration::ZERO,
+                    poll_backoff_factor: 1,
+                    max_poll_interval: Duration::ZERO,
+                    poll_jitter: 0.0,
+                    fetch_receipt_on_accept: false,
+                    await_l1_acceptance: None,
+                    read_storage_on_accept: None,
+                    indexing_grace_period: Duration::from_secs(60),
+                },
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(outcome.declare_transaction_hash, None);
+            assert_eq!(
+                outcome.deploy.confirmation,
+                Confirmation::Status(Status::AcceptedOnL2)
+            );
+        }
+
+        #[tokio::test]
+        async fn other_errors_are_fatal() {
+            let mut sequencer = MockClientApi::new();
+            sequencer
+                .expect_add_declare_transaction()
+                .times(1)
+                .returning(|_, _, _, _, _, _, _, _| {
+                    Err(SequencerError::StarknetError(StarknetError {
+                        code: StarknetErrorCode::InvalidProgram,
+                        message: "Invalid program.".to_owned(),
+                        problems: None,
+                    }))
+                });
+            sequencer.expect_add_deploy_transaction().never();
+
+            declare_and_deploy(
+                &sequencer,
+                declare(),
+                ContractAddressSalt(starkhash!("03")),
+                vec![],
+                None,
+                &AwaitOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        }
+    }
+
+    mod preflight_deploy {
+        use super::*;
+        use crate::core::{EventData, EventKey};
+        use crate::sequencer::error::StarknetError;
+        use crate::sequencer::reply::SimulatedTransaction;
+
+        #[tokio::test]
+        async fn reverted() {
+            let mut sequencer = MockClientApi::new();
+            sequencer
+                .expect_simulate_transaction()
+                .times(1)
+                .withf(|tx, block, _| {
+                    matches!(tx, AddTransaction::Deploy(_)) && *block == BlockId::Pending
+                })
+                .returning(|_, _, _| {
+                    Err(SequencerError::StarknetError(StarknetError {
+                        code: StarknetErrorCode::TransactionFailed,
+                        message: "Error in the called contract: constructor reverted.".to_owned(),
+                        problems: None,
+                    }))
+                });
+
+            let result = preflight_deploy(&sequencer, deploy()).await.unwrap();
+
+            assert_eq!(
+                result,
+                PreflightResult {
+                    success: false,
+                    events: vec![],
+                    revert_reason: Some(
+                        "Error in the called contract: constructor reverted.".to_owned()
+                    ),
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn succeeded() {
+            let mut sequencer = MockClientApi::new();
+            sequencer
+                .expect_simulate_transaction()
+                .times(1)
+                .returning(|_, _, _| {
+                    Ok(
+                        serde_json::from_value::<SimulatedTransaction>(serde_json::json!({
+                            "trace": {
+                                "function_invocation": {
+                                    "events": [{"order": 2, "keys": ["0x7"], "data": []}],
+                                    "internal_calls": [{
+                                        "events": [{"order": 0, "keys": ["0x8"], "data": ["0x9"]}],
+                                        "internal_calls": [],
+                                    }],
+                                },
+                                "signature": [],
+                            },
+                            "fee_estimation": {"overall_fee": 0, "gas_price": 0, "gas_usage": 0},
+                        }))
+                        .unwrap(),
+                    )
+                });
+
+            let result = preflight_deploy(&sequencer, deploy()).await.unwrap();
+
+            assert!(result.success);
+            assert_eq!(result.revert_reason, None);
+            assert_eq!(
+                result.events,
+                vec![
+                    simulation::Event {
+                        order: 0,
+                        data: vec![EventData(starkhash!("09"))],
+                        keys: vec![EventKey(starkhash!("08"))],
+                    },
+                    simulation::Event {
+                        order: 2,
+                        data: vec![],
+                        keys: vec![EventKey(starkhash!("07"))],
+                    },
+                ]
+            );
+        }
+    }
+
+    mod declare_classes {
+        use super::*;
+        use crate::core::TransactionNonce;
+        use crate::rpc::v02::types::ContractClass;
+        use crate::sequencer::error::StarknetError;
+        use crate::sequencer::reply::add_transaction::DeclareResponse;
+
+        fn declare(fixture: &[u8], nonce: TransactionNonce) -> Declare {
+            let definition = zstd::decode_all(fixture).unwrap();
+            let class = ContractClass::from_definition_bytes(&definition).unwrap();
+            Declare {
+                version: TransactionVersion::ONE,
+                max_fee: Fee(Default::default()),
+                signature: vec![],
+                contract_class: ContractDefinition::try_from(class).unwrap(),
+                sender_address: ContractAddress::new_or_panic(starkhash!("05")),
+                nonce,
+                compiled_class_hash: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn already_declared_are_skipped() {
+            let declared = declare(
+                include_bytes!("../../fixtures/contract_definition.json.zst"),
+                TransactionNonce::ZERO,
+            );
+            let new = declare(
+                include_bytes!("../../fixtures/dummy_account.json.zst"),
+                TransactionNonce(starkhash!("01")),
+            );
+            let declared_hash = declared.contract_class.class_hash().unwrap();
+            let new_hash = new.contract_class.class_hash().unwrap();
+
+            let mut sequencer = MockClientApi::new();
+            sequencer
+                .expect_class_by_hash()
+                .times(2)
+                .returning(move |class_hash| {
+                    if class_hash == declared_hash {
+                        Ok(bytes::Bytes::from_static(b"{}"))
+                    } else {
+                        Err(SequencerError::StarknetError(StarknetError {
+                            code: StarknetErrorCode::UndeclaredClass,
+                            message: String::new(),
+                            problems: None,
+                        }))
+                    }
+                });
+            sequencer
+                .expect_add_declare_transaction()
+                .times(1)
+                .withf(|_, _, _, nonce, _, _, _, _| *nonce == TransactionNonce(starkhash!("01")))
+                .returning(move |_, _, _, _, _, _, _, _| {
+                    Ok(DeclareResponse {
+                        code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                        transaction_hash: StarknetTransactionHash(starkhash!("06")),
+                        class_hash: new_hash,
+                    })
+                });
+
+            let results = declare_classes(&sequencer, vec![declared, new], None).await;
+            let results = results.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+            assert_eq!(
+                results,
+                vec![
+                    DeclareOutput::AlreadyDeclared {
+                        class_hash: declared_hash
+                    },
+                    DeclareOutput::Declared(DeclareResponse {
+                        code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                        transaction_hash: StarknetTransactionHash(starkhash!("06")),
+                        class_hash: new_hash,
+                    }),
+                ]
+            );
+        }
+    }
+
+    mod estimate_and_deploy {
+        use super::*;
+        use crate::sequencer::reply::SimulatedTransaction;
+
+        /// Suggests three times the estimate.
+        struct Triple;
+
+        impl FeeStrategy for Triple {
+            fn suggest_max_fee(&self, estimate: &simulation::FeeEstimation) -> Fee {
+                Fee((estimate.overall_fee * 3).to_be_bytes().into())
+            }
+        }
+
+        #[tokio::test]
+        async fn fee_estimate_error() {
+            let mut sequencer = sequencer(vec![Status::AcceptedOnL2]);
+            sequencer
+                .expect_simulate_transaction()
+                .times(1)
+                .withf(|tx, block, _| {
+                    matches!(tx, AddTransaction::Deploy(_)) && *block == BlockId::Pending
+                })
+                .returning(|_, _, _| {
+                    Ok(
+                        serde_json::from_value::<SimulatedTransaction>(serde_json::json!({
+                            "trace": {"function_invocation": null, "signature": []},
+                            "fee_estimation": {"overall_fee": 1200, "gas_price": 10, "gas_usage": 120},
+                        }))
+                        .unwrap(),
+                    )
+                });
+            // The receipt reports an actual fee of 1000.
+            sequencer
+                .expect_transaction_receipt()
+                .times(1)
+                .returning(|_| Ok(receipt()));
+
+            let options = AwaitOptions {
+                poll_interval: Duration::ZERO,
+                poll_backoff_factor: 1,
+                max_poll_interval: Duration::ZERO,
+                poll_jitter: 0.0,
+                fetch_receipt_on_accept: false,
+                await_l1_acceptance: None,
+                read_storage_on_accept: None,
+                indexing_grace_period: Duration::from_secs(60),
+            };
+            let result = estimate_and_deploy(&sequencer, deploy(), &Triple, None, &options)
+                .await
+                .unwrap();
+
+            assert_eq!(result.estimate.overall_fee, 1200);
+            assert_eq!(result.max_fee, Fee(3600u128.to_be_bytes().into()));
+            assert_eq!(result.fee_estimate_error, Some(-200));
+            assert_matches::assert_matches!(result.deploy.confirmation, Confirmation::Receipt(_));
+        }
+    }
+
+    mod estimate_and_deploy_account {
+        use super::*;
+        use crate::core::{CallParam, TransactionNonce};
+        use crate::sequencer::error::StarknetError;
+        use crate::sequencer::reply::add_transaction::DeployAccountResponse;
+        use crate::sequencer::reply::SimulatedTransaction;
+
+        const SIGNATURE: TransactionSignatureElem = TransactionSignatureElem(starkhash!("0519"));
+
+        fn deploy_account() -> DeployAccount {
+            DeployAccount {
+                version: TransactionVersion::ONE,
+                max_fee: Fee(Default::default()),
+                signature: vec![],
+                nonce: TransactionNonce::ZERO,
+                class_hash: ClassHash(starkhash!("04")),
+                contract_address_salt: ContractAddressSalt(starkhash!("03")),
+                constructor_calldata: vec![CallParam(starkhash!("05"))],
+            }
+        }
+
+        fn fee(fee: u128) -> Fee {
+            Fee(fee.to_be_bytes().into())
+        }
+
+        #[tokio::test]
+        async fn estimate_sets_max_fee() {
+            let mut sequencer = MockClientApi::new();
+            sequencer
+                .expect_simulate_transaction()
+                .times(1)
+                .withf(|tx, block, _| {
+                    matches!(tx, AddTransaction::DeployAccount(_)) && *block == BlockId::Pending
+                })
+                .returning(|_, _, _| {
+                    Ok(
+                        serde_json::from_value::<SimulatedTransaction>(serde_json::json!({
+                            "trace": {"function_invocation": null, "signature": []},
+                            "fee_estimation": {"overall_fee": 1000, "gas_price": 10, "gas_usage": 100},
+                        }))
+                        .unwrap(),
+                    )
+                });
+            sequencer
+                .expect_add_deploy_account()
+                .times(1)
+                .withf(|_, max_fee, signature, _, salt, class_hash, calldata| {
+                    *max_fee == fee(1500)
+                        && signature == &[SIGNATURE]
+                        && *salt == ContractAddressSalt(starkhash!("03"))
+                        && *class_hash == ClassHash(starkhash!("04"))
+                        && calldata == &[CallParam(starkhash!("05"))]
+                })
+                .returning(|_, _, _, _, _, _, _| {
+                    Ok(DeployAccountResponse {
+                        code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                        transaction_hash: TRANSACTION_HASH,
+                        address: CONTRACT_ADDRESS,
+                    })
+                });
+
+            let result = estimate_and_deploy_account(
+                &sequencer,
+                deploy_account(),
+                &FeeMultiplier::default(),
+                |max_fee| {
+                    assert_eq!(max_fee, fee(1500));
+                    vec![SIGNATURE]
+                },
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(
+                result,
+                EstimatedDeployAccount {
+                    estimate: simulation::FeeEstimation {
+                        overall_fee: 1000,
+                        l1_gas_consumed: 100,
+                        l1_gas_price: 10,
+                        l2_gas_consumed: None,
+                        l2_gas_price: None,
+                        unit: reply::FeeUnit::Wei,
+                    },
+                    max_fee: fee(1500),
+                    response: DeployAccountResponse {
+                        code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                        transaction_hash: TRANSACTION_HASH,
+                        address: CONTRACT_ADDRESS,
+                    },
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn failed_estimate_is_not_submitted() {
+            let mut sequencer = MockClientApi::new();
+            sequencer
+                .expect_simulate_transaction()
+                .times(1)
+                .returning(|_, _, _| {
+                    Err(SequencerError::StarknetError(StarknetError {
+                        code: StarknetErrorCode::UndeclaredClass,
+                        message: "Class with hash 0x4 is not declared.".to_owned(),
+                        problems: None,
+                    }))
+                });
+            sequencer.expect_add_deploy_account().times(0);
+
+            estimate_and_deploy_account(
+                &sequencer,
+                deploy_account(),
+                &FeeMultiplier::default(),
+                |_| vec![SIGNATURE],
+            )
+            .await
+            .unwrap_err();
+        }
+
+        fn estimate(sequencer: &mut MockClientApi, overall_fee: u128) {
+            sequencer
+                .expect_simulate_transaction()
+                .times(1)
+                .returning(move |_, _, _| {
+                    Ok(
+                        serde_json::from_value::<SimulatedTransaction>(serde_json::json!({
+                            "trace": {"function_invocation": null, "signature": []},
+                            "fee_estimation": {"overall_fee": overall_fee, "gas_price": 10, "gas_usage": 100},
+                        }))
+                        .unwrap(),
+                    )
+                });
+        }
+
+        #[test]
+        fn multiplier_below_one_is_rejected() {
+            FeeMultiplier::new(5_000).unwrap_err();
+            assert_eq!(FeeMultiplier::new(10_000).unwrap(), FeeMultiplier(10_000));
+        }
+
+        #[tokio::test]
+        async fn custom_strategy() {
+            struct Triple;
+
+            impl FeeStrategy for Triple {
+                fn suggest_max_fee(&self, estimate: &simulation::FeeEstimation) -> Fee {
+                    fee(estimate.overall_fee * 3)
+                }
+            }
+
+            let mut sequencer = MockClientApi::new();
+            estimate(&mut sequencer, 1000);
+            sequencer
+                .expect_add_deploy_account()
+                .times(1)
+                .withf(|_, max_fee, _, _, _, _, _| *max_fee == fee(3000))
+                .returning(|_, _, _, _, _, _, _| {
+                    Ok(DeployAccountResponse {
+                        code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                        transaction_hash: TRANSACTION_HASH,
+                        address: CONTRACT_ADDRESS,
+                    })
+                });
+
+            let result = estimate_and_deploy_account(&sequencer, deploy_account(), &Triple, |_| {
+                vec![SIGNATURE]
+            })
+            .await
+            .unwrap();
+
+            assert_eq!(result.max_fee, fee(3000));
+        }
+    }
+}
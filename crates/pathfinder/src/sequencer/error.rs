@@ -1,7 +1,14 @@
 //! Sequencer related error types.
+use crate::core::{Chain, ClassHash, StarknetTransactionHash};
+use crate::rpc::error::ServerErrorCode;
 use crate::rpc::v01::types::reply::ErrorCode as RpcErrorCode;
-use jsonrpsee::{core::error::Error, types::error::CallError};
+use jsonrpsee::{
+    core::error::Error,
+    types::error::{CallError, ErrorObject},
+};
 use serde::{Deserialize, Serialize};
+use stark_hash::StarkHash;
+use std::collections::HashSet;
 
 /// Sequencer errors.
 #[derive(Debug, thiserror::Error)]
@@ -16,6 +23,111 @@ pub enum SequencerError {
     /// not informative enough or bloated
     #[error("error decoding response body: invalid error variant")]
     InvalidStarknetErrorVariant,
+    /// The response body could not be deserialized.
+    #[error("error decoding response body: {0}")]
+    InvalidResponseBody(serde_json::Error),
+    /// The gateway is temporarily unavailable due to maintenance.
+    #[error("gateway is under maintenance: {message}")]
+    GatewayMaintenance { message: String },
+    /// A [RequestMiddleware](super::RequestMiddleware) rejected the request before it was sent.
+    #[error("request rejected by middleware: {0}")]
+    RequestRejected(anyhow::Error),
+    /// The response body exceeded the limit of `limit` bytes, so receiving it was aborted.
+    #[error("response body exceeds the limit of {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+    /// The gateway serves a different network than the `configured` one, [None] if it serves an
+    /// unknown network. Transactions are not submitted, as their hashes would be wrong.
+    #[error(
+        "gateway serves {} but {configured} is configured",
+        .gateway.map_or_else(|| "an unknown network".to_owned(), |chain| chain.to_string())
+    )]
+    ChainIdMismatch {
+        configured: Chain,
+        gateway: Option<Chain>,
+    },
+    /// The gateway did not respond to a request to `endpoint` in time.
+    #[error("request to {endpoint} timed out after {elapsed:?}")]
+    Timeout {
+        elapsed: std::time::Duration,
+        endpoint: &'static str,
+    },
+    /// The gateway does not know the transaction, which happens briefly after submitting it
+    /// and before the gateway indexed it, see [StarknetErrorCode::TransactionNotFound].
+    #[error("transaction {} is not indexed by the gateway yet", .transaction_hash.0)]
+    TransactionNotYetIndexed {
+        transaction_hash: StarknetTransactionHash,
+    },
+    /// The gateway responded with a redirect, which is not followed. Gateways do not redirect,
+    /// so this points at a misconfigured gateway URL, e.g. one of a login page.
+    #[error(
+        "gateway redirected to {}, check the configured gateway URL",
+        .location.as_deref().unwrap_or("an unknown location")
+    )]
+    UnexpectedRedirect { location: Option<String> },
+    /// The client is [offline](super::SequencerConfig::offline), so the request was not sent.
+    #[error("request not sent since the client is offline")]
+    OfflineMode,
+}
+
+impl SequencerError {
+    /// The code of a [SequencerError::StarknetError], [None] for all other errors.
+    pub fn starknet_error_code(&self) -> Option<StarknetErrorCode> {
+        match self {
+            SequencerError::StarknetError(e) => Some(e.code),
+            SequencerError::ReqwestError(_)
+            | SequencerError::InvalidStarknetErrorVariant
+            | SequencerError::InvalidResponseBody(_)
+            | SequencerError::GatewayMaintenance { .. }
+            | SequencerError::RequestRejected(_)
+            | SequencerError::ResponseTooLarge { .. }
+            | SequencerError::ChainIdMismatch { .. }
+            | SequencerError::Timeout { .. }
+            | SequencerError::TransactionNotYetIndexed { .. }
+            | SequencerError::UnexpectedRedirect { .. }
+            | SequencerError::OfflineMode => None,
+        }
+    }
+
+    /// Turns a [StarknetErrorCode::TransactionNotFound] error of a query for
+    /// `transaction_hash` into [SequencerError::TransactionNotYetIndexed].
+    pub(crate) fn not_yet_indexed(self, transaction_hash: StarknetTransactionHash) -> Self {
+        match self {
+            SequencerError::StarknetError(e)
+                if e.code == StarknetErrorCode::TransactionNotFound =>
+            {
+                SequencerError::TransactionNotYetIndexed { transaction_hash }
+            }
+            e => e,
+        }
+    }
+}
+
+/// The JSON-RPC error reported for [SequencerError::GatewayMaintenance].
+pub(crate) fn gateway_maintenance_error(message: &str) -> Error {
+    Error::Call(CallError::Custom(ErrorObject::owned(
+        ServerErrorCode::GatewayMaintenance.code(),
+        format!("Gateway is under maintenance, please retry later: {message}"),
+        None::<()>,
+    )))
+}
+
+/// The JSON-RPC error reported for [SequencerError::Timeout].
+pub(crate) fn gateway_timeout_error() -> Error {
+    Error::Call(CallError::Custom(ErrorObject::owned(
+        ServerErrorCode::GatewayTimeout.code(),
+        "Gateway did not respond in time, please retry",
+        None::<()>,
+    )))
+}
+
+/// The JSON-RPC error reported for a [StarknetErrorCode::InvalidTransactionNonce] which is
+/// recognized as an [InvalidNonce], whose kind is the error data.
+pub(crate) fn invalid_nonce_error(nonce: InvalidNonce, message: &str) -> Error {
+    Error::Call(CallError::Custom(ErrorObject::owned(
+        ServerErrorCode::InvalidNonce.code(),
+        format!("Invalid transaction nonce: {message}"),
+        Some(nonce),
+    )))
 }
 
 impl From<SequencerError> for Error {
@@ -23,6 +135,17 @@ impl From<SequencerError> for Error {
         match e {
             SequencerError::ReqwestError(e) => Error::Call(CallError::Failed(e.into())),
             SequencerError::InvalidStarknetErrorVariant => Error::Call(CallError::Failed(e.into())),
+            SequencerError::InvalidResponseBody(_) => Error::Call(CallError::Failed(e.into())),
+            SequencerError::GatewayMaintenance { message } => gateway_maintenance_error(&message),
+            SequencerError::RequestRejected(_) => Error::Call(CallError::Failed(e.into())),
+            SequencerError::ResponseTooLarge { .. } => Error::Call(CallError::Failed(e.into())),
+            SequencerError::ChainIdMismatch { .. } => Error::Call(CallError::Failed(e.into())),
+            SequencerError::UnexpectedRedirect { .. } => Error::Call(CallError::Failed(e.into())),
+            SequencerError::OfflineMode => Error::Call(CallError::Failed(e.into())),
+            SequencerError::Timeout { .. } => gateway_timeout_error(),
+            SequencerError::TransactionNotYetIndexed { .. } => {
+                RpcErrorCode::InvalidTransactionHash.into()
+            }
             SequencerError::StarknetError(e) => match e.code {
                 StarknetErrorCode::OutOfRangeBlockHash | StarknetErrorCode::BlockNotFound
                     if e.message.contains("Block hash") =>
@@ -31,7 +154,8 @@ impl From<SequencerError> for Error {
                 }
                 StarknetErrorCode::OutOfRangeContractAddress
                 | StarknetErrorCode::UninitializedContract => RpcErrorCode::ContractNotFound.into(),
-                StarknetErrorCode::OutOfRangeTransactionHash => {
+                StarknetErrorCode::OutOfRangeTransactionHash
+                | StarknetErrorCode::TransactionNotFound => {
                     RpcErrorCode::InvalidTransactionHash.into()
                 }
                 StarknetErrorCode::TransactionFailed => RpcErrorCode::InvalidCallData.into(),
@@ -45,16 +169,24 @@ impl From<SequencerError> for Error {
                     RpcErrorCode::InvalidBlockId.into()
                 }
                 StarknetErrorCode::InvalidContractDefinition => RpcErrorCode::ContractError.into(),
+                StarknetErrorCode::InvalidTransactionNonce => match e.invalid_nonce() {
+                    Some(nonce) => invalid_nonce_error(nonce, &e.message),
+                    None => Error::Call(CallError::Failed(e.into())),
+                },
                 StarknetErrorCode::BlockNotFound
                 | StarknetErrorCode::SchemaValidationError
                 | StarknetErrorCode::MalformedRequest
                 | StarknetErrorCode::UnsupportedSelectorForFee
                 | StarknetErrorCode::OutOfRangeBlockHash
                 | StarknetErrorCode::NotPermittedContract
-                | StarknetErrorCode::InvalidTransactionNonce
                 | StarknetErrorCode::OutOfRangeFee
                 | StarknetErrorCode::InvalidTransactionVersion
-                | StarknetErrorCode::InvalidProgram => Error::Call(CallError::Failed(e.into())),
+                | StarknetErrorCode::InvalidProgram
+                | StarknetErrorCode::ClassAlreadyDeclared
+                | StarknetErrorCode::FeeTransferFailure
+                | StarknetErrorCode::DuplicatedTransaction => {
+                    Error::Call(CallError::Failed(e.into()))
+                }
                 StarknetErrorCode::UndeclaredClass => RpcErrorCode::InvalidContractClassHash.into(),
             },
         }
@@ -66,12 +198,200 @@ impl From<SequencerError> for Error {
 pub struct StarknetError {
     pub code: StarknetErrorCode,
     pub message: String,
-    // The `problems` field is intentionally omitted here
-    // Let's deserialize it if it proves necessary
+    /// Which fields of the request failed validation, e.g. for
+    /// [StarknetErrorCode::SchemaValidationError].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub problems: Option<ValidationProblems>,
+}
+
+/// The `problems` of a [StarknetError], which mirror the structure of the rejected request down to
+/// the messages of each field which failed validation.
+///
+/// Any JSON is accepted, so that an unexpected shape of `problems` does not prevent parsing the
+/// error: lone messages and other values become single messages and lists become objects keyed by
+/// index.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ValidationProblems {
+    Messages(Vec<String>),
+    /// The problems of an object's fields or a list's elements, by name or index.
+    Nested(std::collections::BTreeMap<String, ValidationProblems>),
+}
+
+impl ValidationProblems {
+    pub fn parse(value: &serde_json::Value) -> Self {
+        use serde_json::Value;
+
+        match value {
+            Value::Array(values) if values.iter().all(Value::is_string) => Self::Messages(
+                values
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(ToOwned::to_owned)
+                    .collect(),
+            ),
+            Value::Array(values) => Self::Nested(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| (index.to_string(), Self::parse(value)))
+                    .collect(),
+            ),
+            Value::Object(fields) => Self::Nested(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), Self::parse(value)))
+                    .collect(),
+            ),
+            Value::String(message) => Self::Messages(vec![message.clone()]),
+            other => Self::Messages(vec![other.to_string()]),
+        }
+    }
+
+    /// The messages of the nested problems by their path, which joins the names and indices
+    /// leading to them with `.`, e.g. `contract_class.abi.0.type`.
+    pub fn by_path(&self) -> std::collections::BTreeMap<String, &[String]> {
+        fn collect<'a>(
+            problems: &'a ValidationProblems,
+            path: String,
+            paths: &mut std::collections::BTreeMap<String, &'a [String]>,
+        ) {
+            match problems {
+                ValidationProblems::Messages(messages) => {
+                    paths.insert(path, messages);
+                }
+                ValidationProblems::Nested(nested) => {
+                    for (key, problems) in nested {
+                        let path = match path.is_empty() {
+                            true => key.clone(),
+                            false => format!("{path}.{key}"),
+                        };
+                        collect(problems, path, paths);
+                    }
+                }
+            }
+        }
+
+        let mut paths = std::collections::BTreeMap::new();
+        collect(self, String::new(), &mut paths);
+        paths
+    }
+}
+
+impl<'de> Deserialize<'de> for ValidationProblems {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        serde_json::Value::deserialize(deserializer).map(|value| Self::parse(&value))
+    }
 }
 
 impl std::error::Error for StarknetError {}
 
+impl StarknetError {
+    /// Returns the class hash reported by a [StarknetErrorCode::ClassAlreadyDeclared] error.
+    ///
+    /// The hash is only part of the message, which reads like
+    /// `Class with hash 0x1234 is already declared.`
+    pub fn already_declared_class(&self) -> Option<ClassHash> {
+        if self.code != StarknetErrorCode::ClassAlreadyDeclared {
+            return None;
+        }
+
+        self.hash_in_message().map(ClassHash)
+    }
+
+    /// Returns the class hash reported by a [StarknetErrorCode::UndeclaredClass] error.
+    ///
+    /// The hash is only part of the message, which reads like
+    /// `Class with hash 0x1234 is not declared.`
+    pub fn undeclared_class(&self) -> Option<ClassHash> {
+        if self.code != StarknetErrorCode::UndeclaredClass {
+            return None;
+        }
+
+        self.hash_in_message().map(ClassHash)
+    }
+
+    /// Returns the transaction hash reported by a [StarknetErrorCode::DuplicatedTransaction] error.
+    ///
+    /// The hash is only part of the message, which reads like
+    /// `Transaction with hash 0x1234 has already been received.`
+    pub fn already_received_transaction(&self) -> Option<StarknetTransactionHash> {
+        if self.code != StarknetErrorCode::DuplicatedTransaction {
+            return None;
+        }
+
+        self.hash_in_message().map(StarknetTransactionHash)
+    }
+
+    /// Tells apart the causes of a [StarknetErrorCode::InvalidTransactionNonce] error, [None]
+    /// for other errors or if the message does not tell.
+    ///
+    /// The message either says so, e.g. `Nonce too low.`, or compares the nonces, e.g.
+    /// `Invalid transaction nonce of contract at address 0x1234. Account nonce: 0x5; got: 0x3.`
+    /// or `Invalid transaction nonce. Expected: 5, got: 3.`
+    pub fn invalid_nonce(&self) -> Option<InvalidNonce> {
+        if self.code != StarknetErrorCode::InvalidTransactionNonce {
+            return None;
+        }
+
+        let message = self.message.to_ascii_lowercase();
+        if message.contains("too low") {
+            return Some(InvalidNonce::TooLow);
+        }
+        if message.contains("too high") {
+            return Some(InvalidNonce::TooHigh);
+        }
+
+        let nonce_after = |label: &str| {
+            let nonce = message
+                .split_once(label)?
+                .1
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                .find(|word| !word.is_empty())?
+                .trim_end_matches('.');
+            match nonce.strip_prefix("0x") {
+                Some(hex) => u128::from_str_radix(hex, 16).ok(),
+                None => nonce.parse::<u128>().ok(),
+            }
+        };
+        let expected = nonce_after("account nonce:").or_else(|| nonce_after("expected:"))?;
+        let got = nonce_after("got:")?;
+
+        match got.cmp(&expected) {
+            std::cmp::Ordering::Less => Some(InvalidNonce::TooLow),
+            std::cmp::Ordering::Greater => Some(InvalidNonce::TooHigh),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Parses the hash following the first occurrence of `hash ` in the message.
+    fn hash_in_message(&self) -> Option<StarkHash> {
+        let hash = self
+            .message
+            .split_once("hash ")?
+            .1
+            .split_whitespace()
+            .next()?
+            .trim_end_matches('.');
+
+        StarkHash::from_hex_str(hash).ok()
+    }
+}
+
+/// Why the sequencer rejected the nonce of a transaction, see [StarknetError::invalid_nonce].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InvalidNonce {
+    /// The nonce was used already, the transaction should be resubmitted with the current nonce.
+    TooLow,
+    /// There is a gap to the current nonce, the transaction can only be accepted once the
+    /// transactions of the nonces before it are.
+    TooHigh,
+}
+
 impl std::fmt::Display for StarknetError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
@@ -79,7 +399,7 @@ impl std::fmt::Display for StarknetError {
 }
 
 /// Represents starknet specific error codes reported by the sequencer.
-#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(deny_unknown_fields)]
 pub enum StarknetErrorCode {
     #[serde(rename = "StarknetErrorCode.BLOCK_NOT_FOUND")]
@@ -119,4 +439,329 @@ pub enum StarknetErrorCode {
     InvalidTransactionVersion,
     #[serde(rename = "StarknetErrorCode.INVALID_PROGRAM")]
     InvalidProgram,
+    #[serde(rename = "StarknetErrorCode.CLASS_ALREADY_DECLARED")]
+    ClassAlreadyDeclared,
+    #[serde(rename = "StarknetErrorCode.FEE_TRANSFER_FAILURE")]
+    FeeTransferFailure,
+    /// The transaction has already been received, e.g. because a submission was retried.
+    #[serde(rename = "StarknetErrorCode.DUPLICATED_TRANSACTION")]
+    DuplicatedTransaction,
+    /// The transaction is unknown, which for a freshly submitted one means that it is not
+    /// indexed yet.
+    #[serde(rename = "StarknetErrorCode.TRANSACTION_NOT_FOUND")]
+    TransactionNotFound,
+}
+
+const METRIC_ALERTS: &str = "gateway_error_alerts_total";
+
+/// Selects the [StarknetErrorCode]s which operators are alerted about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorAlerts {
+    /// Alert on these codes only.
+    Only(HashSet<StarknetErrorCode>),
+    /// Alert on all codes except these.
+    AllExcept(HashSet<StarknetErrorCode>),
+}
+
+impl Default for ErrorAlerts {
+    /// No alerts at all.
+    fn default() -> Self {
+        Self::Only(HashSet::new())
+    }
+}
+
+impl ErrorAlerts {
+    /// Emits an `alert` tracing event and increments the `gateway_error_alerts_total` counter
+    /// if `error` carries one of the selected codes. Returns whether an alert was raised.
+    pub fn check(&self, error: &SequencerError) -> bool {
+        let error = match error {
+            SequencerError::StarknetError(e) => e,
+            _ => return false,
+        };
+
+        let alert = match self {
+            ErrorAlerts::Only(codes) => codes.contains(&error.code),
+            ErrorAlerts::AllExcept(codes) => !codes.contains(&error.code),
+        };
+
+        if alert {
+            tracing::error!(alert = true, code=?error.code, message=%error.message, "StarkNet error alert");
+            metrics::increment_counter!(METRIC_ALERTS, "code" => format!("{:?}", error.code));
+        }
+
+        alert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod starknet_error_code {
+        use super::super::*;
+
+        #[test]
+        fn starknet_error() {
+            let error = SequencerError::StarknetError(StarknetError {
+                code: StarknetErrorCode::BlockNotFound,
+                message: String::new(),
+                problems: None,
+            });
+            assert_eq!(
+                error.starknet_error_code(),
+                Some(StarknetErrorCode::BlockNotFound)
+            );
+        }
+
+        #[test]
+        fn reqwest_error() {
+            let error = reqwest::Client::new().get("not a url").build().unwrap_err();
+            assert_eq!(SequencerError::from(error).starknet_error_code(), None);
+        }
+
+        #[test]
+        fn invalid_starknet_error_variant() {
+            assert_eq!(
+                SequencerError::InvalidStarknetErrorVariant.starknet_error_code(),
+                None
+            );
+        }
+
+        #[test]
+        fn invalid_response_body() {
+            let error = serde_json::from_str::<u64>("{").unwrap_err();
+            assert_eq!(
+                SequencerError::InvalidResponseBody(error).starknet_error_code(),
+                None
+            );
+        }
+
+        #[test]
+        fn gateway_maintenance() {
+            let error = SequencerError::GatewayMaintenance {
+                message: "back soon".to_owned(),
+            };
+            assert_eq!(error.starknet_error_code(), None);
+        }
+
+        #[test]
+        fn request_rejected() {
+            let error = SequencerError::RequestRejected(anyhow::anyhow!("rejected"));
+            assert_eq!(error.starknet_error_code(), None);
+        }
+
+        #[test]
+        fn response_too_large() {
+            let error = SequencerError::ResponseTooLarge { limit: 1 };
+            assert_eq!(error.starknet_error_code(), None);
+        }
+
+        #[test]
+        fn chain_id_mismatch() {
+            let error = SequencerError::ChainIdMismatch {
+                configured: Chain::Testnet,
+                gateway: None,
+            };
+            assert_eq!(error.starknet_error_code(), None);
+        }
+
+        #[test]
+        fn timeout() {
+            let error = SequencerError::Timeout {
+                elapsed: std::time::Duration::from_secs(1),
+                endpoint: "get_block",
+            };
+            assert_eq!(error.starknet_error_code(), None);
+        }
+    }
+
+    mod invalid_nonce {
+        use super::super::*;
+
+        fn error(message: &str) -> StarknetError {
+            StarknetError {
+                code: StarknetErrorCode::InvalidTransactionNonce,
+                message: message.to_owned(),
+                problems: None,
+            }
+        }
+
+        #[test]
+        fn too_low() {
+            for message in [
+                "Invalid transaction nonce of contract at address 0x3d8a4f6da763a2e2e7ff43b3d0310a6f15a0f52a45cd7ee22cfa61a1e3404e8. Account nonce: 0x5; got: 0x3.",
+                "Invalid transaction nonce. Expected: 5, got: 3.",
+                "Nonce too low.",
+            ] {
+                assert_eq!(
+                    error(message).invalid_nonce(),
+                    Some(InvalidNonce::TooLow),
+                    "{message}"
+                );
+            }
+        }
+
+        #[test]
+        fn too_high() {
+            for message in [
+                "Invalid transaction nonce of contract at address 0x3d8a4f6da763a2e2e7ff43b3d0310a6f15a0f52a45cd7ee22cfa61a1e3404e8. Account nonce: 0x5; got: 0xa.",
+                "Invalid transaction nonce. Expected: 5, got: 10.",
+                "Nonce too high.",
+            ] {
+                assert_eq!(
+                    error(message).invalid_nonce(),
+                    Some(InvalidNonce::TooHigh),
+                    "{message}"
+                );
+            }
+        }
+
+        #[test]
+        fn unrecognized() {
+            assert_eq!(error("Invalid transaction nonce.").invalid_nonce(), None);
+            let other = StarknetError {
+                code: StarknetErrorCode::OutOfRangeFee,
+                ..error("Nonce too low.")
+            };
+            assert_eq!(other.invalid_nonce(), None);
+        }
+
+        #[test]
+        fn error_data() {
+            let error = Error::from(SequencerError::StarknetError(error(
+                "Invalid transaction nonce. Expected: 5, got: 10.",
+            )));
+            assert_matches::assert_matches!(
+                error,
+                Error::Call(CallError::Custom(e)) => {
+                    assert_eq!(e.code(), ServerErrorCode::InvalidNonce.code());
+                    assert_eq!(e.data().unwrap().get(), r#""TOO_HIGH""#);
+                }
+            );
+        }
+    }
+
+    mod validation_problems {
+        use super::super::*;
+
+        /// A declare whose class is missing its prime and has an invalid selector and ABI entry.
+        fn rejected_declare() -> serde_json::Value {
+            serde_json::json!({
+                "code": "StarkErrorCode.SCHEMA_VALIDATION_ERROR",
+                "message": "Invalid tx: ...",
+                "problems": {
+                    "contract_class": {
+                        "abi": {"0": {"type": ["Must be one of: function, constructor, event, struct."]}},
+                        "entry_points_by_type": {
+                            "EXTERNAL": {"1": {"selector": ["Expected hex string, got: \"xyz\"."]}}
+                        },
+                        "program": {"prime": ["Missing data for required field."]}
+                    }
+                }
+            })
+        }
+
+        #[test]
+        fn are_parsed_by_path() {
+            let error = serde_json::from_value::<StarknetError>(rejected_declare()).unwrap();
+            let problems = error.problems.unwrap();
+
+            assert_eq!(
+                problems.by_path(),
+                std::collections::BTreeMap::from([
+                    (
+                        "contract_class.abi.0.type".to_owned(),
+                        &["Must be one of: function, constructor, event, struct.".to_owned()][..]
+                    ),
+                    (
+                        "contract_class.entry_points_by_type.EXTERNAL.1.selector".to_owned(),
+                        &[r#"Expected hex string, got: "xyz"."#.to_owned()][..]
+                    ),
+                    (
+                        "contract_class.program.prime".to_owned(),
+                        &["Missing data for required field.".to_owned()][..]
+                    ),
+                ])
+            );
+        }
+
+        #[test]
+        fn unexpected_shapes_are_accepted() {
+            let problems = ValidationProblems::parse(&serde_json::json!({
+                "_schema": "Invalid input type.",
+                "calldata": [["Not a felt."], 5],
+            }));
+
+            assert_eq!(
+                problems.by_path(),
+                std::collections::BTreeMap::from([
+                    (
+                        "_schema".to_owned(),
+                        &["Invalid input type.".to_owned()][..]
+                    ),
+                    ("calldata.0".to_owned(), &["Not a felt.".to_owned()][..]),
+                    ("calldata.1".to_owned(), &["5".to_owned()][..]),
+                ])
+            );
+        }
+
+        #[test]
+        fn are_optional() {
+            let error = serde_json::from_value::<StarknetError>(serde_json::json!({
+                "code": "StarknetErrorCode.BLOCK_NOT_FOUND",
+                "message": "Block not found",
+            }))
+            .unwrap();
+            assert_eq!(error.problems, None);
+            assert_eq!(
+                serde_json::to_value(&error).unwrap(),
+                serde_json::json!({
+                    "code": "StarknetErrorCode.BLOCK_NOT_FOUND",
+                    "message": "Block not found",
+                })
+            );
+        }
+
+        #[test]
+        fn are_serialized_as_received() {
+            let error = serde_json::from_value::<StarknetError>(rejected_declare()).unwrap();
+            assert_eq!(serde_json::to_value(&error).unwrap(), rejected_declare());
+        }
+    }
+
+    mod alerts {
+        use super::super::*;
+
+        fn error(code: StarknetErrorCode) -> SequencerError {
+            SequencerError::StarknetError(StarknetError {
+                code,
+                message: String::new(),
+                problems: None,
+            })
+        }
+
+        #[test]
+        fn only() {
+            let alerts = ErrorAlerts::Only(HashSet::from([StarknetErrorCode::FeeTransferFailure]));
+
+            assert!(alerts.check(&error(StarknetErrorCode::FeeTransferFailure)));
+            assert!(!alerts.check(&error(StarknetErrorCode::TransactionLimitExceeded)));
+            assert!(!alerts.check(&SequencerError::InvalidStarknetErrorVariant));
+        }
+
+        #[test]
+        fn all_except() {
+            let alerts = ErrorAlerts::AllExcept(HashSet::from([
+                StarknetErrorCode::TransactionLimitExceeded,
+            ]));
+
+            assert!(alerts.check(&error(StarknetErrorCode::FeeTransferFailure)));
+            assert!(!alerts.check(&error(StarknetErrorCode::TransactionLimitExceeded)));
+        }
+
+        #[test]
+        fn none_by_default() {
+            let alerts = ErrorAlerts::default();
+
+            assert!(!alerts.check(&error(StarknetErrorCode::FeeTransferFailure)));
+        }
+    }
 }
@@ -150,7 +150,8 @@ pub async fn with_metrics<T>(
             SequencerError::StarknetError(_) => {
                 increment_failed(meta, REASON_STARKNET);
             }
-            SequencerError::InvalidStarknetErrorVariant => {
+            SequencerError::InvalidStarknetErrorVariant
+            | SequencerError::InvalidResponseBody(_) => {
                 increment_failed(meta, REASON_DECODE);
             }
             SequencerError::ReqwestError(e) if e.is_decode() => {
@@ -163,7 +164,15 @@ pub async fn with_metrics<T>(
             {
                 increment_failed(meta, REASON_RATE_LIMITING);
             }
-            SequencerError::ReqwestError(_) => {}
+            SequencerError::ReqwestError(_)
+            | SequencerError::GatewayMaintenance { .. }
+            | SequencerError::RequestRejected(_)
+            | SequencerError::ResponseTooLarge { .. }
+            | SequencerError::ChainIdMismatch { .. }
+            | SequencerError::Timeout { .. }
+            | SequencerError::TransactionNotYetIndexed { .. }
+            | SequencerError::UnexpectedRedirect { .. }
+            | SequencerError::OfflineMode => {}
         }
 
         e
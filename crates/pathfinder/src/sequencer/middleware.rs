@@ -0,0 +1,12 @@
+//! Hooks into the requests sent by the [Client](super::Client).
+
+/// Runs before every request sent by the [Client](super::Client), including retries.
+///
+/// Allows injecting headers, e.g. for custom authentication, transforming the body
+/// or rejecting the request altogether.
+pub trait RequestMiddleware: std::fmt::Debug + Send + Sync {
+    /// Returning an error rejects `request`, which is then neither sent nor retried.
+    ///
+    /// The body is not compressed yet, even if compression is enabled.
+    fn on_request(&self, request: &mut reqwest::Request) -> anyhow::Result<()>;
+}
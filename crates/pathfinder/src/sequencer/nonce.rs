@@ -0,0 +1,336 @@
+//! Leasing of sequential account nonces to concurrent submissions.
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use stark_hash::StarkHash;
+use tokio::sync::Mutex;
+
+use super::error::SequencerError;
+use super::ClientApi;
+use crate::core::{BlockId, ContractAddress, TransactionNonce};
+use crate::state::felt_math::field_add;
+
+/// Leases sequential nonces of accounts, so that concurrent submissions from the same account
+/// don't collide.
+///
+/// An account's nonce is fetched from the gateway's pending state on its first lease, after
+/// which nonces are leased in order. Each [NonceLease] must either be
+/// [confirmed](NonceManager::confirm) once its transaction is included, or
+/// [reclaimed](NonceManager::reclaim) if its submission failed. Reclaimed nonces are leased again
+/// before any new ones.
+///
+/// Once an account has no outstanding leases it is forgotten, so that its next lease
+/// resynchronizes with the gateway.
+#[derive(Default)]
+pub struct NonceManager {
+    /// The nonces of each account behind a lock of its own, so that fetching the nonce of one
+    /// account doesn't hold up the leases of the others. An account without nonces is being
+    /// fetched, or was just released.
+    accounts: std::sync::Mutex<HashMap<ContractAddress, Arc<Mutex<Option<AccountNonces>>>>>,
+}
+
+struct AccountNonces {
+    /// The nonce following the highest one leased so far.
+    next: StarkHash,
+    /// Nonces of failed submissions, which are leased again first.
+    reclaimed: BTreeSet<StarkHash>,
+    /// Nonces which are leased, but neither confirmed nor reclaimed yet.
+    outstanding: BTreeSet<StarkHash>,
+}
+
+/// A nonce of `account` leased to a single submission by a [NonceManager].
+#[must_use = "a lease must be confirmed or reclaimed"]
+#[derive(Debug, PartialEq, Eq)]
+pub struct NonceLease {
+    pub account: ContractAddress,
+    pub nonce: TransactionNonce,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leases the next nonce of `account`.
+    pub async fn lease(
+        &self,
+        client: &impl ClientApi,
+        account: ContractAddress,
+    ) -> Result<NonceLease, SequencerError> {
+        let account_nonces = self.account(account);
+        let mut nonces = account_nonces.lock().await;
+
+        if nonces.is_none() {
+            // The account's lock is held while fetching, so that concurrent first leases of
+            // the account don't both start from the same nonce.
+            let nonce = client.nonce(account, BlockId::Pending).await;
+            let nonce = match nonce {
+                Ok(nonce) => nonce,
+                Err(e) => {
+                    drop(nonces);
+                    self.forget_if_unused(account, account_nonces);
+                    return Err(e);
+                }
+            };
+            *nonces = Some(AccountNonces {
+                next: nonce.0,
+                reclaimed: BTreeSet::new(),
+                outstanding: BTreeSet::new(),
+            });
+        }
+        let nonces = nonces.as_mut().expect("Set above");
+
+        let nonce = match nonces.reclaimed.iter().next().copied() {
+            Some(nonce) => {
+                nonces.reclaimed.remove(&nonce);
+                nonce
+            }
+            None => {
+                let nonce = nonces.next;
+                nonces.next = field_add(nonce, StarkHash::from_u64(1));
+                nonce
+            }
+        };
+        nonces.outstanding.insert(nonce);
+
+        Ok(NonceLease {
+            account,
+            nonce: TransactionNonce(nonce),
+        })
+    }
+
+    /// Releases `lease` once its transaction has been included in a block.
+    pub async fn confirm(&self, lease: NonceLease) {
+        self.release(lease, false).await
+    }
+
+    /// Releases `lease` after its submission failed, so that its nonce is leased again.
+    pub async fn reclaim(&self, lease: NonceLease) {
+        self.release(lease, true).await
+    }
+
+    async fn release(&self, lease: NonceLease, reclaim: bool) {
+        let account_nonces = match self.accounts.lock().unwrap().get(&lease.account) {
+            Some(account_nonces) => account_nonces.clone(),
+            None => return,
+        };
+
+        let mut guard = account_nonces.lock().await;
+        let nonces = match guard.as_mut() {
+            Some(nonces) => nonces,
+            None => return,
+        };
+
+        if !nonces.outstanding.remove(&lease.nonce.0) {
+            return;
+        }
+
+        if nonces.outstanding.is_empty() {
+            *guard = None;
+            drop(guard);
+            self.forget_if_unused(lease.account, account_nonces);
+        } else if reclaim {
+            nonces.reclaimed.insert(lease.nonce.0);
+        }
+    }
+
+    /// The nonces of `account`, which are added if the account is unknown.
+    fn account(&self, account: ContractAddress) -> Arc<Mutex<Option<AccountNonces>>> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .entry(account)
+            .or_default()
+            .clone()
+    }
+
+    /// Forgets `account` unless its nonces are known or another lease is waiting on them.
+    fn forget_if_unused(
+        &self,
+        account: ContractAddress,
+        account_nonces: Arc<Mutex<Option<AccountNonces>>>,
+    ) {
+        let mut accounts = self.accounts.lock().unwrap();
+        // Held by the map and by us alone, so nobody else can lock it while the map is locked.
+        let unused = Arc::strong_count(&account_nonces) == 2
+            && account_nonces
+                .try_lock()
+                .map(|nonces| nonces.is_none())
+                .unwrap_or(false);
+        if unused {
+            accounts.remove(&account);
+        }
+    }
+
+    /// Leases a nonce of `account` and submits a transaction with it, reclaiming the lease if
+    /// the submission fails.
+    ///
+    /// On success, the lease is returned along with the response of `submit` and should be
+    /// [confirmed](NonceManager::confirm) once the transaction is included.
+    pub async fn submit_with_lease<T, F, Fut>(
+        &self,
+        client: &impl ClientApi,
+        account: ContractAddress,
+        submit: F,
+    ) -> Result<(T, NonceLease), SequencerError>
+    where
+        F: FnOnce(TransactionNonce) -> Fut,
+        Fut: std::future::Future<Output = Result<T, SequencerError>>,
+    {
+        let lease = self.lease(client, account).await?;
+
+        match submit(lease.nonce).await {
+            Ok(response) => Ok((response, lease)),
+            Err(e) => {
+                self.reclaim(lease).await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        ClassHash, ContractAddressSalt, ContractNonce, Fee, StarknetTransactionHash,
+        TransactionVersion,
+    };
+    use crate::sequencer::reply::add_transaction::{AddTransactionCode, DeployAccountResponse};
+    use crate::sequencer::MockClientApi;
+    use crate::starkhash;
+
+    const ACCOUNT: ContractAddress = ContractAddress::new_or_panic(starkhash!("0abc"));
+
+    fn client_with_nonce(nonce: ContractNonce, times: usize) -> MockClientApi {
+        let mut client = MockClientApi::new();
+        client
+            .expect_nonce()
+            .withf(|account, block| *account == ACCOUNT && *block == BlockId::Pending)
+            .times(times)
+            .returning(move |_, _| Ok(nonce));
+        client
+    }
+
+    #[tokio::test]
+    async fn concurrent_submissions_use_distinct_nonces() {
+        let mut client = client_with_nonce(ContractNonce(starkhash!("05")), 1);
+        client
+            .expect_add_deploy_account()
+            .times(2)
+            .returning(|_, _, _, nonce, _, _, _| {
+                Ok(DeployAccountResponse {
+                    code: AddTransactionCode::TransactionReceived,
+                    transaction_hash: StarknetTransactionHash(nonce.0),
+                    address: ACCOUNT,
+                })
+            });
+
+        let manager = NonceManager::new();
+        let submit = |nonce| {
+            client.add_deploy_account(
+                TransactionVersion::ONE,
+                Fee(Default::default()),
+                vec![],
+                nonce,
+                ContractAddressSalt(starkhash!("01")),
+                ClassHash(starkhash!("02")),
+                vec![],
+            )
+        };
+
+        let (first, second) = tokio::join!(
+            manager.submit_with_lease(&client, ACCOUNT, submit),
+            manager.submit_with_lease(&client, ACCOUNT, submit),
+        );
+        let (first, first_lease) = first.unwrap();
+        let (second, second_lease) = second.unwrap();
+
+        let mut nonces = vec![first_lease.nonce.0, second_lease.nonce.0];
+        nonces.sort();
+        assert_eq!(nonces, vec![starkhash!("05"), starkhash!("06")]);
+        // Each submission was sent with its own lease.
+        assert_eq!(first.transaction_hash.0, first_lease.nonce.0);
+        assert_eq!(second.transaction_hash.0, second_lease.nonce.0);
+    }
+
+    #[tokio::test]
+    async fn reclaimed_nonce_is_leased_again() {
+        let client = client_with_nonce(ContractNonce(starkhash!("05")), 1);
+        let manager = NonceManager::new();
+
+        let first = manager.lease(&client, ACCOUNT).await.unwrap();
+        let second = manager.lease(&client, ACCOUNT).await.unwrap();
+        assert_eq!(first.nonce, TransactionNonce(starkhash!("05")));
+        assert_eq!(second.nonce, TransactionNonce(starkhash!("06")));
+
+        manager.reclaim(first).await;
+        let third = manager.lease(&client, ACCOUNT).await.unwrap();
+        assert_eq!(third.nonce, TransactionNonce(starkhash!("05")));
+
+        let fourth = manager.lease(&client, ACCOUNT).await.unwrap();
+        assert_eq!(fourth.nonce, TransactionNonce(starkhash!("07")));
+    }
+
+    #[tokio::test]
+    async fn failed_submission_is_reclaimed() {
+        let client = client_with_nonce(ContractNonce(starkhash!("05")), 1);
+        let manager = NonceManager::new();
+
+        let outstanding = manager.lease(&client, ACCOUNT).await.unwrap();
+        let error = manager
+            .submit_with_lease(&client, ACCOUNT, |_| async {
+                Err::<(), _>(SequencerError::InvalidStarknetErrorVariant)
+            })
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(error, SequencerError::InvalidStarknetErrorVariant);
+
+        let next = manager.lease(&client, ACCOUNT).await.unwrap();
+        assert_eq!(next.nonce, TransactionNonce(starkhash!("06")));
+
+        manager.confirm(outstanding).await;
+        manager.confirm(next).await;
+    }
+
+    #[tokio::test]
+    async fn fetching_a_nonce_does_not_hold_up_other_accounts() {
+        const OTHER: ContractAddress = ContractAddress::new_or_panic(starkhash!("0def"));
+
+        let mut client = MockClientApi::new();
+        client
+            .expect_nonce()
+            .withf(|account, block| *account == OTHER && *block == BlockId::Pending)
+            .times(1)
+            .returning(|_, _| Ok(ContractNonce(starkhash!("02"))));
+        let manager = NonceManager::new();
+
+        // As if the first lease of ACCOUNT was still fetching its nonce.
+        let fetching = manager.account(ACCOUNT);
+        let _fetching = fetching.lock().await;
+
+        let lease = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            manager.lease(&client, OTHER),
+        )
+        .await
+        .expect("Lease of another account should not wait for the fetch")
+        .unwrap();
+        assert_eq!(lease.nonce, TransactionNonce(starkhash!("02")));
+        manager.confirm(lease).await;
+    }
+
+    #[tokio::test]
+    async fn account_is_resynchronized_once_all_leases_are_released() {
+        let client = client_with_nonce(ContractNonce(starkhash!("05")), 2);
+        let manager = NonceManager::new();
+
+        let lease = manager.lease(&client, ACCOUNT).await.unwrap();
+        manager.confirm(lease).await;
+
+        // Fetched again, since nothing is outstanding.
+        let lease = manager.lease(&client, ACCOUNT).await.unwrap();
+        assert_eq!(lease.nonce, TransactionNonce(starkhash!("05")));
+        manager.confirm(lease).await;
+    }
+}
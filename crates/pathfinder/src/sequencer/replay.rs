@@ -0,0 +1,188 @@
+//! Replayable records of submitted `DEPLOY` transactions.
+//!
+//! Records are written to a file, one JSON object per line, and can be re-submitted to any
+//! gateway with [replay], e.g. to reproduce a failed deploy against a local devnet.
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Context;
+
+use super::error::SequencerError;
+use super::reply::add_transaction::DeployResponse;
+use super::request::add_transaction::Deploy;
+use super::ClientApi;
+
+/// A single `DEPLOY` submission.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ReplayRecord {
+    pub deploy: Deploy,
+    /// Only records whether a deploy token was used, the token itself is never written.
+    pub token: Option<Redacted>,
+}
+
+/// Placeholder of a secret, serialized as `"<redacted>"`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Redacted {
+    #[serde(rename = "<redacted>")]
+    Redacted,
+}
+
+impl ReplayRecord {
+    pub fn new(deploy: Deploy, token: Option<&str>) -> Self {
+        Self {
+            deploy,
+            token: token.map(|_| Redacted::Redacted),
+        }
+    }
+}
+
+/// Appends [ReplayRecord]s to a file, one JSON object per line.
+pub struct ReplayLog {
+    file: Mutex<std::fs::File>,
+}
+
+impl ReplayLog {
+    /// Opens the file at `path` for appending, creating it if it does not exist.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Opening replay log {}", path.display()))?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn record(&self, record: &ReplayRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(record).context("Serializing replay record")?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&line).context("Writing replay record")?;
+
+        Ok(())
+    }
+}
+
+/// Reads all records of the replay log at `path`, in submission order.
+pub fn read(path: &Path) -> anyhow::Result<Vec<ReplayRecord>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Opening replay log {}", path.display()))?;
+
+    std::io::BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(i, line)| {
+            let line = line.context("Reading replay log")?;
+            serde_json::from_str(&line)
+                .with_context(|| format!("Parsing replay record on line {}", i + 1))
+        })
+        .collect()
+}
+
+/// Re-submits the deploy of `record` to `client`.
+///
+/// Since tokens are never recorded, the `token` to submit with has to be supplied again if the
+/// original submission used one.
+pub async fn replay(
+    client: &impl ClientApi,
+    record: ReplayRecord,
+    token: Option<String>,
+) -> Result<DeployResponse, SequencerError> {
+    if record.token.is_some() && token.is_none() {
+        tracing::warn!("Replaying a deploy which was submitted with a token, without a token");
+    }
+
+    let Deploy {
+        version,
+        contract_address_salt,
+        contract_definition,
+        constructor_calldata,
+    } = record.deploy;
+
+    client
+        .add_deploy_transaction(
+            version,
+            contract_address_salt,
+            constructor_calldata,
+            contract_definition,
+            token,
+            None,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        ConstructorParam, ContractAddress, ContractAddressSalt, StarknetTransactionHash,
+        TransactionVersion,
+    };
+    use crate::sequencer::reply;
+    use crate::sequencer::request::add_transaction::ContractDefinition;
+    use crate::sequencer::MockClientApi;
+    use crate::starkhash;
+
+    #[tokio::test]
+    async fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("replay.log");
+
+        let deploy = Deploy {
+            version: TransactionVersion::ZERO,
+            contract_address_salt: ContractAddressSalt(starkhash!("03")),
+            contract_definition: ContractDefinition {
+                program: "program".to_owned(),
+                entry_points_by_type: Default::default(),
+                abi: None,
+            },
+            constructor_calldata: vec![ConstructorParam(starkhash!("04"))],
+        };
+
+        let log = ReplayLog::open(&path).unwrap();
+        log.record(&ReplayRecord::new(deploy, Some("secret token")))
+            .unwrap();
+        drop(log);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("secret token"));
+
+        let mut records = read(&path).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = records.remove(0);
+        assert_eq!(record.token, Some(Redacted::Redacted));
+
+        let mut client = MockClientApi::new();
+        client
+            .expect_add_deploy_transaction()
+            .withf(|version, salt, calldata, definition, token, _| {
+                *version == TransactionVersion::ZERO
+                    && *salt == ContractAddressSalt(starkhash!("03"))
+                    && calldata == &[ConstructorParam(starkhash!("04"))]
+                    && definition.program == "program"
+                    && token.as_deref() == Some("new token")
+            })
+            .times(1)
+            .returning(|_, _, _, _, _, _| {
+                Ok(DeployResponse {
+                    code: reply::add_transaction::AddTransactionCode::TransactionReceived,
+                    transaction_hash: StarknetTransactionHash(starkhash!("01")),
+                    address: ContractAddress::new_or_panic(starkhash!("02")),
+                    other: Default::default(),
+                })
+            });
+
+        let response = replay(&client, record, Some("new token".to_owned()))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.transaction_hash,
+            StarknetTransactionHash(starkhash!("01"))
+        );
+    }
+}
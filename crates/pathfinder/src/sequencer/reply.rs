@@ -1,10 +1,11 @@
 //! Structures used for deserializing replies from Starkware's sequencer REST API.
 use crate::{
     core::{
-        EthereumAddress, GasPrice, GlobalRoot, SequencerAddress, StarknetBlockHash,
-        StarknetBlockNumber, StarknetBlockTimestamp,
+        EthereumAddress, Fee, GasPrice, GlobalRoot, SequencerAddress, StarknetBlockHash,
+        StarknetBlockNumber, StarknetBlockTimestamp, StarknetTransactionHash,
+        StarknetTransactionIndex,
     },
-    rpc::serde::{EthereumAddressAsHexStr, GasPriceAsHexStr},
+    rpc::serde::{EthereumAddressAsHexStr, FeeAsHexStr, GasPriceAsHexStr},
 };
 use serde::Deserialize;
 use serde_with::serde_as;
@@ -139,6 +140,18 @@ impl std::fmt::Display for Status {
     }
 }
 
+/// A typed reply together with the raw response body it was parsed from.
+///
+/// Only used by debugging methods such as
+/// [Client::add_deploy_transaction_debug](crate::sequencer::Client::add_deploy_transaction_debug).
+#[cfg(any(test, feature = "debug-responses"))]
+#[derive(Debug)]
+pub struct WithRawBody<T> {
+    /// Fails if `body` does not match the expected reply.
+    pub output: Result<T, serde_json::Error>,
+    pub body: serde_json::Value,
+}
+
 /// Types used when deserializing L2 call related data.
 pub mod call {
     use serde::Deserialize;
@@ -169,16 +182,374 @@ pub struct Transaction {
     pub transaction: Option<transaction::Transaction>,
     #[serde(default)]
     pub transaction_index: Option<u64>,
+    /// The block containing the transaction, only present if it was requested and the
+    /// transaction is in a block.
+    #[serde(default)]
+    pub block: Option<Box<Block>>,
 }
 
 /// Used to deserialize replies to [ClientApi::transaction_status](crate::sequencer::ClientApi::transaction_status).
 #[serde_as]
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct TransactionStatus {
     #[serde(default)]
     pub block_hash: Option<StarknetBlockHash>,
     pub tx_status: Status,
+    /// Only present if the transaction was rejected.
+    #[serde(default)]
+    pub tx_failure_reason: Option<TransactionFailureReason>,
+}
+
+impl TransactionStatus {
+    /// The sequencer's reason for rejecting the transaction, if it was rejected.
+    pub fn rejection_reason(&self) -> Option<&str> {
+        match self.tx_status {
+            Status::Rejected => self
+                .tx_failure_reason
+                .as_ref()
+                .map(|reason| reason.error_message.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// Why the sequencer rejected a transaction.
+///
+/// Unknown fields are not denied, only the code and message are of interest to us.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct TransactionFailureReason {
+    pub code: String,
+    #[serde(default)]
+    pub error_message: String,
+}
+
+/// Used to deserialize replies to [ClientApi::transaction_receipt](crate::sequencer::ClientApi::transaction_receipt).
+///
+/// Apart from the status, all fields are only present once the transaction has been executed.
+/// Unknown fields are not denied, since the set of fields differs between statuses.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct TransactionReceipt {
+    pub status: Status,
+    #[serde(default)]
+    pub block_hash: Option<StarknetBlockHash>,
+    #[serde(default)]
+    pub block_number: Option<StarknetBlockNumber>,
+    #[serde(default)]
+    pub transaction_index: Option<StarknetTransactionIndex>,
+    #[serde(default)]
+    pub transaction_hash: Option<StarknetTransactionHash>,
+    #[serde_as(as = "Option<FeeAsHexStr>")]
+    #[serde(default)]
+    pub actual_fee: Option<Fee>,
+    #[serde(default)]
+    pub events: Vec<transaction::Event>,
+    #[serde(default)]
+    pub execution_resources: Option<transaction::ExecutionResources>,
+    #[serde(default)]
+    pub l1_to_l2_consumed_message: Option<transaction::L1ToL2Message>,
+    #[serde(default)]
+    pub l2_to_l1_messages: Vec<transaction::L2ToL1Message>,
+}
+
+impl TransactionReceipt {
+    /// The fee charged for the transaction along with its unit, once it has been executed.
+    pub fn actual_fee(&self) -> Option<ActualFee> {
+        self.actual_fee.map(|amount| ActualFee {
+            amount,
+            unit: FeeUnit::Wei,
+        })
+    }
+}
+
+/// A fee charged by the sequencer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ActualFee {
+    pub amount: Fee,
+    pub unit: FeeUnit,
+}
+
+/// The unit a fee is denominated in.
+///
+/// The gateway does not report a unit for actual fees, since the supported transactions always
+/// pay in ETH. Fee estimates of v3 transactions are in STRK, and report so.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeUnit {
+    Wei,
+    /// The smallest unit of STRK.
+    Fri,
+}
+
+/// Wei, which is what the gateway means if it does not report a unit.
+impl Default for FeeUnit {
+    fn default() -> Self {
+        Self::Wei
+    }
+}
+
+/// Used to deserialize replies to [ClientApi::simulate_transaction](crate::sequencer::ClientApi::simulate_transaction).
+///
+/// Unknown fields are not denied, only the trace and the fee estimate are of interest to us.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct SimulatedTransaction {
+    pub trace: simulation::TransactionTrace,
+    pub fee_estimation: simulation::FeeEstimation,
+}
+
+/// Used to deserialize replies to [ClientApi::class_definition](crate::sequencer::ClientApi::class_definition).
+#[derive(Debug, Deserialize)]
+pub struct ClassDefinition {
+    /// The gzip-compressed then base64 encoded program, as in classes served over RPC.
+    ///
+    /// By far the largest part of a class, which is compressed while it is being parsed so that
+    /// the uncompressed program is never held in memory.
+    #[serde(deserialize_with = "compressed_program::deserialize")]
+    pub program: String,
+    pub entry_points_by_type: std::collections::HashMap<
+        super::request::contract::EntryPointType,
+        Vec<super::request::contract::SelectorAndOffset>,
+    >,
+    #[serde(default)]
+    pub abi: Option<serde_json::Value>,
+}
+
+/// Compresses JSON values while they are being deserialized.
+mod compressed_program {
+    use serde::de::{DeserializeSeed, Deserializer, Error, MapAccess, SeqAccess, Visitor};
+    use std::io::Write;
+
+    /// The key under which `serde_json` passes numbers as strings, see its `arbitrary_precision`
+    /// feature.
+    const NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+    /// Deserializes a JSON value into its gzip-compressed then base64 encoded representation.
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<String, D::Error> {
+        let encoder = base64::write::EncoderStringWriter::new(base64::STANDARD);
+        let mut writer = flate2::write::GzEncoder::new(encoder, flate2::Compression::fast());
+
+        deserializer.deserialize_any(Transcode(&mut writer))?;
+
+        let encoder = writer.finish().map_err(D::Error::custom)?;
+        Ok(encoder.into_inner())
+    }
+
+    /// Writes the JSON value it deserializes to the writer, without holding the value itself.
+    struct Transcode<'a, W>(&'a mut W);
+
+    impl<'de, W: Write> DeserializeSeed<'de> for Transcode<'_, W> {
+        type Value = ();
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+            deserializer.deserialize_any(self)
+        }
+    }
+
+    /// Like [Transcode], but writes a prefix before the value, e.g. the separator of array
+    /// elements, which is only known to be needed once there is a value.
+    struct Prefixed<'a, W>(&'a [u8], &'a mut W);
+
+    impl<'de, W: Write> DeserializeSeed<'de> for Prefixed<'_, W> {
+        type Value = ();
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+            self.1.write_all(self.0).map_err(D::Error::custom)?;
+            deserializer.deserialize_any(Transcode(self.1))
+        }
+    }
+
+    impl<W: Write> Transcode<'_, W> {
+        fn write<E: Error>(&mut self, bytes: &[u8]) -> Result<(), E> {
+            self.0.write_all(bytes).map_err(E::custom)
+        }
+
+        fn write_value<E: Error, T: serde::Serialize>(&mut self, value: &T) -> Result<(), E> {
+            serde_json::to_writer(&mut *self.0, value).map_err(E::custom)
+        }
+    }
+
+    impl<'de, W: Write> Visitor<'de> for Transcode<'_, W> {
+        type Value = ();
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            formatter.write_str("a JSON value")
+        }
+
+        fn visit_bool<E: Error>(mut self, v: bool) -> Result<(), E> {
+            self.write_value(&v)
+        }
+
+        fn visit_i64<E: Error>(mut self, v: i64) -> Result<(), E> {
+            self.write_value(&v)
+        }
+
+        fn visit_u64<E: Error>(mut self, v: u64) -> Result<(), E> {
+            self.write_value(&v)
+        }
+
+        fn visit_f64<E: Error>(mut self, v: f64) -> Result<(), E> {
+            self.write_value(&v)
+        }
+
+        fn visit_str<E: Error>(mut self, v: &str) -> Result<(), E> {
+            self.write_value(&v)
+        }
+
+        fn visit_unit<E: Error>(mut self) -> Result<(), E> {
+            self.write(b"null")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+            self.write(b"[")?;
+            let mut separator: &[u8] = b"";
+            while seq
+                .next_element_seed(Prefixed(separator, &mut *self.0))?
+                .is_some()
+            {
+                separator = b",";
+            }
+            self.write(b"]")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+            let mut first = true;
+            while let Some(key) = map.next_key::<String>()? {
+                if key == NUMBER_TOKEN {
+                    // An arbitrary precision number, which is written as is.
+                    let number = map.next_value::<String>()?;
+                    return self.write(number.as_bytes());
+                }
+
+                self.write(if first { b"{" } else { b"," })?;
+                self.write_value(&key)?;
+                self.write(b":")?;
+                map.next_value_seed(Transcode(&mut *self.0))?;
+                first = false;
+            }
+            self.write(if first { b"{}" } else { b"}" })
+        }
+    }
+}
+
+/// Used to deserialize replies to [ClientApi::estimate_fee_bulk](crate::sequencer::ClientApi::estimate_fee_bulk).
+///
+/// Unknown fields are not denied.
+///
+/// Estimates of v3 transactions break down the consumption of L1 and L2 gas, while older
+/// replies only report a single `gas_price` and `gas_usage`, which are taken to be L1 gas.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "RawFeeEstimate")]
+pub struct FeeEstimate {
+    /// In the [unit](Self::unit) of the estimate.
+    pub overall_fee: u128,
+    pub l1_gas_consumed: u64,
+    /// In the [unit](Self::unit) of the estimate.
+    pub l1_gas_price: u128,
+    /// Only reported for v3 transactions.
+    pub l2_gas_consumed: Option<u64>,
+    /// Only reported for v3 transactions, in the [unit](Self::unit) of the estimate.
+    pub l2_gas_price: Option<u128>,
+    /// Wei unless reported otherwise, fri for v3 transactions.
+    pub unit: FeeUnit,
+}
+
+#[derive(Deserialize)]
+struct RawFeeEstimate {
+    overall_fee: u128,
+    #[serde(default)]
+    unit: Option<FeeUnit>,
+    #[serde(default)]
+    l1_gas_consumed: Option<u64>,
+    #[serde(default)]
+    l1_gas_price: Option<u128>,
+    #[serde(default)]
+    l2_gas_consumed: Option<u64>,
+    #[serde(default)]
+    l2_gas_price: Option<u128>,
+    #[serde(default)]
+    gas_usage: Option<u64>,
+    #[serde(default)]
+    gas_price: Option<u128>,
+}
+
+impl TryFrom<RawFeeEstimate> for FeeEstimate {
+    type Error = &'static str;
+
+    fn try_from(raw: RawFeeEstimate) -> Result<Self, Self::Error> {
+        Ok(Self {
+            overall_fee: raw.overall_fee,
+            l1_gas_consumed: raw
+                .l1_gas_consumed
+                .or(raw.gas_usage)
+                .ok_or("missing field `l1_gas_consumed` or `gas_usage`")?,
+            l1_gas_price: raw
+                .l1_gas_price
+                .or(raw.gas_price)
+                .ok_or("missing field `l1_gas_price` or `gas_price`")?,
+            l2_gas_consumed: raw.l2_gas_consumed,
+            l2_gas_price: raw.l2_gas_price,
+            unit: raw.unit.unwrap_or(FeeUnit::Wei),
+        })
+    }
+}
+
+/// Types used when deserializing the traces of simulated and executed transactions.
+pub mod simulation {
+    use crate::core::{CallParam, CallResultValue, ContractAddress, EventData, EventKey};
+    use serde::Deserialize;
+
+    /// Simulations report their fee estimate in the same shapes as
+    /// [ClientApi::estimate_fee_bulk](crate::sequencer::ClientApi::estimate_fee_bulk).
+    pub type FeeEstimation = super::FeeEstimate;
+
+    /// Also used to deserialize replies to
+    /// [ClientApi::transaction_trace](crate::sequencer::ClientApi::transaction_trace).
+    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    pub struct TransactionTrace {
+        /// Not present if nothing was executed, e.g. for declare transactions.
+        #[serde(default)]
+        pub function_invocation: Option<FunctionInvocation>,
+        /// The account's `__validate__` call, not present for transactions without one.
+        #[serde(default)]
+        pub validate_invocation: Option<FunctionInvocation>,
+        /// The transfer of the fee to the sequencer, not present if no fee was charged.
+        #[serde(default)]
+        pub fee_transfer_invocation: Option<FunctionInvocation>,
+    }
+
+    impl TransactionTrace {
+        /// The constructor call of a deploy, which the gateway reports as its
+        /// `function_invocation`.
+        pub fn constructor_invocation(&self) -> Option<&FunctionInvocation> {
+            self.function_invocation.as_ref()
+        }
+    }
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    pub struct FunctionInvocation {
+        #[serde(default)]
+        pub contract_address: Option<ContractAddress>,
+        #[serde(default)]
+        pub calldata: Vec<CallParam>,
+        #[serde(default)]
+        pub result: Vec<CallResultValue>,
+        #[serde(default)]
+        pub events: Vec<Event>,
+        #[serde(default)]
+        pub internal_calls: Vec<FunctionInvocation>,
+    }
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[serde(deny_unknown_fields)]
+    pub struct Event {
+        /// Position of the event within the whole transaction.
+        pub order: u64,
+        pub data: Vec<EventData>,
+        pub keys: Vec<EventKey>,
+    }
 }
 
 /// Types used when deserializing L2 transaction related data.
@@ -671,11 +1042,21 @@ pub struct EthContractAddresses {
 pub mod add_transaction {
     use crate::core::{ClassHash, ContractAddress, StarknetTransactionHash};
 
+    /// The gateway's acknowledgement of a submitted transaction.
+    ///
+    /// Any other code fails to deserialize, so a response is only ever accepted if the gateway
+    /// actually received the transaction.
+    #[derive(Copy, Clone, Debug, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+    pub enum AddTransactionCode {
+        #[serde(rename = "TRANSACTION_RECEIVED")]
+        TransactionReceived,
+    }
+
     /// API response for an INVOKE_FUNCTION transaction
     #[derive(Clone, Debug, serde::Deserialize, PartialEq, Eq)]
     #[serde(deny_unknown_fields)]
     pub struct InvokeResponse {
-        pub code: String, // TRANSACTION_RECEIVED
+        pub code: AddTransactionCode,
         pub transaction_hash: StarknetTransactionHash,
     }
 
@@ -683,25 +1064,27 @@ pub mod add_transaction {
     #[derive(Clone, Debug, serde::Deserialize, PartialEq, Eq)]
     #[serde(deny_unknown_fields)]
     pub struct DeclareResponse {
-        pub code: String, // TRANSACTION_RECEIVED
+        pub code: AddTransactionCode,
         pub transaction_hash: StarknetTransactionHash,
         pub class_hash: ClassHash,
     }
 
     /// API response for a DEPLOY transaction
     #[derive(Clone, Debug, serde::Deserialize, PartialEq, Eq)]
-    #[serde(deny_unknown_fields)]
     pub struct DeployResponse {
-        pub code: String, // TRANSACTION_RECEIVED
+        pub code: AddTransactionCode,
         pub transaction_hash: StarknetTransactionHash,
         pub address: ContractAddress,
+        /// Any other fields of the response, which are captured instead of being rejected.
+        #[serde(flatten)]
+        pub other: serde_json::Map<String, serde_json::Value>,
     }
 
     /// API response for a DEPLOY ACCOUNT transaction
     #[derive(Clone, Debug, serde::Deserialize, PartialEq, Eq)]
     #[serde(deny_unknown_fields)]
     pub struct DeployAccountResponse {
-        pub code: String, // TRANSACTION_RECEIVED
+        pub code: AddTransactionCode,
         pub transaction_hash: StarknetTransactionHash,
         pub address: ContractAddress,
     }
@@ -716,7 +1099,7 @@ pub mod add_transaction {
         fn test_invoke_response() {
             let result = serde_json::from_str::<InvokeResponse>(r#"{"code": "TRANSACTION_RECEIVED", "transaction_hash": "0x389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"}"#).unwrap();
             let expected = InvokeResponse {
-                code: "TRANSACTION_RECEIVED".to_owned(),
+                code: AddTransactionCode::TransactionReceived,
                 transaction_hash: StarknetTransactionHash(starkhash!(
                     "0389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"
                 )),
@@ -728,16 +1111,55 @@ pub mod add_transaction {
         fn test_deploy_response() {
             let result = serde_json::from_str::<DeployResponse>(r#"{"code": "TRANSACTION_RECEIVED", "transaction_hash": "0x296fb89b8a1c7487a1d4b27e1a1e33f440b05548e64980d06052bc089b1a51f", "address": "0x677bb1cdc050e8d63855e8743ab6e09179138def390676cc03c484daf112ba1"}"#).unwrap();
             let expected = DeployResponse {
-                code: "TRANSACTION_RECEIVED".to_owned(),
+                code: AddTransactionCode::TransactionReceived,
+                transaction_hash: StarknetTransactionHash(starkhash!(
+                    "0296fb89b8a1c7487a1d4b27e1a1e33f440b05548e64980d06052bc089b1a51f"
+                )),
+                address: ContractAddress::new_or_panic(starkhash!(
+                    "0677bb1cdc050e8d63855e8743ab6e09179138def390676cc03c484daf112ba1"
+                )),
+                other: Default::default(),
+            };
+            assert_eq!(expected, result);
+        }
+
+        #[test]
+        fn test_full_deploy_response() {
+            let result = serde_json::from_str::<DeployResponse>(r#"{"code": "TRANSACTION_RECEIVED", "transaction_hash": "0x296fb89b8a1c7487a1d4b27e1a1e33f440b05548e64980d06052bc089b1a51f", "address": "0x677bb1cdc050e8d63855e8743ab6e09179138def390676cc03c484daf112ba1", "tx_status": "RECEIVED", "class_hash": "0x1fac3074c9d5282f0acc5c69a4781a1c711efea5e73c550c5d9fb253cf7fd3d"}"#).unwrap();
+            let expected = DeployResponse {
+                code: AddTransactionCode::TransactionReceived,
                 transaction_hash: StarknetTransactionHash(starkhash!(
                     "0296fb89b8a1c7487a1d4b27e1a1e33f440b05548e64980d06052bc089b1a51f"
                 )),
                 address: ContractAddress::new_or_panic(starkhash!(
                     "0677bb1cdc050e8d63855e8743ab6e09179138def390676cc03c484daf112ba1"
                 )),
+                other: serde_json::json!({
+                    "tx_status": "RECEIVED",
+                    "class_hash": "0x1fac3074c9d5282f0acc5c69a4781a1c711efea5e73c550c5d9fb253cf7fd3d"
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
             };
             assert_eq!(expected, result);
         }
+
+        #[test]
+        fn received_code() {
+            let code =
+                serde_json::from_str::<AddTransactionCode>(r#""TRANSACTION_RECEIVED""#).unwrap();
+            assert_eq!(code, AddTransactionCode::TransactionReceived);
+            assert_eq!(
+                serde_json::to_value(code).unwrap(),
+                serde_json::json!("TRANSACTION_RECEIVED")
+            );
+        }
+
+        #[test]
+        fn unexpected_code_is_rejected() {
+            serde_json::from_str::<InvokeResponse>(r#"{"code": "TRANSACTION_REJECTED", "transaction_hash": "0x389dd0629f42176cc8b6c43acefc0713d0064ecdfc0470e0fc179f53421a38b"}"#).unwrap_err();
+        }
     }
 }
 
@@ -747,6 +1169,137 @@ mod tests {
     /// deserialize replies from the mainnet sequencer when it still is using some
     /// previous version of cairo while at the same time the goerli sequencer is
     /// already using a newer version.
+    mod transaction_receipt {
+        use super::super::{ActualFee, FeeUnit, Status, TransactionReceipt};
+        use crate::core::Fee;
+        use web3::types::H128;
+
+        #[test]
+        fn actual_fee_is_parsed() {
+            let json = serde_json::json!({
+                "status": "ACCEPTED_ON_L2",
+                "transaction_hash": "0x1",
+                "actual_fee": "0x2386f26fc10000",
+                "events": [],
+                "l2_to_l1_messages": [],
+            });
+            let receipt = serde_json::from_value::<TransactionReceipt>(json).unwrap();
+
+            assert_eq!(receipt.status, Status::AcceptedOnL2);
+            assert_eq!(
+                receipt.actual_fee(),
+                Some(ActualFee {
+                    amount: Fee(H128::from_low_u64_be(10_000_000_000_000_000)),
+                    unit: FeeUnit::Wei,
+                })
+            );
+        }
+
+        #[test]
+        fn events_are_parsed() {
+            use crate::core::{ContractAddress, EventData, EventKey};
+            use crate::starkhash;
+
+            let json = serde_json::json!({
+                "status": "ACCEPTED_ON_L2",
+                "transaction_hash": "0x1",
+                "events": [{
+                    "from_address": "0x2",
+                    "keys": ["0x99cd8bde557814842a3121e8ddfd433a539b8c9f14bf31ebf108d12e6196e9"],
+                    "data": ["0x3", "0x4"],
+                }],
+                "l2_to_l1_messages": [],
+            });
+            let receipt = serde_json::from_value::<TransactionReceipt>(json).unwrap();
+
+            assert_eq!(
+                receipt.events,
+                vec![super::super::transaction::Event {
+                    data: vec![EventData(starkhash!("03")), EventData(starkhash!("04"))],
+                    from_address: ContractAddress::new_or_panic(starkhash!("02")),
+                    keys: vec![EventKey(starkhash!(
+                        "0099cd8bde557814842a3121e8ddfd433a539b8c9f14bf31ebf108d12e6196e9"
+                    ))],
+                }]
+            );
+        }
+
+        #[test]
+        fn actual_fee_is_absent_before_execution() {
+            let json = serde_json::json!({ "status": "RECEIVED" });
+            let receipt = serde_json::from_value::<TransactionReceipt>(json).unwrap();
+
+            assert_eq!(receipt.actual_fee(), None);
+        }
+    }
+
+    mod fee_estimate {
+        use super::super::{FeeEstimate, FeeUnit};
+
+        #[test]
+        fn legacy_gas_is_l1_gas() {
+            let json = serde_json::json!({
+                "overall_fee": 1000,
+                "gas_price": 10,
+                "gas_usage": 100,
+                "unit": "wei",
+            });
+            let estimate = serde_json::from_value::<FeeEstimate>(json).unwrap();
+
+            assert_eq!(
+                estimate,
+                FeeEstimate {
+                    overall_fee: 1000,
+                    l1_gas_consumed: 100,
+                    l1_gas_price: 10,
+                    l2_gas_consumed: None,
+                    l2_gas_price: None,
+                    unit: FeeUnit::Wei,
+                }
+            );
+        }
+
+        #[test]
+        fn v3_gas_is_broken_down() {
+            let json = serde_json::json!({
+                "overall_fee": 1600,
+                "l1_gas_consumed": 100,
+                "l1_gas_price": 10,
+                "l2_gas_consumed": 300,
+                "l2_gas_price": 2,
+                "unit": "fri",
+            });
+            let estimate = serde_json::from_value::<FeeEstimate>(json).unwrap();
+
+            assert_eq!(
+                estimate,
+                FeeEstimate {
+                    overall_fee: 1600,
+                    l1_gas_consumed: 100,
+                    l1_gas_price: 10,
+                    l2_gas_consumed: Some(300),
+                    l2_gas_price: Some(2),
+                    unit: FeeUnit::Fri,
+                }
+            );
+        }
+
+        #[test]
+        fn unit_defaults_to_wei() {
+            let json =
+                serde_json::json!({ "overall_fee": 1000, "gas_price": 10, "gas_usage": 100 });
+            let estimate = serde_json::from_value::<FeeEstimate>(json).unwrap();
+
+            assert_eq!(estimate.unit, FeeUnit::Wei);
+        }
+
+        #[test]
+        fn missing_gas_is_rejected() {
+            let json = serde_json::json!({ "overall_fee": 1000, "gas_price": 10 });
+            serde_json::from_value::<FeeEstimate>(json).unwrap_err();
+        }
+    }
+
     mod backward_compatibility {
 
         use super::super::{StateUpdate, Transaction};
@@ -1,5 +1,16 @@
 //! Structures used for serializing requests to Starkware's sequencer REST API.
-use crate::core::{CallParam, ContractAddress, EntryPoint, Fee, TransactionSignatureElem};
+use crate::core::{
+    CallParam, ContractAddress, EntryPoint, Fee, StarknetBlockTimestamp, TransactionSignatureElem,
+};
+
+/// Overrides of the block a transaction is simulated in.
+///
+/// Gateways which do not support an override ignore it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockContext {
+    /// Relevant for constructors which read the block timestamp.
+    pub timestamp: Option<StarknetBlockTimestamp>,
+}
 
 pub mod contract {
     use std::fmt;
@@ -45,17 +56,36 @@ pub mod contract {
             }
         }
     }
+
+    impl TryFrom<&SelectorAndOffset> for crate::rpc::v02::types::class::ContractEntryPoint {
+        type Error = anyhow::Error;
+
+        fn try_from(entry_point: &SelectorAndOffset) -> Result<Self, Self::Error> {
+            let bytes = entry_point.offset.0.as_be_bytes();
+            let (high, low) = bytes.split_at(24);
+            anyhow::ensure!(
+                high.iter().all(|b| *b == 0),
+                "Entry point offset does not fit into 64 bits"
+            );
+
+            Ok(Self {
+                offset: u64::from_be_bytes(low.try_into().expect("slice is 8 bytes long")),
+                selector: entry_point.selector.0,
+            })
+        }
+    }
 }
 
 pub mod add_transaction {
     use std::collections::HashMap;
 
     use crate::core::{
-        ClassHash, ConstructorParam, ContractAddressSalt, TransactionNonce, TransactionVersion,
+        CasmHash, ClassHash, ConstructorParam, ContractAddressSalt, GasPrice, TransactionNonce,
+        TransactionVersion,
     };
     use crate::rpc::serde::{
-        CallParamAsDecimalStr, ConstructorParamAsDecimalStr, FeeAsHexStr,
-        TransactionSignatureElemAsDecimalStr, TransactionVersionAsHexStr,
+        CallParamAsDecimalStr, ConstructorParamAsDecimalStr, FeeAsHexStr, GasPriceAsHexStr,
+        TransactionSignatureElemAsDecimalStr, TransactionVersionAsHexStr, U64AsHexStr,
     };
 
     use serde_with::serde_as;
@@ -119,9 +149,48 @@ pub mod add_transaction {
         }
     }
 
+    impl ContractDefinition {
+        /// Returns the entry points of each [EntryPointType] as a struct instead of a map.
+        ///
+        /// Entry point types which are missing from the map have no entry points.
+        pub fn entry_points(&self) -> anyhow::Result<crate::rpc::v02::types::ContractEntryPoints> {
+            let entry_points = |entry_point_type| {
+                self.entry_points_by_type
+                    .get(&entry_point_type)
+                    .map(|entry_points| entry_points.iter().map(TryInto::try_into).collect())
+                    .unwrap_or_else(|| Ok(Vec::new()))
+            };
+
+            Ok(crate::rpc::v02::types::ContractEntryPoints {
+                constructor: entry_points(EntryPointType::Constructor)?,
+                external: entry_points(EntryPointType::External)?,
+                l1_handler: entry_points(EntryPointType::L1Handler)?,
+            })
+        }
+
+        /// Computes the hash of the class, as the sequencer does once it has been submitted.
+        pub fn class_hash(&self) -> anyhow::Result<ClassHash> {
+            use anyhow::Context;
+
+            let compressed_program = base64::decode(&self.program).context("Decoding program")?;
+            let gzip_decoder = flate2::read::GzDecoder::new(compressed_program.as_slice());
+            let program = serde_json::from_reader::<_, serde_json::Value>(gzip_decoder)
+                .context("Parsing program")?;
+
+            let definition = serde_json::json!({
+                "abi": self.abi,
+                "entry_points_by_type": self.entry_points_by_type,
+                "program": program,
+            });
+            let definition = serde_json::to_vec(&definition).context("Serializing definition")?;
+
+            crate::state::compute_class_hash(&definition)
+        }
+    }
+
     /// Contract deployment transaction details.
     #[serde_as]
-    #[derive(Debug, serde::Deserialize, serde::Serialize)]
+    #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
     pub struct Deploy {
         // Transacion properties
         #[serde_as(as = "TransactionVersionAsHexStr")]
@@ -135,7 +204,7 @@ pub mod add_transaction {
 
     /// Account deployment transaction details.
     #[serde_as]
-    #[derive(Debug, serde::Deserialize, serde::Serialize)]
+    #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
     pub struct DeployAccount {
         // Transaction properties
         #[serde_as(as = "TransactionVersionAsHexStr")]
@@ -172,6 +241,28 @@ pub mod add_transaction {
         pub entry_point_selector: Option<EntryPoint>,
         #[serde_as(as = "Vec<CallParamAsDecimalStr>")]
         pub calldata: Vec<CallParam>,
+
+        /// Replaces `max_fee` for v3 transactions.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub resource_bounds: Option<ResourceBounds>,
+    }
+
+    /// The maximum resources a v3 transaction may consume.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+    pub struct ResourceBounds {
+        #[serde(rename = "L1_GAS")]
+        pub l1_gas: ResourceBound,
+        #[serde(rename = "L2_GAS")]
+        pub l2_gas: ResourceBound,
+    }
+
+    #[serde_as]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+    pub struct ResourceBound {
+        #[serde_as(as = "U64AsHexStr")]
+        pub max_amount: u64,
+        #[serde_as(as = "GasPriceAsHexStr")]
+        pub max_price_per_unit: GasPrice,
     }
 
     /// Declare transaction details.
@@ -191,6 +282,9 @@ pub mod add_transaction {
         pub contract_class: ContractDefinition,
         pub sender_address: ContractAddress,
         pub nonce: TransactionNonce,
+        /// Only present when declaring a Cairo 1 class.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub compiled_class_hash: Option<CasmHash>,
     }
 
     /// Add transaction API operation.
@@ -233,3 +327,75 @@ pub mod add_transaction {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    mod entry_points {
+        use crate::rpc::v02::types::{ContractClass, ContractEntryPoint};
+        use crate::sequencer::request::add_transaction::ContractDefinition;
+        use crate::starkhash;
+
+        fn fixture() -> ContractClass {
+            let definition = zstd::decode_all(std::io::Cursor::new(include_bytes!(
+                "../../fixtures/contract_definition.json.zst"
+            )))
+            .unwrap();
+            ContractClass::from_definition_bytes(&definition).unwrap()
+        }
+
+        #[test]
+        fn round_trip() {
+            let class = fixture();
+            let definition = ContractDefinition::try_from(class.clone()).unwrap();
+
+            assert_eq!(
+                definition.entry_points().unwrap(),
+                class.entry_points_by_type
+            );
+        }
+
+        #[test]
+        fn fixture_entry_points() {
+            let json = include_bytes!("../../resources/deploy_transaction.json");
+            let json = serde_json::from_slice::<serde_json::Value>(json).unwrap();
+            let definition =
+                serde_json::from_value::<ContractDefinition>(json["contract_definition"].clone())
+                    .unwrap();
+            let entry_points = definition.entry_points().unwrap();
+
+            assert_eq!(entry_points.constructor, vec![]);
+            assert_eq!(entry_points.l1_handler, vec![]);
+            assert_eq!(
+                entry_points.external,
+                vec![
+                    ContractEntryPoint {
+                        offset: 0x3a,
+                        selector: starkhash!(
+                            "0362398bec32bc0ebb411203221a35a0301193a96f317ebe5e40be9f60d15320"
+                        ),
+                    },
+                    ContractEntryPoint {
+                        offset: 0x5b,
+                        selector: starkhash!(
+                            "039e11d48192e4333233c7eb19d10ad67c362bb28580c604d67884c85da39695"
+                        ),
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn missing_types_are_empty() {
+            let definition = ContractDefinition {
+                program: String::new(),
+                entry_points_by_type: Default::default(),
+                abi: None,
+            };
+            let entry_points = definition.entry_points().unwrap();
+
+            assert!(entry_points.constructor.is_empty());
+            assert!(entry_points.external.is_empty());
+            assert!(entry_points.l1_handler.is_empty());
+        }
+    }
+}
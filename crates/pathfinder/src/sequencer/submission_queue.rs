@@ -0,0 +1,230 @@
+//! Limiting of concurrent submissions, servicing urgent ones first.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::oneshot;
+
+/// How urgent a submission is, see [Client::with_priority](super::Client::with_priority).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SubmissionPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// Grants at most `capacity` submissions a [SubmissionPermit] at a time.
+///
+/// When a permit is freed it is handed to the waiting submission of the highest priority, and
+/// among those of the same priority to the one which waited longest.
+#[derive(Debug)]
+pub(super) struct SubmissionQueue {
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    available: usize,
+    waiting: BinaryHeap<Waiter>,
+    /// Orders waiters of the same priority by their arrival.
+    arrivals: u64,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    priority: SubmissionPriority,
+    arrival: Reverse<u64>,
+    permit: oneshot::Sender<SubmissionPermit>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        (self.priority, self.arrival) == (other.priority, other.arrival)
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.priority, self.arrival).cmp(&(other.priority, other.arrival))
+    }
+}
+
+/// Allows a single submission, which is passed on to the next one when dropped.
+#[derive(Debug)]
+pub(super) struct SubmissionPermit {
+    /// Only [None] once the permit has been passed on.
+    queue: Option<Arc<SubmissionQueue>>,
+}
+
+impl SubmissionQueue {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            state: Mutex::new(State {
+                available: capacity.get(),
+                waiting: BinaryHeap::new(),
+                arrivals: 0,
+            }),
+        }
+    }
+
+    /// Waits for a permit, which is granted right away if one is available and nobody is
+    /// waiting already.
+    pub async fn acquire(self: &Arc<Self>, priority: SubmissionPriority) -> SubmissionPermit {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 && state.waiting.is_empty() {
+                state.available -= 1;
+                return SubmissionPermit {
+                    queue: Some(self.clone()),
+                };
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            let arrival = Reverse(state.arrivals);
+            state.arrivals += 1;
+            state.waiting.push(Waiter {
+                priority,
+                arrival,
+                permit: sender,
+            });
+            receiver
+        };
+
+        // If the waiting submission is cancelled after it was handed a permit, the permit is
+        // dropped along with the channel and passed on again.
+        receiver
+            .await
+            .expect("Waiters are only dropped once handed a permit")
+    }
+
+    fn release(self: &Arc<Self>) {
+        loop {
+            let waiter = {
+                let mut state = self.state.lock().unwrap();
+                match state.waiting.pop() {
+                    Some(waiter) => waiter,
+                    None => {
+                        state.available += 1;
+                        return;
+                    }
+                }
+            };
+
+            let permit = SubmissionPermit {
+                queue: Some(self.clone()),
+            };
+            match waiter.permit.send(permit) {
+                Ok(()) => return,
+                // The submission stopped waiting, so the permit goes to the next one.
+                Err(mut permit) => permit.queue = None,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn waiting(&self) -> usize {
+        self.state.lock().unwrap().waiting.len()
+    }
+}
+
+impl Drop for SubmissionPermit {
+    fn drop(&mut self) {
+        if let Some(queue) = self.queue.take() {
+            queue.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Yields until `count` submissions are waiting, so that they are queued in a known order.
+    async fn until_waiting(queue: &SubmissionQueue, count: usize) {
+        while queue.waiting() < count {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn high_priority_jumps_the_queue() {
+        let queue = Arc::new(SubmissionQueue::new(NonZeroUsize::new(1).unwrap()));
+        let serviced = Arc::new(Mutex::new(Vec::new()));
+        let submit = |priority| {
+            let queue = queue.clone();
+            let serviced = serviced.clone();
+            tokio::spawn(async move {
+                let _permit = queue.acquire(priority).await;
+                serviced.lock().unwrap().push(priority);
+            })
+        };
+
+        let permit = queue.acquire(SubmissionPriority::Normal).await;
+        let low = submit(SubmissionPriority::Low);
+        until_waiting(&queue, 1).await;
+        let high = submit(SubmissionPriority::High);
+        until_waiting(&queue, 2).await;
+
+        drop(permit);
+        low.await.unwrap();
+        high.await.unwrap();
+
+        assert_eq!(
+            *serviced.lock().unwrap(),
+            vec![SubmissionPriority::High, SubmissionPriority::Low]
+        );
+    }
+
+    #[tokio::test]
+    async fn same_priority_is_first_come_first_served() {
+        let queue = Arc::new(SubmissionQueue::new(NonZeroUsize::new(1).unwrap()));
+        let serviced = Arc::new(Mutex::new(Vec::new()));
+        let submit = |id| {
+            let queue = queue.clone();
+            let serviced = serviced.clone();
+            tokio::spawn(async move {
+                let _permit = queue.acquire(SubmissionPriority::Normal).await;
+                serviced.lock().unwrap().push(id);
+            })
+        };
+
+        let permit = queue.acquire(SubmissionPriority::Normal).await;
+        let first = submit(1);
+        until_waiting(&queue, 1).await;
+        let second = submit(2);
+        until_waiting(&queue, 2).await;
+
+        drop(permit);
+        first.await.unwrap();
+        second.await.unwrap();
+
+        assert_eq!(*serviced.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_passes_the_permit_on() {
+        let queue = Arc::new(SubmissionQueue::new(NonZeroUsize::new(1).unwrap()));
+
+        let permit = queue.acquire(SubmissionPriority::Normal).await;
+        let cancelled = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire(SubmissionPriority::High).await }
+        });
+        until_waiting(&queue, 1).await;
+        cancelled.abort();
+        let _ = cancelled.await;
+
+        drop(permit);
+        let _permit = queue.acquire(SubmissionPriority::Low).await;
+    }
+}
@@ -11,10 +11,12 @@ use crate::{
 
 pub mod block_hash;
 pub(crate) mod class_hash;
+pub mod felt_math;
 pub mod merkle_node;
 pub mod merkle_tree;
 pub mod state_tree;
 mod sync;
+pub mod transaction_hash;
 
 pub use class_hash::compute_class_hash;
 pub use sync::{l1, l2, sync, PendingData, State as SyncState};
@@ -3,6 +3,7 @@ use serde::Serialize;
 use sha3::Digest;
 use stark_hash::{HashChain, StarkHash};
 
+use super::felt_math::pedersen_hash_array;
 use crate::core::ClassHash;
 use crate::sequencer::request::contract::EntryPointType;
 
@@ -201,19 +202,17 @@ fn compute_class_hash0(mut contract_definition: json::ContractDefinition<'_>) ->
     [External, L1Handler, Constructor]
         .iter()
         .map(|key| {
-            contract_definition
-                .entry_points_by_type
-                .get(key)
-                .unwrap_or(&Vec::new())
-                .iter()
-                // flatten each entry point to get a list of (selector, offset, selector, offset, ...)
-                .flat_map(|x| [x.selector.0, x.offset.0].into_iter())
-                .fold(HashChain::default(), |mut hc, next| {
-                    hc.update(next);
-                    hc
-                })
+            pedersen_hash_array(
+                contract_definition
+                    .entry_points_by_type
+                    .get(key)
+                    .unwrap_or(&Vec::new())
+                    .iter()
+                    // flatten each entry point to get a list of (selector, offset, selector, offset, ...)
+                    .flat_map(|x| [x.selector.0, x.offset.0].into_iter()),
+            )
         })
-        .for_each(|x| outer.update(x.finalize()));
+        .for_each(|x| outer.update(x));
 
     let builtins = contract_definition
         .program
@@ -0,0 +1,96 @@
+//! Field element arithmetic shared by the hash and address computations.
+//!
+//! All operations are modulo the StarkNet field prime `P = 2**251 + 17 * 2**192 + 1`.
+use stark_hash::{HashChain, StarkHash};
+
+/// The [Pedersen hash](stark_hash::stark_hash) of `a` and `b`.
+pub fn pedersen_hash(a: StarkHash, b: StarkHash) -> StarkHash {
+    stark_hash::stark_hash(a, b)
+}
+
+/// Hashes `elements` in order, finishing with their count.
+///
+/// This is `compute_hash_on_elements` of cairo-lang: `H(H(H(H(0, e0), e1), ..., en), n)`.
+pub fn pedersen_hash_array(elements: impl IntoIterator<Item = StarkHash>) -> StarkHash {
+    let mut chain = HashChain::default();
+    elements.into_iter().for_each(|e| chain.update(e));
+    chain.finalize()
+}
+
+/// `a + b mod P`.
+pub fn field_add(a: StarkHash, b: StarkHash) -> StarkHash {
+    a + b
+}
+
+/// `a * b mod P`.
+pub fn field_mul(a: StarkHash, b: StarkHash) -> StarkHash {
+    a * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::starkhash;
+
+    /// `P - 1`, the largest field element.
+    const MAX: StarkHash =
+        starkhash!("0800000000000011000000000000000000000000000000000000000000000000");
+
+    #[test]
+    fn pedersen_hash_vector() {
+        // From https://github.com/starkware-libs/crypto-cpp/blob/master/src/starkware/crypto/pedersen_hash_test.cc
+        let a = starkhash!("03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb");
+        let b = starkhash!("0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a");
+        assert_eq!(
+            pedersen_hash(a, b),
+            starkhash!("030e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662")
+        );
+    }
+
+    #[test]
+    fn pedersen_hash_array_vector() {
+        // `hex(compute_hash_on_elements([1, 2, 3, 4]))` of cairo-lang.
+        let elements = [
+            starkhash!("01"),
+            starkhash!("02"),
+            starkhash!("03"),
+            starkhash!("04"),
+        ];
+        assert_eq!(
+            pedersen_hash_array(elements),
+            starkhash!("066bd4335902683054d08a0572747ea78ebd9e531536fb43125424ca9f902084")
+        );
+    }
+
+    #[test]
+    fn pedersen_hash_array_of_nothing() {
+        assert_eq!(
+            pedersen_hash_array([]),
+            pedersen_hash(StarkHash::ZERO, StarkHash::ZERO)
+        );
+    }
+
+    #[test]
+    fn field_add_wraps() {
+        assert_eq!(
+            field_add(starkhash!("02"), starkhash!("03")),
+            starkhash!("05")
+        );
+        assert_eq!(field_add(MAX, starkhash!("02")), starkhash!("01"));
+    }
+
+    #[test]
+    fn field_mul_wraps() {
+        assert_eq!(
+            field_mul(starkhash!("02"), starkhash!("03")),
+            starkhash!("06")
+        );
+        assert_eq!(field_mul(MAX, MAX), starkhash!("01"));
+        // 2**128 * 2**128 = 2**256 mod P
+        let two_to_128 = starkhash!("0100000000000000000000000000000000");
+        assert_eq!(
+            field_mul(two_to_128, two_to_128),
+            starkhash!("07fffffffffffdf0ffffffffffffffffffffffffffffffffffffffffffffffe1")
+        );
+    }
+}
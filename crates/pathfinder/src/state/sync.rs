@@ -931,17 +931,20 @@ mod tests {
     use super::{l1, l2};
     use crate::{
         core::{
-            CallParam, Chain, ClassHash, ConstructorParam, ContractAddress, ContractAddressSalt,
-            EntryPoint, EthereumBlockHash, EthereumBlockNumber, EthereumLogIndex,
-            EthereumTransactionHash, EthereumTransactionIndex, Fee, GasPrice, GlobalRoot,
-            SequencerAddress, StarknetBlockHash, StarknetBlockNumber, StarknetBlockTimestamp,
-            StarknetTransactionHash, StorageAddress, StorageValue, TransactionNonce,
-            TransactionSignatureElem, TransactionVersion,
+            CallParam, CasmHash, Chain, ClassHash, ConstructorParam, ContractAddress,
+            ContractAddressSalt, ContractNonce, EntryPoint, EthereumBlockHash, EthereumBlockNumber,
+            EthereumLogIndex, EthereumTransactionHash, EthereumTransactionIndex, Fee, GasPrice,
+            GlobalRoot, SequencerAddress, StarknetBlockHash, StarknetBlockNumber,
+            StarknetBlockTimestamp, StarknetTransactionHash, StorageAddress, StorageValue,
+            TransactionNonce, TransactionSignatureElem, TransactionVersion,
         },
         ethereum,
         rpc::v01::types::BlockHashOrTag,
         sequencer::{
-            self, error::SequencerError, reply, request::add_transaction::ContractDefinition,
+            self,
+            error::SequencerError,
+            reply,
+            request::add_transaction::{ContractDefinition, ResourceBounds},
         },
         state::{self, sync::PendingData},
         storage::{self, L1StateTable, RefsTable, StarknetBlocksTable, Storage},
@@ -1019,7 +1022,11 @@ mod tests {
             unimplemented!()
         }
 
-        async fn class_hash_at(&self, _: ContractAddress) -> Result<ClassHash, SequencerError> {
+        async fn class_hash_at(
+            &self,
+            _: ContractAddress,
+            _: crate::core::BlockId,
+        ) -> Result<ClassHash, SequencerError> {
             unimplemented!()
         }
 
@@ -1032,9 +1039,18 @@ mod tests {
             unimplemented!()
         }
 
+        async fn nonce(
+            &self,
+            _: ContractAddress,
+            _: crate::core::BlockId,
+        ) -> Result<ContractNonce, SequencerError> {
+            unimplemented!()
+        }
+
         async fn transaction(
             &self,
             _: StarknetTransactionHash,
+            _: bool,
         ) -> Result<reply::Transaction, SequencerError> {
             unimplemented!()
         }
@@ -1046,6 +1062,60 @@ mod tests {
             unimplemented!()
         }
 
+        async fn transaction_statuses(
+            &self,
+            _: &[StarknetTransactionHash],
+        ) -> Vec<Result<reply::TransactionStatus, SequencerError>> {
+            unimplemented!()
+        }
+
+        async fn transaction_receipt(
+            &self,
+            _: StarknetTransactionHash,
+        ) -> Result<reply::TransactionReceipt, SequencerError> {
+            unimplemented!()
+        }
+
+        async fn await_block_finality(
+            &self,
+            _: StarknetBlockNumber,
+            _: sequencer::deploy::Finality,
+            _: Duration,
+        ) -> Result<sequencer::deploy::Finality, SequencerError> {
+            unimplemented!()
+        }
+
+        async fn class_definition(
+            &self,
+            _: ClassHash,
+        ) -> Result<reply::ClassDefinition, SequencerError> {
+            unimplemented!()
+        }
+
+        async fn transaction_trace(
+            &self,
+            _: StarknetTransactionHash,
+        ) -> Result<reply::simulation::TransactionTrace, SequencerError> {
+            unimplemented!()
+        }
+
+        async fn simulate_transaction(
+            &self,
+            _: crate::sequencer::request::add_transaction::AddTransaction,
+            _: crate::core::BlockId,
+            _: crate::sequencer::request::BlockContext,
+        ) -> Result<reply::SimulatedTransaction, SequencerError> {
+            unimplemented!()
+        }
+
+        async fn estimate_fee_bulk(
+            &self,
+            _: Vec<crate::sequencer::request::add_transaction::AddTransaction>,
+            _: crate::core::BlockId,
+        ) -> Result<Vec<reply::FeeEstimate>, SequencerError> {
+            unimplemented!()
+        }
+
         async fn state_update(
             &self,
             _: crate::core::BlockId,
@@ -1068,6 +1138,7 @@ mod tests {
             _: ContractAddress,
             _: Option<EntryPoint>,
             _: Vec<CallParam>,
+            _: Option<ResourceBounds>,
         ) -> Result<reply::add_transaction::InvokeResponse, SequencerError> {
             unimplemented!()
         }
@@ -1080,6 +1151,7 @@ mod tests {
             _: TransactionNonce,
             _: ContractDefinition,
             _: ContractAddress,
+            _: Option<CasmHash>,
             _: Option<String>,
         ) -> Result<reply::add_transaction::DeclareResponse, SequencerError> {
             unimplemented!()
@@ -1092,6 +1164,7 @@ mod tests {
             _: Vec<ConstructorParam>,
             _: ContractDefinition,
             _: Option<String>,
+            _: Option<std::time::Duration>,
         ) -> Result<reply::add_transaction::DeployResponse, SequencerError> {
             unimplemented!()
         }
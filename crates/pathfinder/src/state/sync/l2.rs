@@ -836,6 +836,7 @@ mod tests {
             SequencerError::StarknetError(StarknetError {
                 code: StarknetErrorCode::BlockNotFound,
                 message: String::new(),
+                problems: None,
             })
         }
 
@@ -0,0 +1,281 @@
+//! Local computation of StarkNet transaction hashes and contract addresses.
+//!
+//! These mirror `calculate_contract_address_from_hash` and the `calculate_*_transaction_hash`
+//! helpers of cairo-lang, which lets us assemble (and sign) transactions before submitting them.
+use num_bigint::BigUint;
+use stark_hash::{stark_hash, StarkHash};
+
+use super::felt_math::pedersen_hash_array;
+use crate::core::{
+    ClassHash, ConstructorParam, ContractAddress, ContractAddressSalt, EntryPoint,
+    StarknetTransactionHash, TransactionVersion,
+};
+use crate::sequencer::request::add_transaction::DeployAccount;
+
+/// The address of the deployer used by `DEPLOY` and `DEPLOY_ACCOUNT` transactions.
+pub const ZERO_DEPLOYER_ADDRESS: ContractAddress = ContractAddress::new_or_panic(StarkHash::ZERO);
+
+/// The address of the universal deployer contract, which is the same on all networks.
+pub const UDC_ADDRESS: ContractAddress = ContractAddress::new_or_panic(crate::starkhash!(
+    "041a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf"
+));
+
+/// Computes the address of a contract deployed by `deployer_address`.
+///
+/// The constructor calldata is taken as raw [StarkHash] values so that both `DEPLOY`
+/// and `DEPLOY_ACCOUNT` calldata types can be used.
+pub fn compute_contract_address(
+    salt: ContractAddressSalt,
+    class_hash: ClassHash,
+    constructor_calldata: impl IntoIterator<Item = StarkHash>,
+    deployer_address: ContractAddress,
+) -> ContractAddress {
+    lazy_static::lazy_static!(
+        static ref CONTRACT_ADDRESS_PREFIX: StarkHash =
+            StarkHash::from_be_slice(b"STARKNET_CONTRACT_ADDRESS").unwrap();
+        // 2**251 - 256
+        static ref ADDRESS_UPPER_BOUND: BigUint =
+            (BigUint::from(1u8) << 251usize) - BigUint::from(256u16);
+    );
+
+    let calldata_hash = pedersen_hash_array(constructor_calldata);
+    let address = pedersen_hash_array([
+        *CONTRACT_ADDRESS_PREFIX,
+        *deployer_address.get(),
+        salt.0,
+        class_hash.0,
+        calldata_hash,
+    ]);
+
+    // Addresses are taken modulo the upper bound, which means that the result
+    // always fits into 251 bits.
+    let address = BigUint::from_bytes_be(address.as_be_bytes()) % &*ADDRESS_UPPER_BOUND;
+    let address =
+        StarkHash::from_be_slice(&address.to_bytes_be()).expect("value is below the upper bound");
+
+    ContractAddress::new_or_panic(address)
+}
+
+/// Computes the address of a contract deployed by `caller` through the [universal deployer
+/// contract](UDC_ADDRESS), mirroring its `deployContract` entry point.
+///
+/// Unique deploys are derived from the UDC as deployer and from `salt` hashed with `caller`, so
+/// that different callers can't deploy to the same address. Other deploys are derived like
+/// `DEPLOY` transactions, from the [zero deployer](ZERO_DEPLOYER_ADDRESS) and `salt` itself.
+pub fn compute_udc_contract_address(
+    salt: ContractAddressSalt,
+    class_hash: ClassHash,
+    constructor_calldata: impl IntoIterator<Item = StarkHash>,
+    caller: ContractAddress,
+    unique: bool,
+) -> ContractAddress {
+    match unique {
+        true => compute_contract_address(
+            ContractAddressSalt(stark_hash(*caller.get(), salt.0)),
+            class_hash,
+            constructor_calldata,
+            UDC_ADDRESS,
+        ),
+        false => compute_contract_address(
+            salt,
+            class_hash,
+            constructor_calldata,
+            ZERO_DEPLOYER_ADDRESS,
+        ),
+    }
+}
+
+/// Computes the address and the transaction hash of a `DEPLOY` transaction of the class
+/// hashing to `class_hash`.
+///
+/// The class itself is not part of the hash, which is why the transaction is passed by its
+/// fields instead of a request which includes the class.
+///
+/// `chain_id` is the chain id used when executing transactions on the target network.
+pub fn compute_deploy_transaction_hash(
+    version: TransactionVersion,
+    contract_address_salt: ContractAddressSalt,
+    constructor_calldata: &[ConstructorParam],
+    class_hash: ClassHash,
+    chain_id: StarkHash,
+) -> (ContractAddress, StarknetTransactionHash) {
+    lazy_static::lazy_static!(
+        static ref DEPLOY_PREFIX: StarkHash = StarkHash::from_be_slice(b"deploy").unwrap();
+    );
+
+    let contract_address = compute_contract_address(
+        contract_address_salt,
+        class_hash,
+        constructor_calldata.iter().map(|x| x.0),
+        ZERO_DEPLOYER_ADDRESS,
+    );
+
+    let hash = pedersen_hash_array([
+        *DEPLOY_PREFIX,
+        StarkHash::from_be_slice(version.0.as_bytes()).expect("version fits into a felt"),
+        *contract_address.get(),
+        EntryPoint::hashed(b"constructor").0,
+        pedersen_hash_array(constructor_calldata.iter().map(|x| x.0)),
+        // Deploy transactions have no fee.
+        StarkHash::ZERO,
+        chain_id,
+    ]);
+
+    (contract_address, StarknetTransactionHash(hash))
+}
+
+/// Computes the address and the transaction hash of a `DEPLOY_ACCOUNT` transaction.
+///
+/// `chain_id` is the chain id used when executing transactions on the target network.
+pub fn compute_deploy_account_transaction_hash(
+    tx: &DeployAccount,
+    chain_id: StarkHash,
+) -> (ContractAddress, StarknetTransactionHash) {
+    lazy_static::lazy_static!(
+        static ref DEPLOY_ACCOUNT_PREFIX: StarkHash =
+            StarkHash::from_be_slice(b"deploy_account").unwrap();
+    );
+
+    let contract_address = compute_contract_address(
+        tx.contract_address_salt,
+        tx.class_hash,
+        tx.constructor_calldata.iter().map(|x| x.0),
+        ZERO_DEPLOYER_ADDRESS,
+    );
+
+    // The calldata of a deploy account transaction is the class hash and salt
+    // followed by the constructor calldata.
+    let calldata_hash = pedersen_hash_array(
+        [tx.class_hash.0, tx.contract_address_salt.0]
+            .into_iter()
+            .chain(tx.constructor_calldata.iter().map(|x| x.0)),
+    );
+
+    let hash = pedersen_hash_array([
+        *DEPLOY_ACCOUNT_PREFIX,
+        StarkHash::from_be_slice(tx.version.0.as_bytes()).expect("version fits into a felt"),
+        *contract_address.get(),
+        // Entry point selector is always zero for deploy account transactions.
+        StarkHash::ZERO,
+        calldata_hash,
+        StarkHash::from_be_slice(tx.max_fee.0.as_bytes()).expect("fee fits into a felt"),
+        chain_id,
+        tx.nonce.0,
+    ]);
+
+    (contract_address, StarknetTransactionHash(hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{CallParam, Chain, Fee, TransactionNonce};
+    use crate::starkhash;
+
+    /// Values from a deploy account transaction submitted to the integration network.
+    fn deploy_account_transaction() -> DeployAccount {
+        DeployAccount {
+            version: TransactionVersion::ONE,
+            max_fee: Fee(web3::types::H128::from_low_u64_be(0xbf391377813)),
+            signature: vec![],
+            nonce: TransactionNonce::ZERO,
+            contract_address_salt: ContractAddressSalt(starkhash!(
+                "06d44a6aecb4339e23a9619355f101cf3cb9baec289fcd9fd51486655c1bb8a8"
+            )),
+            constructor_calldata: vec![CallParam(starkhash!(
+                "0677bb1cdc050e8d63855e8743ab6e09179138def390676cc03c484daf112ba1"
+            ))],
+            class_hash: ClassHash(starkhash!(
+                "01fac3074c9d5282f0acc5c69a4781a1c711efea5e73c550c5d9fb253cf7fd3d"
+            )),
+        }
+    }
+
+    #[test]
+    fn contract_address() {
+        let tx = deploy_account_transaction();
+        let address = compute_contract_address(
+            tx.contract_address_salt,
+            tx.class_hash,
+            tx.constructor_calldata.iter().map(|x| x.0),
+            ZERO_DEPLOYER_ADDRESS,
+        );
+
+        assert_eq!(
+            address,
+            ContractAddress::new_or_panic(starkhash!(
+                "042ae26ab2b8236242bb384c23e74c69af7204bb2fc711a99da63e0dd6adf33f"
+            ))
+        );
+    }
+
+    #[test]
+    fn udc_contract_address() {
+        let tx = deploy_account_transaction();
+        let calldata = || tx.constructor_calldata.iter().map(|x| x.0);
+        let caller = ContractAddress::new_or_panic(starkhash!("0abc"));
+        let legacy = compute_contract_address(
+            tx.contract_address_salt,
+            tx.class_hash,
+            calldata(),
+            ZERO_DEPLOYER_ADDRESS,
+        );
+
+        let not_unique = compute_udc_contract_address(
+            tx.contract_address_salt,
+            tx.class_hash,
+            calldata(),
+            caller,
+            false,
+        );
+        assert_eq!(not_unique, legacy);
+
+        let unique = compute_udc_contract_address(
+            tx.contract_address_salt,
+            tx.class_hash,
+            calldata(),
+            caller,
+            true,
+        );
+        // Computed independently by a Python implementation of the Pedersen hash and of the
+        // UDC's `deployContract`, which also reproduces the legacy address above.
+        assert_eq!(
+            unique,
+            ContractAddress::new_or_panic(starkhash!(
+                "033873ec2b0812d8050822f09c7e9c9d2730f11be2917bc41733325c5792dec1"
+            ))
+        );
+        // The caller is part of the derivation.
+        assert_ne!(
+            unique,
+            compute_udc_contract_address(
+                tx.contract_address_salt,
+                tx.class_hash,
+                calldata(),
+                ContractAddress::new_or_panic(starkhash!("0def")),
+                true,
+            )
+        );
+    }
+
+    #[test]
+    fn deploy_account() {
+        let (address, hash) = compute_deploy_account_transaction_hash(
+            &deploy_account_transaction(),
+            Chain::Integration.execution_chain_id(),
+        );
+
+        assert_eq!(
+            address,
+            ContractAddress::new_or_panic(starkhash!(
+                "042ae26ab2b8236242bb384c23e74c69af7204bb2fc711a99da63e0dd6adf33f"
+            ))
+        );
+        assert_eq!(
+            hash,
+            StarknetTransactionHash(starkhash!(
+                "0273fb3c38b20037839d6bad8811cd0afd82f2bc3c95c061eb8f30ce5cedc377"
+            ))
+        );
+    }
+}
@@ -19,6 +19,7 @@ mod revision_0018;
 mod revision_0019;
 mod revision_0020;
 mod revision_0021;
+mod revision_0022;
 
 type MigrationFn = fn(&rusqlite::Transaction<'_>) -> anyhow::Result<()>;
 
@@ -47,5 +48,6 @@ pub fn migrations() -> &'static [MigrationFn] {
         revision_0019::migrate,
         revision_0020::migrate,
         revision_0021::migrate,
+        revision_0022::migrate,
     ]
 }
@@ -0,0 +1,16 @@
+use anyhow::Context;
+
+/// Adds the `submissions` table, which records the transactions submitted through this node.
+pub(crate) fn migrate(tx: &rusqlite::Transaction<'_>) -> anyhow::Result<()> {
+    tx.execute(
+        r"CREATE TABLE submissions (
+            transaction_hash BLOB PRIMARY KEY NOT NULL,
+            contract_address BLOB NOT NULL,
+            timestamp INTEGER NOT NULL
+        )",
+        [],
+    )
+    .context("Creating submissions table")?;
+
+    Ok(())
+}
@@ -4,7 +4,8 @@ mod curve;
 mod field;
 
 pub use curve::{
-    AffinePoint, ProjectivePoint, PEDERSEN_P0, PEDERSEN_P1, PEDERSEN_P2, PEDERSEN_P3, PEDERSEN_P4,
+    AffinePoint, ProjectivePoint, CURVE_G, PEDERSEN_P0, PEDERSEN_P1, PEDERSEN_P2, PEDERSEN_P3,
+    PEDERSEN_P4,
 };
 pub use field::{FieldElement, FieldElementRepr};
 
@@ -262,6 +262,15 @@ impl std::ops::Add for StarkHash {
     }
 }
 
+impl std::ops::Mul for StarkHash {
+    type Output = StarkHash;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let result = FieldElement::from(self) * FieldElement::from(rhs);
+        StarkHash::from(result)
+    }
+}
+
 /// Computes the [Starknet Pedersen hash] on `a` and `b` using precomputed points.
 ///
 /// [Starknet Pedersen hash]: https://docs.starkware.co/starkex-v3/crypto/pedersen-hash-function
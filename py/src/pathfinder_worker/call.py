@@ -23,6 +23,8 @@ try:
     from starkware.starknet.definitions.general_config import StarknetChainId
     from starkware.starknet.services.api.gateway.transaction import (
         AccountTransaction,
+        Deploy,
+        Transaction,
     )
     from starkware.storage.storage import Storage
     from starkware.starknet.business_logic.state.state import CachedState
@@ -34,7 +36,7 @@ except ModuleNotFoundError:
 
 
 # used from tests, and the query which asserts that the schema is of expected version.
-EXPECTED_SCHEMA_REVISION = 21
+EXPECTED_SCHEMA_REVISION = 22
 EXPECTED_CAIRO_VERSION = "0.10.1"
 
 # used by the sqlite adapter to communicate "contract state not found, nor was the patricia tree key"
@@ -47,6 +49,7 @@ DEV_MODE = os.environ.get("PATHFINDER_PROFILE") == "dev"
 class Verb(Enum):
     CALL = 0
     ESTIMATE_FEE = 1
+    SIMULATE_DEPLOY = 2
 
 
 class Chain(Enum):
@@ -166,11 +169,35 @@ class EstimateFee(Command):
         )
 
 
+@marshmallow_dataclass.dataclass(frozen=True)
+class SimulateDeploy(Command):
+    verb: ClassVar[Verb] = Verb.SIMULATE_DEPLOY
+
+    pending_updates: Dict[int, List[StorageDiff]] = field(
+        metadata=pending_updates_metadata
+    )
+    pending_deployed: List[DeployedContract] = field(metadata=pending_deployed_metadata)
+    pending_nonces: Dict[int, int] = field(metadata=pending_nonces_metadata)
+
+    # only DEPLOY transactions are accepted, but they are not account transactions
+    transaction: Transaction
+
+    gas_price: int = 0
+
+    def has_pending_data(self):
+        return (
+            len(self.pending_updates) > 0
+            or len(self.pending_deployed) > 0
+            or len(self.pending_nonces) > 0
+        )
+
+
 class CommandSchema(marshmallow_oneofschema.OneOfSchema):
     type_field = "verb"
     type_schemas: Dict[str, Type[Schema]] = {
         Verb.CALL.name: Call.Schema,
         Verb.ESTIMATE_FEE.name: EstimateFee.Schema,
+        Verb.SIMULATE_DEPLOY.name: SimulateDeploy.Schema,
     }
 
     at_block = mfields.Str()
@@ -380,6 +407,20 @@ def loop_inner(connection, command: Command):
             )
         )
         ret = (command.verb, result.retdata, timings)
+    elif isinstance(command, SimulateDeploy):
+        contract_address = asyncio.run(
+            do_simulate_deploy(
+                adapter,
+                general_config,
+                global_root,
+                block_info,
+                command.transaction,
+                pending_updates,
+                pending_deployed,
+                pending_nonces,
+            )
+        )
+        ret = (command.verb, contract_address, timings)
     else:
         assert isinstance(command, EstimateFee)
         fees = asyncio.run(
@@ -412,6 +453,8 @@ def render(verb, vals):
 
     if verb == Verb.CALL:
         return list(map(prefixed_hex, vals))
+    elif verb == Verb.SIMULATE_DEPLOY:
+        return {"contract_address": prefixed_hex(vals)}
     else:
         assert verb == Verb.ESTIMATE_FEE
         return {
@@ -819,6 +862,53 @@ async def do_estimate_fee(
     }
 
 
+async def do_simulate_deploy(
+    adapter,
+    general_config,
+    root,
+    block_info,
+    transaction: Deploy,
+    pending_updates,
+    pending_deployed,
+    pending_nonces,
+):
+    """
+    Runs the constructor of a DEPLOY transaction without storing anything. The deployed class does
+    not need to have been declared, it is taken from the transaction as the sequencer would.
+
+    A reverting constructor raises like a failing call would.
+    """
+    from starkware.cairo.lang.vm.crypto import pedersen_hash_func
+    from starkware.starknet.business_logic.fact_state.patricia_state import (
+        PatriciaStateReader,
+    )
+    from starkware.starknet.business_logic.state.state import CachedState
+    from starkware.starknet.business_logic.transaction.objects import InternalDeploy
+    from starkware.starkware_utils.commitment_tree.patricia_tree.patricia_tree import (
+        PatriciaTree,
+    )
+    from starkware.storage.storage import FactFetchingContext
+
+    if not isinstance(transaction, Deploy):
+        raise TypeError(f"expected a DEPLOY transaction, got {type(transaction)}")
+
+    tx = InternalDeploy.from_external(transaction, general_config)
+
+    ffc = FactFetchingContext(storage=adapter, hash_func=pedersen_hash_func)
+    state_reader = PatriciaStateReader(PatriciaTree(root, 251), ffc)
+    async_state = CachedState(block_info, state_reader)
+
+    apply_pending(async_state, pending_updates, pending_deployed, pending_nonces)
+
+    await async_state.set_contract_class(
+        tx.contract_hash, transaction.contract_definition
+    )
+
+    await tx.apply_state_updates(async_state, general_config)
+
+    return tx.contract_address
+
+
 def apply_pending(
     state: CachedState,
     updates: Dict[int, List[StorageDiff]],
@@ -6,7 +6,11 @@ import sqlite3
 import pytest
 import zstandard
 from starkware.starknet.public.abi import get_selector_from_name
-from starkware.starknet.services.api.gateway.transaction import InvokeFunction, Declare
+from starkware.starknet.services.api.gateway.transaction import (
+    Declare,
+    InvokeFunction,
+    Transaction,
+)
 from starkware.starknet.services.api.contract_class import ContractClass
 from starkware.starkware_utils.error_handling import WebFriendlyException
 
@@ -17,6 +21,7 @@ from pathfinder_worker.call import (
     Call,
     Command,
     EstimateFee,
+    SimulateDeploy,
     check_cairolang_version,
     do_loop,
     loop_inner,
@@ -570,6 +575,41 @@ def test_fee_estimate_for_declare_transaction_directly():
     }
 
 
+def test_simulate_deploy_with_reverting_constructor():
+    con = inmemory_with_tables()
+    populate_test_contract_with_132_on_3(con)
+
+    path = test_relative_path(
+        "../../../crates/pathfinder/resources/deploy_openzeppelin_account.json"
+    )
+
+    with open(path, "r") as file:
+        deploy = Transaction.Schema().loads(file.read())
+
+    def simulate(transaction):
+        con.execute("BEGIN")
+        try:
+            command = SimulateDeploy(
+                at_block="latest",
+                chain=call.Chain.GOERLI,
+                pending_updates={},
+                pending_deployed=[],
+                pending_nonces={},
+                transaction=transaction,
+            )
+            (_verb, output, _timings) = loop_inner(con, command)
+            return output
+        finally:
+            con.rollback()
+
+    assert simulate(deploy) != 0
+
+    # the account constructor expects the public key
+    without_calldata = dataclasses.replace(deploy, constructor_calldata=[])
+    with pytest.raises(Exception):
+        simulate(without_calldata)
+
+
 def test_fee_estimate_on_positive():
     con = inmemory_with_tables()
     contract_address = hex(populate_test_contract_with_132_on_3(con))